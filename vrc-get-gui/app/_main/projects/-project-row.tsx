@@ -8,7 +8,10 @@ import { CircleHelp, CircleUserRound, Ellipsis, Globe } from "lucide-react";
 import React, { type ComponentProps, useContext } from "react";
 import { copyProject } from "@/app/_main/projects/manage/-copy-project";
 import { MigrationCopyingDialog } from "@/app/_main/projects/manage/-unity-migration";
-import { BackupProjectDialog } from "@/components/BackupProjectDialog";
+import {
+	BackupProjectDialog,
+	createAutomaticBackup,
+} from "@/components/BackupProjectDialog";
 import { FavoriteStarToggleButton } from "@/components/FavoriteStarButton";
 import { OpenUnityButton } from "@/components/OpenUnityButton";
 import { RemoveProjectDialog } from "@/components/RemoveProjectDialog";
@@ -368,9 +371,12 @@ async function migrateVpm(projectPath: string) {
 
 	let migrateProjectPath: string;
 	switch (backupType) {
-		case "none":
+		case "none": {
+			const header = tc("projects:dialog:vpm migrate header");
+			if (!(await createAutomaticBackup(projectPath, dialog, header))) return;
 			migrateProjectPath = projectPath;
 			break;
+		}
 		case "copy": {
 			migrateProjectPath = await dialog.ask(MigrationCopyingDialog, {
 				header: tc("projects:dialog:vpm migrate header"),
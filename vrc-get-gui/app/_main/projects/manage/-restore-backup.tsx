@@ -0,0 +1,228 @@
+import { useQuery } from "@tanstack/react-query";
+import type { NavigateFn } from "@tanstack/react-router";
+import { useEffect, useId, useState } from "react";
+import { Button } from "@/components/ui/button";
+import { DialogFooter, DialogTitle } from "@/components/ui/dialog";
+import { Label } from "@/components/ui/label";
+import { Progress } from "@/components/ui/progress";
+import { RadioGroup, RadioGroupItem } from "@/components/ui/radio-group";
+import {
+	commands,
+	type TauriProjectBackup,
+	type TauriRestoreBackupProgress,
+} from "@/lib/bindings";
+import { callAsyncCommand } from "@/lib/call-async-command";
+import { dateToString } from "@/lib/dateToString";
+import { type DialogContext, showDialog } from "@/lib/dialog";
+import { tc } from "@/lib/i18n";
+import { directoryFromPath, nameFromPath, pathSeparator } from "@/lib/os";
+import { queryClient } from "@/lib/query-client";
+import { toastSuccess } from "@/lib/toast";
+
+type RestoreTarget = {
+	backup: TauriProjectBackup;
+	restorePath: string;
+};
+
+export async function restoreBackup(
+	projectPath: string,
+	navigate?: NavigateFn,
+) {
+	using dialog = showDialog();
+	const target = await dialog.ask(SelectBackupDialog, { projectPath });
+	if (target == null) return; // cancelled
+	await dialog.ask(RestoringDialog, {
+		backupPath: target.backup.path,
+		restorePath: target.restorePath,
+		overwrite: target.restorePath === projectPath,
+	});
+	dialog.close();
+	toastSuccess(
+		tc("projects:toast:successfully restored backup", {
+			name: target.backup.name,
+		}),
+	);
+
+	await Promise.all([
+		queryClient.invalidateQueries({
+			queryKey: ["projectDetails", target.restorePath],
+		}),
+		queryClient.invalidateQueries({
+			queryKey: ["environmentProjects"],
+		}),
+	]);
+
+	if (target.restorePath !== projectPath) {
+		await navigate?.({
+			replace: true,
+			to: "/projects/manage",
+			search: { projectPath: target.restorePath },
+		});
+	}
+}
+
+function SelectBackupDialog({
+	dialog,
+	projectPath,
+}: {
+	dialog: DialogContext<RestoreTarget | null>;
+	projectPath: string;
+}) {
+	const id = useId();
+	const backupsQuery = useQuery({
+		queryKey: ["projectListBackups", projectPath],
+		queryFn: () => commands.projectListBackups(projectPath),
+	});
+	const backups = backupsQuery.data ?? [];
+
+	const [selectedPath, setSelectedPath] = useState<string | null>(null);
+	const selected = backups.find((backup) => backup.path === selectedPath);
+
+	// restoring as a new project uses the name of the backup as the project name
+	const newProjectPath =
+		selected &&
+		`${directoryFromPath(projectPath)}${pathSeparator()}${selected.name}`;
+
+	return (
+		<div className={"contents whitespace-normal"}>
+			<DialogTitle>
+				{tc("projects:dialog:restore backup", {
+					name: nameFromPath(projectPath),
+				})}
+			</DialogTitle>
+			<div>
+				{backupsQuery.isLoading ? (
+					<p>{tc("general:loading...")}</p>
+				) : backups.length === 0 ? (
+					<p>{tc("projects:dialog:no backups found")}</p>
+				) : (
+					<RadioGroup
+						onValueChange={(path) => setSelectedPath(path)}
+						value={selectedPath ?? undefined}
+					>
+						{backups.map((backup) => (
+							<div key={backup.path} className={"flex items-center gap-2"}>
+								<RadioGroupItem
+									value={backup.path}
+									id={`${id}:${backup.path}`}
+								/>
+								<Label htmlFor={`${id}:${backup.path}`}>
+									{dateToString(backup.created_at)}
+									{" "}
+									({tc(`projects:backup format:${backup.format}`)})
+								</Label>
+							</div>
+						))}
+					</RadioGroup>
+				)}
+				{newProjectPath && (
+					<small className={"block mt-2"}>
+						{tc(
+							"projects:hint:path of restoring project",
+							{ path: newProjectPath },
+							{
+								components: {
+									path: (
+										<span
+											className={
+												"p-0.5 font-path whitespace-pre bg-secondary text-secondary-foreground"
+											}
+										/>
+									),
+								},
+							},
+						)}
+					</small>
+				)}
+				<small className={"block mt-2"}>
+					{tc("projects:hint:restore over this project")}
+				</small>
+			</div>
+			<DialogFooter className={"gap-2"}>
+				<Button onClick={() => dialog.close(null)}>
+					{tc("general:button:cancel")}
+				</Button>
+				<Button
+					variant={"destructive"}
+					disabled={selected == null}
+					onClick={() =>
+						selected &&
+						dialog.close({ backup: selected, restorePath: projectPath })
+					}
+				>
+					{tc("projects:button:restore over this project")}
+				</Button>
+				<Button
+					disabled={selected == null}
+					onClick={() =>
+						selected &&
+						newProjectPath &&
+						dialog.close({ backup: selected, restorePath: newProjectPath })
+					}
+				>
+					{tc("projects:button:restore as new project")}
+				</Button>
+			</DialogFooter>
+		</div>
+	);
+}
+
+function RestoringDialog({
+	backupPath,
+	restorePath,
+	overwrite,
+	dialog,
+}: {
+	backupPath: string;
+	restorePath: string;
+	overwrite: boolean;
+	dialog: DialogContext<string>;
+}) {
+	const [progress, setProgress] = useState<TauriRestoreBackupProgress>({
+		proceed: 0,
+		total: 1,
+		last_proceed: "",
+	});
+
+	useEffect(() => {
+		const [_, promise] = callAsyncCommand(
+			commands.environmentRestoreProjectBackup,
+			[backupPath, restorePath, overwrite],
+			(progress) => {
+				setProgress((prev) => {
+					if (prev.proceed > progress.proceed) return prev;
+					return progress;
+				});
+			},
+		);
+
+		promise.then(dialog.close, dialog.error);
+	}, [backupPath, restorePath, overwrite, dialog.close, dialog.error]);
+
+	return (
+		<>
+			<DialogTitle>
+				{tc("projects:dialog:restore backup", {
+					name: nameFromPath(restorePath),
+				})}
+			</DialogTitle>
+			<div>
+				<p>{tc("projects:dialog:restoring...")}</p>
+				<p>
+					{tc("projects:dialog:proceed k/n", {
+						count: progress.proceed,
+						total: progress.total,
+					})}
+				</p>
+				<p className={"overflow-hidden w-full whitespace-pre"}>
+					{progress.last_proceed}
+				</p>
+				<Progress value={progress.proceed} max={progress.total} />
+				<p>{tc("projects:do not close")}</p>
+			</div>
+			<DialogFooter className={"gap-2"}>
+				<Button disabled>{tc("general:button:cancel")}</Button>
+			</DialogFooter>
+		</>
+	);
+}
@@ -1,6 +1,9 @@
 import type { NavigateFn } from "@tanstack/react-router";
 import React, { Fragment, useEffect, useState } from "react";
-import { BackupProjectDialog } from "@/components/BackupProjectDialog";
+import {
+	BackupProjectDialog,
+	createAutomaticBackup,
+} from "@/components/BackupProjectDialog";
 import { Button } from "@/components/ui/button";
 import { DialogFooter, DialogTitle } from "@/components/ui/dialog";
 import { Progress } from "@/components/ui/progress";
@@ -102,6 +105,7 @@ export async function unityVersionChange({
 		let migrateProjectPath: string;
 		switch (backupType) {
 			case "none":
+				if (!(await createAutomaticBackup(projectPath, dialog, header))) return;
 				migrateProjectPath = projectPath;
 				break;
 			case "copy": {
@@ -3,6 +3,7 @@ import { queryOptions, type UseMutationOptions } from "@tanstack/react-query";
 import { CircleAlert } from "lucide-react";
 import type React from "react";
 import { Fragment } from "react";
+import { createAutomaticBackup } from "@/components/BackupProjectDialog";
 import { DelayedButton } from "@/components/DelayedButton";
 import { ExternalLink } from "@/components/ExternalLink";
 import { Button } from "@/components/ui/button";
@@ -126,6 +127,7 @@ export async function applyChanges(
 			// close window
 			return;
 		}
+		if (!(await createAutomaticBackup(projectPath))) return;
 		await commands.projectApplyPendingChanges(
 			projectPath,
 			changes.changes_version,
@@ -368,6 +370,13 @@ function ProjectChangesDialog({
 														.map((p) => getPackageDisplayName(p))
 														.join(", "),
 												})}
+												{conflict.explanation && (
+													<span
+														className={"block text-sm text-muted-foreground"}
+													>
+														{conflict.explanation}
+													</span>
+												)}
 											</TypographyItem>
 										);
 									})}
@@ -18,6 +18,7 @@ import { ArrowLeft, ChevronDown } from "lucide-react";
 import type React from "react";
 import { Suspense, useMemo } from "react";
 import { copyProject } from "@/app/_main/projects/manage/-copy-project";
+import { restoreBackup } from "@/app/_main/projects/manage/-restore-backup";
 import { BackupProjectDialog } from "@/components/BackupProjectDialog";
 import { HNavBar, VStack } from "@/components/layout";
 import { OpenUnityButton } from "@/components/OpenUnityButton";
@@ -683,6 +684,15 @@ function DropdownMenuContentBody({
 		}
 	};
 
+	const onRestoreBackup = async () => {
+		try {
+			await restoreBackup(projectPath, navigate);
+		} catch (e) {
+			console.error(e);
+			toastThrownError(e);
+		}
+	};
+
 	const unityPath = unityPathQuery.data;
 
 	return (
@@ -704,6 +714,9 @@ function DropdownMenuContentBody({
 			<DropdownMenuItem onClick={onBackup}>
 				{tc("projects:menuitem:backup")}
 			</DropdownMenuItem>
+			<DropdownMenuItem onClick={onRestoreBackup}>
+				{tc("projects:menuitem:restore backup")}
+			</DropdownMenuItem>
 			<DropdownMenuItem
 				onClick={removeProject}
 				className={"text-destructive focus:text-destructive"}
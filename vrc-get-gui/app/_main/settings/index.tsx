@@ -12,6 +12,7 @@ import { Suspense, useEffect, useTransition } from "react";
 import Loading from "@/app/-loading";
 import { CheckForUpdateMessage } from "@/components/CheckForUpdateMessage";
 import {
+	AutomaticBackupKeepCountSelect,
 	BackupFormatSelect,
 	BackupPathWarnings,
 	FilePathRow,
@@ -529,13 +530,21 @@ function BackupCard() {
 	const queryClient = useQueryClient();
 
 	const {
-		data: { projectBackupPath, backupFormat, excludeVpmPackagesFromBackup },
+		data: {
+			projectBackupPath,
+			backupFormat,
+			excludeVpmPackagesFromBackup,
+			automaticBackup,
+			automaticBackupKeepCount,
+		},
 	} = useSuspenseQuery({
 		...environmentGetSettings,
 		select: (data) => ({
 			projectBackupPath: data.project_backup_path,
 			backupFormat: data.backup_format,
 			excludeVpmPackagesFromBackup: data.exclude_vpm_packages_from_backup,
+			automaticBackup: data.automatic_backup,
+			automaticBackupKeepCount: data.automatic_backup_keep_count,
 		}),
 	});
 
@@ -583,6 +592,24 @@ function BackupCard() {
 		},
 	});
 
+	const setAutomaticBackup = useMutation({
+		mutationFn: async (flag: boolean) =>
+			await commands.environmentSetAutomaticBackup(flag),
+		onError: (e) => {
+			console.error(e);
+			toastThrownError(e);
+		},
+		onSuccess: (_, flag) => {
+			queryClient.setQueryData(environmentGetSettings.queryKey, (old) => {
+				if (old == null) return old;
+				return { ...old, automatic_backup: flag };
+			});
+		},
+		onSettled: async () => {
+			await queryClient.invalidateQueries(environmentGetSettings);
+		},
+	});
+
 	return (
 		<SettingsCard>
 			<h2>{tc("projects:backup")}</h2>
@@ -620,6 +647,25 @@ function BackupCard() {
 					{tc("settings:backup:exclude vpm packages from backup description")}
 				</p>
 			</div>
+			<div className="mt-2">
+				<label className={"flex items-center gap-2"}>
+					<Checkbox
+						checked={automaticBackup}
+						onCheckedChange={(e) => setAutomaticBackup.mutate(e === true)}
+					/>
+					{tc("settings:backup:automatic backup")}
+				</label>
+				<p className={"text-sm whitespace-normal"}>
+					{tc("settings:backup:automatic backup description")}
+				</p>
+				<label className={"flex items-center gap-2"}>
+					{tc("settings:backup:automatic backup keep count")}
+					<AutomaticBackupKeepCountSelect
+						keepCount={automaticBackupKeepCount}
+						disabled={!automaticBackup}
+					/>
+				</label>
+			</div>
 		</SettingsCard>
 	);
 }
@@ -6,19 +6,44 @@ import { Progress } from "@/components/ui/progress";
 import type { TauriCreateBackupProgress } from "@/lib/bindings";
 import { commands } from "@/lib/bindings";
 import { callAsyncCommand } from "@/lib/call-async-command";
-import type { DialogContext } from "@/lib/dialog";
+import {
+	type DialogApi,
+	type DialogContext,
+	openSingleDialog,
+} from "@/lib/dialog";
 import { tc } from "@/lib/i18n";
 import { toastNormal, toastSuccess } from "@/lib/toast";
 import { useEffectEvent } from "@/lib/use-effect-event";
 
+/**
+ * Creates an automatic backup before changing the project if enabled in the settings.
+ *
+ * @returns false if the backup is cancelled and the change should be aborted
+ */
+export async function createAutomaticBackup(
+	projectPath: string,
+	dialog?: DialogApi,
+	header?: React.ReactNode,
+): Promise<boolean> {
+	if (!(await commands.environmentAutomaticBackup())) return true;
+	const props = { projectPath, header, automatic: true };
+	const result = dialog
+		? await dialog.ask(BackupProjectDialog, props)
+		: await openSingleDialog(BackupProjectDialog, props);
+	return result !== "cancelled";
+}
+
 export function BackupProjectDialog({
 	projectPath,
 	dialog,
 	header,
+	automatic,
 }: {
 	projectPath: string;
 	dialog: DialogContext<null | "cancelled">;
 	header?: React.ReactNode;
+	// automatic backups are removed by the retention policy
+	automatic?: boolean;
 }) {
 	const [progress, setProgress] = useState<TauriCreateBackupProgress>({
 		proceed: 0,
@@ -31,7 +56,9 @@ export function BackupProjectDialog({
 	const start = useEffectEvent(
 		(projectPath: string, dialog: DialogContext<null | "cancelled">) => {
 			const [cancel, promise] = callAsyncCommand(
-				commands.projectCreateBackup,
+				automatic
+					? commands.projectCreateAutomaticBackup
+					: commands.projectCreateBackup,
 				[projectPath],
 				(progress) => {
 					setProgress((prev) => {
@@ -64,7 +91,12 @@ export function BackupProjectDialog({
 
 	return (
 		<div className={"contents whitespace-normal"}>
-			<DialogTitle>{header ?? tc("projects:dialog:backup header")}</DialogTitle>
+			<DialogTitle>
+				{header ??
+					(automatic
+						? tc("projects:dialog:automatic backup header")
+						: tc("projects:dialog:backup header"))}
+			</DialogTitle>
 			<div>
 				<p>{tc("projects:dialog:creating backup...")}</p>
 				<p>
@@ -381,6 +381,66 @@ export function BackupFormatSelect({ backupFormat }: { backupFormat: string }) {
 					<SelectItem value={"zip-best"}>
 						{tc("settings:backup:format:zip-best")}
 					</SelectItem>
+					<SelectItem value={"tar-zst"}>
+						{tc("settings:backup:format:tar-zst")}
+					</SelectItem>
+					<SelectItem value={"incremental"}>
+						{tc("settings:backup:format:incremental")}
+					</SelectItem>
+				</SelectGroup>
+			</SelectContent>
+		</Select>
+	);
+}
+
+export function AutomaticBackupKeepCountSelect({
+	keepCount,
+	disabled,
+}: {
+	keepCount: number;
+	disabled?: boolean;
+}) {
+	const queryClient = useQueryClient();
+	const setKeepCount = useMutation({
+		mutationFn: async (count: number) =>
+			await commands.environmentSetAutomaticBackupKeepCount(count),
+		onMutate: async (count: number) => {
+			await queryClient.cancelQueries(environmentGetSettings);
+			const current = queryClient.getQueryData(environmentGetSettings.queryKey);
+			if (current != null) {
+				queryClient.setQueryData(environmentGetSettings.queryKey, {
+					...current,
+					automatic_backup_keep_count: count,
+				});
+			}
+			return current;
+		},
+		onError: (e, _, prev) => {
+			console.error(e);
+			toastThrownError(e);
+			queryClient.setQueryData(environmentGetSettings.queryKey, prev);
+		},
+		onSettled: async () => {
+			await queryClient.invalidateQueries(environmentGetSettings);
+		},
+	});
+
+	return (
+		<Select
+			value={`${keepCount}`}
+			onValueChange={(value) => setKeepCount.mutate(Number(value))}
+			disabled={disabled}
+		>
+			<SelectTrigger>
+				<SelectValue />
+			</SelectTrigger>
+			<SelectContent>
+				<SelectGroup>
+					{[1, 3, 5, 10, 20].map((count) => (
+						<SelectItem key={count} value={`${count}`}>
+							{tc("settings:backup:automatic backup keep count:n", { count })}
+						</SelectItem>
+					))}
 				</SelectGroup>
 			</SelectContent>
 		</Select>
@@ -28,6 +28,13 @@ export const commands = {
 	environmentRemoveProjectByPath: (projectPath: string, directory: boolean) => __TAURI_INVOKE<null>("environment_remove_project_by_path", { projectPath, directory }),
 	environmentCopyProjectForMigration: (channel: string, sourcePath: string) => __TAURI_INVOKE<AsyncCallResult<TauriCopyProjectProgress, string>>("environment_copy_project_for_migration", { channel, sourcePath }),
 	environmentCopyProject: (channel: string, sourcePath: string, newPath: string) => __TAURI_INVOKE<AsyncCallResult<TauriCopyProjectProgress, string>>("environment_copy_project", { channel, sourcePath, newPath }),
+	/**
+	 * Restores the backup to `restore_path` and adds the restored project to the project list.
+	 * 
+	 * `restore_path` can be an existing directory, in which case the files in the backup overwrite existing files.
+	 * VPM packages excluded from the backup are installed again by resolving the project.
+	 */
+	environmentRestoreProjectBackup: (channel: string, backupPath: string, restorePath: string, overwrite: boolean) => __TAURI_INVOKE<AsyncCallResult<TauriRestoreBackupProgress, string>>("environment_restore_project_backup", { channel, backupPath, restorePath, overwrite }),
 	environmentSetFavoriteProject: (projectPath: string, favorite: boolean) => __TAURI_INVOKE<null>("environment_set_favorite_project", { projectPath, favorite }),
 	environmentProjectCreationInformation: () => __TAURI_INVOKE<TauriProjectCreationInformation>("environment_project_creation_information"),
 	environmentCheckProjectName: (basePath: string, projectName: string) => __TAURI_INVOKE<TauriProjectDirCheckResult>("environment_check_project_name", { basePath, projectName }),
@@ -59,6 +66,12 @@ export const commands = {
 	environmentSetShowPrereleasePackages: (value: boolean) => __TAURI_INVOKE<null>("environment_set_show_prerelease_packages", { value }),
 	environmentSetBackupFormat: (backupFormat: string) => __TAURI_INVOKE<null>("environment_set_backup_format", { backupFormat }),
 	environmentSetExcludeVpmPackagesFromBackup: (excludeVpmPackagesFromBackup: boolean) => __TAURI_INVOKE<null>("environment_set_exclude_vpm_packages_from_backup", { excludeVpmPackagesFromBackup }),
+	/**
+	 * Returns whether to create a backup before changing the project
+	 */
+	environmentAutomaticBackup: () => __TAURI_INVOKE<boolean>("environment_automatic_backup"),
+	environmentSetAutomaticBackup: (automaticBackup: boolean) => __TAURI_INVOKE<null>("environment_set_automatic_backup", { automaticBackup }),
+	environmentSetAutomaticBackupKeepCount: (automaticBackupKeepCount: number) => __TAURI_INVOKE<null>("environment_set_automatic_backup_keep_count", { automaticBackupKeepCount }),
 	environmentSetReleaseChannel: (releaseChannel: string) => __TAURI_INVOKE<null>("environment_set_release_channel", { releaseChannel }),
 	environmentSetUseAlcomForVccProtocol: (useAlcomForVccProtocol: boolean) => __TAURI_INVOKE<null>("environment_set_use_alcom_for_vcc_protocol", { useAlcomForVccProtocol }),
 	environmentGetDefaultUnityArguments: () => __TAURI_INVOKE<string[]>("environment_get_default_unity_arguments"),
@@ -87,6 +100,18 @@ export const commands = {
 	projectOpenUnity: (projectPath: string, unityPath: string) => __TAURI_INVOKE<boolean>("project_open_unity", { projectPath, unityPath }),
 	projectIsUnityLaunching: (projectPath: string) => __TAURI_INVOKE<boolean>("project_is_unity_launching", { projectPath }),
 	projectCreateBackup: (channel: string, projectPath: string) => __TAURI_INVOKE<AsyncCallResult<TauriCreateBackupProgress, null>>("project_create_backup", { channel, projectPath }),
+	/**
+	 * Creates an automatic backup before changing the project, and removes old automatic backups
+	 * of the project beyond the configured count.
+	 */
+	projectCreateAutomaticBackup: (channel: string, projectPath: string) => __TAURI_INVOKE<AsyncCallResult<TauriCreateBackupProgress, null>>("project_create_automatic_backup", { channel, projectPath }),
+	projectListBackups: (projectPath: string) => __TAURI_INVOKE<TauriProjectBackup[]>("project_list_backups", { projectPath }),
+	projectListIncrementalBackups: (projectPath: string) => __TAURI_INVOKE<TauriIncrementalBackup[]>("project_list_incremental_backups", { projectPath }),
+	/**
+	 * Removes old incremental backups of the project by count and/or age.
+	 * The latest backup is always kept.
+	 */
+	projectPruneIncrementalBackups: (projectPath: string, keepCount: number | null, maxAgeDays: number | null) => __TAURI_INVOKE<TauriPruneIncrementalBackupsResult>("project_prune_incremental_backups", { projectPath, keepCount, maxAgeDays }),
 	projectGetCustomUnityArgs: (projectPath: string) => __TAURI_INVOKE<string[] | null>("project_get_custom_unity_args", { projectPath }),
 	projectSetCustomUnityArgs: (projectPath: string, args: string[] | null) => __TAURI_INVOKE<boolean>("project_set_custom_unity_args", { projectPath, args }),
 	projectGetUnityPath: (projectPath: string) => __TAURI_INVOKE<string | null>("project_get_unity_path", { projectPath }),
@@ -200,6 +225,8 @@ export type TauriAlcomTemplate = {
 	unity_packages: string[],
 };
 
+export type TauriBackupFormat = "Zip" | "TarZst" | "Incremental";
+
 export type TauriBasePackageInfo = {
 	name: string,
 	display_name: string | null,
@@ -220,6 +247,7 @@ export type TauriConflictInfo = {
 	packages: string[],
 	unity_conflict: boolean,
 	unlocked_names: string[],
+	explanation: string | null,
 };
 
 export type TauriCopyProjectProgress = {
@@ -254,6 +282,8 @@ export type TauriEnvironmentSettings = {
 	gui_compact: boolean,
 	unity_hub_access_method: UnityHubAccessMethod,
 	exclude_vpm_packages_from_backup: boolean,
+	automatic_backup: boolean,
+	automatic_backup_keep_count: number,
 };
 
 export type TauriImportDuplicated = TauriImportDuplicated_Serialize | TauriImportDuplicated_Deserialize;
@@ -292,6 +322,13 @@ export type TauriImportTemplateResult_Serialize = {
 	duplicates: TauriImportDuplicated_Serialize[],
 };
 
+export type TauriIncrementalBackup = {
+	name: string,
+	created_at: number,
+	file_count: number,
+	total_size: number,
+};
+
 export type TauriPackage = {
 	source: TauriPackageSource,
 } & (TauriBasePackageInfo);
@@ -321,6 +358,12 @@ export type TauriPickUnityPackageResult = { type: "NoFolderSelected" } | { type:
 
 export type TauriPickUnityResult = "NoFolderSelected" | "InvalidSelection" | "AlreadyAdded" | "Successful";
 
+export type TauriPruneIncrementalBackupsResult = {
+	removed_backups: number,
+	removed_chunks: number,
+	freed_bytes: number,
+};
+
 export type TauriProject = {
 	name: string,
 	path: string,
@@ -334,6 +377,14 @@ export type TauriProject = {
 	is_valid: boolean | null,
 };
 
+export type TauriProjectBackup = {
+	name: string,
+	path: string,
+	format: TauriBackupFormat,
+	created_at: number,
+	automatic: boolean,
+};
+
 export type TauriProjectCreationInformation = {
 	templates: TauriProjectTemplateInfo[],
 	recent_project_locations: string[],
@@ -386,6 +437,12 @@ export type TauriRepositoryDescriptor = {
 	headers: { [key in string]: string },
 };
 
+export type TauriRestoreBackupProgress = {
+	total: number,
+	proceed: number,
+	last_proceed: string,
+};
+
 export type TauriUnityVersions = {
 	unity_paths: ([string, string, boolean])[],
 	recommended_version: string,
@@ -0,0 +1,310 @@
+//! Listing, restoring, and removing the backups created by `project_create_backup`.
+//!
+//! Backups are found by the `{project_name}-{timestamp}` naming in the backup directory,
+//! which is shared by the zip backups and the incremental backups.
+//! The automatic backups created before changing the project have the `-auto` suffix.
+
+use crate::commands::AsyncCommandContext;
+use crate::incremental_backup::{remove_unreferenced_chunks, restore_incremental_backup};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use log::info;
+use serde::Serialize;
+use std::io;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+use vrc_get_vpm::unity_project::{AUTOMATIC_BACKUP_SUFFIX, BACKUP_TIMESTAMP_FORMAT, BackupFormat};
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+pub struct TauriRestoreBackupProgress {
+    total: usize,
+    proceed: usize,
+    last_proceed: String,
+}
+
+impl TauriRestoreBackupProgress {
+    pub fn new(total: usize, proceed: usize, last_proceed: String) -> Self {
+        Self {
+            total,
+            proceed,
+            last_proceed,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RestoreError {
+    Io(io::Error),
+    Zip(async_zip::error::ZipError),
+}
+
+impl From<io::Error> for RestoreError {
+    fn from(value: io::Error) -> Self {
+        RestoreError::Io(value)
+    }
+}
+
+impl From<async_zip::error::ZipError> for RestoreError {
+    fn from(value: async_zip::error::ZipError) -> Self {
+        RestoreError::Zip(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, specta::Type)]
+pub enum TauriBackupFormat {
+    Zip,
+    TarZst,
+    Incremental,
+}
+
+impl From<BackupFormat> for TauriBackupFormat {
+    fn from(value: BackupFormat) -> Self {
+        match value {
+            BackupFormat::Zip => Self::Zip,
+            BackupFormat::TarZst => Self::TarZst,
+            BackupFormat::Incremental => Self::Incremental,
+        }
+    }
+}
+
+pub(crate) struct BackupFile {
+    pub path: PathBuf,
+    pub format: BackupFormat,
+    pub created_at: NaiveDateTime,
+    pub automatic: bool,
+}
+
+impl BackupFile {
+    fn parse(project_name: &str, path: PathBuf) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        let (name, format) = BackupFormat::from_file_name(file_name)?;
+        let timestamp = name.strip_prefix(project_name)?.strip_prefix('-')?;
+        let (timestamp, automatic) = match timestamp.strip_suffix(AUTOMATIC_BACKUP_SUFFIX) {
+            Some(timestamp) => (timestamp, true),
+            None => (timestamp, false),
+        };
+        // backups of other projects with the same prefix fail to parse here
+        let created_at = NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT).ok()?;
+
+        Some(Self {
+            path,
+            format,
+            created_at,
+            automatic,
+        })
+    }
+
+    /// The name of the backup, `{project_name}-{timestamp}` or `{project_name}-{timestamp}-auto`
+    pub fn name(&self) -> &str {
+        let file_name = self.path.file_name().unwrap().to_str().unwrap();
+        BackupFormat::from_file_name(file_name).unwrap().0
+    }
+
+    /// The creation time in milliseconds since the unix epoch, recovered from the local timestamp in the name
+    pub fn created_at_millis(&self) -> i64 {
+        Local
+            .from_local_datetime(&self.created_at)
+            .earliest()
+            .map_or_else(
+                || self.created_at.and_utc().timestamp_millis(),
+                |x| x.timestamp_millis(),
+            )
+    }
+}
+
+/// Lists the backups of the project, newest first.
+pub(crate) async fn list_backups(
+    backup_dir: &Path,
+    project_name: &str,
+) -> io::Result<Vec<BackupFile>> {
+    let mut read_dir = match tokio::fs::read_dir(backup_dir).await {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e),
+    };
+
+    let mut backups = vec![];
+    while let Some(entry) = read_dir.next_entry().await? {
+        if let Some(backup) = BackupFile::parse(project_name, entry.path()) {
+            backups.push(backup);
+        }
+    }
+
+    backups.sort_by_key(|x| std::cmp::Reverse(x.created_at));
+    Ok(backups)
+}
+
+/// Removes the automatic backups of the project except for the newest `keep_count` backups.
+///
+/// Backups created manually are never removed.
+pub(crate) async fn remove_old_automatic_backups(
+    backup_dir: &Path,
+    project_name: &str,
+    keep_count: usize,
+) -> io::Result<()> {
+    let backups = list_backups(backup_dir, project_name).await?;
+
+    let mut removed_snapshot = false;
+    for backup in backups.iter().filter(|x| x.automatic).skip(keep_count) {
+        info!("removing old automatic backup {}", backup.path.display());
+        tokio::fs::remove_file(&backup.path).await?;
+        removed_snapshot |= backup.format == BackupFormat::Incremental;
+    }
+
+    if removed_snapshot {
+        remove_unreferenced_chunks(backup_dir).await?;
+    }
+
+    Ok(())
+}
+
+/// Joins the relative path in the backup to the restore path,
+/// rejecting paths pointing outside the restore path.
+pub(crate) fn join_backup_path(restore_path: &Path, relative: &str) -> io::Result<PathBuf> {
+    let relative = Path::new(relative);
+    if !relative
+        .components()
+        .all(|x| matches!(x, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid path in backup: {}", relative.display()),
+        ));
+    }
+    Ok(restore_path.join(relative))
+}
+
+/// Extracts the backup to the restore path.
+///
+/// The files in the backup overwrite existing files, and other files in the restore path are kept,
+/// so the restore path should be empty.
+pub(crate) async fn restore_backup(
+    backup_path: &Path,
+    restore_path: &Path,
+    ctx: &AsyncCommandContext<TauriRestoreBackupProgress>,
+) -> Result<(), RestoreError> {
+    info!(
+        "restoring backup {} to {}",
+        backup_path.display(),
+        restore_path.display()
+    );
+
+    let format = (backup_path.file_name().and_then(|x| x.to_str()))
+        .and_then(BackupFormat::from_file_name)
+        .map(|(_, format)| format);
+
+    match format {
+        Some(BackupFormat::Incremental) => {
+            restore_incremental_backup(backup_path, restore_path, ctx).await?
+        }
+        Some(BackupFormat::TarZst) => {
+            extract_backup_tar_zst(backup_path, restore_path, ctx).await?
+        }
+        // zip is the format of the backups created before other formats are added
+        Some(BackupFormat::Zip) | None => {
+            extract_backup_zip(backup_path, restore_path, ctx).await?
+        }
+    }
+
+    info!("restoring backup {} finished", backup_path.display());
+
+    Ok(())
+}
+
+async fn extract_backup_zip(
+    backup_path: &Path,
+    restore_path: &Path,
+    ctx: &AsyncCommandContext<TauriRestoreBackupProgress>,
+) -> Result<(), RestoreError> {
+    let file = tokio::fs::File::open(backup_path).await?;
+    let mut reader =
+        async_zip::tokio::read::seek::ZipFileReader::with_tokio(BufReader::new(file)).await?;
+
+    let total = reader.file().entries().len();
+
+    for index in 0..total {
+        let entry = &reader.file().entries()[index];
+        let name = entry.filename().as_str()?.to_string();
+        let is_dir = entry.dir()?;
+
+        let path = join_backup_path(restore_path, &name)?;
+
+        if is_dir {
+            tokio::fs::create_dir_all(&path).await?;
+        } else {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let mut entry_reader = reader.reader_with_entry(index).await?.compat();
+            let mut file = tokio::fs::File::create(&path).await?;
+            tokio::io::copy(&mut entry_reader, &mut file).await?;
+            file.flush().await?;
+        }
+
+        let _ = ctx.emit(TauriRestoreBackupProgress::new(total, index + 1, name));
+    }
+
+    Ok(())
+}
+
+/// Extracts the tar.zst backup.
+///
+/// The number of entries is unknown until reading the whole archive,
+/// so the progress is reported in bytes of the compressed file.
+async fn extract_backup_tar_zst(
+    backup_path: &Path,
+    restore_path: &Path,
+    ctx: &AsyncCommandContext<TauriRestoreBackupProgress>,
+) -> Result<(), RestoreError> {
+    struct CountingReader<R> {
+        inner: R,
+        read: Arc<AtomicUsize>,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.read.fetch_add(read, Ordering::Relaxed);
+            Ok(read)
+        }
+    }
+
+    let backup_path = backup_path.to_path_buf();
+    let restore_path = restore_path.to_path_buf();
+    let ctx = ctx.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&backup_path)?;
+        let total = file.metadata()?.len() as usize;
+        let read = Arc::new(AtomicUsize::new(0));
+        let reader = CountingReader {
+            inner: file,
+            read: read.clone(),
+        };
+
+        let mut archive = tar::Archive::new(zstd::stream::read::Decoder::new(reader)?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().into_owned();
+            // unpack_in skips the entries pointing outside the restore path
+            if !entry.unpack_in(&restore_path)? {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid path in backup: {name}"),
+                ));
+            }
+
+            let proceed = read.load(Ordering::Relaxed);
+            let _ = ctx.emit(TauriRestoreBackupProgress::new(total, proceed, name));
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(io::Error::other)??;
+
+    Ok(())
+}
@@ -83,6 +83,7 @@ pub(crate) fn handlers() -> impl Fn(Invoke) -> bool + Send + Sync + 'static {
         environment::projects::environment_remove_project_by_path,
         environment::projects::environment_copy_project_for_migration,
         environment::projects::environment_copy_project,
+        environment::projects::environment_restore_project_backup,
         environment::projects::environment_set_favorite_project,
         environment::projects::environment_project_creation_information,
         environment::projects::environment_check_project_name,
@@ -114,6 +115,9 @@ pub(crate) fn handlers() -> impl Fn(Invoke) -> bool + Send + Sync + 'static {
         environment::settings::environment_set_show_prerelease_packages,
         environment::settings::environment_set_backup_format,
         environment::settings::environment_set_exclude_vpm_packages_from_backup,
+        environment::settings::environment_automatic_backup,
+        environment::settings::environment_set_automatic_backup,
+        environment::settings::environment_set_automatic_backup_keep_count,
         environment::settings::environment_set_release_channel,
         environment::settings::environment_set_use_alcom_for_vcc_protocol,
         environment::settings::environment_get_default_unity_arguments,
@@ -142,6 +146,10 @@ pub(crate) fn handlers() -> impl Fn(Invoke) -> bool + Send + Sync + 'static {
         project::project_open_unity,
         project::project_is_unity_launching,
         project::project_create_backup,
+        project::project_create_automatic_backup,
+        project::project_list_backups,
+        project::project_list_incremental_backups,
+        project::project_prune_incremental_backups,
         project::project_get_custom_unity_args,
         project::project_set_custom_unity_args,
         project::project_get_unity_path,
@@ -193,6 +201,7 @@ pub(crate) fn export_ts() {
             environment::projects::environment_remove_project_by_path,
             environment::projects::environment_copy_project_for_migration,
             environment::projects::environment_copy_project,
+            environment::projects::environment_restore_project_backup,
             environment::projects::environment_set_favorite_project,
             environment::projects::environment_project_creation_information,
             environment::projects::environment_check_project_name,
@@ -224,6 +233,9 @@ pub(crate) fn export_ts() {
             environment::settings::environment_set_show_prerelease_packages,
             environment::settings::environment_set_backup_format,
             environment::settings::environment_set_exclude_vpm_packages_from_backup,
+            environment::settings::environment_automatic_backup,
+            environment::settings::environment_set_automatic_backup,
+            environment::settings::environment_set_automatic_backup_keep_count,
             environment::settings::environment_set_release_channel,
             environment::settings::environment_set_use_alcom_for_vcc_protocol,
             environment::settings::environment_get_default_unity_arguments,
@@ -252,6 +264,10 @@ pub(crate) fn export_ts() {
             project::project_open_unity,
             project::project_is_unity_launching,
             project::project_create_backup,
+            project::project_create_automatic_backup,
+            project::project_list_backups,
+            project::project_list_incremental_backups,
+            project::project_prune_incremental_backups,
             project::project_get_custom_unity_args,
             project::project_set_custom_unity_args,
             project::project_get_unity_path,
@@ -391,6 +407,7 @@ impl_from_error!(
     async_zip::error::ZipError,
     vrc_get_vpm::environment::AddRepositoryErr,
     vrc_get_vpm::unity_project::RemovePackageErr,
+    vrc_get_vpm::package_source::FetchSourceError,
     fs_extra::error::Error,
 );
 
@@ -411,6 +428,15 @@ impl From<crate::compressor::CompressError> for RustError {
     }
 }
 
+impl From<crate::backup_restore::RestoreError> for RustError {
+    fn from(value: crate::backup_restore::RestoreError) -> Self {
+        match value {
+            crate::backup_restore::RestoreError::Io(e) => e.into(),
+            crate::backup_restore::RestoreError::Zip(e) => e.into(),
+        }
+    }
+}
+
 impl From<crate::updater::Error> for RustError {
     fn from(value: crate::updater::Error) -> Self {
         log::error!(gui_toast = false; "updater error: {value}");
@@ -340,7 +340,16 @@ pub async fn environment_add_repository(
     };
 
     let mut settings = settings.load_mut(io.inner()).await?;
-    add_remote_repo(&mut settings, url, None, headers, io.inner(), http.inner()).await?;
+    add_remote_repo(
+        &mut settings,
+        url,
+        None,
+        headers,
+        None,
+        io.inner(),
+        http.inner(),
+    )
+    .await?;
     settings.save().await?;
 
     // force update repository
@@ -569,6 +578,7 @@ pub async fn environment_import_add_repositories(
             adding_repo.url,
             None,
             adding_repo.headers,
+            None,
             io.inner(),
             http.inner(),
         )
@@ -1,12 +1,11 @@
 use crate::commands::prelude::*;
 use std::cmp::Reverse;
 
+use crate::backup_restore::{TauriRestoreBackupProgress, restore_backup};
 use crate::commands::async_command::{AsyncCallResult, AsyncCommandContext, With, async_command};
 use crate::templates;
 use crate::templates::{CreateProjectErr, ProjectTemplateInfo};
-use crate::utils::{
-    FileSystemTree, collect_notable_project_files_tree, default_project_path, trash_delete,
-};
+use crate::utils::{default_project_path, trash_delete};
 use futures::future::{join_all, try_join_all};
 use futures::prelude::*;
 use itertools::Itertools;
@@ -25,6 +24,7 @@ use vrc_get_vpm::environment::{
     ValidRealProjectInformation, VccDatabaseConnection,
 };
 use vrc_get_vpm::io::{DefaultEnvironmentIo, DefaultProjectIo};
+use vrc_get_vpm::unity_project::{FileSystemTree, collect_notable_project_files_tree};
 use vrc_get_vpm::version::UnityVersion;
 
 #[derive(Debug, Clone, Serialize, specta::Type)]
@@ -549,6 +549,122 @@ where
     .await
 }
 
+/// Restores the backup to `restore_path` and adds the restored project to the project list.
+///
+/// `restore_path` can be an existing directory, in which case the files in the backup overwrite existing files.
+/// VPM packages excluded from the backup are installed again by resolving the project.
+#[tauri::command]
+#[specta::specta]
+pub async fn environment_restore_project_backup(
+    window: Window,
+    channel: String,
+    backup_path: String,
+    restore_path: String,
+    overwrite: bool,
+) -> Result<AsyncCallResult<TauriRestoreBackupProgress, String>, RustError> {
+    async_command(channel, window, async {
+        let existing = !is_empty_or_missing_dir(Path::new(&restore_path)).await?;
+        if existing && !overwrite {
+            return Err(
+                localizable_error!("projects:error:restore path not empty", path => restore_path),
+            );
+        }
+
+        // the backup is extracted to a sibling directory and moved into place after extracting,
+        // so the existing project is kept if restoring fails
+        let temp_path = restore_temp_path(Path::new(&restore_path));
+        super::super::create_dir_all_with_err(&temp_path).await?;
+
+        With::<TauriRestoreBackupProgress>::continue_async(move |ctx| async move {
+            if let Err(e) = restore_backup(Path::new(&backup_path), &temp_path, &ctx).await {
+                tokio::fs::remove_dir_all(&temp_path).await.ok();
+                return Err(e.into());
+            }
+
+            // the files added after the backup must not be left in the restored project,
+            // so the existing project is moved to the trash instead of being overwritten
+            if existing {
+                info!("moving existing project {restore_path} to trash before restoring");
+                if let Err(e) = trash_delete(PathBuf::from(&restore_path)).await {
+                    tokio::fs::remove_dir_all(&temp_path).await.ok();
+                    return Err(format!("failed to move the existing project to trash: {e}").into());
+                }
+            } else {
+                // the empty directory cannot be replaced by renaming on windows
+                tokio::fs::remove_dir(&restore_path).await.ok();
+            }
+
+            tokio::fs::rename(&temp_path, &restore_path)
+                .await
+                .map_err(|e| {
+                    format!(
+                        "failed to move the restored project from {} to {restore_path}: {e}",
+                        temp_path.display()
+                    )
+                })?;
+
+            info!("restored backup. adding to project list and resolving packages");
+
+            let mut unity_project = load_project(restore_path.clone()).await?;
+
+            let settings = ctx.state::<SettingsState>();
+            let packages_state = ctx.state::<PackagesState>();
+            let io = ctx.state::<DefaultEnvironmentIo>();
+            let http = ctx.state::<reqwest::Client>();
+
+            let packages;
+            {
+                let mut settings = settings.load_mut(io.inner()).await?;
+                packages = packages_state
+                    .load_fully(&settings, io.inner(), http.inner())
+                    .await?;
+
+                let mut connection = VccDatabaseConnection::connect(io.inner()).await?;
+                migrate_sanitize_projects(&mut connection, io.inner(), &settings).await?;
+                // the project is already in the list if restored to the existing project
+                if connection.find_project(&restore_path)?.is_none() {
+                    connection.add_project(&unity_project).await?;
+                    connection.save(io.inner()).await?;
+                    settings.load_from_db(&connection)?;
+                    settings.save().await?;
+                }
+            }
+
+            {
+                let installer = PackageInstaller::new(io.inner(), Some(http.inner()));
+
+                let packages =
+                    (packages.with_package_sources(io.inner(), http.inner(), &unity_project))
+                        .await?;
+                let request = unity_project.resolve_request(packages.collection()).await?;
+                unity_project
+                    .apply_pending_changes(&installer, request)
+                    .await?;
+            }
+
+            Ok(restore_path)
+        })
+    })
+    .await
+}
+
+/// Returns the temporary directory next to the restore path to extract the backup to.
+fn restore_temp_path(restore_path: &Path) -> PathBuf {
+    let name = restore_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    restore_path.with_file_name(format!(".{name}.restoring-{}", uuid::Uuid::new_v4()))
+}
+
+async fn is_empty_or_missing_dir(path: &Path) -> io::Result<bool> {
+    match tokio::fs::read_dir(path).await {
+        Ok(mut read_dir) => Ok(read_dir.next_entry().await?.is_none()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(true),
+        Err(e) => Err(e),
+    }
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn environment_set_favorite_project(
@@ -847,6 +963,8 @@ pub async fn environment_create_project(
         let installer = PackageInstaller::new(io.inner(), Some(http.inner()));
 
         // finally, resolve the project folder
+        let packages =
+            (packages.with_package_sources(io.inner(), http.inner(), &unity_project)).await?;
         let request = unity_project.resolve_request(packages.collection()).await?;
         unity_project
             .apply_pending_changes(&installer, request)
@@ -63,6 +63,8 @@ pub struct TauriEnvironmentSettings {
     gui_compact: bool,
     unity_hub_access_method: UnityHubAccessMethod,
     exclude_vpm_packages_from_backup: bool,
+    automatic_backup: bool,
+    automatic_backup_keep_count: u32,
 }
 
 #[tauri::command]
@@ -85,6 +87,8 @@ pub async fn environment_get_settings(
     let gui_compact;
     let unity_hub_access_method;
     let exclude_vpm_packages_from_backup;
+    let automatic_backup;
+    let automatic_backup_keep_count;
 
     {
         let config = config.get();
@@ -96,6 +100,8 @@ pub async fn environment_get_settings(
         gui_compact = config.gui_compact;
         unity_hub_access_method = config.unity_hub_access_method;
         exclude_vpm_packages_from_backup = config.exclude_vpm_packages_from_backup;
+        automatic_backup = config.automatic_backup;
+        automatic_backup_keep_count = config.automatic_backup_keep_count;
     }
 
     {
@@ -140,6 +146,8 @@ pub async fn environment_get_settings(
         gui_compact,
         unity_hub_access_method,
         exclude_vpm_packages_from_backup,
+        automatic_backup,
+        automatic_backup_keep_count,
     })
 }
 
@@ -424,6 +432,39 @@ pub async fn environment_set_exclude_vpm_packages_from_backup(
     Ok(())
 }
 
+/// Returns whether to create a backup before changing the project
+#[tauri::command]
+#[specta::specta]
+pub async fn environment_automatic_backup(
+    config: State<'_, GuiConfigState>,
+) -> Result<bool, RustError> {
+    Ok(config.get().automatic_backup)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn environment_set_automatic_backup(
+    config: State<'_, GuiConfigState>,
+    automatic_backup: bool,
+) -> Result<(), RustError> {
+    let mut config = config.load_mut().await?;
+    config.automatic_backup = automatic_backup;
+    config.save().await?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn environment_set_automatic_backup_keep_count(
+    config: State<'_, GuiConfigState>,
+    automatic_backup_keep_count: u32,
+) -> Result<(), RustError> {
+    let mut config = config.load_mut().await?;
+    config.automatic_backup_keep_count = automatic_backup_keep_count;
+    config.save().await?;
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn environment_set_release_channel(
@@ -1,9 +1,13 @@
+use crate::backup_restore::{TauriBackupFormat, list_backups, remove_old_automatic_backups};
 use crate::commands::DEFAULT_UNITY_ARGUMENTS;
 use crate::commands::async_command::*;
 use crate::commands::prelude::*;
 use crate::compressor::TauriCreateBackupProgress;
-use crate::compressor::parallel_compress_zip;
-use crate::utils::{collect_notable_project_files_tree, project_backup_path};
+use crate::compressor::{compress_tar_zst, parallel_compress_zip};
+use crate::incremental_backup::{
+    create_incremental_backup, list_project_snapshots, prune_incremental_backups,
+};
+use crate::utils::project_backup_path;
 use async_zip::{Compression, DeflateOption};
 use log::{error, info, warn};
 use serde::Serialize;
@@ -16,10 +20,14 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 use vrc_get_vpm::environment::{PackageInstaller, VccDatabaseConnection};
 use vrc_get_vpm::io::DefaultEnvironmentIo;
+use vrc_get_vpm::repository::SignatureError;
 use vrc_get_vpm::unity_project::pending_project_changes::{
     ConflictInfo, PackageChange, RemoveReason,
 };
-use vrc_get_vpm::unity_project::{AddPackageOperation, PendingProjectChanges};
+use vrc_get_vpm::unity_project::{
+    AUTOMATIC_BACKUP_SUFFIX, AddPackageOperation, BACKUP_TIMESTAMP_FORMAT, BackupFormat,
+    PendingProjectChanges, collect_notable_project_files_tree,
+};
 use vrc_get_vpm::version::{StrictEqVersion, Version};
 
 #[derive(Serialize, specta::Type)]
@@ -132,6 +140,7 @@ struct TauriConflictInfo {
     packages: Vec<String>,
     unity_conflict: bool,
     unlocked_names: Vec<String>,
+    explanation: Option<String>,
 }
 
 impl From<&ConflictInfo> for TauriConflictInfo {
@@ -148,6 +157,7 @@ impl From<&ConflictInfo> for TauriConflictInfo {
                 .iter()
                 .map(|x| x.to_string())
                 .collect(),
+            explanation: value.explanation().map(|x| x.to_string()),
         }
     }
 }
@@ -180,6 +190,7 @@ pub async fn project_install_packages(
     packages: State<'_, PackagesState>,
     changes: State<'_, ChangesState>,
     io: State<'_, DefaultEnvironmentIo>,
+    http: State<'_, reqwest::Client>,
     project_path: String,
     installs: Vec<(String, String)>,
 ) -> Result<TauriPendingProjectChanges, RustError> {
@@ -189,6 +200,8 @@ pub async fn project_install_packages(
             "Internal Error: environment version mismatch",
         ));
     };
+    let unity_project = load_project(project_path).await?;
+    let packages = (packages.with_package_sources(&io, &http, &unity_project)).await?;
     let Some(installs) = installs
         .into_iter()
         .map(|(id, v)| Some((id, Version::from_str(&v).ok()?)))
@@ -213,8 +226,6 @@ pub async fn project_install_packages(
             return Err(RustError::unrecoverable_str("some packages not found"));
         };
 
-        let unity_project = load_project(project_path).await?;
-
         let allow_prerelease = settings.show_prerelease_packages();
 
         unity_project
@@ -242,11 +253,11 @@ pub async fn project_reinstall_packages(
     package_ids: Vec<String>,
 ) -> Result<TauriPendingProjectChanges, RustError> {
     let settings = settings.load(&io).await?;
+    let unity_project = load_project(project_path).await?;
     let packages = packages.load(&settings, &io, &http, app_handle).await?;
+    let packages = (packages.with_package_sources(&io, &http, &unity_project)).await?;
 
     changes!(packages, changes, |collection| {
-        let unity_project = load_project(project_path).await?;
-
         let package_ids = package_ids.iter().map(|x| x.as_str()).collect::<Vec<_>>();
 
         unity_project
@@ -267,10 +278,10 @@ pub async fn project_resolve(
     project_path: String,
 ) -> Result<TauriPendingProjectChanges, RustError> {
     let settings = settings.load(&io).await?;
+    let unity_project = load_project(project_path).await?;
     let packages = packages.load(&settings, &io, &http, app_handle).await?;
+    let packages = (packages.with_package_sources(&io, &http, &unity_project)).await?;
     changes!(packages, changes, |collection| {
-        let unity_project = load_project(project_path).await?;
-
         unity_project.resolve_request(collection).await?
     })
 }
@@ -310,9 +321,15 @@ pub async fn project_apply_pending_changes(
 
     let mut unity_project = load_project(project_path).await?;
 
-    unity_project
+    if let Err(e) = unity_project
         .apply_pending_changes(&installer, changes)
-        .await?;
+        .await
+    {
+        if let Some(e) = e.get_ref().and_then(|e| e.downcast_ref::<SignatureError>()) {
+            return Err(localizable_error!("projects:error:repository signature", err => e));
+        }
+        return Err(e.into());
+    }
 
     update_project_last_modified(&io, unity_project.project_dir()).await;
     Ok(())
@@ -339,8 +356,9 @@ pub async fn project_migrate_project_to_2022(
 ) -> Result<(), RustError> {
     {
         let settings = settings.load(io.inner()).await?;
-        let packages = packages.load(&settings, &io, &http, app_handle).await?;
         let mut unity_project = load_project(project_path).await?;
+        let packages = packages.load(&settings, &io, &http, app_handle).await?;
+        let packages = (packages.with_package_sources(&io, &http, &unity_project)).await?;
 
         let installer = PackageInstaller::new(io.inner(), Some(http.inner()));
 
@@ -445,9 +463,10 @@ pub async fn project_migrate_project_to_vpm(
     project_path: String,
 ) -> Result<(), RustError> {
     let settings = settings.load(&io).await?;
+    let mut unity_project = load_project(project_path).await?;
     let packages = packages.load(&settings, &io, &http, app_handle).await?;
+    let packages = (packages.with_package_sources(&io, &http, &unity_project)).await?;
 
-    let mut unity_project = load_project(project_path).await?;
     let installer = PackageInstaller::new(io.inner(), Some(http.inner()));
 
     unity_project
@@ -566,6 +585,35 @@ async fn create_backup_zip(
     Ok(())
 }
 
+async fn create_backup_tar_zst(
+    backup_path: &Path,
+    project_path: &Path,
+    exclude_vpm: bool,
+    ctx: AsyncCommandContext<TauriCreateBackupProgress>,
+) -> Result<(), RustError> {
+    info!("Collecting files to backup {}...", project_path.display());
+
+    let start = std::time::Instant::now();
+    let file_tree =
+        collect_notable_project_files_tree(PathBuf::from(project_path), exclude_vpm, true).await?;
+
+    let total_files = file_tree.count_all();
+
+    info!(
+        "Collecting files took {}, starting creating archive with {total_files} files...",
+        start.elapsed().as_secs_f64()
+    );
+
+    compress_tar_zst(file_tree, backup_path.to_path_buf(), ctx).await?;
+
+    info!(
+        "Creating backup archive for {} finished!",
+        project_path.display()
+    );
+
+    Ok(())
+}
+
 struct RemoveOnDrop<'a>(&'a Path);
 
 impl<'a> RemoveOnDrop<'a> {
@@ -584,6 +632,133 @@ impl Drop for RemoveOnDrop<'_> {
     }
 }
 
+/// Creates a backup of the project in the backup directory with the format.
+///
+/// Automatic backups have the `-auto` suffix to be removed by the retention policy.
+async fn backup_project(
+    backup_dir: &str,
+    backup_format: &str,
+    exclude_vpm: bool,
+    project_path: &str,
+    automatic: bool,
+    ctx: AsyncCommandContext<TauriCreateBackupProgress>,
+) -> Result<(), RustError> {
+    let project_name = Path::new(project_path)
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap();
+
+    let backup_name = format!(
+        "{project_name}-{timestamp}{suffix}",
+        project_name = project_name,
+        timestamp = chrono::Local::now().format(BACKUP_TIMESTAMP_FORMAT),
+        suffix = if automatic {
+            AUTOMATIC_BACKUP_SUFFIX
+        } else {
+            ""
+        },
+    );
+
+    super::create_dir_all_with_err(backup_dir).await?;
+
+    log::info!("backup project: {project_name} with {backup_format} (automatic: {automatic})");
+    let timer = std::time::Instant::now();
+
+    let backup_path: PathBuf;
+    let remove_on_drop: RemoveOnDrop;
+    match backup_format {
+        "zip-store" => {
+            backup_path = Path::new(backup_dir)
+                .join(&backup_name)
+                .with_added_extension(BackupFormat::Zip.extension());
+            remove_on_drop = RemoveOnDrop::new(&backup_path);
+            create_backup_zip(
+                &backup_path,
+                project_path.as_ref(),
+                Compression::Stored,
+                DeflateOption::Fast, // unused
+                exclude_vpm,
+                ctx,
+            )
+            .await?;
+        }
+        "default" | "zip-fast" => {
+            backup_path = Path::new(backup_dir)
+                .join(&backup_name)
+                .with_added_extension(BackupFormat::Zip.extension());
+            remove_on_drop = RemoveOnDrop::new(&backup_path);
+            create_backup_zip(
+                &backup_path,
+                project_path.as_ref(),
+                Compression::Deflate,
+                DeflateOption::Fast,
+                exclude_vpm,
+                ctx,
+            )
+            .await?;
+        }
+        "tar-zst" => {
+            backup_path = Path::new(backup_dir)
+                .join(&backup_name)
+                .with_added_extension(BackupFormat::TarZst.extension());
+            remove_on_drop = RemoveOnDrop::new(&backup_path);
+            create_backup_tar_zst(&backup_path, project_path.as_ref(), exclude_vpm, ctx).await?;
+        }
+        "incremental" => {
+            backup_path = Path::new(backup_dir)
+                .join(&backup_name)
+                .with_added_extension(BackupFormat::Incremental.extension());
+            remove_on_drop = RemoveOnDrop::new(&backup_path);
+            create_incremental_backup(
+                Path::new(backup_dir),
+                &backup_name,
+                project_path.as_ref(),
+                exclude_vpm,
+                ctx,
+            )
+            .await?;
+        }
+        "zip-best" => {
+            backup_path = Path::new(backup_dir)
+                .join(&backup_name)
+                .with_added_extension(BackupFormat::Zip.extension());
+            remove_on_drop = RemoveOnDrop::new(&backup_path);
+            create_backup_zip(
+                &backup_path,
+                project_path.as_ref(),
+                Compression::Deflate,
+                DeflateOption::Maximum,
+                exclude_vpm,
+                ctx,
+            )
+            .await?;
+        }
+        _ => {
+            warn!("unknown backup format: {backup_format}, using zip-fast");
+
+            backup_path = Path::new(backup_dir)
+                .join(&backup_name)
+                .with_added_extension(BackupFormat::Zip.extension());
+            remove_on_drop = RemoveOnDrop::new(&backup_path);
+            create_backup_zip(
+                &backup_path,
+                project_path.as_ref(),
+                Compression::Deflate,
+                DeflateOption::Fast,
+                exclude_vpm,
+                ctx,
+            )
+            .await?;
+        }
+    };
+
+    remove_on_drop.forget();
+
+    log::info!("backup finished in {:?}", timer.elapsed());
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn project_create_backup(
@@ -603,99 +778,180 @@ pub async fn project_create_backup(
         settings.maybe_save().await?;
 
         With::<TauriCreateBackupProgress>::continue_async(move |ctx| async move {
+            backup_project(
+                &backup_dir,
+                &backup_format,
+                exclude_vpm,
+                &project_path,
+                false,
+                ctx,
+            )
+            .await
+        })
+    })
+    .await
+}
+
+/// Creates an automatic backup before changing the project, and removes old automatic backups
+/// of the project beyond the configured count.
+#[tauri::command]
+#[specta::specta]
+pub async fn project_create_automatic_backup(
+    config: State<'_, GuiConfigState>,
+    settings: State<'_, SettingsState>,
+    io: State<'_, DefaultEnvironmentIo>,
+    window: Window,
+    channel: String,
+    project_path: String,
+) -> Result<AsyncCallResult<TauriCreateBackupProgress, ()>, RustError> {
+    async_command(channel, window, async {
+        let backup_format = config.get().backup_format.to_ascii_lowercase();
+        let exclude_vpm = config.get().exclude_vpm_packages_from_backup;
+        let keep_count = config.get().automatic_backup_keep_count;
+
+        let mut settings = settings.load_mut(io.inner()).await?;
+        let backup_dir = project_backup_path(&mut settings).to_string();
+        settings.maybe_save().await?;
+
+        With::<TauriCreateBackupProgress>::continue_async(move |ctx| async move {
+            backup_project(
+                &backup_dir,
+                &backup_format,
+                exclude_vpm,
+                &project_path,
+                true,
+                ctx,
+            )
+            .await?;
+
             let project_name = Path::new(&project_path)
                 .file_name()
                 .unwrap()
                 .to_str()
                 .unwrap();
+            remove_old_automatic_backups(Path::new(&backup_dir), project_name, keep_count as usize)
+                .await?;
 
-            let backup_name = format!(
-                "{project_name}-{timestamp}",
-                project_name = project_name,
-                timestamp = chrono::Local::now().format("%Y-%m-%dT%H-%M-%S"),
-            );
-
-            super::create_dir_all_with_err(&backup_dir).await?;
-
-            log::info!("backup project: {project_name} with {backup_format}");
-            let timer = std::time::Instant::now();
-
-            let backup_path: PathBuf;
-            let remove_on_drop: RemoveOnDrop;
-            match backup_format.as_str() {
-                "zip-store" => {
-                    backup_path = Path::new(&backup_dir)
-                        .join(&backup_name)
-                        .with_added_extension("zip");
-                    remove_on_drop = RemoveOnDrop::new(&backup_path);
-                    create_backup_zip(
-                        &backup_path,
-                        project_path.as_ref(),
-                        Compression::Stored,
-                        DeflateOption::Fast, // unused
-                        exclude_vpm,
-                        ctx,
-                    )
-                    .await?;
-                }
-                "default" | "zip-fast" => {
-                    backup_path = Path::new(&backup_dir)
-                        .join(&backup_name)
-                        .with_added_extension("zip");
-                    remove_on_drop = RemoveOnDrop::new(&backup_path);
-                    create_backup_zip(
-                        &backup_path,
-                        project_path.as_ref(),
-                        Compression::Deflate,
-                        DeflateOption::Fast,
-                        exclude_vpm,
-                        ctx,
-                    )
-                    .await?;
-                }
-                "zip-best" => {
-                    backup_path = Path::new(&backup_dir)
-                        .join(&backup_name)
-                        .with_added_extension("zip");
-                    remove_on_drop = RemoveOnDrop::new(&backup_path);
-                    create_backup_zip(
-                        &backup_path,
-                        project_path.as_ref(),
-                        Compression::Deflate,
-                        DeflateOption::Maximum,
-                        exclude_vpm,
-                        ctx,
-                    )
-                    .await?;
-                }
-                _ => {
-                    warn!("unknown backup format: {backup_format}, using zip-fast");
-
-                    backup_path = Path::new(&backup_dir)
-                        .join(&backup_name)
-                        .with_added_extension("zip");
-                    remove_on_drop = RemoveOnDrop::new(&backup_path);
-                    create_backup_zip(
-                        &backup_path,
-                        project_path.as_ref(),
-                        Compression::Deflate,
-                        DeflateOption::Fast,
-                        exclude_vpm,
-                        ctx,
-                    )
-                    .await?;
-                }
-            };
-
-            remove_on_drop.forget();
-
-            log::info!("backup finished in {:?}", timer.elapsed());
             Ok(())
         })
     })
     .await
 }
 
+#[derive(Serialize, specta::Type)]
+pub struct TauriProjectBackup {
+    name: String,
+    path: String,
+    format: TauriBackupFormat,
+    created_at: i64,
+    automatic: bool,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn project_list_backups(
+    settings: State<'_, SettingsState>,
+    io: State<'_, DefaultEnvironmentIo>,
+    project_path: String,
+) -> Result<Vec<TauriProjectBackup>, RustError> {
+    let mut settings = settings.load_mut(io.inner()).await?;
+    let backup_dir = project_backup_path(&mut settings).to_string();
+    settings.maybe_save().await?;
+
+    let project_name = Path::new(&project_path)
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap();
+
+    let backups = list_backups(Path::new(&backup_dir), project_name).await?;
+
+    Ok(backups
+        .iter()
+        .map(|x| TauriProjectBackup {
+            name: x.name().to_string(),
+            path: x.path.to_string_lossy().into_owned(),
+            format: x.format.into(),
+            created_at: x.created_at_millis(),
+            automatic: x.automatic,
+        })
+        .collect())
+}
+
+#[derive(Serialize, specta::Type)]
+pub struct TauriIncrementalBackup {
+    name: String,
+    created_at: i64,
+    file_count: usize,
+    total_size: u64,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn project_list_incremental_backups(
+    settings: State<'_, SettingsState>,
+    io: State<'_, DefaultEnvironmentIo>,
+    project_path: String,
+) -> Result<Vec<TauriIncrementalBackup>, RustError> {
+    let mut settings = settings.load_mut(io.inner()).await?;
+    let backup_dir = project_backup_path(&mut settings).to_string();
+    settings.maybe_save().await?;
+
+    let snapshots = list_project_snapshots(Path::new(&backup_dir), &project_path).await?;
+
+    Ok(snapshots
+        .iter()
+        .map(|x| TauriIncrementalBackup {
+            name: x.name().to_string(),
+            created_at: x.snapshot.created_at().timestamp_millis(),
+            file_count: x.snapshot.file_count(),
+            total_size: x.snapshot.total_size(),
+        })
+        .collect())
+}
+
+#[derive(Serialize, specta::Type)]
+pub struct TauriPruneIncrementalBackupsResult {
+    removed_backups: usize,
+    removed_chunks: usize,
+    freed_bytes: u64,
+}
+
+/// Removes old incremental backups of the project by count and/or age.
+/// The latest backup is always kept.
+#[tauri::command]
+#[specta::specta]
+pub async fn project_prune_incremental_backups(
+    settings: State<'_, SettingsState>,
+    io: State<'_, DefaultEnvironmentIo>,
+    project_path: String,
+    keep_count: Option<u32>,
+    max_age_days: Option<u32>,
+) -> Result<TauriPruneIncrementalBackupsResult, RustError> {
+    let mut settings = settings.load_mut(io.inner()).await?;
+    let backup_dir = project_backup_path(&mut settings).to_string();
+    settings.maybe_save().await?;
+
+    let result = prune_incremental_backups(
+        Path::new(&backup_dir),
+        &project_path,
+        keep_count.map(|x| x as usize),
+        max_age_days.map(|x| chrono::Duration::days(x.into())),
+    )
+    .await?;
+
+    info!(
+        "pruned {} backups and {} chunks of {project_path}, {} bytes freed",
+        result.removed_snapshots, result.removed_chunks, result.freed_bytes
+    );
+
+    Ok(TauriPruneIncrementalBackupsResult {
+        removed_backups: result.removed_snapshots,
+        removed_chunks: result.removed_chunks,
+        freed_bytes: result.freed_bytes,
+    })
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn project_get_custom_unity_args(
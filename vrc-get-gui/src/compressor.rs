@@ -1,15 +1,15 @@
 use crate::commands::AsyncCommandContext;
-use crate::utils::FileSystemTree;
 use async_zip::base::write::ZipFileWriter;
 use async_zip::{Compression, DeflateOption, ZipEntryBuilder};
 use serde::Serialize;
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use tokio::fs::File;
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio_util::compat::Compat;
+use vrc_get_vpm::unity_project::{FileSystemTree, write_backup_tar_zst};
 
 #[derive(Debug, Clone, Serialize, specta::Type)]
 pub struct TauriCreateBackupProgress {
@@ -18,6 +18,16 @@ pub struct TauriCreateBackupProgress {
     last_proceed: String,
 }
 
+impl TauriCreateBackupProgress {
+    pub fn new(total: usize, proceed: usize, last_proceed: String) -> Self {
+        Self {
+            total,
+            proceed,
+            last_proceed,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum CompressError {
     Io(std::io::Error),
@@ -310,3 +320,58 @@ pub(crate) async fn parallel_compress_zip(
 
     Ok(())
 }
+
+/// Sets the flag when dropped, to stop the blocking compression when the backup is cancelled.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Creates tar archive compressed with zstd.
+///
+/// The archive is written with [`write_backup_tar_zst`] in one blocking thread,
+/// which is stopped when the backup is cancelled.
+pub async fn compress_tar_zst(
+    file_tree: FileSystemTree,
+    dest_file: PathBuf,
+    ctx: AsyncCommandContext<TauriCreateBackupProgress>,
+) -> Result<(), CompressError> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _cancel_on_drop = CancelOnDrop(cancelled.clone());
+
+    tokio::task::spawn_blocking(move || {
+        let total = file_tree.count_all();
+
+        let file = std::fs::File::create(&dest_file)?;
+        let file = write_backup_tar_zst(&file_tree, file, |index, entry| {
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "backup cancelled",
+                ));
+            }
+
+            let _ = ctx.emit(TauriCreateBackupProgress {
+                total,
+                proceed: index + 1,
+                last_proceed: entry.relative_path().to_string(),
+            });
+
+            Ok(())
+        })?;
+
+        let _ = ctx.emit(TauriCreateBackupProgress {
+            total,
+            proceed: total,
+            last_proceed: "finalizing...".to_string(),
+        });
+
+        file.sync_all()
+    })
+    .await??;
+
+    Ok(())
+}
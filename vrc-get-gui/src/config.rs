@@ -46,6 +46,12 @@ pub struct GuiConfig {
     pub recent_project_locations: Vec<String>,
     #[serde(default)]
     pub exclude_vpm_packages_from_backup: bool,
+    /// Creates a backup before applying changes or migrating projects
+    #[serde(default)]
+    pub automatic_backup: bool,
+    /// The number of the automatic backups kept for each project
+    #[serde(default = "automatic_backup_keep_count_default")]
+    pub automatic_backup_keep_count: u32,
     /// the list of favorite templates by id
     /// those templates will be shown at the top of template selection on project creation
     /// or derived templates
@@ -87,6 +93,8 @@ impl Default for GuiConfig {
             unity_hub_access_method: UnityHubAccessMethod::ReadConfig,
             recent_project_locations: Vec::new(),
             exclude_vpm_packages_from_backup: false,
+            automatic_backup: false,
+            automatic_backup_keep_count: automatic_backup_keep_count_default(),
             favorite_templates: vec![],
             last_used_template: None,
         }
@@ -110,6 +118,10 @@ impl GuiConfig {
     }
 }
 
+fn automatic_backup_keep_count_default() -> u32 {
+    5
+}
+
 fn language_default() -> String {
     for locale in sys_locale::get_locales() {
         if locale.starts_with("en") {
@@ -0,0 +1,564 @@
+//! Incremental backups of projects with a content-addressed chunk store.
+//!
+//! Files are split into fixed-size chunks, and the chunks are stored in the `.chunks` folder
+//! of the backup directory compressed with zstd, with the SHA-256 of the uncompressed chunk as the file name.
+//! Each backup is a small snapshot manifest `{project_name}-{timestamp}.snapshot.json`
+//! referencing the chunks of each file, so only changed content is written to the disk.
+//!
+//! Backups and removals of chunks are serialized with the `.chunks.lock` file in the backup directory,
+//! so chunks being written by a backup are never removed before the snapshot referencing them is written.
+
+use crate::backup_restore::{TauriRestoreBackupProgress, join_backup_path};
+use crate::commands::AsyncCommandContext;
+use crate::compressor::TauriCreateBackupProgress;
+use chrono::{DateTime, Utc};
+use futures::prelude::*;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use vrc_get_vpm::unity_project::{BackupFormat, collect_notable_project_files_tree};
+
+pub(crate) const SNAPSHOT_EXTENSION: &str = BackupFormat::Incremental.extension();
+const CHUNKS_DIR: &str = ".chunks";
+const LOCK_FILE: &str = ".chunks.lock";
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    version: u32,
+    project_path: String,
+    created_at: DateTime<Utc>,
+    entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum SnapshotEntry {
+    /// The relative path ends with `/`
+    Directory { path: String },
+    File {
+        path: String,
+        size: u64,
+        /// The modification time in milliseconds since the unix epoch, used to skip reading unchanged files
+        modified: Option<i64>,
+        chunks: Vec<String>,
+    },
+}
+
+impl Snapshot {
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn file_count(&self) -> usize {
+        (self.entries.iter())
+            .filter(|x| matches!(x, SnapshotEntry::File { .. }))
+            .count()
+    }
+
+    pub fn total_size(&self) -> u64 {
+        (self.entries.iter())
+            .map(|x| match x {
+                SnapshotEntry::Directory { .. } => 0,
+                SnapshotEntry::File { size, .. } => *size,
+            })
+            .sum()
+    }
+}
+
+/// The snapshot with the path of the manifest file.
+pub(crate) struct SnapshotFile {
+    pub path: PathBuf,
+    pub snapshot: Snapshot,
+}
+
+impl SnapshotFile {
+    /// The name of the backup, `{project_name}-{timestamp}`
+    pub fn name(&self) -> &str {
+        let file_name = self.path.file_name().unwrap().to_str().unwrap();
+        file_name
+            .strip_suffix(SNAPSHOT_EXTENSION)
+            .and_then(|x| x.strip_suffix('.'))
+            .unwrap_or(file_name)
+    }
+}
+
+/// The exclusive lock of the chunk store, released on drop.
+struct ChunkStoreLock {
+    _file: std::fs::File,
+}
+
+impl ChunkStoreLock {
+    /// Waits for other backups or removals of chunks to finish and locks the chunk store.
+    async fn acquire(backup_dir: &Path) -> io::Result<Self> {
+        tokio::fs::create_dir_all(backup_dir).await?;
+        let path = backup_dir.join(LOCK_FILE);
+        tokio::task::spawn_blocking(move || {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(path)?;
+            file.lock()?;
+            Ok(Self { _file: file })
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+fn modified_millis(metadata: &std::fs::Metadata) -> Option<i64> {
+    let modified = metadata.modified().ok()?;
+    let duration = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some(duration.as_millis() as i64)
+}
+
+/// Reads the file and stores the chunks not in the store yet.
+fn store_file(chunks_dir: &Path, path: &Path) -> io::Result<Vec<String>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut chunks = vec![];
+
+    loop {
+        // fill the buffer since read may return partial chunk
+        let mut filled = 0;
+        while filled < CHUNK_SIZE {
+            match file.read(&mut buffer[filled..])? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        if filled == 0 {
+            break;
+        }
+
+        let data = &buffer[..filled];
+        let hash = hex::encode(Sha256::digest(data));
+        let chunk_path = chunk_path(chunks_dir, &hash);
+
+        if !chunk_path.exists() {
+            std::fs::create_dir_all(chunk_path.parent().unwrap())?;
+            let compressed = zstd::bulk::compress(data, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            // write to temporary file and rename to prevent partially written chunks
+            let temp_path = chunk_path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+            std::fs::write(&temp_path, compressed)?;
+            std::fs::rename(&temp_path, &chunk_path)?;
+        }
+
+        chunks.push(hash);
+
+        if filled < CHUNK_SIZE {
+            break;
+        }
+    }
+
+    Ok(chunks)
+}
+
+fn chunk_path(chunks_dir: &Path, hash: &str) -> PathBuf {
+    chunks_dir.join(&hash[..2]).join(hash)
+}
+
+/// Returns `true` if all the chunks are in the store.
+async fn has_all_chunks(chunks_dir: &Path, chunks: &[String]) -> io::Result<bool> {
+    for hash in chunks {
+        if !tokio::fs::try_exists(chunk_path(chunks_dir, hash)).await? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Lists the snapshots in the backup directory. Snapshots failed to load are skipped.
+pub(crate) async fn list_snapshots(backup_dir: &Path) -> io::Result<Vec<SnapshotFile>> {
+    read_snapshots(backup_dir, true).await
+}
+
+/// Reads the snapshots in the backup directory.
+///
+/// If `skip_broken` is `false`, fails if any snapshot cannot be loaded.
+async fn read_snapshots(backup_dir: &Path, skip_broken: bool) -> io::Result<Vec<SnapshotFile>> {
+    let mut read_dir = match tokio::fs::read_dir(backup_dir).await {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e),
+    };
+
+    let mut snapshots = vec![];
+    while let Some(entry) = read_dir.next_entry().await? {
+        let path = entry.path();
+        let is_snapshot = (path.file_name().and_then(|x| x.to_str()))
+            .is_some_and(|x| x.ends_with(&format!(".{SNAPSHOT_EXTENSION}")));
+        if !is_snapshot {
+            continue;
+        }
+
+        let snapshot = match tokio::fs::read(&path).await {
+            Ok(json) => match serde_json::from_slice::<Snapshot>(&json) {
+                Ok(snapshot) => snapshot,
+                Err(e) if skip_broken => {
+                    log::warn!("failed to parse snapshot {}: {e}", path.display());
+                    continue;
+                }
+                Err(e) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("failed to parse snapshot {}: {e}", path.display()),
+                    ));
+                }
+            },
+            Err(e) if skip_broken => {
+                log::warn!("failed to read snapshot {}: {e}", path.display());
+                continue;
+            }
+            Err(e) => {
+                return Err(io::Error::new(
+                    e.kind(),
+                    format!("failed to read snapshot {}: {e}", path.display()),
+                ));
+            }
+        };
+
+        snapshots.push(SnapshotFile { path, snapshot });
+    }
+
+    Ok(snapshots)
+}
+
+/// Lists the snapshots of the project, newest first.
+pub(crate) async fn list_project_snapshots(
+    backup_dir: &Path,
+    project_path: &str,
+) -> io::Result<Vec<SnapshotFile>> {
+    let mut snapshots = list_snapshots(backup_dir).await?;
+    snapshots.retain(|x| x.snapshot.project_path == project_path);
+    snapshots.sort_by_key(|x| std::cmp::Reverse(x.snapshot.created_at));
+    Ok(snapshots)
+}
+
+pub(crate) async fn create_incremental_backup(
+    backup_dir: &Path,
+    backup_name: &str,
+    project_path: &Path,
+    exclude_vpm: bool,
+    ctx: AsyncCommandContext<TauriCreateBackupProgress>,
+) -> io::Result<()> {
+    let project_path_str = project_path.to_string_lossy().into_owned();
+
+    info!("Collecting files to backup {}...", project_path.display());
+
+    let _ = ctx.emit(TauriCreateBackupProgress::new(
+        0,
+        0,
+        "Collecting files".to_string(),
+    ));
+
+    let _lock = ChunkStoreLock::acquire(backup_dir).await?;
+
+    let tree =
+        collect_notable_project_files_tree(project_path.to_path_buf(), exclude_vpm, true).await?;
+    let total = tree.count_all();
+
+    // the files not changed since the latest snapshot are not read again
+    let previous = list_project_snapshots(backup_dir, &project_path_str)
+        .await?
+        .into_iter()
+        .next()
+        .map(|x| {
+            (x.snapshot.entries.into_iter())
+                .filter_map(|entry| match entry {
+                    SnapshotEntry::File {
+                        path,
+                        size,
+                        modified: Some(modified),
+                        chunks,
+                    } => Some((path, (size, modified, chunks))),
+                    _ => None,
+                })
+                .collect::<HashMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    info!("Creating incremental backup with {total} files...");
+
+    let chunks_dir = backup_dir.join(CHUNKS_DIR);
+    tokio::fs::create_dir_all(&chunks_dir).await?;
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut proceed = 0;
+
+    let entries = stream::iter(tree.recursive())
+        .map(|entry| {
+            let relative_path = entry.relative_path().to_string();
+            let absolute_path = entry.absolute_path().to_path_buf();
+            let is_dir = entry.is_dir();
+            let chunks_dir = chunks_dir.clone();
+            let previous = &previous;
+            async move {
+                if is_dir {
+                    return Ok(SnapshotEntry::Directory {
+                        path: relative_path,
+                    });
+                }
+
+                let metadata = tokio::fs::metadata(&absolute_path).await?;
+                let size = metadata.len();
+                let modified = modified_millis(&metadata);
+
+                let unchanged = match (previous.get(&relative_path), modified) {
+                    (Some((prev_size, prev_modified, chunks)), Some(modified))
+                        if *prev_size == size && *prev_modified == modified =>
+                    {
+                        Some(chunks)
+                    }
+                    _ => None,
+                };
+
+                // the chunks may have been removed from the store, so the file is read again in that case
+                let chunks = match unchanged {
+                    Some(chunks) if has_all_chunks(&chunks_dir, chunks).await? => chunks.clone(),
+                    _ => {
+                        tokio::task::spawn_blocking(move || store_file(&chunks_dir, &absolute_path))
+                            .await
+                            .map_err(io::Error::other)??
+                    }
+                };
+
+                Ok::<_, io::Error>(SnapshotEntry::File {
+                    path: relative_path,
+                    size,
+                    modified,
+                    chunks,
+                })
+            }
+        })
+        .buffered(threads)
+        .inspect_ok(|entry| {
+            proceed += 1;
+            let path = match entry {
+                SnapshotEntry::Directory { path } => path,
+                SnapshotEntry::File { path, .. } => path,
+            };
+            let _ = ctx.emit(TauriCreateBackupProgress::new(total, proceed, path.clone()));
+        })
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let _ = ctx.emit(TauriCreateBackupProgress::new(
+        total,
+        total,
+        "finalizing...".to_string(),
+    ));
+
+    let snapshot = Snapshot {
+        version: SNAPSHOT_VERSION,
+        project_path: project_path_str,
+        created_at: Utc::now(),
+        entries,
+    };
+
+    let snapshot_path = backup_dir
+        .join(backup_name)
+        .with_added_extension(SNAPSHOT_EXTENSION);
+    let json = serde_json::to_vec(&snapshot)?;
+    // write to temporary file and rename so partially written snapshots are never loaded
+    let temp_path = snapshot_path.with_added_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+    if let Err(e) = write_and_rename(&temp_path, &snapshot_path, &json).await {
+        tokio::fs::remove_file(&temp_path).await.ok();
+        return Err(e);
+    }
+
+    info!(
+        "Creating incremental backup for {} finished!",
+        project_path.display()
+    );
+
+    Ok(())
+}
+
+async fn write_and_rename(temp_path: &Path, path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut file = tokio::fs::File::create(temp_path).await?;
+    tokio::io::AsyncWriteExt::write_all(&mut file, data).await?;
+    file.sync_all().await?;
+    drop(file);
+    tokio::fs::rename(temp_path, path).await
+}
+
+/// Writes the file by concatenating the decompressed chunks, verifying the hash of each chunk.
+fn restore_file(chunks_dir: &Path, path: &Path, chunks: &[String]) -> io::Result<()> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for hash in chunks {
+        let compressed = std::fs::read(chunk_path(chunks_dir, hash))?;
+        let data = zstd::stream::decode_all(compressed.as_slice())?;
+        if hex::encode(Sha256::digest(&data)) != *hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("chunk {hash} is corrupted"),
+            ));
+        }
+        file.write_all(&data)?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// Restores the files in the snapshot to the restore path.
+pub(crate) async fn restore_incremental_backup(
+    snapshot_path: &Path,
+    restore_path: &Path,
+    ctx: &AsyncCommandContext<TauriRestoreBackupProgress>,
+) -> io::Result<()> {
+    let snapshot: Snapshot = serde_json::from_slice(&tokio::fs::read(snapshot_path).await?)?;
+    if snapshot.version > SNAPSHOT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported snapshot version: {}", snapshot.version),
+        ));
+    }
+
+    let chunks_dir = snapshot_path.parent().unwrap().join(CHUNKS_DIR);
+    let total = snapshot.entries.len();
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut proceed = 0;
+
+    // directories are created first so files can be written in parallel
+    for entry in &snapshot.entries {
+        if let SnapshotEntry::Directory { path } = entry {
+            tokio::fs::create_dir_all(join_backup_path(restore_path, path)?).await?;
+        }
+    }
+
+    stream::iter(snapshot.entries)
+        .map(|entry| {
+            let chunks_dir = chunks_dir.clone();
+            async move {
+                match entry {
+                    SnapshotEntry::Directory { path } => Ok(path),
+                    SnapshotEntry::File { path, chunks, .. } => {
+                        let absolute_path = join_backup_path(restore_path, &path)?;
+                        if let Some(parent) = absolute_path.parent() {
+                            tokio::fs::create_dir_all(parent).await?;
+                        }
+                        tokio::task::spawn_blocking(move || {
+                            restore_file(&chunks_dir, &absolute_path, &chunks)
+                        })
+                        .await
+                        .map_err(io::Error::other)??;
+                        Ok::<_, io::Error>(path)
+                    }
+                }
+            }
+        })
+        .buffered(threads)
+        .try_for_each(|path| {
+            proceed += 1;
+            let _ = ctx.emit(TauriRestoreBackupProgress::new(total, proceed, path));
+            future::ok(())
+        })
+        .await
+}
+
+pub(crate) struct PruneResult {
+    pub removed_snapshots: usize,
+    pub removed_chunks: usize,
+    pub freed_bytes: u64,
+}
+
+/// Removes the snapshots of the project exceeding `keep_count` or older than `max_age`,
+/// and removes the chunks no longer referenced by any snapshot in the backup directory.
+///
+/// The latest snapshot of the project is always kept.
+pub(crate) async fn prune_incremental_backups(
+    backup_dir: &Path,
+    project_path: &str,
+    keep_count: Option<usize>,
+    max_age: Option<chrono::Duration>,
+) -> io::Result<PruneResult> {
+    let _lock = ChunkStoreLock::acquire(backup_dir).await?;
+
+    let now = Utc::now();
+    let snapshots = list_project_snapshots(backup_dir, project_path).await?;
+
+    let mut removed_snapshots = 0;
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        if index == 0 {
+            continue;
+        }
+        let exceeds_count = keep_count.is_some_and(|count| index >= count);
+        let too_old = max_age.is_some_and(|max_age| now - snapshot.snapshot.created_at > max_age);
+        if exceeds_count || too_old {
+            info!("removing snapshot {}", snapshot.path.display());
+            tokio::fs::remove_file(&snapshot.path).await?;
+            removed_snapshots += 1;
+        }
+    }
+
+    let (removed_chunks, freed_bytes) = remove_unreferenced_chunks_locked(backup_dir).await?;
+
+    Ok(PruneResult {
+        removed_snapshots,
+        removed_chunks,
+        freed_bytes,
+    })
+}
+
+/// Removes the chunks not referenced by any snapshot.
+///
+/// Snapshots of all projects share the chunk store, so this must be called after removing snapshots.
+/// Nothing is removed if any snapshot cannot be loaded, since the chunks may be referenced by the snapshot.
+/// returns: the number of removed chunks and the freed bytes
+pub(crate) async fn remove_unreferenced_chunks(backup_dir: &Path) -> io::Result<(usize, u64)> {
+    let _lock = ChunkStoreLock::acquire(backup_dir).await?;
+    remove_unreferenced_chunks_locked(backup_dir).await
+}
+
+async fn remove_unreferenced_chunks_locked(backup_dir: &Path) -> io::Result<(usize, u64)> {
+    // chunks written after this are kept even if not referenced
+    // since file locks may not work on some file systems like network drives
+    let started_at = SystemTime::now();
+
+    let referenced = (read_snapshots(backup_dir, false).await?.into_iter())
+        .flat_map(|x| x.snapshot.entries)
+        .flat_map(|entry| match entry {
+            SnapshotEntry::Directory { .. } => vec![],
+            SnapshotEntry::File { chunks, .. } => chunks,
+        })
+        .collect::<HashSet<_>>();
+
+    let mut removed_chunks = 0;
+    let mut freed_bytes = 0;
+
+    let mut prefix_dirs = match tokio::fs::read_dir(backup_dir.join(CHUNKS_DIR)).await {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((0, 0)),
+        Err(e) => return Err(e),
+    };
+    while let Some(prefix_dir) = prefix_dirs.next_entry().await? {
+        if !prefix_dir.file_type().await?.is_dir() {
+            continue;
+        }
+        let mut chunks = tokio::fs::read_dir(prefix_dir.path()).await?;
+        while let Some(chunk) = chunks.next_entry().await? {
+            let name = chunk.file_name();
+            if name.to_str().is_some_and(|x| referenced.contains(x)) {
+                continue;
+            }
+            let metadata = chunk.metadata().await?;
+            if metadata.modified().is_ok_and(|x| x >= started_at) {
+                continue;
+            }
+            // this also removes temporary files left by interrupted backups
+            freed_bytes += metadata.len();
+            tokio::fs::remove_file(chunk.path()).await?;
+            removed_chunks += 1;
+        }
+    }
+
+    Ok((removed_chunks, freed_bytes))
+}
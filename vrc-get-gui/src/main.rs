@@ -4,10 +4,12 @@
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 
+mod backup_restore;
 mod commands;
 mod compressor;
 mod config;
 mod deep_link_support;
+mod incremental_backup;
 mod logging;
 mod templates;
 
@@ -8,7 +8,8 @@ use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use vrc_get_vpm::environment::{PackageCollection, Settings};
 use vrc_get_vpm::io::DefaultEnvironmentIo;
-use vrc_get_vpm::{PackageCollection as _, PackageInfo};
+use vrc_get_vpm::package_source::FetchSourceError;
+use vrc_get_vpm::{PackageCollection as _, PackageInfo, UnityProject};
 use yoke::{Yoke, Yokeable};
 
 #[derive(Yokeable)]
@@ -159,14 +160,6 @@ impl PackagesState {
             PackageCollection::load(settings, io, Some(http)).await?
         };
 
-        fn collect_packages(
-            collection: PackageCollection,
-        ) -> Yoke<YokeData<'static>, Arc<PackageCollection>> {
-            Yoke::<YokeData<'static>, _>::attach_to_cart(Arc::new(collection), |x| {
-                YokeData::new(x.get_all_packages().collect())
-            })
-        }
-
         let arc = Arc::new(PackagesStateInner::new(collect_packages(collection)));
         self.inner.store(Some(arc.clone()));
 
@@ -191,12 +184,54 @@ impl PackagesState {
     }
 }
 
+fn collect_packages(collection: PackageCollection) -> Data {
+    Yoke::<YokeData<'static>, _>::attach_to_cart(Arc::new(collection), |x| {
+        YokeData::new(x.get_all_packages().collect())
+    })
+}
+
+/// Fetches the packages from the sources in vpm-manifest.json of the project,
+/// and returns the packages with them if the project has any source.
+///
+/// The fetched packages are only for the project so the shared state is not updated.
+async fn with_package_sources(
+    inner: Arc<PackagesStateInner>,
+    io: &DefaultEnvironmentIo,
+    http: &reqwest::Client,
+    project: &UnityProject,
+) -> Result<Arc<PackagesStateInner>, FetchSourceError> {
+    if project.package_sources().next().is_none() {
+        return Ok(inner);
+    }
+
+    let mut collection = PackageCollection::clone(inner.data.backing_cart());
+    collection
+        .fetch_package_sources(io, Some(http), project)
+        .await?;
+    Ok(Arc::new(PackagesStateInner::new(collect_packages(
+        collection,
+    ))))
+}
+
 pub struct PackagesStateRef<'a> {
     arc: Arc<PackagesStateInner>,
     _phantom_data: PhantomData<&'a ()>,
 }
 
 impl PackagesStateRef<'_> {
+    /// Returns the packages with the packages from the sources in vpm-manifest.json of the project.
+    pub async fn with_package_sources(
+        self,
+        io: &DefaultEnvironmentIo,
+        http: &reqwest::Client,
+        project: &UnityProject,
+    ) -> Result<Self, FetchSourceError> {
+        Ok(Self {
+            arc: with_package_sources(self.arc, io, http, project).await?,
+            _phantom_data: PhantomData,
+        })
+    }
+
     pub fn collection(&self) -> &PackageCollection {
         self.arc.data.backing_cart()
     }
@@ -228,6 +263,19 @@ pub struct PackagesVersionRef<'a> {
 
 #[allow(dead_code)]
 impl PackagesVersionRef<'_> {
+    /// Returns the packages with the packages from the sources in vpm-manifest.json of the project.
+    pub async fn with_package_sources(
+        self,
+        io: &DefaultEnvironmentIo,
+        http: &reqwest::Client,
+        project: &UnityProject,
+    ) -> Result<Self, FetchSourceError> {
+        Ok(Self {
+            arc: with_package_sources(self.arc, io, http, project).await?,
+            _phantom_data: PhantomData,
+        })
+    }
+
     pub fn collection(&self) -> &PackageCollection {
         self.arc.data.backing_cart()
     }
@@ -1,4 +1,3 @@
-use async_compression::futures::bufread::GzipDecoder;
 use fs_extra::error::ErrorKind;
 use futures::io::BufReader;
 use futures::*;
@@ -6,15 +5,14 @@ use indexmap::IndexMap;
 use indexmap::map::Entry;
 use log::{info, warn};
 use std::collections::{HashMap, HashSet};
-use std::mem::forget;
 use std::path::{Path, PathBuf};
 use std::{fmt, io};
 use tokio_util::compat::*;
 use vrc_get_vpm::io::{DefaultEnvironmentIo, DefaultProjectIo, DirEntry, IoTrait};
 
-use crate::utils::TarArchive;
 pub use alcom_template::*;
 use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::unity_project::ImportConflictHandling;
 use vrc_get_vpm::version::{DependencyRange, UnityVersion, VersionRange};
 
 pub mod alcom_template;
@@ -412,10 +410,21 @@ pub async fn create_project(
     }
 
     // extract unity packages
-    for unity_package in template_info.unity_packages {
-        info!("extracting unity package: {}", unity_package.display());
-        let unity_package = tokio::fs::File::open(unity_package).await?;
-        import_unitypackage(project_path, &mut BufReader::new(unity_package.compat())).await?;
+    if !template_info.unity_packages.is_empty() {
+        // the project is loaded again later since unitypackages may change the manifests
+        let project = UnityProject::load(DefaultProjectIo::new(project_path.into())).await?;
+        for unity_package in template_info.unity_packages {
+            info!("extracting unity package: {}", unity_package.display());
+            let unity_package = tokio::fs::File::open(unity_package).await?;
+            project
+                .import_unitypackage(
+                    BufReader::new(unity_package.compat()),
+                    ImportConflictHandling::Replace,
+                    false,
+                )
+                .await
+                .map_err(io::Error::other)?;
+        }
     }
 
     // update ProjectSettings.asset
@@ -497,248 +506,6 @@ pub async fn create_project(
     }
 }
 
-/// Imports the unitypackage at the `unitypackage`
-///
-/// This importer holds metadata on the memory and extracts the data to `Library/.temp-dir.<random>/<guid>`,
-/// and then move to corresponding directory
-pub async fn import_unitypackage(
-    project_path: &Path,
-    unitypackage: &mut (dyn AsyncBufRead + Unpin + Send + Sync),
-) -> io::Result<()> {
-    let temp_dir = {
-        let library = project_path.join("Library");
-        let _ = tokio::fs::create_dir(&library).await;
-        loop {
-            let temp = library.join(format!(".temp-dir.{}", uuid::Uuid::new_v4().as_simple()));
-            match tokio::fs::create_dir(&temp).await {
-                Ok(_) => break temp,
-                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
-                Err(e) => return Err(e),
-            }
-        }
-    };
-
-    /// This struct is responsible for removing the project_path synchronously when the
-    /// main part panics, or asynchronously when error, or finishes successfully
-    struct Scope<'a> {
-        temp_dir: &'a Path,
-    }
-
-    impl Scope<'_> {
-        async fn drop_async(self) -> io::Result<()> {
-            let temp_dir = self.temp_dir;
-            forget(self);
-            tokio::fs::remove_dir_all(temp_dir).await
-        }
-    }
-
-    impl Drop for Scope<'_> {
-        fn drop(&mut self) {
-            // ignore error here; we won't double panic
-            let _ = std::fs::remove_dir_all(self.temp_dir);
-        }
-    }
-
-    let scope = Scope {
-        temp_dir: &temp_dir,
-    };
-
-    let result = import_unitypackage_impl(project_path, &temp_dir, unitypackage).await;
-    let remove_result = scope.drop_async().await;
-
-    result.and(remove_result)
-}
-
-// the main part of import_unitypackage
-// This part does almost all thing of import_unitypackage except for temp_dir management.
-async fn import_unitypackage_impl(
-    project_path: &Path,
-    temp_dir: &Path,
-    unitypackage: &mut (dyn AsyncBufRead + Unpin + Send + Sync),
-) -> io::Result<()> {
-    #[derive(Default)]
-    struct UnityPackageEntry {
-        // empty means not exists.
-        metadata: Vec<u8>,
-        // empty means not exists.
-        pathname: String,
-        has_file: bool,
-    }
-
-    let gunzip = GzipDecoder::new(unitypackage);
-    let mut untar = TarArchive::new(gunzip);
-
-    type GuidBuf = [u8; 32];
-    let mut entries = HashMap::<GuidBuf, UnityPackageEntry>::new();
-
-    while let Some(mut tar_entry) = untar.next_entry().await? {
-        let path = tar_entry.header().path_bytes();
-        let path = path.as_ref();
-        let mut components = Vec::new();
-        for component in path.split(|&b| b == b'/' || b == b'\\') {
-            match component {
-                b"" | b"." => (), // no-op
-                b".." => {
-                    components.pop();
-                }
-                c => components.push(c),
-            }
-        }
-
-        let [guid, filename] = components[..] else {
-            continue;
-        };
-        let Ok(guid) = GuidBuf::try_from(guid) else {
-            continue;
-        };
-        if !guid.iter().all(|x| matches!(x, b'a'..=b'f' | b'0'..=b'9')) {
-            // the GUID is not guid
-            continue;
-        }
-        match filename {
-            b"asset" => {
-                // The contents of the asset.
-                let package_entry = entries.entry(guid).or_default();
-                if package_entry.has_file {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "duplicate asset for {guid}",
-                            guid = std::str::from_utf8(&guid).unwrap()
-                        ),
-                    ));
-                }
-                let temp = temp_dir.join(std::str::from_utf8(&guid).unwrap());
-                futures::io::copy(
-                    tar_entry,
-                    &mut tokio::fs::File::create(&temp).await?.compat(),
-                )
-                .await?;
-                package_entry.has_file = true;
-            }
-            b"asset.meta" => {
-                // The metadata of the asset.
-                let package_entry = entries.entry(guid).or_default();
-                if !package_entry.metadata.is_empty() {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "duplicate asset.meta for {guid}",
-                            guid = std::str::from_utf8(&guid).unwrap()
-                        ),
-                    ));
-                }
-                tar_entry.read_to_end(&mut package_entry.metadata).await?;
-            }
-            b"pathname" => {
-                // The pathname of the asset.
-                let package_entry = entries.entry(guid).or_default();
-                if !package_entry.pathname.is_empty() {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "duplicate pathname for {guid}",
-                            guid = std::str::from_utf8(&guid).unwrap(),
-                        ),
-                    ));
-                }
-                let mut buffer = Vec::new();
-                tar_entry.read_to_end(&mut buffer).await?;
-                let pathname = String::from_utf8(buffer).map_err(|_| {
-                    io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "non-utf8 pathname for {guid}",
-                            guid = std::str::from_utf8(&guid).unwrap(),
-                        ),
-                    )
-                })?;
-                // Those are filename-banned characters for windows except for portable path separator '/'
-                if pathname.contains(['<', '>', ':', '"', '|', '?', '*', '\0']) {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "bad pathname for {guid} (banned chars)",
-                            guid = std::str::from_utf8(&guid).unwrap(),
-                        ),
-                    ));
-                }
-                if pathname.split('/').any(|c| matches!(c, "" | "." | "..")) {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "bad pathname for {guid} (possible path traversal)",
-                            guid = std::str::from_utf8(&guid).unwrap(),
-                        ),
-                    ));
-                }
-                // ignoring paths for non-Assets / Packages
-                if !pathname.starts_with("Assets/") && !pathname.starts_with("Packages/") {
-                    warn!(
-                        "asset is not under Assets or Packages: {guid}: {pathname:?}",
-                        guid = std::str::from_utf8(&guid).unwrap()
-                    );
-                    continue;
-                }
-                // https://github.com/vrc-get/vrc-get/issues/2634
-                // https://issuetracker.unity3d.com/product/unity/issues/guid/UUM-132869
-                // if there is '\n' in their pathname, remove after last '\n'
-                let pathname = if let Some(index) = pathname.rfind('\n') {
-                    let mut pathname = pathname;
-                    pathname.replace_range(index.., "");
-                    pathname
-                } else {
-                    pathname
-                };
-                package_entry.pathname = pathname;
-            }
-            _ => continue, // non unitypackage entry
-        }
-    }
-
-    // validate entire entries
-    for (guid, entry) in &entries {
-        if entry.pathname.is_empty() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "no pathname for {guid}",
-                    guid = std::str::from_utf8(&guid[..]).unwrap()
-                ),
-            ));
-        }
-        if entry.metadata.is_empty() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "no metadata for {path} ({guid})",
-                    path = entry.pathname,
-                    guid = std::str::from_utf8(&guid[..]).unwrap()
-                ),
-            ));
-        }
-    }
-
-    // actually extract the archive
-    for (guid, entry) in &entries {
-        let path = project_path.join(&entry.pathname);
-        let meta_path = path.with_added_extension("meta");
-        let temp_path = temp_dir.join(std::str::from_utf8(&guid[..]).unwrap());
-        if entry.has_file {
-            tokio::fs::create_dir_all(path.parent().unwrap()).await?;
-            try_join!(
-                tokio::fs::write(meta_path, &entry.metadata),
-                tokio::fs::rename(temp_path, &path),
-            )?;
-        } else {
-            tokio::fs::create_dir_all(path).await?;
-            try_join!(tokio::fs::write(meta_path, &entry.metadata))?;
-        }
-    }
-
-    Ok(())
-}
-
 async fn update_project_name_and_guid(path: &Path, project_name: &str) -> io::Result<()> {
     let settings_path = path.join("ProjectSettings/ProjectSettings.asset");
     let mut settings_file = match tokio::fs::File::options()
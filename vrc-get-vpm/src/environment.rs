@@ -11,12 +11,13 @@ mod package_installer;
 #[cfg(feature = "experimental-project-management")]
 mod project_management;
 mod settings;
+mod source_packages;
 #[cfg(feature = "experimental-unity-management")]
 mod unity_management;
 
 use crate::io;
-use crate::repository::RemoteRepository;
 use crate::repository::local::LocalCachedRepository;
+use crate::repository::{RemoteRepository, SignatureError};
 use crate::traits::HttpClient;
 use crate::utils::to_vec_pretty_os_eol;
 use futures::prelude::*;
@@ -48,27 +49,39 @@ const CURATED_URL_STR: &str = "https://packages.vrchat.com/curated?download";
 const LOCAL_CURATED_PATH: &str = "Repos/vrc-curated.json";
 const REPO_CACHE_FOLDER: &str = "Repos";
 
+/// Adds the remote repository to the settings and caches the repository.
+///
+/// If `public_key` is specified, the key is pinned for the repository, and the repository is
+/// added only if the signature of the repository is verified with the key.
 pub async fn add_remote_repo(
     settings: &mut Settings,
     url: Url,
     name: Option<&str>,
     headers: IndexMap<Box<str>, Box<str>>,
+    public_key: Option<&str>,
     io: &DefaultEnvironmentIo,
     http: &impl HttpClient,
 ) -> Result<(), AddRepositoryErr> {
-    let (remote_repo, etag) = RemoteRepository::download(http, &url, &headers).await?;
+    let (remote_repo, etag) = match public_key {
+        None => RemoteRepository::download(http, &url, &headers).await?,
+        Some(public_key) => {
+            let Some((remote_repo, etag, verified)) =
+                RemoteRepository::download_signed(http, &url, &headers, None, public_key).await?
+            else {
+                unreachable!("downloading without etag should must return Ok(Some)")
+            };
+            verified.map_err(AddRepositoryErr::Signature)?;
+            (remote_repo, etag)
+        }
+    };
 
     if !settings.can_add_remote_repo(&url, &remote_repo) {
         return Err(AddRepositoryErr::AlreadyAdded);
     }
 
     let mut local_cache = LocalCachedRepository::new(remote_repo, headers.clone());
-    if let Some(etag) = etag {
-        local_cache
-            .vrc_get
-            .get_or_insert_with(Default::default)
-            .etag = etag;
-    }
+    local_cache.set_etag(etag);
+    local_cache.set_verified_public_key(public_key.map(Into::into));
 
     io.create_dir_all(REPO_CACHE_FOLDER.as_ref()).await?;
     let file_name = write_new_repo(&local_cache, io).await?;
@@ -78,6 +91,9 @@ pub async fn add_remote_repo(
         settings.add_remote_repo(&url, name, headers, local_cache.repo(), &repo_path),
         "add_remote_repo failed unexpectedly"
     );
+    if public_key.is_some() {
+        settings.set_repo_public_key(|x| x.url() == Some(&url), public_key);
+    }
 
     Ok(())
 }
@@ -230,6 +246,8 @@ pub enum AddRepositoryErr {
     Io(io::Error),
     AlreadyAdded,
     OfflineMode,
+    /// The signature of the repository is not verified with the public key.
+    Signature(SignatureError),
 }
 
 impl fmt::Display for AddRepositoryErr {
@@ -240,6 +258,9 @@ impl fmt::Display for AddRepositoryErr {
             AddRepositoryErr::OfflineMode => {
                 f.write_str("you can't add remote repo in offline mode")
             }
+            AddRepositoryErr::Signature(e) => {
+                write!(f, "verifying the signature of the repository: {e}")
+            }
         }
     }
 }
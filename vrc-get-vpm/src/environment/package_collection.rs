@@ -1,10 +1,17 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 use crate::PackageCollection as _;
+use crate::environment::package_installer::is_package_cached;
 use crate::environment::{RepoHolder, Settings, UserPackageCollection};
 use crate::io::{DefaultEnvironmentIo, IoTrait};
+use crate::package_source::SourcePackage;
 use crate::repository::LocalCachedRepository;
-use crate::{HttpClient, PackageInfo, PackageManifest, UserRepoSetting, VersionSelector, io};
+use crate::version::Version;
+use crate::{
+    HttpClient, PackageInfo, PackageManifest, ScopedRegistry, UserRepoSetting, VersionSelector, io,
+};
+use futures::future::join_all;
 use futures::prelude::*;
 use itertools::Itertools;
 use log::error;
@@ -14,6 +21,11 @@ use log::error;
 pub struct PackageCollection {
     pub(super) repositories: RepoHolder,
     pub(super) user_packages: Vec<(PathBuf, PackageManifest)>,
+    /// Packages fetched from the sources in the project.
+    /// These packages replace packages with the same name in repositories and user packages.
+    pub(super) source_packages: Vec<SourcePackage>,
+    /// Packages matching these rules are only taken from the repositories of the rules.
+    pub(super) scoped_registries: Vec<ScopedRegistry>,
 }
 
 impl PackageCollection {
@@ -21,6 +33,8 @@ impl PackageCollection {
         Self {
             repositories: RepoHolder::new(),
             user_packages: Vec::new(),
+            source_packages: Vec::new(),
+            scoped_registries: Vec::new(),
         }
     }
 
@@ -33,6 +47,8 @@ impl PackageCollection {
         Ok(Self {
             repositories,
             user_packages: user_packages.into_packages(),
+            source_packages: Vec::new(),
+            scoped_registries: settings.scoped_registries().to_vec(),
         })
     }
 
@@ -49,6 +65,8 @@ impl PackageCollection {
         Ok(Self {
             repositories,
             user_packages: user_packages.into_packages(),
+            source_packages: Vec::new(),
+            scoped_registries: settings.scoped_registries().to_vec(),
         })
     }
 
@@ -56,6 +74,36 @@ impl PackageCollection {
         self.repositories.update_cache(io, http).await
     }
 
+    /// Creates a collection with packages available without downloading.
+    ///
+    /// Packages in remote repositories are kept only if the zip file is in the package cache
+    /// and the recorded SHA256 hash matches the repository.
+    /// User packages and fetched source packages are always kept.
+    ///
+    /// The zip files are not hashed here; they are verified when the packages are installed.
+    pub async fn cached_only(&self, io: &DefaultEnvironmentIo) -> Self {
+        let mut result = self.clone();
+
+        for repo in result.repositories.iter_mut() {
+            let packages = repo.get_packages().flat_map(|x| x.all_versions());
+            let cached = join_all(packages.map(|package| async move {
+                is_package_cached(io, package)
+                    .await
+                    .then(|| (package.name().into(), package.version().clone()))
+            }))
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<HashSet<(Box<str>, Version)>>();
+
+            repo.repo.retain_versions(|package| {
+                cached.contains(&(package.name().into(), package.version().clone()))
+            });
+        }
+
+        result
+    }
+
     pub async fn remove_repositories(
         &mut self,
         remove_repos: &[UserRepoSetting],
@@ -81,6 +129,55 @@ impl PackageCollection {
         &self.user_packages
     }
 
+    pub fn source_packages(&self) -> &[SourcePackage] {
+        &self.source_packages
+    }
+
+    /// Adds the rules to restrict packages to be from specific repositories,
+    /// usually the rules of the project from [`UnityProject::scoped_registries`](crate::UnityProject::scoped_registries).
+    pub fn add_scoped_registries(&mut self, rules: &[ScopedRegistry]) {
+        for rule in rules {
+            if !self.scoped_registries.contains(rule) {
+                self.scoped_registries.push(rule.clone());
+            }
+        }
+    }
+
+    /// Returns the rules restricting the repositories of the package.
+    pub fn scoped_registries_for<'a>(
+        &'a self,
+        package: &'a str,
+    ) -> impl Iterator<Item = &'a ScopedRegistry> {
+        (self.scoped_registries.iter()).filter(move |x| x.matches(package))
+    }
+
+    /// Returns the versions of the package ignored since the repositories are not allowed by the rules.
+    pub fn find_packages_out_of_scope<'a>(
+        &'a self,
+        package: &'a str,
+    ) -> impl Iterator<Item = PackageInfo<'a>> {
+        self.repositories
+            .iter()
+            .filter(move |repo| !self.is_in_scope(package, repo))
+            .flat_map(move |repo| {
+                repo.repo
+                    .get_package(package)
+                    .into_iter()
+                    .flat_map(|x| x.all_versions().map(|pkg| PackageInfo::remote(pkg, repo)))
+            })
+    }
+
+    /// Returns `true` if the package can be taken from the repository under the scoped registries.
+    fn is_in_scope(&self, package: &str, repo: &LocalCachedRepository) -> bool {
+        ScopedRegistry::allows(&self.scoped_registries, package, repo.id(), repo.url())
+    }
+
+    fn is_from_source(&self, package: &str) -> bool {
+        self.source_packages
+            .iter()
+            .any(|x| x.manifest().name() == package)
+    }
+
     pub fn find_whole_all_packages(
         &self,
         version_selector: VersionSelector,
@@ -108,6 +205,7 @@ impl crate::PackageCollection for PackageCollection {
                 repo.repo()
                     .get_packages()
                     .filter_map(move |x| x.get_latest(version_selector))
+                    .filter(|json| self.is_in_scope(json.name(), repo))
                     .map(|json| PackageInfo::remote(json, repo))
             })
             .into_iter()
@@ -119,30 +217,42 @@ impl crate::PackageCollection for PackageCollection {
             repo.repo
                 .get_packages()
                 .flat_map(|x| x.all_versions())
+                .filter(|pkg| self.is_in_scope(pkg.name(), repo))
                 .map(|pkg| PackageInfo::remote(pkg, repo))
         });
         let local = self
             .user_packages
             .iter()
             .map(|(path, json)| PackageInfo::local(json, path));
+        let source = self.source_packages.iter().map(PackageInfo::source);
 
-        remote.chain(local)
+        (remote.chain(local))
+            .filter(|x| !self.is_from_source(x.name()))
+            .chain(source)
     }
 
     fn find_packages(&self, package: &str) -> impl Iterator<Item = PackageInfo<'_>> {
-        let remote = self.repositories.iter().flat_map(|repo| {
-            repo.repo
-                .get_package(package)
-                .into_iter()
-                .flat_map(|x| x.all_versions().map(|pkg| PackageInfo::remote(pkg, repo)))
-        });
+        let from_source = self.is_from_source(package);
+        let remote = (self.repositories.iter())
+            .filter(move |repo| self.is_in_scope(package, repo))
+            .flat_map(|repo| {
+                repo.repo
+                    .get_package(package)
+                    .into_iter()
+                    .flat_map(|x| x.all_versions().map(|pkg| PackageInfo::remote(pkg, repo)))
+            });
         let local = self
             .user_packages
             .iter()
             .filter(move |(_, json)| json.name() == package)
             .map(|(path, json)| PackageInfo::local(json, path));
+        let source = (self.source_packages.iter())
+            .filter(move |x| x.manifest().name() == package)
+            .map(PackageInfo::source);
 
-        remote.chain(local)
+        (remote.chain(local))
+            .filter(move |_| !from_source)
+            .chain(source)
     }
 
     fn find_package_by_name(
@@ -150,13 +260,23 @@ impl crate::PackageCollection for PackageCollection {
         package: &str,
         package_selector: VersionSelector,
     ) -> Option<PackageInfo<'_>> {
-        let remote = self.repositories.iter().flat_map(|repo| {
-            repo.repo
-                .get_package(package)
-                .into_iter()
-                .flat_map(|pkg| pkg.get_latest(package_selector))
-                .map(|pkg| PackageInfo::remote(pkg, repo))
-        });
+        if self.is_from_source(package) {
+            return (self.source_packages.iter())
+                .filter(|x| x.manifest().name() == package)
+                .filter(|x| package_selector.satisfies(x.manifest()))
+                .map(PackageInfo::source)
+                .max_by_key(|x| x.version());
+        }
+
+        let remote = (self.repositories.iter())
+            .filter(|repo| self.is_in_scope(package, repo))
+            .flat_map(|repo| {
+                repo.repo
+                    .get_package(package)
+                    .into_iter()
+                    .flat_map(|pkg| pkg.get_latest(package_selector))
+                    .map(|pkg| PackageInfo::remote(pkg, repo))
+            });
 
         let local = self
             .user_packages
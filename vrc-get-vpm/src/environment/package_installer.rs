@@ -22,6 +22,19 @@ impl<'a, T: HttpClient> PackageInstaller<'a, T> {
     pub fn new(io: &'a DefaultEnvironmentIo, http: Option<&'a T>) -> Self {
         Self { io, http }
     }
+
+    /// Gets the zip file of the package in the repository.
+    ///
+    /// The cached zip file is used if it's valid, otherwise the zip file is downloaded into the cache.
+    ///
+    /// returns: the readable zip file and its SHA256 hash
+    pub async fn get_package_zip(
+        &self,
+        repository: &LocalCachedRepository,
+        package: &PackageManifest,
+    ) -> io::Result<(TokioFile, [u8; 256 / 8])> {
+        get_package(self.io, self.http, repository, package).await
+    }
 }
 
 impl<T: HttpClient> crate::PackageInstaller for PackageInstaller<'_, T> {
@@ -41,7 +54,7 @@ impl<T: HttpClient> crate::PackageInstaller for PackageInstaller<'_, T> {
         );
         match package.inner {
             PackageInfoInner::Remote(package, user_repo) => {
-                let zip_file = get_package(self.io, self.http, user_repo, package).await?;
+                let (zip_file, _) = get_package(self.io, self.http, user_repo, package).await?;
 
                 // downloading may take a long time, so check abort again
                 abort.check()?;
@@ -76,16 +89,31 @@ impl<T: HttpClient> crate::PackageInstaller for PackageInstaller<'_, T> {
                 crate::utils::copy_recursive(self.io, path.into(), io, dest_dir.into()).await?;
                 Ok(())
             }
+            PackageInfoInner::Source(package) => {
+                let path = package.path().into();
+                crate::utils::copy_recursive(self.io, path, io, dest_dir.into()).await?;
+                Ok(())
+            }
         }
     }
 }
 
-async fn get_package<T: HttpClient>(
+/// Checks the zip file of the package is in the cache and the recorded hash matches the repository.
+///
+/// This does not hash the zip file; the zip file is verified when the package is installed.
+pub(super) async fn is_package_cached(
     io: &DefaultEnvironmentIo,
-    http: Option<&T>,
-    repository: &LocalCachedRepository,
     package: &PackageManifest,
-) -> io::Result<TokioFile> {
+) -> bool {
+    let (_, zip_path, sha_path) = package_cache_paths(package);
+    io.is_file(&zip_path).await
+        && read_cached_hash(io, &sha_path, package.zip_sha_256())
+            .await
+            .is_some()
+}
+
+/// returns: (zip file name, zip file path, sha256 file path)
+fn package_cache_paths(package: &PackageManifest) -> (String, PathBuf, PathBuf) {
     let zip_file_name = format!("vrc-get-{}-{}.zip", package.name(), package.version());
     let zip_path = PathBuf::from(format!(
         "{REPO_CACHE_FOLDER}/{}/{}",
@@ -93,12 +121,26 @@ async fn get_package<T: HttpClient>(
         zip_file_name
     ));
     let sha_path = zip_path.with_extension("zip.sha256");
+    (zip_file_name, zip_path, sha_path)
+}
 
-    if let Some(cache_file) =
+async fn get_package<T: HttpClient>(
+    io: &DefaultEnvironmentIo,
+    http: Option<&T>,
+    repository: &LocalCachedRepository,
+    package: &PackageManifest,
+) -> io::Result<(TokioFile, [u8; 256 / 8])> {
+    repository
+        .check_signature(package)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let (zip_file_name, zip_path, sha_path) = package_cache_paths(package);
+
+    if let Some(cache) =
         try_load_package_cache(io, &zip_path, &sha_path, package.zip_sha_256()).await
     {
         debug!("using cache for {}@{}", package.name(), package.version());
-        Ok(cache_file)
+        Ok(cache)
     } else {
         io.create_dir_all(zip_path.parent().unwrap()).await?;
 
@@ -148,7 +190,7 @@ async fn get_package<T: HttpClient>(
             ));
         }
 
-        Ok(zip_file)
+        Ok((zip_file, zip_hash))
     }
 }
 
@@ -160,15 +202,39 @@ async fn get_package<T: HttpClient>(
 /// * `sha_path`: the path to sha256 file
 /// * `sha256`: sha256 hash if specified
 ///
-/// returns: Option<File> readable zip file or None
+/// returns: Option<(File, hash)> readable zip file and its hash or None
 async fn try_load_package_cache(
     io: &DefaultEnvironmentIo,
     zip_path: &Path,
     sha_path: &Path,
     sha256: Option<&str>,
-) -> Option<TokioFile> {
+) -> Option<(TokioFile, [u8; 256 / 8])> {
     let mut cache_file = io.open(zip_path).await.ok()?;
 
+    let hex = read_cached_hash(io, sha_path, sha256).await?;
+
+    let mut hasher = Sha256AsyncWrite::new(io::sink());
+
+    io::copy(&mut cache_file, &mut hasher).await.ok()?;
+
+    let hash = &hasher.finalize().1[..];
+    if hash != &hex[..] {
+        return None;
+    }
+
+    cache_file.seek(SeekFrom::Start(0)).await.ok()?;
+
+    Some((cache_file, hex))
+}
+
+/// Reads the hash of the cached zip file from the sha256 file
+///
+/// returns: None if the sha256 file is missing, broken, or doesn't match `sha256` in the repository
+async fn read_cached_hash(
+    io: &DefaultEnvironmentIo,
+    sha_path: &Path,
+    sha256: Option<&str>,
+) -> Option<[u8; 256 / 8]> {
     let mut buf = [0u8; 256 / 4];
     io.open(sha_path)
         .await
@@ -186,18 +252,7 @@ async fn try_load_package_cache(
         return None;
     }
 
-    let mut hasher = Sha256AsyncWrite::new(io::sink());
-
-    io::copy(&mut cache_file, &mut hasher).await.ok()?;
-
-    let hash = &hasher.finalize().1[..];
-    if hash != &hex[..] {
-        return None;
-    }
-
-    cache_file.seek(SeekFrom::Start(0)).await.ok()?;
-
-    Some(cache_file)
+    Some(hex)
 }
 
 /// downloads the zip file from the url to the specified path
@@ -25,7 +25,7 @@ pub(crate) struct RepoHolder {
 #[derive(Debug, Clone)]
 enum Repository {
     Loaded(LocalCachedRepository),
-    NotDownloaded(Url, IndexMap<Box<str>, Box<str>>),
+    NotDownloaded(Url, IndexMap<Box<str>, Box<str>>, Option<Box<str>>),
     UnableToLoad,
 }
 
@@ -42,12 +42,17 @@ impl Repository {
             Repository::Loaded(repo) => repo.url().map(|url| RemoteDownloadInfo {
                 url,
                 headers: repo.headers(),
-                etag: repo.vrc_get.as_ref().map(|x| x.etag.as_ref()),
+                // download again if the cache is not verified with the pinned key
+                etag: (repo.vrc_get.as_ref())
+                    .filter(|_| repo.public_key().is_none() || repo.is_signature_verified())
+                    .map(|x| x.etag.as_ref()),
+                public_key: repo.public_key(),
             }),
-            Repository::NotDownloaded(url, headers) => Some(RemoteDownloadInfo {
+            Repository::NotDownloaded(url, headers, public_key) => Some(RemoteDownloadInfo {
                 url,
                 headers,
                 etag: None,
+                public_key: public_key.as_deref(),
             }),
             Repository::UnableToLoad => None,
         }
@@ -58,6 +63,7 @@ struct RemoteDownloadInfo<'a> {
     url: &'a Url,
     headers: &'a IndexMap<Box<str>, Box<str>>,
     etag: Option<&'a str>,
+    public_key: Option<&'a str>,
 }
 
 impl RepoHolder {
@@ -78,6 +84,13 @@ impl RepoHolder {
         self.cached_repos_new.values().filter_map(|x| x.as_loaded())
     }
 
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut LocalCachedRepository> {
+        self.cached_repos_new.values_mut().filter_map(|x| match x {
+            Repository::Loaded(x) => Some(x),
+            _ => None,
+        })
+    }
+
     pub fn find_by_id(&self, id: &str) -> Option<&LocalCachedRepository> {
         self.iter().find(|x| x.id() == Some(id))
     }
@@ -166,7 +179,13 @@ impl RepoHolder {
                 (
                     src.cache_path().into(),
                     src.url()
-                        .map(|u| Repository::NotDownloaded(u.clone(), src.headers().clone()))
+                        .map(|u| {
+                            Repository::NotDownloaded(
+                                u.clone(),
+                                src.headers().clone(),
+                                src.public_key().map(Into::into),
+                            )
+                        })
                         .unwrap_or(Repository::UnableToLoad),
                 )
             }
@@ -197,6 +216,7 @@ impl RepoHolder {
         if let Some(url) = source.url() {
             if let Some(mut loaded) = try_load_json::<LocalCachedRepository>(io, path).await? {
                 loaded.set_url(url.clone());
+                loaded.public_key = source.public_key().map(Into::into);
                 Ok(Some(loaded))
             } else {
                 warn!("Local cache for {url} does not exist");
@@ -220,27 +240,48 @@ impl RepoHolder {
             async |(path, repository)| {
                 if let Some(info) = repository.remote_download_info() {
                     log::debug!("downloading remote repo '{}'", info.url);
-                    match RemoteRepository::download_with_etag(
-                        client,
-                        info.url,
-                        info.headers,
-                        info.etag,
-                    )
-                    .await
-                    {
-                        Ok(Some((remote_repo, etag))) => {
+                    let downloaded = match info.public_key {
+                        None => RemoteRepository::download_with_etag(
+                            client,
+                            info.url,
+                            info.headers,
+                            info.etag,
+                        )
+                        .await
+                        .map(|x| x.map(|(repo, etag)| (repo, etag, None))),
+                        Some(public_key) => RemoteRepository::download_signed(
+                            client,
+                            info.url,
+                            info.headers,
+                            info.etag,
+                            public_key,
+                        )
+                        .await
+                        .map(|x| {
+                            x.map(|(repo, etag, verified)| match verified {
+                                Ok(()) => (repo, etag, Some(public_key.into())),
+                                Err(e) => {
+                                    error!("verifying signature of '{}': {e}", info.url);
+                                    (repo, etag, None)
+                                }
+                            })
+                        }),
+                    };
+                    match downloaded {
+                        Ok(Some((remote_repo, etag, verified_public_key))) => {
                             log::debug!("successfully downloaded '{}'", info.url);
 
                             let headers = info.headers.clone();
+                            let public_key = info.public_key.map(Box::<str>::from);
                             let new_repository = if let Repository::Loaded(existing) = repository {
                                 existing.set_repo(remote_repo);
                                 existing
                             } else {
                                 //let headers = headers.clone(); // lifetime error
-                                *repository = Repository::Loaded(LocalCachedRepository::new(
-                                    remote_repo,
-                                    headers,
-                                ));
+                                let mut new_repository =
+                                    LocalCachedRepository::new(remote_repo, headers);
+                                new_repository.public_key = public_key;
+                                *repository = Repository::Loaded(new_repository);
                                 match repository {
                                     Repository::Loaded(x) => x,
                                     _ => unreachable!(),
@@ -248,6 +289,7 @@ impl RepoHolder {
                             };
 
                             new_repository.set_etag(etag);
+                            new_repository.set_verified_public_key(verified_public_key);
 
                             async fn save_repository(
                                 io: &DefaultEnvironmentIo,
@@ -6,6 +6,7 @@ pub(crate) struct RepoSource<'a> {
     cache_path: &'a Path,
     headers: &'a IndexMap<Box<str>, Box<str>>,
     url: Option<&'a Url>,
+    public_key: Option<&'a str>,
 }
 
 impl<'a> RepoSource<'a> {
@@ -18,9 +19,15 @@ impl<'a> RepoSource<'a> {
             cache_path,
             headers,
             url,
+            public_key: None,
         }
     }
 
+    pub fn with_public_key(mut self, public_key: Option<&'a str>) -> Self {
+        self.public_key = public_key;
+        self
+    }
+
     pub fn cache_path(&self) -> &Path {
         self.cache_path
     }
@@ -32,4 +39,8 @@ impl<'a> RepoSource<'a> {
     pub fn url(&self) -> Option<&Url> {
         self.url
     }
+
+    pub fn public_key(&self) -> Option<&str> {
+        self.public_key
+    }
 }
@@ -12,7 +12,7 @@ use crate::io::DefaultEnvironmentIo;
 use crate::package_manifest::LooseManifest;
 use crate::repository::RemoteRepository;
 use crate::utils::{normalize_path, try_load_json};
-use crate::{UserRepoSetting, io};
+use crate::{ScopedRegistry, UserRepoSetting, io};
 
 #[derive(Debug, Clone)]
 pub struct Settings {
@@ -185,6 +185,11 @@ impl Settings {
         self.vpm.user_repos()
     }
 
+    /// The rules to restrict packages to be from specific repositories for all projects.
+    pub fn scoped_registries(&self) -> &[ScopedRegistry] {
+        self.vpm.scoped_registries()
+    }
+
     pub fn can_add_remote_repo(&self, url: &Url, remote_repo: &RemoteRepository) -> bool {
         let user_repos = self.get_user_repos();
         if user_repos.iter().any(|x| x.url() == Some(url)) {
@@ -269,6 +274,24 @@ impl Settings {
         self.vpm.retain_user_repos(|x| !condition(x))
     }
 
+    /// Pins the public key to verify the signature of the repositories, or unpins with `None`.
+    ///
+    /// Returns the number of repositories updated.
+    pub fn set_repo_public_key(
+        &mut self,
+        condition: impl Fn(&UserRepoSetting) -> bool,
+        public_key: Option<&str>,
+    ) -> usize {
+        let mut count = 0;
+        for repo in self.vpm.user_repos_mut() {
+            if condition(repo) {
+                repo.public_key = public_key.map(Into::into);
+                count += 1;
+            }
+        }
+        count
+    }
+
     pub fn remove_repo_at_index(&mut self, index: usize) -> Option<UserRepoSetting> {
         self.vpm.remove_user_repo_at_index(index)
     }
@@ -0,0 +1,316 @@
+use crate::environment::{PackageCollection, REPO_CACHE_FOLDER};
+use crate::io::{DefaultEnvironmentIo, DefaultProjectIo, DirEntry, IoTrait};
+use crate::package_manifest::LooseManifest;
+use crate::package_source::{
+    ArchiveKind, FetchSourceError, PackageSource, SourcePackage, archive_kind,
+};
+use crate::utils::{TarArchive, extract_zip, is_complete_relative, read_to_end, try_load_json};
+use crate::{HttpClient, UnityProject, io};
+use async_compression::futures::bufread::GzipDecoder;
+use futures::prelude::*;
+use indexmap::IndexMap;
+use log::debug;
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use tokio::process::Command;
+
+impl PackageCollection {
+    /// Fetches the packages from the sources of the dependencies in the project
+    /// into the package cache, and adds them to this collection.
+    ///
+    /// Packages locked with the same source are fetched at the locked commit,
+    /// or verified with the locked SHA256 for archives.
+    /// If `http` is `None`, only the packages already fetched can be used.
+    pub async fn fetch_package_sources(
+        &mut self,
+        io: &DefaultEnvironmentIo,
+        http: Option<&impl HttpClient>,
+        project: &UnityProject,
+    ) -> Result<(), FetchSourceError> {
+        for (name, source, locked_hash) in project.package_sources() {
+            // the name is used as a folder name in the package cache
+            if !matches!(
+                *Path::new(name).components().collect::<Vec<_>>(),
+                [Component::Normal(_)]
+            ) {
+                return Err(FetchSourceError::InvalidName {
+                    package: name.into(),
+                });
+            }
+            let package = fetch_package(io, http, name, source, locked_hash).await?;
+            if package.manifest().name() != name {
+                return Err(FetchSourceError::NameMismatch {
+                    package: name.into(),
+                    actual: package.manifest().name().into(),
+                });
+            }
+            self.source_packages.retain(|x| x.manifest().name() != name);
+            self.source_packages.push(package);
+        }
+        Ok(())
+    }
+}
+
+/// returns: the folder of the fetched source in the package cache
+fn source_cache_path(name: &str, hash: &str) -> PathBuf {
+    PathBuf::from(format!("{REPO_CACHE_FOLDER}/{name}/vrc-get-{name}-{hash}"))
+}
+
+async fn fetch_package(
+    io: &DefaultEnvironmentIo,
+    http: Option<&impl HttpClient>,
+    name: &str,
+    source: &PackageSource,
+    locked_hash: Option<&str>,
+) -> Result<SourcePackage, FetchSourceError> {
+    if let Some(hash) = locked_hash
+        && let Some(cached) = load_cached(io, name, source, hash).await?
+    {
+        debug!("using cache for {name} from {source}");
+        return Ok(cached);
+    }
+
+    let Some(http) = http else {
+        return Err(FetchSourceError::OfflineMode {
+            package: name.into(),
+        });
+    };
+
+    let temp_dir = PathBuf::from(format!(
+        "{REPO_CACHE_FOLDER}/{name}/.vrc-get-fetch-{}",
+        uuid::Uuid::new_v4()
+    ));
+    io.create_dir_all(&temp_dir).await?;
+
+    let fetched = match source {
+        PackageSource::Git { url, revision, .. } => {
+            let target = locked_hash.or(revision.as_deref());
+            fetch_git(io, name, url, target, &temp_dir).await
+        }
+        PackageSource::Archive { url } => {
+            let kind = archive_kind(url.path()).expect("archive source must have known extension");
+            fetch_archive(io, http, name, url, kind, locked_hash, &temp_dir).await
+        }
+    };
+    let hash = match fetched {
+        Ok(hash) => hash,
+        Err(e) => {
+            io.remove_dir_all(&temp_dir).await.ok();
+            return Err(e);
+        }
+    };
+
+    let dest_dir = source_cache_path(name, &hash);
+    if io.is_dir(&dest_dir).await {
+        // fetched by another project or a broken cache; replace with the fresh one
+        io.remove_dir_all(&dest_dir).await?;
+    }
+    io.rename(&temp_dir, &dest_dir).await?;
+
+    load_cached(io, name, source, &hash).await?.ok_or_else(|| {
+        FetchSourceError::Io(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("package.json not found in the source of {name}"),
+        ))
+    })
+}
+
+async fn load_cached(
+    io: &DefaultEnvironmentIo,
+    name: &str,
+    source: &PackageSource,
+    hash: &str,
+) -> io::Result<Option<SourcePackage>> {
+    let cache_dir = source_cache_path(name, hash);
+    if !io.is_dir(&cache_dir).await {
+        return Ok(None);
+    }
+
+    let package_dir = match source {
+        PackageSource::Git {
+            path: Some(path), ..
+        } => {
+            if !is_complete_relative(Path::new(path.as_ref())) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("directory traversal detected: {path}"),
+                ));
+            }
+            cache_dir.join(path.as_ref())
+        }
+        PackageSource::Git { .. } => cache_dir,
+        // archives from npm have all files in `package` folder
+        PackageSource::Archive { .. } => archive_package_root(io, cache_dir).await?,
+    };
+
+    let Some(LooseManifest(manifest)) =
+        try_load_json::<LooseManifest>(io, &package_dir.join("package.json")).await?
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(SourcePackage::new(
+        source.clone(),
+        hash.into(),
+        io.resolve(&package_dir),
+        manifest,
+    )))
+}
+
+/// Returns the folder with package.json, which is the root or the only folder in the archive.
+async fn archive_package_root(io: &DefaultEnvironmentIo, dir: PathBuf) -> io::Result<PathBuf> {
+    if io.is_file(&dir.join("package.json")).await {
+        return Ok(dir);
+    }
+
+    let entries = io.read_dir(&dir).await?.try_collect::<Vec<_>>().await?;
+    if let [entry] = entries.as_slice()
+        && entry.file_type().await?.is_dir()
+    {
+        return Ok(dir.join(entry.file_name()));
+    }
+
+    Ok(dir)
+}
+
+/// Fetches the revision of the git repository to the folder and returns the commit hash.
+async fn fetch_git(
+    io: &DefaultEnvironmentIo,
+    name: &str,
+    url: &str,
+    revision: Option<&str>,
+    dir: &Path,
+) -> Result<Box<str>, FetchSourceError> {
+    let git_dir = dir.join(".git");
+    let dir = io.resolve(dir);
+
+    git(name, &dir, &["init", "-q"]).await?;
+    let shallow = git(
+        name,
+        &dir,
+        &[
+            "fetch",
+            "-q",
+            "--depth",
+            "1",
+            "--end-of-options",
+            url,
+            revision.unwrap_or("HEAD"),
+        ],
+    )
+    .await;
+    match (shallow, revision) {
+        (Ok(_), _) => git(name, &dir, &["checkout", "-q", "--detach", "FETCH_HEAD"]).await?,
+        // some servers do not allow fetching commits directly, so fetch all and checkout
+        (Err(_), Some(revision)) => {
+            git(
+                name,
+                &dir,
+                &[
+                    "fetch",
+                    "-q",
+                    "--end-of-options",
+                    url,
+                    "+refs/heads/*:refs/remotes/origin/*",
+                    "+refs/tags/*:refs/tags/*",
+                ],
+            )
+            .await?;
+            let commit = resolve_revision(name, &dir, revision).await?;
+            git(name, &dir, &["checkout", "-q", "--detach", &commit]).await?
+        }
+        (Err(e), None) => return Err(e),
+    };
+    let commit = git(name, &dir, &["rev-parse", "HEAD"]).await?;
+
+    io.remove_dir_all(&git_dir).await?;
+
+    Ok(commit.trim().into())
+}
+
+/// Resolves the tag, branch, or commit fetched from the remote to the commit hash.
+async fn resolve_revision(
+    name: &str,
+    dir: &Path,
+    revision: &str,
+) -> Result<String, FetchSourceError> {
+    let candidates = [
+        format!("refs/tags/{revision}^{{commit}}"),
+        format!("refs/remotes/origin/{revision}^{{commit}}"),
+        format!("{revision}^{{commit}}"),
+    ];
+    for candidate in &candidates {
+        let args = ["rev-parse", "-q", "--verify", "--end-of-options", candidate];
+        if let Ok(commit) = git(name, dir, &args).await {
+            return Ok(commit.trim().to_owned());
+        }
+    }
+
+    Err(FetchSourceError::Git {
+        package: name.into(),
+        message: format!("revision {revision} not found in the repository"),
+    })
+}
+
+/// Runs git in the folder and returns the stdout.
+async fn git(name: &str, dir: &Path, args: &[&str]) -> Result<String, FetchSourceError> {
+    debug!("running git {} in {}", args.join(" "), dir.display());
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| FetchSourceError::Git {
+            package: name.into(),
+            message: format!("failed to run git: {e}"),
+        })?;
+
+    if !output.status.success() {
+        return Err(FetchSourceError::Git {
+            package: name.into(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Downloads and extracts the archive to the folder and returns the SHA256 of the archive.
+async fn fetch_archive(
+    io: &DefaultEnvironmentIo,
+    http: &impl HttpClient,
+    name: &str,
+    url: &url::Url,
+    kind: ArchiveKind,
+    locked_hash: Option<&str>,
+    dir: &Path,
+) -> Result<Box<str>, FetchSourceError> {
+    let archive = read_to_end(std::pin::pin!(http.get(url, &IndexMap::new()).await?)).await?;
+    let hash = hex::encode(Sha256::digest(&archive));
+
+    if let Some(locked_hash) = locked_hash
+        && !locked_hash.eq_ignore_ascii_case(&hash)
+    {
+        return Err(FetchSourceError::HashMismatch {
+            package: name.into(),
+            expected: locked_hash.into(),
+            actual: hash.into(),
+        });
+    }
+
+    let dest_io = DefaultProjectIo::new(io.resolve(dir).into_boxed_path());
+    match kind {
+        ArchiveKind::Zip => {
+            extract_zip(futures::io::Cursor::new(archive), &dest_io, Path::new("")).await?;
+        }
+        ArchiveKind::TarGz => {
+            let gunzip = GzipDecoder::new(futures::io::Cursor::new(&archive[..]));
+            (TarArchive::new(gunzip))
+                .extract_to(&dest_io, Path::new(""))
+                .await?;
+        }
+    }
+
+    Ok(hash.into())
+}
@@ -1,8 +1,8 @@
-use crate::UserRepoSetting;
 use crate::environment::PackageCollection;
 use crate::io;
 use crate::io::DefaultEnvironmentIo;
 use crate::utils::{save_json, try_load_json};
+use crate::{ScopedRegistry, UserRepoSetting};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::path::{Path, PathBuf};
@@ -73,6 +73,9 @@ struct AsJson {
     last_selected_project: Box<str>,
     #[serde(default)]
     user_repos: Vec<UserRepoSetting>,
+    // vrc-get specific
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scoped_registries: Vec<ScopedRegistry>,
 
     #[serde(flatten)]
     rest: JsonObject,
@@ -117,6 +120,14 @@ impl VpmSettings {
         &self.parsed.user_repos
     }
 
+    pub(crate) fn user_repos_mut(&mut self) -> &mut [UserRepoSetting] {
+        &mut self.parsed.user_repos
+    }
+
+    pub(crate) fn scoped_registries(&self) -> &[ScopedRegistry] {
+        &self.parsed.scoped_registries
+    }
+
     pub(crate) fn user_package_folders(&self) -> &[PathBuf] {
         &self.parsed.user_package_folders
     }
@@ -14,7 +14,9 @@ use version::{ReleaseType, UnityVersion, Version, VersionRange};
 
 pub mod environment;
 pub mod io;
+pub mod lint;
 mod package_manifest;
+pub mod package_source;
 pub mod repository;
 mod structs;
 mod traits;
@@ -30,10 +32,12 @@ pub mod unity;
 #[cfg(feature = "unity-hub")]
 pub mod unity_hub;
 
+use crate::package_source::SourcePackage;
 use crate::repository::local::LocalCachedRepository;
 
 pub use package_manifest::PackageManifest;
 pub use package_manifest::PartialUnityVersion;
+pub use structs::setting::ScopedRegistry;
 pub use structs::setting::UserRepoSetting;
 pub use traits::AbortCheck;
 pub use traits::HttpClient;
@@ -57,6 +61,7 @@ impl std::fmt::Debug for PackageInfo<'_> {
         enum SourceEnum<'a> {
             Local(&'a Path),
             Remote(&'a str),
+            Source(String),
         }
 
         let source = match self.inner {
@@ -66,6 +71,7 @@ impl std::fmt::Debug for PackageInfo<'_> {
                     .unwrap_or("(unknown id)"),
             ),
             PackageInfoInner::Local(_, path) => SourceEnum::Local(path),
+            PackageInfoInner::Source(package) => SourceEnum::Source(package.source().to_string()),
         };
 
         f.debug_struct("PackageInfo")
@@ -79,6 +85,7 @@ impl std::fmt::Debug for PackageInfo<'_> {
 enum PackageInfoInner<'a> {
     Remote(&'a PackageManifest, &'a LocalCachedRepository),
     Local(&'a PackageManifest, &'a Path),
+    Source(&'a SourcePackage),
 }
 
 impl<'a> PackageInfo<'a> {
@@ -87,6 +94,7 @@ impl<'a> PackageInfo<'a> {
         match self.inner {
             PackageInfoInner::Remote(pkg, _) => pkg,
             PackageInfoInner::Local(pkg, _) => pkg,
+            PackageInfoInner::Source(package) => package.manifest(),
         }
     }
 
@@ -102,10 +110,24 @@ impl<'a> PackageInfo<'a> {
         }
     }
 
+    pub fn source(package: &'a SourcePackage) -> Self {
+        Self {
+            inner: PackageInfoInner::Source(package),
+        }
+    }
+
     pub fn repo(self) -> Option<&'a LocalCachedRepository> {
         match self.inner {
             PackageInfoInner::Remote(_, repo) => Some(repo),
-            PackageInfoInner::Local(_, _) => None,
+            PackageInfoInner::Local(_, _) | PackageInfoInner::Source(_) => None,
+        }
+    }
+
+    /// Returns the fetched package if the package is from a [`package_source::PackageSource`].
+    pub fn source_package(self) -> Option<&'a SourcePackage> {
+        match self.inner {
+            PackageInfoInner::Source(package) => Some(package),
+            PackageInfoInner::Remote(_, _) | PackageInfoInner::Local(_, _) => None,
         }
     }
 
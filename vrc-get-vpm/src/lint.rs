@@ -0,0 +1,515 @@
+//! Validation of package manifests and repositories for package authors.
+//!
+//! Broken package manifests are ignored when loading repositories,
+//! so this module reports the problems that would make packages disappear or fail to resolve.
+
+use crate::PartialUnityVersion;
+use crate::version::{Version, VersionRange};
+use crate::{PackageCollection, PackageManifest};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A problem found in a package manifest.
+#[derive(Debug, Clone)]
+pub struct LintIssue {
+    package: Box<str>,
+    version: Option<Box<str>>,
+    kind: LintIssueKind,
+}
+
+impl LintIssue {
+    /// The name of the package with the problem.
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// The version of the package with the problem, or `None` if the problem is not of one version.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn kind(&self) -> &LintIssueKind {
+        &self.kind
+    }
+}
+
+impl Display for LintIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{}@{}: {}", self.package, version, self.kind),
+            None => write!(f, "{}: {}", self.package, self.kind),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum LintIssueKind {
+    /// The manifest cannot be loaded as a package manifest.
+    InvalidManifest { error: String },
+    /// The version range of the dependency cannot be parsed.
+    InvalidVersionRange { dependency: Box<str>, range: String },
+    /// The dependency is not in any known repository.
+    UnknownDependency { dependency: Box<str> },
+    /// The package versions depend on each other.
+    /// The first package of the cycle is the package and version of the issue.
+    CircularDependency { cycle: Vec<Box<str>> },
+    /// The GUID of the legacy folder or file is not 32 hexadecimal digits.
+    InvalidLegacyGuid { path: Box<str>, guid: String },
+    /// The `unity` field is not `major` or `major.minor`.
+    InvalidUnityVersion { unity: String },
+    /// `zipSHA256` is not specified.
+    MissingZipSha256,
+    /// The name in the manifest differs from the key in the repository.
+    MismatchedName { name: String },
+    /// The version in the manifest differs from the key in the repository.
+    MismatchedVersion { version: String },
+}
+
+impl Display for LintIssueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintIssueKind::InvalidManifest { error } => {
+                write!(f, "invalid package manifest: {error}")
+            }
+            LintIssueKind::InvalidVersionRange { dependency, range } => {
+                write!(f, "invalid version range for {dependency}: {range}")
+            }
+            LintIssueKind::UnknownDependency { dependency } => {
+                write!(f, "dependency {dependency} is not found in any repository")
+            }
+            LintIssueKind::CircularDependency { cycle } => {
+                write!(f, "circular dependency: ")?;
+                for package in cycle {
+                    write!(f, "{package} -> ")?;
+                }
+                write!(f, "{}", cycle[0])
+            }
+            LintIssueKind::InvalidLegacyGuid { path, guid } => {
+                write!(f, "invalid GUID for legacy asset {path}: {guid}")
+            }
+            LintIssueKind::InvalidUnityVersion { unity } => {
+                write!(f, "invalid unity version: {unity}")
+            }
+            LintIssueKind::MissingZipSha256 => write!(f, "zipSHA256 is not specified"),
+            LintIssueKind::MismatchedName { name } => {
+                write!(f, "name in the manifest is {name}")
+            }
+            LintIssueKind::MismatchedVersion { version } => {
+                write!(f, "version in the manifest is {version}")
+            }
+        }
+    }
+}
+
+/// Validates a package manifest like `package.json`.
+///
+/// The dependencies are looked up in `known` to check that they exist,
+/// and that they don't depend back on the package.
+/// `zipSHA256` is only required if the manifest has `url`, which means the manifest is from a repository.
+pub fn lint_package_manifest(json: &Value, known: &impl PackageCollection) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+
+    let name = json
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("<unknown>");
+    let version = json.get("version").and_then(Value::as_str);
+    let require_sha256 = json.get("url").is_some();
+
+    let is_known = |dependency: &str| known.find_packages(dependency).next().is_some();
+    lint_manifest(json, name, version, require_sha256, &is_known, &mut issues);
+
+    let mut graph = DependencyGraph::new(known);
+    graph.add_linted(name, version, json);
+    graph.report_cycles(&mut issues);
+
+    issues
+}
+
+/// Validates all package versions in a repository json.
+///
+/// The dependencies not in the repository are looked up in `known` to check that they exist.
+/// Circular dependencies are checked with the package versions matching the version ranges,
+/// including the packages in `known`.
+pub fn lint_repository(json: &Value, known: &impl PackageCollection) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+
+    let Some(packages) = json.get("packages").and_then(Value::as_object) else {
+        return issues;
+    };
+
+    let is_known =
+        |name: &str| packages.contains_key(name) || known.find_packages(name).next().is_some();
+
+    let mut graph = DependencyGraph::new(known);
+
+    for (name, package) in packages {
+        let Some(versions) = package.get("versions").and_then(Value::as_object) else {
+            issues.push(LintIssue {
+                package: name.as_str().into(),
+                version: None,
+                kind: LintIssueKind::InvalidManifest {
+                    error: "versions is not specified".into(),
+                },
+            });
+            continue;
+        };
+
+        for (version, manifest) in versions {
+            lint_manifest(manifest, name, Some(version), true, &is_known, &mut issues);
+
+            let issue = |kind| LintIssue {
+                package: name.as_str().into(),
+                version: Some(version.as_str().into()),
+                kind,
+            };
+
+            match manifest.get("name").and_then(Value::as_str) {
+                Some(actual) if actual != name => {
+                    issues.push(issue(LintIssueKind::MismatchedName {
+                        name: actual.into(),
+                    }));
+                }
+                _ => {}
+            }
+
+            match manifest.get("version").and_then(Value::as_str) {
+                Some(actual) if !same_version(actual, version) => {
+                    issues.push(issue(LintIssueKind::MismatchedVersion {
+                        version: actual.into(),
+                    }));
+                }
+                _ => {}
+            }
+
+            graph.add_linted(name, Some(version), manifest);
+        }
+    }
+
+    graph.report_cycles(&mut issues);
+
+    issues
+}
+
+fn same_version(a: &str, b: &str) -> bool {
+    match (Version::from_str(a), Version::from_str(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn lint_manifest(
+    json: &Value,
+    name: &str,
+    version: Option<&str>,
+    require_sha256: bool,
+    is_known: &impl Fn(&str) -> bool,
+    issues: &mut Vec<LintIssue>,
+) {
+    let found = issues.len();
+    let issue = |kind| LintIssue {
+        package: name.into(),
+        version: version.map(Into::into),
+        kind,
+    };
+
+    let Value::Object(json) = json else {
+        issues.push(issue(LintIssueKind::InvalidManifest {
+            error: "manifest is not an object".into(),
+        }));
+        return;
+    };
+
+    if let Some(Value::Object(dependencies)) = json.get("vpmDependencies") {
+        for (dependency, range) in dependencies {
+            let valid_range = range
+                .as_str()
+                .is_some_and(|range| VersionRange::from_str(range).is_ok());
+            if !valid_range {
+                issues.push(issue(LintIssueKind::InvalidVersionRange {
+                    dependency: dependency.as_str().into(),
+                    range: value_to_string(range),
+                }));
+            }
+            if !is_known(dependency) {
+                issues.push(issue(LintIssueKind::UnknownDependency {
+                    dependency: dependency.as_str().into(),
+                }));
+            }
+        }
+    }
+
+    for key in ["legacyFolders", "legacyFiles"] {
+        let Some(Value::Object(legacy)) = json.get(key) else {
+            continue;
+        };
+        for (path, guid) in legacy {
+            let valid = match guid {
+                Value::Null => true,
+                Value::String(guid) => guid.is_empty() || is_guid(guid),
+                _ => false,
+            };
+            if !valid {
+                issues.push(issue(LintIssueKind::InvalidLegacyGuid {
+                    path: path.as_str().into(),
+                    guid: value_to_string(guid),
+                }));
+            }
+        }
+    }
+
+    if let Some(unity) = json.get("unity")
+        && !unity.is_null()
+        && PartialUnityVersion::deserialize(unity).is_err()
+    {
+        issues.push(issue(LintIssueKind::InvalidUnityVersion {
+            unity: value_to_string(unity),
+        }));
+    }
+
+    if require_sha256 && !json.get("zipSHA256").is_some_and(|x| x.is_string()) {
+        issues.push(issue(LintIssueKind::MissingZipSha256));
+    }
+
+    // report other problems only if the problem is not reported above
+    if issues.len() == found
+        && let Err(e) = PackageManifest::deserialize(Value::Object(json.clone()))
+    {
+        issues.push(issue(LintIssueKind::InvalidManifest {
+            error: e.to_string(),
+        }));
+    }
+}
+
+fn is_guid(guid: &str) -> bool {
+    guid.len() == 32 && guid.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// A package version in the dependency graph.
+struct Node<'a> {
+    name: &'a str,
+    version: Version,
+    /// Whether the version is being linted, not loaded from the known packages.
+    linted: bool,
+    dependencies: Vec<(&'a str, VersionRange)>,
+}
+
+/// The dependency graph of the package versions being linted,
+/// and the known package versions they may depend on.
+///
+/// The known versions of the packages being linted are replaced with the linted versions.
+struct DependencyGraph<'a, C> {
+    known: &'a C,
+    nodes: Vec<Node<'a>>,
+    /// package name -> indices of the versions in `nodes`
+    by_name: BTreeMap<&'a str, Vec<usize>>,
+}
+
+impl<'a, C: PackageCollection> DependencyGraph<'a, C> {
+    fn new(known: &'a C) -> Self {
+        Self {
+            known,
+            nodes: Vec::new(),
+            by_name: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the package version being linted.
+    ///
+    /// The version is skipped if the version cannot be parsed, which is reported by `lint_manifest`.
+    fn add_linted(&mut self, name: &'a str, version: Option<&str>, json: &'a Value) {
+        let Some(version) = version.and_then(|x| Version::from_str(x).ok()) else {
+            return;
+        };
+
+        let dependencies = (json.get("vpmDependencies").and_then(Value::as_object))
+            .into_iter()
+            .flatten()
+            .filter_map(|(dependency, range)| {
+                let range = VersionRange::from_str(range.as_str()?).ok()?;
+                Some((dependency.as_str(), range))
+            })
+            .collect();
+
+        self.by_name.entry(name).or_default().push(self.nodes.len());
+        self.nodes.push(Node {
+            name,
+            version,
+            linted: true,
+            dependencies,
+        });
+    }
+
+    /// Adds all the known versions of the packages the added versions depend on.
+    fn load_known(&mut self) {
+        let mut index = 0;
+        while index < self.nodes.len() {
+            for dependency in 0..self.nodes[index].dependencies.len() {
+                let name = self.nodes[index].dependencies[dependency].0;
+                if self.by_name.contains_key(name) {
+                    continue;
+                }
+                let versions = self.by_name.entry(name).or_default();
+                for package in self.known.find_packages(name) {
+                    versions.push(self.nodes.len());
+                    self.nodes.push(Node {
+                        name,
+                        version: package.version().clone(),
+                        linted: false,
+                        dependencies: (package.vpm_dependencies().iter())
+                            .map(|(name, range)| (name.as_ref(), range.clone()))
+                            .collect(),
+                    });
+                }
+            }
+            index += 1;
+        }
+    }
+
+    /// Reports the circular dependencies including any linted version.
+    ///
+    /// Each strongly connected component of package versions is reported once,
+    /// with the shortest cycle from the smallest linted version in the component.
+    fn report_cycles(mut self, issues: &mut Vec<LintIssue>) {
+        self.load_known();
+
+        let edges = (self.nodes.iter())
+            .map(|node| {
+                (node.dependencies.iter())
+                    .flat_map(|(name, range)| {
+                        (self.by_name.get(name).into_iter().flatten().copied())
+                            .filter(|&index| range.matches(&self.nodes[index].version))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut cycles = Vec::new();
+        for component in strongly_connected_components(&edges) {
+            let Some(&start) = (component.iter())
+                .filter(|&&index| self.nodes[index].linted)
+                .min_by_key(|&&index| (self.nodes[index].name, &self.nodes[index].version))
+            else {
+                continue;
+            };
+            if let Some(cycle) = shortest_cycle(&edges, &component, start) {
+                cycles.push((start, cycle));
+            }
+        }
+        cycles.sort_by_key(|&(start, _)| (self.nodes[start].name, &self.nodes[start].version));
+
+        for (start, cycle) in cycles {
+            issues.push(LintIssue {
+                package: self.nodes[start].name.into(),
+                version: Some(self.nodes[start].version.to_string().into()),
+                kind: LintIssueKind::CircularDependency {
+                    cycle: (cycle.into_iter())
+                        .map(|index| self.nodes[index].name.into())
+                        .collect(),
+                },
+            });
+        }
+    }
+}
+
+/// Finds the strongly connected components of the graph with Tarjan's algorithm.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut index = vec![UNVISITED; edges.len()];
+    let mut low_link = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for root in 0..edges.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+
+        // (node, the index of the next edge to visit), to avoid stack overflow with recursion
+        let mut call_stack = vec![(root, 0)];
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((node, edge)) = call_stack.last_mut() {
+            let node = *node;
+            if let Some(&next) = edges[node].get(*edge) {
+                *edge += 1;
+                if index[next] == UNVISITED {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    call_stack.push((next, 0));
+                } else if on_stack[next] {
+                    low_link[node] = low_link[node].min(index[next]);
+                }
+            } else {
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+                if low_link[node] == index[node] {
+                    let mut component = Vec::new();
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+    }
+
+    components
+}
+
+/// Finds the shortest cycle from `start` in the strongly connected component.
+///
+/// returns: the nodes in the cycle starting from `start`, or None if the component has no cycle,
+/// which means the component is a single node without self-dependency.
+fn shortest_cycle(edges: &[Vec<usize>], component: &[usize], start: usize) -> Option<Vec<usize>> {
+    let in_component = component.iter().copied().collect::<HashSet<_>>();
+    let mut parents = HashMap::<usize, usize>::new();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        for &next in &edges[node] {
+            if next == start {
+                let mut cycle = vec![node];
+                while let Some(&parent) = parents.get(cycle.last().unwrap()) {
+                    cycle.push(parent);
+                }
+                cycle.reverse();
+                return Some(cycle);
+            }
+            if in_component.contains(&next) && !parents.contains_key(&next) {
+                parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
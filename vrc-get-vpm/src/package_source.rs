@@ -0,0 +1,322 @@
+//! Packages fetched from git repositories or archives instead of VPM repositories.
+//!
+//! A dependency in `vpm-manifest.json` can have `source` to fetch the package from outside of repositories.
+//! The notation of the source is similar to the one of Unity Package Manager:
+//!
+//! - `git+https://example.com/repo.git#v1.0.0` or `https://example.com/repo.git#<commit>` for git repositories.
+//!   `?path=/Packages/com.example.package` can be used for packages in subdirectories.
+//!   Only `https://`, `ssh://`, `git://`, and scp-like `git@example.com:repo.git` urls are allowed
+//!   since the manifest may come from untrusted projects.
+//! - `https://example.com/package-1.0.0.tgz` for `.tgz`, `.tar.gz`, or `.zip` archives.
+//!
+//! The fetched package is locked by the commit hash for git, or the SHA256 of the archive.
+//!
+//! Sources are fetched only by the CLI for now; ALCOM does not call
+//! [`PackageCollection::fetch_package_sources`](crate::environment::PackageCollection::fetch_package_sources)
+//! so projects with sources cannot be resolved in ALCOM.
+
+use crate::PackageManifest;
+use crate::io;
+use crate::utils::is_complete_relative;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use url::Url;
+
+/// The source to fetch the package from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PackageSource {
+    /// The git repository, fetched with the `git` command.
+    Git {
+        url: Box<str>,
+        /// The branch, tag, or commit to fetch. Defaults to the default branch.
+        revision: Option<Box<str>>,
+        /// The path to the package in the repository.
+        path: Option<Box<str>>,
+    },
+    /// The `.zip`, `.tgz`, or `.tar.gz` archive of the package.
+    Archive { url: Url },
+}
+
+impl PackageSource {
+    pub fn is_git(&self) -> bool {
+        matches!(self, PackageSource::Git { .. })
+    }
+}
+
+impl FromStr for PackageSource {
+    type Err = ParsePackageSourceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParsePackageSourceError { notation: s.into() };
+
+        let (rest, revision) = match s.split_once('#') {
+            Some((rest, revision)) if !revision.is_empty() => (rest, Some(revision)),
+            Some((rest, _)) => (rest, None),
+            None => (s, None),
+        };
+
+        let (explicit_git, rest) = match rest.strip_prefix("git+") {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+
+        let (url, path) = match rest.split_once("?path=") {
+            Some((url, path)) => (url, Some(path.trim_matches('/'))),
+            None => (rest, None),
+        };
+
+        let is_git = explicit_git
+            || url.starts_with("git@")
+            || url.starts_with("git:")
+            || url.starts_with("ssh:")
+            || url.trim_end_matches('/').ends_with(".git");
+
+        if is_git {
+            // arguments starting with `-` would be options of git
+            if !is_allowed_git_url(url) || revision.is_some_and(|x| x.starts_with('-')) {
+                return Err(error());
+            }
+            if let Some(path) = path
+                && !is_complete_relative(Path::new(path))
+            {
+                return Err(error());
+            }
+            return Ok(PackageSource::Git {
+                url: url.into(),
+                revision: revision.map(Into::into),
+                path: path.filter(|x| !x.is_empty()).map(Into::into),
+            });
+        }
+
+        if revision.is_some() || path.is_some() {
+            return Err(error());
+        }
+
+        let url = Url::parse(s).map_err(|_| error())?;
+        if !matches!(url.scheme(), "http" | "https") || archive_kind(url.path()).is_none() {
+            return Err(error());
+        }
+
+        Ok(PackageSource::Archive { url })
+    }
+}
+
+impl Display for PackageSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageSource::Git {
+                url,
+                revision,
+                path,
+            } => {
+                let implicit = url.starts_with("git@")
+                    || url.starts_with("git:")
+                    || url.starts_with("ssh:")
+                    || url.trim_end_matches('/').ends_with(".git");
+                if !implicit {
+                    f.write_str("git+")?;
+                }
+                f.write_str(url)?;
+                if let Some(path) = path {
+                    write!(f, "?path=/{path}")?;
+                }
+                if let Some(revision) = revision {
+                    write!(f, "#{revision}")?;
+                }
+                Ok(())
+            }
+            PackageSource::Archive { url } => f.write_str(url.as_str()),
+        }
+    }
+}
+
+impl Serialize for PackageSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PackageSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let notation = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        PackageSource::from_str(&notation).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Returns whether git fetches the url over the network.
+///
+/// Other transports like `file://` or `ext::` are not allowed to fetch from untrusted manifests.
+fn is_allowed_git_url(url: &str) -> bool {
+    if url.starts_with('-') || url.contains("::") {
+        return false;
+    }
+    if let Some((scheme, _)) = url.split_once("://") {
+        return matches!(
+            scheme.to_ascii_lowercase().as_str(),
+            "https" | "ssh" | "git"
+        );
+    }
+    // scp-like syntax for ssh; git treats it as a local path if there is a slash before the colon,
+    // and a single letter before the colon is a drive letter on windows
+    match url.split_once(':') {
+        Some((host, path)) => host.len() > 1 && !host.contains('/') && !path.is_empty(),
+        None => false,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+pub(crate) fn archive_kind(path: &str) -> Option<ArchiveKind> {
+    let path = path.to_ascii_lowercase();
+    if path.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if path.ends_with(".tgz") || path.ends_with(".tar.gz") {
+        Some(ArchiveKind::TarGz)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug)]
+pub struct ParsePackageSourceError {
+    notation: Box<str>,
+}
+
+impl Display for ParsePackageSourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid package source: {}; expected a git url or a url to .zip, .tgz, or .tar.gz",
+            self.notation
+        )
+    }
+}
+
+impl std::error::Error for ParsePackageSourceError {}
+
+/// The package fetched from a [`PackageSource`] into the package cache.
+#[derive(Debug, Clone)]
+pub struct SourcePackage {
+    source: PackageSource,
+    hash: Box<str>,
+    path: PathBuf,
+    manifest: PackageManifest,
+}
+
+impl SourcePackage {
+    pub(crate) fn new(
+        source: PackageSource,
+        hash: Box<str>,
+        path: PathBuf,
+        manifest: PackageManifest,
+    ) -> Self {
+        Self {
+            source,
+            hash,
+            path,
+            manifest,
+        }
+    }
+
+    pub fn source(&self) -> &PackageSource {
+        &self.source
+    }
+
+    /// The commit hash for git, or the SHA256 of the archive in hex.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// The path to the fetched package folder.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn manifest(&self) -> &PackageManifest {
+        &self.manifest
+    }
+}
+
+/// The error fetching packages from [`PackageSource`]s.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FetchSourceError {
+    Io(io::Error),
+    /// The `git` command failed.
+    Git {
+        package: Box<str>,
+        message: String,
+    },
+    /// The SHA256 of the archive is not the locked one.
+    HashMismatch {
+        package: Box<str>,
+        expected: Box<str>,
+        actual: Box<str>,
+    },
+    /// The name in package.json is not the name of the dependency.
+    NameMismatch {
+        package: Box<str>,
+        actual: Box<str>,
+    },
+    /// The package is not fetched yet but fetching is not allowed in offline mode.
+    OfflineMode {
+        package: Box<str>,
+    },
+    /// The name of the dependency cannot be used as a folder name.
+    InvalidName {
+        package: Box<str>,
+    },
+}
+
+impl Display for FetchSourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchSourceError::Io(e) => Display::fmt(e, f),
+            FetchSourceError::Git { package, message } => {
+                write!(f, "git failed fetching {package}: {message}")
+            }
+            FetchSourceError::HashMismatch {
+                package,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "the archive of {package} has SHA256 {actual} but {expected} is locked"
+            ),
+            FetchSourceError::NameMismatch { package, actual } => {
+                write!(f, "the package fetched for {package} is named {actual}")
+            }
+            FetchSourceError::OfflineMode { package } => {
+                write!(
+                    f,
+                    "{package} is not fetched yet and cannot be fetched offline"
+                )
+            }
+            FetchSourceError::InvalidName { package } => {
+                write!(f, "{package} is not a valid package name to fetch")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FetchSourceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FetchSourceError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FetchSourceError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
@@ -0,0 +1,116 @@
+use crate::io;
+use crate::repository::{PackageZip, RemoteRepository};
+use crate::version::Version;
+use log::warn;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+type JsonMap = Map<String, Value>;
+
+/// Builds a [`RemoteRepository`] from package zips.
+///
+/// Packages are sorted by name and versions are sorted by version,
+/// so the same set of packages always produces the same json.
+#[derive(Debug, Clone, Default)]
+pub struct RepositoryBuilder {
+    repo: JsonMap,
+    packages: BTreeMap<Box<str>, BTreeMap<Version, JsonMap>>,
+}
+
+impl RepositoryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a builder with the information and all package versions of the existing repository.
+    pub fn from_repository(repository: &RemoteRepository) -> io::Result<Self> {
+        let Value::Object(mut repo) = serde_json::to_value(repository)? else {
+            unreachable!("repository is serialized as a map")
+        };
+        let packages = match repo.remove("packages") {
+            Some(Value::Object(packages)) => packages,
+            _ => JsonMap::new(),
+        };
+
+        let mut builder = Self {
+            repo,
+            packages: BTreeMap::new(),
+        };
+
+        for (name, package) in packages {
+            let Some(versions) = package.get("versions").and_then(Value::as_object) else {
+                continue;
+            };
+            for (version, manifest) in versions {
+                let (Ok(version), Value::Object(manifest)) = (Version::from_str(version), manifest)
+                else {
+                    warn!("ignoring invalid package version {name}@{version}");
+                    continue;
+                };
+                (builder.packages.entry(name.as_str().into()).or_default())
+                    .insert(version, manifest.clone());
+            }
+        }
+
+        Ok(builder)
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.set("name", name);
+    }
+
+    pub fn set_id(&mut self, id: &str) {
+        self.set("id", id);
+    }
+
+    pub fn set_url(&mut self, url: &str) {
+        self.set("url", url);
+    }
+
+    pub fn set_author(&mut self, author: &str) {
+        self.set("author", author);
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        self.repo
+            .insert(key.to_owned(), Value::String(value.to_owned()));
+    }
+
+    /// Adds the package zip served at `url`.
+    ///
+    /// Returns the json of the version previously in the repository if the version is replaced.
+    pub fn add_package(&mut self, package: &PackageZip, url: &str) -> Option<JsonMap> {
+        let mut json = package.json().clone();
+        json.insert("url".to_owned(), Value::String(url.to_owned()));
+        json.insert(
+            "zipSHA256".to_owned(),
+            Value::String(hex::encode(package.sha256())),
+        );
+
+        let manifest = package.manifest();
+        (self.packages.entry(manifest.name().into()).or_default())
+            .insert(manifest.version().clone(), json)
+    }
+
+    pub fn build(self) -> io::Result<RemoteRepository> {
+        let packages = self
+            .packages
+            .into_iter()
+            .map(|(name, versions)| {
+                let versions = versions
+                    .into_iter()
+                    .map(|(version, json)| (version.to_string(), Value::Object(json)))
+                    .collect::<JsonMap>();
+                let package =
+                    JsonMap::from_iter([("versions".to_owned(), Value::Object(versions))]);
+                (name.into(), Value::Object(package))
+            })
+            .collect::<JsonMap>();
+
+        let mut repo = self.repo;
+        repo.insert("packages".to_owned(), Value::Object(packages));
+
+        RemoteRepository::parse(repo)
+    }
+}
@@ -1,5 +1,5 @@
 use crate::PackageManifest;
-use crate::repository::{RemotePackages, RemoteRepository};
+use crate::repository::{RemotePackages, RemoteRepository, SignatureError};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -12,6 +12,9 @@ pub struct LocalCachedRepository {
     #[serde(rename = "vrc-get")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) vrc_get: Option<VrcGetMeta>,
+    /// The public key pinned in the settings. This is not saved to the cache.
+    #[serde(skip)]
+    pub(crate) public_key: Option<Box<str>>,
 }
 
 impl LocalCachedRepository {
@@ -20,6 +23,7 @@ impl LocalCachedRepository {
             repo,
             headers,
             vrc_get: None,
+            public_key: None,
         }
     }
 
@@ -49,6 +53,55 @@ impl LocalCachedRepository {
         }
     }
 
+    /// Records the public key verified the signature of the current repository, or `None` if not verified.
+    pub(crate) fn set_verified_public_key(&mut self, public_key: Option<Box<str>>) {
+        if public_key.is_some() || self.vrc_get.is_some() {
+            self.vrc_get
+                .get_or_insert_with(Default::default)
+                .verified_public_key = public_key;
+        }
+    }
+
+    /// The public key pinned for this repository in the settings.
+    pub fn public_key(&self) -> Option<&str> {
+        self.public_key.as_deref()
+    }
+
+    /// Returns `true` if the repository has a pinned public key and
+    /// the repository is verified with the key.
+    pub fn is_signature_verified(&self) -> bool {
+        self.public_key.is_some()
+            && self.public_key.as_deref()
+                == (self.vrc_get.as_ref()).and_then(|x| x.verified_public_key.as_deref())
+    }
+
+    /// Checks the package can be installed from this repository.
+    ///
+    /// If the repository has a pinned public key, the repository must be verified with the key
+    /// and the package must have `zipSHA256` so the zip file can be verified.
+    pub(crate) fn check_signature(&self, package: &PackageManifest) -> Result<(), SignatureError> {
+        if self.public_key.is_none() {
+            return Ok(());
+        }
+
+        if !self.is_signature_verified() {
+            let repository = (self.url().map(Url::as_str))
+                .or(self.id())
+                .unwrap_or("<unknown>");
+            return Err(SignatureError::NotVerified {
+                repository: repository.into(),
+            });
+        }
+
+        if package.zip_sha_256().is_none() {
+            return Err(SignatureError::MissingZipSha256 {
+                package: format!("{}@{}", package.name(), package.version()).into(),
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn url(&self) -> Option<&Url> {
         self.repo().url()
     }
@@ -81,4 +134,7 @@ impl LocalCachedRepository {
 pub struct VrcGetMeta {
     #[serde(default, skip_serializing_if = "str::is_empty")]
     pub etag: Box<str>,
+    /// The public key that verified the signature of the repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_public_key: Option<Box<str>>,
 }
@@ -1,6 +1,14 @@
+mod builder;
 pub(crate) mod local;
+mod package_zip;
 mod remote;
+mod signature;
 
+pub use builder::RepositoryBuilder;
 pub use local::LocalCachedRepository;
+pub use package_zip::PackageZip;
 pub use remote::RemotePackages;
 pub use remote::RemoteRepository;
+pub use signature::RepositoryPublicKey;
+pub use signature::SignatureError;
+pub use signature::signature_url;
@@ -0,0 +1,158 @@
+use crate::PackageManifest;
+use crate::io;
+use crate::io::{BufReader, DirEntry, IoTrait, SeekFrom};
+use crate::utils::{MapResultExt, Sha256AsyncWrite, deserialize_json, deserialize_json_slice};
+use async_zip::base::read::seek::ZipFileReader;
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipDateTimeBuilder, ZipEntryBuilder};
+use futures::prelude::*;
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+type JsonMap = Map<String, Value>;
+
+/// A package zip file with its `package.json` and the SHA256 hash of the zip file.
+#[derive(Debug, Clone)]
+pub struct PackageZip {
+    json: JsonMap,
+    manifest: PackageManifest,
+    sha256: [u8; 256 / 8],
+}
+
+impl PackageZip {
+    /// Reads `package.json` at the root of the package zip file.
+    pub async fn read(mut zip_file: impl AsyncRead + AsyncSeek + Unpin) -> io::Result<Self> {
+        let mut hasher = Sha256AsyncWrite::new(io::sink());
+        io::copy(&mut zip_file, &mut hasher).await?;
+        let sha256 = hasher.finalize().1.into();
+
+        zip_file.seek(SeekFrom::Start(0)).await?;
+
+        let mut zip_reader = ZipFileReader::new(BufReader::new(zip_file))
+            .await
+            .err_mapped()?;
+        let Some(index) = (zip_reader.file().entries().iter())
+            .position(|entry| entry.filename().as_str().ok() == Some("package.json"))
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "package.json not found in the zip file",
+            ));
+        };
+
+        let mut buffer = Vec::new();
+        (zip_reader.reader_without_entry(index).await.err_mapped()?)
+            .read_to_end(&mut buffer)
+            .await?;
+
+        let (json, manifest) = parse_package_json(&buffer)?;
+
+        Ok(Self {
+            json,
+            manifest,
+            sha256,
+        })
+    }
+
+    /// Creates a package zip from the package folder `io` points to.
+    ///
+    /// Files are added in the order of their paths with a fixed timestamp,
+    /// so the same folder always produces the same zip file.
+    /// `.git` folders and folders whose names end with `~`, which are ignored by unity, are not included.
+    pub async fn create(io: &impl IoTrait, zip_file: impl AsyncWrite + Unpin) -> io::Result<Self> {
+        let package_json = io.open("package.json".as_ref()).await?;
+        let (json, manifest) = parse_package_json(&crate::utils::read_to_end(package_json).await?)?;
+
+        let mut files = Vec::new();
+        collect_files(io, PathBuf::new(), &mut files).await?;
+        files.sort();
+
+        let timestamp = ZipDateTimeBuilder::new().year(1980).month(1).day(1).build();
+
+        let mut writer = ZipFileWriter::new(Sha256AsyncWrite::new(zip_file));
+
+        for (name, path) in files {
+            let content = crate::utils::read_to_end(io.open(&path).await?).await?;
+            let entry = ZipEntryBuilder::new(name.into(), Compression::Deflate)
+                .last_modification_date(timestamp)
+                .unix_permissions(0o644);
+            writer
+                .write_entry_whole(entry, &content)
+                .await
+                .err_mapped()?;
+        }
+
+        let mut hasher = writer.close().await.err_mapped()?;
+        hasher.flush().await?;
+        let sha256 = hasher.finalize().1.into();
+
+        Ok(Self {
+            json,
+            manifest,
+            sha256,
+        })
+    }
+
+    /// The content of `package.json` in the zip file.
+    pub fn json(&self) -> &JsonMap {
+        &self.json
+    }
+
+    pub fn manifest(&self) -> &PackageManifest {
+        &self.manifest
+    }
+
+    /// The SHA256 hash of the zip file.
+    pub fn sha256(&self) -> &[u8; 256 / 8] {
+        &self.sha256
+    }
+}
+
+fn parse_package_json(bytes: &[u8]) -> io::Result<(JsonMap, PackageManifest)> {
+    let no_bom = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let json: JsonMap = deserialize_json_slice(no_bom)?;
+    let manifest = deserialize_json(Value::Object(json.clone()))?;
+    Ok((json, manifest))
+}
+
+fn is_excluded(file_name: &str) -> bool {
+    file_name == ".git" || file_name.ends_with('~')
+}
+
+/// Collects the files in the folder as pairs of the name in the zip file and the path
+async fn collect_files(
+    io: &impl IoTrait,
+    folder: PathBuf,
+    files: &mut Vec<(String, PathBuf)>,
+) -> io::Result<()> {
+    let mut read_dir = io.read_dir(&folder).await?;
+    let mut entries = Vec::new();
+    while let Some(entry) = read_dir.try_next().await? {
+        entries.push(entry);
+    }
+
+    for entry in entries {
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("file name is not utf8: {file_name:?}"),
+            ));
+        };
+        let path = folder.join(file_name);
+
+        if entry.file_type().await?.is_dir() {
+            if !is_excluded(file_name) {
+                Box::pin(collect_files(io, path, files)).await?;
+            }
+        } else {
+            let name = (path.components())
+                .map(|x| x.as_os_str().to_str().unwrap())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((name, path));
+        }
+    }
+
+    Ok(())
+}
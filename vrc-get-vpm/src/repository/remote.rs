@@ -1,4 +1,5 @@
 use crate::PackageManifest;
+use crate::repository::{RepositoryPublicKey, SignatureError, signature_url};
 use crate::traits::HttpClient;
 use crate::utils::{deserialize_json, deserialize_json_slice};
 use crate::version::Version;
@@ -10,6 +11,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::pin::pin;
+use std::str::FromStr;
 use url::Url;
 
 type JsonMap = Map<String, Value>;
@@ -59,21 +61,53 @@ impl RemoteRepository {
         headers: &IndexMap<Box<str>, Box<str>>,
         current_etag: Option<&str>,
     ) -> io::Result<Option<(RemoteRepository, Option<Box<str>>)>> {
-        let Some((stream, etag)) = client.get_with_etag(url, headers, current_etag).await? else {
+        let Some((bytes, etag)) = download_json(client, url, headers, current_etag).await? else {
             return Ok(None);
         };
 
-        let mut bytes = Vec::new();
-        pin!(stream).read_to_end(&mut bytes).await?;
+        Ok(Some((Self::parse_downloaded(&bytes, url)?, etag)))
+    }
+
+    /// Downloads the repository and verifies the signature at [`signature_url`] with the public key.
+    ///
+    /// The repository is returned even if the signature does not match, with the result of the verification.
+    pub(crate) async fn download_signed(
+        client: &impl HttpClient,
+        url: &Url,
+        headers: &IndexMap<Box<str>, Box<str>>,
+        current_etag: Option<&str>,
+        public_key: &str,
+    ) -> io::Result<Option<SignedDownload>> {
+        let Some((bytes, etag)) = download_json(client, url, headers, current_etag).await? else {
+            return Ok(None);
+        };
+
+        let repo = Self::parse_downloaded(&bytes, url)?;
+
+        let verified = match RepositoryPublicKey::from_str(public_key) {
+            Ok(public_key) => {
+                let headers = (headers.iter())
+                    .map(|(k, v)| (k.as_ref(), v.as_ref()))
+                    .collect();
+                let mut signature = Vec::new();
+                pin!(client.get(&signature_url(url), &headers).await?)
+                    .read_to_end(&mut signature)
+                    .await?;
+                public_key.verify(&bytes, &signature)
+            }
+            Err(e) => Err(e),
+        };
 
-        let no_bom = bytes
-            .strip_prefix(b"\xEF\xBB\xBF")
-            .unwrap_or(bytes.as_ref());
+        Ok(Some((repo, etag, verified)))
+    }
+
+    fn parse_downloaded(bytes: &[u8], url: &Url) -> io::Result<RemoteRepository> {
+        let no_bom = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
         let json = deserialize_json_slice(no_bom)?;
 
         let mut repo = RemoteRepository::parse(json)?;
         repo.set_url_if_none(|| url.clone());
-        Ok(Some((repo, etag)))
+        Ok(repo)
     }
 
     pub(crate) fn set_id_if_none(&mut self, f: impl FnOnce() -> Box<str>) {
@@ -98,6 +132,38 @@ impl RemoteRepository {
         }
     }
 
+    /// Removes package versions that does not match the predicate.
+    pub(crate) fn retain_versions(&mut self, mut f: impl FnMut(&PackageManifest) -> bool) {
+        let actual_packages = self
+            .actual
+            .get_mut("packages")
+            .and_then(Value::as_object_mut);
+
+        let mut removed = Vec::new();
+        for (name, package) in &mut self.parsed.packages {
+            package.versions.retain(|version, manifest| {
+                let retain = f(manifest);
+                if !retain {
+                    removed.push((name.clone(), version.clone()));
+                }
+                retain
+            });
+        }
+
+        if let Some(actual_packages) = actual_packages {
+            for (name, version) in removed {
+                if let Some(versions) = actual_packages
+                    .get_mut(name.as_ref())
+                    .and_then(|x| x.get_mut("versions"))
+                    .and_then(Value::as_object_mut)
+                {
+                    versions
+                        .retain(|key, _| Version::from_str(key).ok().as_ref() != Some(&version));
+                }
+            }
+        }
+    }
+
     pub fn url(&self) -> Option<&Url> {
         self.parsed.url.as_ref()
     }
@@ -139,6 +205,29 @@ impl RemoteRepository {
     }
 }
 
+/// The repository, the etag, and the result of the signature verification
+pub(crate) type SignedDownload = (
+    RemoteRepository,
+    Option<Box<str>>,
+    Result<(), SignatureError>,
+);
+
+async fn download_json(
+    client: &impl HttpClient,
+    url: &Url,
+    headers: &IndexMap<Box<str>, Box<str>>,
+    current_etag: Option<&str>,
+) -> io::Result<Option<(Vec<u8>, Option<Box<str>>)>> {
+    let Some((stream, etag)) = client.get_with_etag(url, headers, current_etag).await? else {
+        return Ok(None);
+    };
+
+    let mut bytes = Vec::new();
+    pin!(stream).read_to_end(&mut bytes).await?;
+
+    Ok(Some((bytes, etag)))
+}
+
 impl Serialize for RemoteRepository {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -0,0 +1,94 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use url::Url;
+
+/// The ed25519 public key trusted to sign a repository.
+///
+/// The key is written as base64 of the 32 bytes raw public key.
+/// The signature of the repository is served at [`signature_url`] as base64 of the 64 bytes raw signature
+/// for the exact bytes of the repository json.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryPublicKey([u8; 32]);
+
+impl RepositoryPublicKey {
+    /// Verifies the signature of the repository json.
+    pub fn verify(&self, json: &[u8], signature: &[u8]) -> Result<(), SignatureError> {
+        let signature = BASE64
+            .decode(signature.trim_ascii())
+            .map_err(|_| SignatureError::InvalidSignature)?;
+        if signature.len() != 64 {
+            return Err(SignatureError::InvalidSignature);
+        }
+
+        ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, &self.0)
+            .verify(json, &signature)
+            .map_err(|_| SignatureError::Mismatch)
+    }
+}
+
+impl FromStr for RepositoryPublicKey {
+    type Err = SignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = BASE64
+            .decode(s.trim())
+            .map_err(|_| SignatureError::InvalidPublicKey)?;
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| SignatureError::InvalidPublicKey)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl Display for RepositoryPublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&BASE64.encode(self.0))
+    }
+}
+
+/// Returns the url of the signature for the repository at the url.
+///
+/// The signature is served at the path of the repository with `.sig` appended.
+pub fn signature_url(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_path(&format!("{}.sig", url.path()));
+    url
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SignatureError {
+    /// The public key is not base64 of 32 bytes.
+    InvalidPublicKey,
+    /// The signature is not base64 of 64 bytes.
+    InvalidSignature,
+    /// The signature is not made for the repository json with the public key.
+    Mismatch,
+    /// The repository has a pinned public key but the repository is not verified with the key.
+    NotVerified { repository: Box<str> },
+    /// The package is from a repository with a pinned public key but has no `zipSHA256`,
+    /// so the zip file cannot be verified.
+    MissingZipSha256 { package: Box<str> },
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureError::InvalidPublicKey => f.write_str("invalid public key"),
+            SignatureError::InvalidSignature => f.write_str("invalid signature"),
+            SignatureError::Mismatch => f.write_str("signature does not match"),
+            SignatureError::NotVerified { repository } => write!(
+                f,
+                "the signature of repository {repository} is not verified with the pinned public key"
+            ),
+            SignatureError::MissingZipSha256 { package } => write!(
+                f,
+                "{package} is from a repository with a pinned public key but has no zipSHA256"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
@@ -18,6 +18,9 @@ pub mod setting {
         pub(crate) id: Option<Box<str>>,
         #[serde(default)]
         pub(crate) headers: IndexMap<Box<str>, Box<str>>,
+        /// The public key to verify the signature of the repository. vrc-get specific
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub(crate) public_key: Option<Box<str>>,
     }
 
     impl UserRepoSetting {
@@ -33,6 +36,7 @@ pub mod setting {
                 id: id.or(url.as_ref().map(Url::to_string).map(Into::into)),
                 url,
                 headers: IndexMap::new(),
+                public_key: None,
             }
         }
 
@@ -56,8 +60,68 @@ pub mod setting {
             &self.headers
         }
 
+        pub fn public_key(&self) -> Option<&str> {
+            self.public_key.as_deref()
+        }
+
         pub(crate) fn to_source(&self) -> RepoSource<'_> {
             RepoSource::new(&self.local_path, &self.headers, self.url.as_ref())
+                .with_public_key(self.public_key.as_deref())
+        }
+    }
+
+    /// The rule to restrict the packages to be from the specified repository. vrc-get specific
+    ///
+    /// Packages matching `scopes` are only taken from the repository with `repository` as id or url.
+    /// A scope is a package id, or a prefix of package ids ending with `*` like `com.example.*`.
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ScopedRegistry {
+        repository: Box<str>,
+        scopes: Vec<Box<str>>,
+    }
+
+    impl ScopedRegistry {
+        pub fn new(repository: Box<str>, scopes: Vec<Box<str>>) -> Self {
+            Self { repository, scopes }
+        }
+
+        /// The id or url of the repository.
+        pub fn repository(&self) -> &str {
+            &self.repository
+        }
+
+        pub fn scopes(&self) -> &[Box<str>] {
+            &self.scopes
+        }
+
+        /// Returns `true` if the package is in the scopes of this rule.
+        pub fn matches(&self, package: &str) -> bool {
+            self.scopes
+                .iter()
+                .any(|scope| match scope.strip_suffix('*') {
+                    Some(prefix) => package.starts_with(prefix),
+                    None => package == scope.as_ref(),
+                })
+        }
+
+        /// Returns `true` if the repository is the repository of this rule.
+        pub fn is_repository(&self, id: Option<&str>, url: Option<&Url>) -> bool {
+            id == Some(self.repository.as_ref())
+                || url.is_some_and(|url| url.as_str() == self.repository.as_ref())
+        }
+
+        /// Returns `true` if the package can be taken from the repository under the rules.
+        ///
+        /// Packages not matching any rule can be taken from any repository.
+        pub(crate) fn allows(
+            rules: &[ScopedRegistry],
+            package: &str,
+            id: Option<&str>,
+            url: Option<&Url>,
+        ) -> bool {
+            let mut rules = rules.iter().filter(|x| x.matches(package)).peekable();
+            rules.peek().is_none() || rules.any(|x| x.is_repository(id, url))
         }
     }
 }
@@ -1,21 +1,31 @@
 mod add_package;
+mod backup;
+mod dependency_path;
 mod find_legacy_assets;
+mod import_unitypackage;
+mod migrate_unity;
 mod migrate_unity_2022;
 mod migrate_vpm;
 mod package_resolution;
 pub mod pending_project_changes;
+mod project_files;
 mod project_type;
 mod reinstall;
 mod remove_package;
 mod resolve;
+mod scoped_collection;
+mod transaction_journal;
+mod unity_compatibility;
 mod upm_manifest;
+mod verify_packages;
 mod vpm_manifest;
 
+use crate::unity_project::transaction_journal::{Recovered, TransactionJournal};
 use crate::unity_project::upm_manifest::UpmManifest;
 use crate::unity_project::vpm_manifest::VpmManifest;
 use crate::utils::{PathBufExt, try_load_json};
 use crate::version::{DependencyRange, UnityVersion, Version, VersionRange};
-use crate::{PackageManifest, io};
+use crate::{PackageManifest, ScopedRegistry, io};
 use futures::future::try_join;
 use futures::prelude::*;
 use indexmap::IndexMap;
@@ -28,14 +38,40 @@ use std::path::{Path, PathBuf};
 
 use crate::io::{DefaultProjectIo, DirEntry, IoTrait, TokioDirEntry};
 use crate::package_manifest::LooseManifest;
+use crate::package_source::PackageSource;
 pub use add_package::AddPackageErr;
 pub use add_package::AddPackageOperation;
+pub use backup::AUTOMATIC_BACKUP_SUFFIX;
+pub use backup::BACKUP_TIMESTAMP_FORMAT;
+pub use backup::BackupFormat;
+pub use backup::ZipCompression;
+pub use backup::write_backup_tar_zst;
+pub use dependency_path::DependencyEdge;
+pub use dependency_path::DependencyPath;
+pub use import_unitypackage::ImportConflict;
+pub use import_unitypackage::ImportConflictHandling;
+pub use import_unitypackage::ImportUnityPackageError;
+pub use import_unitypackage::UnityPackageAsset;
+pub use migrate_unity::MigrateUnityError;
 pub use migrate_unity_2022::MigrateUnity2022Error;
 pub use migrate_vpm::MigrateVpmError;
 pub use pending_project_changes::PendingProjectChanges;
+pub use project_files::FileSystemTree;
+pub use project_files::FileSystemTreeIter;
+pub use project_files::FileSystemTreeRecursive;
+pub use project_files::collect_notable_project_files_tree;
 pub use reinstall::ReinstalPackagesError;
 pub use remove_package::RemovePackageErr;
+pub use resolve::FrozenLockViolation;
 pub use resolve::ResolvePackageErr;
+pub use unity_compatibility::CompatibilityStatus;
+pub use unity_compatibility::PackageCompatibility;
+pub use unity_compatibility::UnityCompatibility;
+pub use upm_manifest::AddUpmDependencyErr;
+pub use upm_manifest::UpmDependency;
+pub use upm_manifest::UpmScopedRegistry;
+pub use verify_packages::PackageVerification;
+pub use verify_packages::VerifyPackagesResult;
 
 #[derive(Debug)]
 pub struct UnityProject {
@@ -57,6 +93,17 @@ pub struct UnityProject {
 // basic lifecycle
 impl UnityProject {
     pub async fn load(io: DefaultProjectIo) -> io::Result<Self> {
+        // recover the project if the process was killed while applying changes
+        match TransactionJournal::recover(&io).await? {
+            None => {}
+            Some(Recovered::RolledBack) => {
+                log::warn!("rolled back the interrupted changes to the project");
+            }
+            Some(Recovered::Completed) => {
+                log::warn!("completed the interrupted changes to the project");
+            }
+        }
+
         let manifest = VpmManifest::load(&io).await?;
         let upm_manifest = UpmManifest::load(&io).await?;
 
@@ -237,10 +284,42 @@ impl UnityProject {
         self.manifest.dependencies().map(|(name, _)| name)
     }
 
+    pub fn dependencies_with_range(&self) -> impl Iterator<Item = (&str, &DependencyRange)> {
+        self.manifest.dependencies()
+    }
+
     pub fn get_locked(&self, name: &str) -> Option<LockedDependencyInfo<'_>> {
         self.manifest.get_locked(name)
     }
 
+    /// The rules to restrict packages to be from specific repositories for this project.
+    ///
+    /// These rules are used in addition to the rules in the settings.
+    pub fn scoped_registries(&self) -> &[ScopedRegistry] {
+        self.manifest.scoped_registries()
+    }
+
+    /// Returns the dependencies fetched from [`PackageSource`]s instead of repositories.
+    ///
+    /// The third element is the locked commit hash or SHA256 if the package is locked with the same source.
+    pub fn package_sources(&self) -> impl Iterator<Item = (&str, &PackageSource, Option<&str>)> {
+        self.manifest.sources()
+    }
+
+    /// The version ranges used instead of the ranges required by other packages.
+    ///
+    /// The ranges in `dependencies` section are not overridden.
+    pub fn overrides(&self) -> impl Iterator<Item = (&str, &VersionRange)> {
+        self.manifest
+            .overrides()
+            .iter()
+            .map(|(name, range)| (name.as_ref(), range))
+    }
+
+    pub fn get_override(&self, name: &str) -> Option<&VersionRange> {
+        self.manifest.overrides().get(name)
+    }
+
     pub fn is_locked(&self, name: &str) -> bool {
         self.manifest.get_locked(name).is_some()
     }
@@ -287,10 +366,61 @@ impl UnityProject {
         self.unity_revision.as_deref()
     }
 
+    pub fn upm_dependencies(&self) -> impl Iterator<Item = (&str, &UpmDependency)> {
+        self.upm_manifest.dependencies()
+    }
+
     pub fn has_upm_package(&self, name: &str) -> bool {
         self.upm_manifest.get_dependency(name).is_some()
     }
 
+    pub fn get_upm_dependency(&self, name: &str) -> Option<&UpmDependency> {
+        self.upm_manifest.get_dependency(name)
+    }
+
+    pub fn upm_scoped_registries(&self) -> &[UpmScopedRegistry] {
+        self.upm_manifest.scoped_registries()
+    }
+
+    /// Adds the dependency to `Packages/manifest.json`, replacing the existing one.
+    ///
+    /// If `registry` is specified, the package is added to the scopes of the scoped registry
+    /// with the url, and the registry is added with the name if not exists.
+    /// The trailing `/` of the url is ignored to find the registry.
+    pub fn add_upm_dependency(
+        &mut self,
+        name: &str,
+        dependency: UpmDependency,
+        registry: Option<(&str, &str)>,
+    ) -> Result<(), AddUpmDependencyErr> {
+        if let Some((registry_name, url)) = registry {
+            self.upm_manifest.add_scope(registry_name, url, name)?;
+        }
+        self.upm_manifest.add_dependency(name, dependency);
+        Ok(())
+    }
+
+    /// Removes the dependency and the scope for the package from `Packages/manifest.json`.
+    ///
+    /// The scope is kept if other dependencies match the scope,
+    /// and the scoped registries without other scopes are removed.
+    /// Returns `false` if the dependency is not found.
+    pub fn remove_upm_dependency(&mut self, name: &str) -> bool {
+        let removed = self.upm_manifest.remove_dependency(name);
+        self.upm_manifest.remove_scope(name);
+        removed
+    }
+
+    /// Returns `true` if the package is also managed by vpm in this project.
+    ///
+    /// Unity uses the package installed by vpm in the `Packages` folder instead of the package in
+    /// `Packages/manifest.json`, so having both may cause unexpected version to be used.
+    pub fn is_vpm_package(&self, name: &str) -> bool {
+        self.manifest.get_locked(name).is_some()
+            || self.manifest.dependencies().any(|(dep, _)| dep == name)
+            || self.installed_packages.contains_key(name)
+    }
+
     /// Adds dependency without actually adding package.
     /// This only modifies manifest
     pub fn add_dependency_raw(&mut self, name: &str, version: DependencyRange) {
@@ -2,7 +2,7 @@ use crate::unity_project::package_resolution::MissingDependencies;
 use crate::unity_project::pending_project_changes::RemoveReason;
 use crate::unity_project::vpm_manifest::VpmManifest;
 use crate::unity_project::{PendingProjectChanges, package_resolution};
-use crate::version::{DependencyRange, VersionRange};
+use crate::version::{DependencyRange, UnityVersion, VersionRange};
 use crate::{PackageCollection, PackageInfo, UnityProject};
 use log::debug;
 use std::fmt;
@@ -22,6 +22,8 @@ pub enum AddPackageErr {
     UpgradingWithDowngrade {
         package_name: Box<str>,
     },
+    /// The resolution gave up since there are too many combinations of versions to try.
+    ResolutionStepLimitExceeded,
 }
 
 impl fmt::Display for AddPackageErr {
@@ -51,6 +53,10 @@ impl fmt::Display for AddPackageErr {
                 f,
                 "Package {package_name} is locked, so it cannot be downgraded"
             ),
+            AddPackageErr::ResolutionStepLimitExceeded => write!(
+                f,
+                "Gave up resolving dependencies since there are too many combinations of package versions"
+            ),
         }
     }
 }
@@ -77,6 +83,26 @@ impl UnityProject {
         packages: &[PackageInfo<'env>],
         operation: AddPackageOperation,
         allow_prerelease: bool,
+    ) -> Result<PendingProjectChanges<'env>, AddPackageErr> {
+        self.add_package_request_for_unity(
+            env,
+            packages,
+            operation,
+            allow_prerelease,
+            self.unity_version(),
+        )
+        .await
+    }
+
+    /// Creates a new `AddPackageRequest` with resolving dependencies for the specified Unity version
+    /// instead of the Unity version of the project.
+    pub(super) async fn add_package_request_for_unity<'env>(
+        &self,
+        env: &'env impl PackageCollection,
+        packages: &[PackageInfo<'env>],
+        operation: AddPackageOperation,
+        allow_prerelease: bool,
+        unity_version: UnityVersion,
     ) -> Result<PendingProjectChanges<'env>, AddPackageErr> {
         // if same or newer requested package is in locked dependencies,
         // just add requested version into dependencies
@@ -295,8 +321,9 @@ impl UnityProject {
             self.manifest.all_locked(),
             self.unlocked_packages.iter(),
             |pkg| self.manifest.get_locked(pkg),
-            Some(self.unity_version()),
-            env,
+            self.manifest.overrides(),
+            Some(unity_version),
+            self.scoped(env),
             adding_packages,
             allow_prerelease,
             &mut missing_dependencies,
@@ -306,6 +333,9 @@ impl UnityProject {
                 dependencies: missing_dependencies.into_vec(),
             });
         }
+        if result.step_limit_exceeded {
+            return Err(AddPackageErr::ResolutionStepLimitExceeded);
+        }
 
         debug!("Resolving finished");
 
@@ -329,6 +359,10 @@ impl UnityProject {
             changes.conflict_multiple(package, conflicts_with);
         }
 
+        for (package, explanation) in result.explanations {
+            changes.conflict_explanation(package, explanation);
+        }
+
         for name in result
             .found_legacy_packages
             .into_iter()
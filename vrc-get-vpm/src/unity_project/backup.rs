@@ -0,0 +1,147 @@
+use crate::UnityProject;
+use crate::io;
+use crate::io::IoTrait;
+use crate::unity_project::FileSystemTree;
+use crate::unity_project::project_files::{collect_project_files_tree, locked_package_folders};
+use crate::utils::MapResultExt;
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, DeflateOption, ZipEntryBuilder};
+use futures::prelude::*;
+use std::path::Path;
+
+/// The format of the timestamps in the names of the backups, `{project_name}-{timestamp}`.
+pub const BACKUP_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H-%M-%S";
+/// The suffix of the names of the backups created automatically before changing the project.
+pub const AUTOMATIC_BACKUP_SUFFIX: &str = "-auto";
+
+/// The format of the backup files, shared with ALCOM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupFormat {
+    /// The zip archive created by [`UnityProject::create_backup_zip`].
+    Zip,
+    /// The tar archive compressed with zstd created by [`write_backup_tar_zst`].
+    TarZst,
+    /// The snapshot of the incremental backups of ALCOM.
+    Incremental,
+}
+
+impl BackupFormat {
+    /// The extension of the backup file without the leading dot.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            BackupFormat::Zip => "zip",
+            BackupFormat::TarZst => "tar.zst",
+            BackupFormat::Incremental => "snapshot.json",
+        }
+    }
+
+    /// Splits the file name of the backup into the backup name and the format.
+    pub fn from_file_name(file_name: &str) -> Option<(&str, Self)> {
+        [
+            BackupFormat::Incremental,
+            BackupFormat::TarZst,
+            BackupFormat::Zip,
+        ]
+        .into_iter()
+        .find_map(|format| {
+            let name = file_name
+                .strip_suffix(format.extension())?
+                .strip_suffix('.')?;
+            Some((name, format))
+        })
+    }
+}
+
+/// The compression of the files in the zip backup, corresponding to the zip backup formats of ALCOM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZipCompression {
+    /// The files are stored without compression.
+    Stored,
+    /// The files are compressed with the fastest deflate.
+    #[default]
+    Fast,
+    /// The files are compressed with the best deflate.
+    Best,
+}
+
+impl UnityProject {
+    /// Creates a zip archive of the project for backup.
+    ///
+    /// The files are collected with [`collect_notable_project_files_tree`] like the backups of ALCOM.
+    /// With `exclude_vpm`, the locked VPM packages are not included since they can be restored by resolving.
+    ///
+    /// [`collect_notable_project_files_tree`]: crate::unity_project::collect_notable_project_files_tree
+    pub async fn create_backup_zip(
+        &self,
+        zip_file: impl AsyncWrite + Unpin,
+        compression: ZipCompression,
+        exclude_vpm: bool,
+    ) -> io::Result<()> {
+        let excluded_packages = if exclude_vpm {
+            locked_package_folders(self)
+        } else {
+            vec![]
+        };
+
+        let tree =
+            collect_project_files_tree(self.project_dir().to_path_buf(), &excluded_packages, true)
+                .await?;
+
+        let mut writer = ZipFileWriter::new(zip_file);
+
+        for entry in tree.recursive() {
+            let name = entry.relative_path();
+            if entry.is_dir() {
+                let entry = ZipEntryBuilder::new(name.into(), Compression::Stored);
+                writer.write_entry_whole(entry, &[]).await.err_mapped()?;
+            } else {
+                let mut file = self.io.open(Path::new(name)).await?;
+                let entry = match compression {
+                    ZipCompression::Stored => {
+                        ZipEntryBuilder::new(name.into(), Compression::Stored)
+                    }
+                    ZipCompression::Fast => ZipEntryBuilder::new(name.into(), Compression::Deflate)
+                        .deflate_option(DeflateOption::Fast),
+                    ZipCompression::Best => ZipEntryBuilder::new(name.into(), Compression::Deflate)
+                        .deflate_option(DeflateOption::Maximum),
+                };
+                let mut entry_writer = writer.write_entry_stream(entry).await.err_mapped()?;
+                io::copy(&mut file, &mut entry_writer).await?;
+                entry_writer.close().await.err_mapped()?;
+            }
+        }
+
+        let mut zip_file = writer.close().await.err_mapped()?;
+        zip_file.flush().await?;
+
+        Ok(())
+    }
+}
+
+/// Writes the tar archive compressed with zstd of the files in the tree with the default level.
+///
+/// Since tar is a sequential format, this blocks the thread while writing the files,
+/// and zstd compresses the stream with multiple worker threads.
+/// `on_entry` is called with the index of the entry after adding each entry, and returning an error aborts writing.
+pub fn write_backup_tar_zst<W: std::io::Write>(
+    tree: &FileSystemTree,
+    file: W,
+    mut on_entry: impl FnMut(usize, &FileSystemTree) -> std::io::Result<()>,
+) -> std::io::Result<W> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    let mut encoder = zstd::stream::write::Encoder::new(file, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+    encoder.multithread(threads as u32)?;
+    let mut builder = tar::Builder::new(encoder);
+
+    for (index, entry) in tree.recursive().enumerate() {
+        if entry.is_dir() {
+            builder.append_dir(entry.relative_path(), entry.absolute_path())?;
+        } else {
+            builder.append_path_with_name(entry.absolute_path(), entry.relative_path())?;
+        }
+        on_entry(index, entry)?;
+    }
+
+    builder.into_inner()?.finish()
+}
@@ -0,0 +1,107 @@
+use crate::UnityProject;
+use crate::unity_project::LockedDependencyInfo;
+use crate::version::VersionRange;
+use std::collections::HashMap;
+
+/// A chain of dependencies from the `dependencies` of the project to some package.
+#[derive(Clone)]
+pub struct DependencyPath<'a> {
+    edges: Vec<DependencyEdge<'a>>,
+}
+
+impl<'a> DependencyPath<'a> {
+    /// The edges of the path.
+    ///
+    /// The first edge is always from the project, and the last edge is always to the package.
+    pub fn edges(&self) -> &[DependencyEdge<'a>] {
+        &self.edges
+    }
+}
+
+/// One dependency in [`DependencyPath`].
+#[derive(Clone)]
+pub struct DependencyEdge<'a> {
+    dependant: Option<LockedDependencyInfo<'a>>,
+    dependency: &'a str,
+    range: VersionRange,
+}
+
+impl<'a> DependencyEdge<'a> {
+    /// The package requiring the dependency, or `None` if this is from `dependencies` of the project.
+    pub fn dependant(&self) -> Option<&LockedDependencyInfo<'a>> {
+        self.dependant.as_ref()
+    }
+
+    /// The name of the package required.
+    pub fn dependency(&self) -> &'a str {
+        self.dependency
+    }
+
+    /// The version range required for the dependency.
+    pub fn range(&self) -> &VersionRange {
+        &self.range
+    }
+}
+
+impl UnityProject {
+    /// Lists every dependency path from the `dependencies` of the project to the package.
+    ///
+    /// Paths are built from the dependencies of locked packages and installed but not locked packages.
+    /// Cyclic dependencies are not followed, so each package appears at most once in a path.
+    pub fn dependency_paths(&self, package: &str) -> Vec<DependencyPath<'_>> {
+        let packages = self
+            .all_packages()
+            .map(|x| (x.name(), x))
+            .collect::<HashMap<_, _>>();
+
+        let mut paths = vec![];
+        let mut current = vec![];
+
+        for (name, range) in self.manifest.dependencies() {
+            current.push(DependencyEdge {
+                dependant: None,
+                dependency: name,
+                range: range.as_range(),
+            });
+            walk(&packages, package, &mut current, &mut paths);
+            current.pop();
+        }
+
+        paths
+    }
+}
+
+fn walk<'a>(
+    packages: &HashMap<&'a str, LockedDependencyInfo<'a>>,
+    target: &str,
+    current: &mut Vec<DependencyEdge<'a>>,
+    paths: &mut Vec<DependencyPath<'a>>,
+) {
+    let name = current.last().unwrap().dependency;
+    if name == target {
+        paths.push(DependencyPath {
+            edges: current.clone(),
+        });
+        return;
+    }
+
+    let Some(package) = packages.get(name) else {
+        return;
+    };
+
+    for (dependency, range) in package.dependencies() {
+        let dependency = dependency.as_ref();
+        if current.iter().any(|edge| edge.dependency == dependency) {
+            // cyclic dependency
+            continue;
+        }
+
+        current.push(DependencyEdge {
+            dependant: Some(package.clone()),
+            dependency,
+            range: range.clone(),
+        });
+        walk(packages, target, current, paths);
+        current.pop();
+    }
+}
@@ -184,7 +184,7 @@ async fn check_guid(io: &DefaultProjectIo, path: &Path, guid: Option<Guid>) -> b
     true
 }
 
-async fn try_parse_meta(io: &DefaultProjectIo, path: &Path) -> Option<Guid> {
+pub(super) async fn try_parse_meta(io: &DefaultProjectIo, path: &Path) -> Option<Guid> {
     let mut file = BufReader::new(io.open(path).await.ok()?);
     let mut buffer = String::new();
     while file.read_line(&mut buffer).await.ok()? != 0 {
@@ -270,10 +270,16 @@ enum LegacySearchResult<'a> {
 }
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq)]
-struct Guid([u8; 16]);
+pub(super) struct Guid([u8; 16]);
 
 impl Guid {
-    fn parse(guid: &str) -> Option<Guid> {
+    pub(super) fn parse(guid: &str) -> Option<Guid> {
         FromHex::from_hex(guid).ok().map(Guid)
     }
 }
+
+impl std::fmt::Display for Guid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
@@ -0,0 +1,509 @@
+use crate::io::{DefaultProjectIo, IoTrait};
+use crate::unity_project::find_legacy_assets::{Guid, try_parse_meta};
+use crate::unity_project::pending_project_changes::PKG_TEMP_DIR;
+use crate::utils::{TarArchive, walk_dir_relative};
+use crate::{UnityProject, io};
+use async_compression::futures::bufread::GzipDecoder;
+use futures::prelude::*;
+use log::warn;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+
+/// How to handle the assets in the unitypackage conflicting with the project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportConflictHandling {
+    /// Fails without changing the project if any asset conflicts.
+    Fail,
+    /// Imports the assets except for the conflicting assets.
+    Skip,
+    /// Overwrites the conflicting assets in the project.
+    ///
+    /// Like Unity, the asset with the GUID existing in the project is written to the existing path.
+    /// Fails without changing the project if any asset cannot be overwritten.
+    /// See [`UnityPackageAsset::can_overwrite`].
+    Overwrite,
+    /// Writes the assets to the paths in the unitypackage, replacing the existing files and metadata.
+    ///
+    /// Unlike [`Overwrite`](ImportConflictHandling::Overwrite), the existing assets with the same GUID are not taken into account.
+    /// This is how the unitypackages of the templates are extracted to the new project.
+    /// Fails without changing the project if a file and a folder would be replaced with each other.
+    Replace,
+}
+
+/// The conflict of an asset in the unitypackage with the project.
+///
+/// An asset may have multiple conflicts, for example, both [`PathExists`] and [`GuidExists`].
+///
+/// [`PathExists`]: ImportConflict::PathExists
+/// [`GuidExists`]: ImportConflict::GuidExists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportConflict {
+    /// A file already exists at the path of the asset.
+    ///
+    /// Existing folders are not conflicts for folder assets; the contents are merged.
+    PathExists,
+    /// The existing asset at the path of the asset has another GUID.
+    ///
+    /// The asset cannot be overwritten at the path since the GUID of the existing asset would change.
+    GuidMismatch(Box<str>),
+    /// An asset with the same GUID exists at another path.
+    ///
+    /// The asset is written to the existing path when overwriting, like Unity.
+    GuidExists(Box<Path>),
+    /// The asset cannot be written since a folder exists at the path for the file asset,
+    /// or a file exists at the path or the parent folders for the folder asset.
+    ///
+    /// The path is the existing path of the GUID if [`GuidExists`](ImportConflict::GuidExists).
+    TypeMismatch,
+}
+
+/// An asset in the unitypackage.
+#[derive(Debug)]
+pub struct UnityPackageAsset {
+    guid: Box<str>,
+    path: Box<str>,
+    is_folder: bool,
+    conflicts: Vec<ImportConflict>,
+}
+
+impl UnityPackageAsset {
+    pub fn guid(&self) -> &str {
+        &self.guid
+    }
+
+    /// The path of the asset relative to the project, separated by `/`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn is_folder(&self) -> bool {
+        self.is_folder
+    }
+
+    /// The conflicts with the project. Empty if the asset does not conflict.
+    pub fn conflicts(&self) -> &[ImportConflict] {
+        &self.conflicts
+    }
+
+    /// Returns whether the asset can be imported with [`ImportConflictHandling::Overwrite`].
+    ///
+    /// The asset cannot be overwritten if the GUID of the existing asset would change,
+    /// or a file and a folder would be replaced with each other.
+    pub fn can_overwrite(&self) -> bool {
+        let guid_exists = self.existing_guid_path().is_some();
+        self.conflicts.iter().all(|conflict| match conflict {
+            ImportConflict::PathExists | ImportConflict::GuidExists(_) => true,
+            // the asset is written to the existing path of the GUID instead
+            ImportConflict::GuidMismatch(_) => guid_exists,
+            ImportConflict::TypeMismatch => false,
+        })
+    }
+
+    fn is_type_mismatch(&self) -> bool {
+        self.conflicts.contains(&ImportConflict::TypeMismatch)
+    }
+
+    fn existing_guid_path(&self) -> Option<&Path> {
+        self.conflicts.iter().find_map(|conflict| match conflict {
+            ImportConflict::GuidExists(path) => Some(&**path),
+            _ => None,
+        })
+    }
+
+    /// The path the asset is written to.
+    fn target_path(&self, conflict_handling: ImportConflictHandling) -> &Path {
+        match conflict_handling {
+            ImportConflictHandling::Replace => Path::new(self.path()),
+            _ => self
+                .existing_guid_path()
+                .unwrap_or_else(|| Path::new(self.path())),
+        }
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ImportUnityPackageError {
+    /// Some assets conflict with the project with [`ImportConflictHandling::Fail`].
+    Conflicts(Vec<UnityPackageAsset>),
+    /// Some assets cannot be overwritten with [`ImportConflictHandling::Overwrite`].
+    ///
+    /// See [`UnityPackageAsset::can_overwrite`].
+    /// With [`ImportConflictHandling::Replace`], the assets replacing a file with a folder or vice versa.
+    CannotOverwrite(Vec<UnityPackageAsset>),
+    Io(io::Error),
+}
+
+impl std::error::Error for ImportUnityPackageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportUnityPackageError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ImportUnityPackageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportUnityPackageError::Conflicts(assets) => {
+                write!(f, "{} assets conflict with the project", assets.len())
+            }
+            ImportUnityPackageError::CannotOverwrite(assets) => {
+                write!(f, "{} assets cannot be overwritten", assets.len())
+            }
+            ImportUnityPackageError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<io::Error> for ImportUnityPackageError {
+    fn from(err: io::Error) -> Self {
+        ImportUnityPackageError::Io(err)
+    }
+}
+
+#[derive(Default)]
+struct UnityPackageEntry {
+    // empty means not exists.
+    metadata: Vec<u8>,
+    // empty means not exists.
+    pathname: String,
+    has_file: bool,
+}
+
+impl UnityProject {
+    /// Imports the gzip-compressed unitypackage to the project.
+    ///
+    /// This importer holds metadata on the memory and extracts the data to the temporary folder in the project,
+    /// and then move to corresponding paths.
+    /// With `dry_run`, the unitypackage is read to collect the assets and conflicts, but the project is not changed.
+    /// The conflicts are reported in the result instead of failing with `dry_run`.
+    ///
+    /// returns: the assets in the unitypackage sorted by path, with the conflicts with the project
+    pub async fn import_unitypackage(
+        &self,
+        unitypackage: impl AsyncBufRead + Unpin,
+        conflict_handling: ImportConflictHandling,
+        dry_run: bool,
+    ) -> Result<Vec<UnityPackageAsset>, ImportUnityPackageError> {
+        let temp_dir = PathBuf::from(format!("{PKG_TEMP_DIR}/{}", uuid::Uuid::new_v4()));
+        if !dry_run {
+            self.io.create_dir_all(&temp_dir).await?;
+        }
+
+        let result = self
+            .import_unitypackage_impl(unitypackage, conflict_handling, dry_run, &temp_dir)
+            .await;
+
+        if !dry_run && let Err(e) = self.io.remove_dir_all(&temp_dir).await {
+            warn!(
+                "failed to remove temporary folder {}: {e}",
+                temp_dir.display()
+            );
+        }
+
+        result
+    }
+
+    // the main part of import_unitypackage except for temp_dir management.
+    async fn import_unitypackage_impl(
+        &self,
+        unitypackage: impl AsyncBufRead + Unpin,
+        conflict_handling: ImportConflictHandling,
+        dry_run: bool,
+        temp_dir: &Path,
+    ) -> Result<Vec<UnityPackageAsset>, ImportUnityPackageError> {
+        let extract_to = if dry_run { None } else { Some(temp_dir) };
+        let entries = read_unitypackage(&self.io, unitypackage, extract_to).await?;
+
+        let existing_guids = collect_guids(&self.io).await;
+
+        let mut assets = Vec::with_capacity(entries.len());
+        for (guid_str, entry) in &entries {
+            let path = Path::new(&entry.pathname);
+            let is_folder = !entry.has_file;
+            let guid = Guid::parse(guid_str);
+            let mut conflicts = Vec::new();
+
+            if let Ok(metadata) = self.io.metadata(path).await
+                && !(is_folder && metadata.is_dir())
+            {
+                conflicts.push(ImportConflict::PathExists);
+                let meta_path = path.with_added_extension("meta");
+                if let Some(existing) = try_parse_meta(&self.io, &meta_path).await
+                    && guid != Some(existing)
+                {
+                    let existing = existing.to_string().into_boxed_str();
+                    conflicts.push(ImportConflict::GuidMismatch(existing));
+                }
+            }
+
+            if let Some(guid_path) = guid.and_then(|x| existing_guids.get(&x))
+                && guid_path != path
+            {
+                let guid_path = guid_path.clone().into_boxed_path();
+                conflicts.push(ImportConflict::GuidExists(guid_path));
+            }
+
+            assets.push(UnityPackageAsset {
+                guid: guid_str.as_str().into(),
+                path: entry.pathname.as_str().into(),
+                is_folder,
+                conflicts,
+            });
+        }
+        assets.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // validate the paths the assets are written to before writing anything
+        let file_paths = (assets.iter())
+            .filter(|x| !x.is_folder)
+            .map(|x| x.target_path(conflict_handling).to_path_buf())
+            .collect::<HashSet<_>>();
+        for asset in &mut assets {
+            let target = asset.target_path(conflict_handling);
+            let under_file_asset = target.ancestors().skip(1).any(|x| file_paths.contains(x));
+            if under_file_asset || !self.can_write_asset(target, asset.is_folder).await {
+                asset.conflicts.push(ImportConflict::TypeMismatch);
+            }
+        }
+
+        if !dry_run {
+            match conflict_handling {
+                ImportConflictHandling::Fail if assets.iter().any(|x| !x.conflicts.is_empty()) => {
+                    let conflicts = assets.into_iter().filter(|x| !x.conflicts.is_empty());
+                    return Err(ImportUnityPackageError::Conflicts(conflicts.collect()));
+                }
+                ImportConflictHandling::Overwrite if assets.iter().any(|x| !x.can_overwrite()) => {
+                    let assets = assets.into_iter().filter(|x| !x.can_overwrite());
+                    return Err(ImportUnityPackageError::CannotOverwrite(assets.collect()));
+                }
+                ImportConflictHandling::Replace if assets.iter().any(|x| x.is_type_mismatch()) => {
+                    let assets = assets.into_iter().filter(|x| x.is_type_mismatch());
+                    return Err(ImportUnityPackageError::CannotOverwrite(assets.collect()));
+                }
+                _ => {}
+            }
+        }
+
+        if dry_run {
+            return Ok(assets);
+        }
+
+        // actually extract the archive
+        for asset in &assets {
+            if !asset.conflicts.is_empty() && conflict_handling == ImportConflictHandling::Skip {
+                continue;
+            }
+            let path = asset.target_path(conflict_handling);
+            let entry = &entries[asset.guid()];
+            let meta_path = path.with_added_extension("meta");
+
+            if asset.is_folder {
+                // keep the metadata of existing folders unless replacing
+                if conflict_handling != ImportConflictHandling::Replace
+                    && self.io.is_dir(path).await
+                {
+                    continue;
+                }
+                self.io.create_dir_all(path).await?;
+                self.io.write(&meta_path, &entry.metadata).await?;
+            } else {
+                if let Some(parent) = path.parent() {
+                    self.io.create_dir_all(parent).await?;
+                }
+                let temp_path = temp_dir.join(asset.guid());
+                self.io.write(&meta_path, &entry.metadata).await?;
+                self.io.rename(&temp_path, path).await?;
+            }
+        }
+
+        Ok(assets)
+    }
+
+    /// Returns whether the asset can be written to `path` without replacing a file with a folder or vice versa.
+    async fn can_write_asset(&self, path: &Path, is_folder: bool) -> bool {
+        for (index, ancestor) in path.ancestors().enumerate() {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+            if let Ok(metadata) = self.io.metadata(ancestor).await {
+                // the parent folders are checked until the nearest existing one
+                return if index == 0 {
+                    metadata.is_dir() == is_folder
+                } else {
+                    metadata.is_dir()
+                };
+            }
+        }
+        true
+    }
+}
+
+/// Reads the entries of the unitypackage, and extracts the asset files to `extract_to` if specified.
+///
+/// The entries are keyed by the GUID. The pathname and metadata are validated.
+async fn read_unitypackage(
+    io: &DefaultProjectIo,
+    unitypackage: impl AsyncBufRead + Unpin,
+    extract_to: Option<&Path>,
+) -> io::Result<HashMap<String, UnityPackageEntry>> {
+    type GuidBuf = [u8; 32];
+
+    let gunzip = GzipDecoder::new(unitypackage);
+    let mut untar = TarArchive::new(gunzip);
+
+    let mut entries = HashMap::<GuidBuf, UnityPackageEntry>::new();
+    let mut ignored = HashSet::<GuidBuf>::new();
+
+    while let Some(mut tar_entry) = untar.next_entry().await? {
+        let path = tar_entry.header().path_bytes();
+        let path = path.as_ref();
+        let mut components = Vec::new();
+        for component in path.split(|&b| b == b'/' || b == b'\\') {
+            match component {
+                b"" | b"." => (), // no-op
+                b".." => {
+                    components.pop();
+                }
+                c => components.push(c),
+            }
+        }
+
+        let [guid, filename] = components[..] else {
+            continue;
+        };
+        let Ok(guid) = GuidBuf::try_from(guid) else {
+            continue;
+        };
+        if !guid.iter().all(|x| matches!(x, b'a'..=b'f' | b'0'..=b'9')) {
+            // the GUID is not guid
+            continue;
+        }
+        let guid_str = std::str::from_utf8(&guid).unwrap();
+        match filename {
+            b"asset" => {
+                // The contents of the asset.
+                let package_entry = entries.entry(guid).or_default();
+                if package_entry.has_file {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("duplicate asset for {guid_str}"),
+                    ));
+                }
+                if let Some(extract_to) = extract_to {
+                    let mut file = io.create(&extract_to.join(guid_str)).await?;
+                    io::copy(&mut tar_entry, &mut file).await?;
+                    file.flush().await?;
+                }
+                package_entry.has_file = true;
+            }
+            b"asset.meta" => {
+                // The metadata of the asset.
+                let package_entry = entries.entry(guid).or_default();
+                if !package_entry.metadata.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("duplicate asset.meta for {guid_str}"),
+                    ));
+                }
+                tar_entry.read_to_end(&mut package_entry.metadata).await?;
+            }
+            b"pathname" => {
+                // The pathname of the asset.
+                let package_entry = entries.entry(guid).or_default();
+                if !package_entry.pathname.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("duplicate pathname for {guid_str}"),
+                    ));
+                }
+                let mut buffer = Vec::new();
+                tar_entry.read_to_end(&mut buffer).await?;
+                let pathname = String::from_utf8(buffer).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("non-utf8 pathname for {guid_str}"),
+                    )
+                })?;
+                // the pathname is validated after removing the suffix since the suffix may hide invalid components
+                // https://github.com/vrc-get/vrc-get/issues/2634
+                // https://issuetracker.unity3d.com/product/unity/issues/guid/UUM-132869
+                // if there is '\n' in their pathname, remove after last '\n'
+                let pathname = if let Some(index) = pathname.rfind('\n') {
+                    let mut pathname = pathname;
+                    pathname.replace_range(index.., "");
+                    pathname
+                } else {
+                    pathname
+                };
+                // Those are filename-banned characters for windows except for portable path separator '/'
+                if pathname.contains(['<', '>', ':', '"', '|', '?', '*', '\0']) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("bad pathname for {guid_str} (banned chars)"),
+                    ));
+                }
+                if pathname.split('/').any(|c| matches!(c, "" | "." | "..")) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("bad pathname for {guid_str} (possible path traversal)"),
+                    ));
+                }
+                // ignoring paths for non-Assets / Packages
+                if !pathname.starts_with("Assets/") && !pathname.starts_with("Packages/") {
+                    warn!("asset is not under Assets or Packages: {guid_str}: {pathname:?}");
+                    ignored.insert(guid);
+                    continue;
+                }
+                package_entry.pathname = pathname;
+            }
+            _ => continue, // non unitypackage entry
+        }
+    }
+
+    entries.retain(|guid, _| !ignored.contains(guid));
+
+    // validate entire entries
+    for (guid, entry) in &entries {
+        let guid = std::str::from_utf8(&guid[..]).unwrap();
+        if entry.pathname.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no pathname for {guid}"),
+            ));
+        }
+        if entry.metadata.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no metadata for {path} ({guid})", path = entry.pathname),
+            ));
+        }
+    }
+
+    Ok(entries
+        .into_iter()
+        .map(|(guid, entry)| (String::from_utf8(guid.to_vec()).unwrap(), entry))
+        .collect())
+}
+
+/// Collects the GUIDs of the assets in the `Assets` and `Packages` folders.
+async fn collect_guids(io: &DefaultProjectIo) -> HashMap<Guid, PathBuf> {
+    async fn get_guid(io: &DefaultProjectIo, relative: PathBuf) -> Option<(Guid, PathBuf)> {
+        if relative.extension() != Some(OsStr::new("meta")) {
+            return None;
+        }
+        let guid = try_parse_meta(io, &relative).await?;
+        // remove .meta extension
+        let mut path = relative;
+        path.set_extension("");
+        Some((guid, path))
+    }
+
+    let paths = [PathBuf::from("Assets"), PathBuf::from("Packages")];
+    let stream = pin!(walk_dir_relative(io, paths).filter_map(|(x, _)| get_guid(io, x)));
+    stream.collect().await
+}
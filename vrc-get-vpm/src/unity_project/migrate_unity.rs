@@ -0,0 +1,193 @@
+use crate::io::IoTrait;
+use crate::unity_project::migrate_unity_2022::remove_legacy_xr_packages;
+use crate::unity_project::unity_compatibility::CompatibilityStatus;
+use crate::unity_project::{
+    AddPackageErr, AddPackageOperation, PendingProjectChanges, pending_project_changes,
+};
+use crate::version::UnityVersion;
+use crate::{PackageCollection, PackageInstaller, UnityProject, io};
+
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum MigrateUnityError {
+    /// The target Unity version is older than the Unity version of the project.
+    UnityVersionDowngrade,
+    /// No version of the packages for the target Unity version is found.
+    NoCompatibleVersion(Vec<Box<str>>),
+    AddPackageErr(AddPackageErr),
+    Io(io::Error),
+}
+
+impl std::error::Error for MigrateUnityError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MigrateUnityError::AddPackageErr(err) => Some(err),
+            MigrateUnityError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MigrateUnityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrateUnityError::UnityVersionDowngrade => {
+                write!(f, "Unity version is newer than the target version")
+            }
+            MigrateUnityError::NoCompatibleVersion(packages) => {
+                write!(
+                    f,
+                    "Following packages have no version for the target Unity: {}",
+                    packages.join(", ")
+                )
+            }
+            MigrateUnityError::AddPackageErr(err) => write!(f, "{err}"),
+            MigrateUnityError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<AddPackageErr> for MigrateUnityError {
+    fn from(err: AddPackageErr) -> Self {
+        MigrateUnityError::AddPackageErr(err)
+    }
+}
+
+impl From<io::Error> for MigrateUnityError {
+    fn from(err: io::Error) -> Self {
+        MigrateUnityError::Io(err)
+    }
+}
+
+type Result<T = (), E = MigrateUnityError> = std::result::Result<T, E>;
+
+impl UnityProject {
+    /// Migrates the project to the specified Unity version.
+    ///
+    /// The packages not compatible with the target Unity are changed to the versions for the target,
+    /// and ProjectVersion.txt is updated to the target version.
+    /// `revision` is written to `m_EditorVersionWithRevision` if specified.
+    /// You should launch the target Unity to reimport the project after this.
+    pub async fn migrate_unity(
+        &mut self,
+        collection: &impl PackageCollection,
+        installer: &impl PackageInstaller,
+        target: UnityVersion,
+        revision: Option<&str>,
+    ) -> Result {
+        migrate_unity(self, collection, installer, target, revision).await
+    }
+
+    /// Migrates the project with the changes collected by [`migrate_unity_request`](Self::migrate_unity_request)
+    /// for the same `target`.
+    ///
+    /// This is the same as [`migrate_unity`](Self::migrate_unity) but without collecting the changes again
+    /// after showing them to the user.
+    pub async fn migrate_unity_with_changes<'env>(
+        &mut self,
+        installer: &'env impl PackageInstaller,
+        changes: PendingProjectChanges<'env>,
+        target: UnityVersion,
+        revision: Option<&str>,
+    ) -> Result {
+        migrate_unity_with_changes(self, installer, changes, target, revision).await
+    }
+
+    /// Collects the package changes of [`migrate_unity`](Self::migrate_unity)
+    /// without changing the project.
+    ///
+    /// The legacy XR packages to be removed from `Packages/manifest.json` are included in the changes.
+    pub async fn migrate_unity_request<'env>(
+        &self,
+        collection: &'env impl PackageCollection,
+        target: UnityVersion,
+    ) -> Result<PendingProjectChanges<'env>> {
+        migrate_unity_request(self, collection, target).await
+    }
+}
+
+async fn migrate_unity(
+    project: &mut UnityProject,
+    collection: &impl PackageCollection,
+    installer: &impl PackageInstaller,
+    target: UnityVersion,
+    revision: Option<&str>,
+) -> Result {
+    let request = migrate_unity_request(project, collection, target).await?;
+    migrate_unity_with_changes(project, installer, request, target, revision).await
+}
+
+async fn migrate_unity_with_changes<'env>(
+    project: &mut UnityProject,
+    installer: &'env impl PackageInstaller,
+    request: PendingProjectChanges<'env>,
+    target: UnityVersion,
+    revision: Option<&str>,
+) -> Result {
+    project.apply_pending_changes(installer, request).await?;
+
+    let mut project_version = format!("m_EditorVersion: {target}\n");
+    if let Some(revision) = revision {
+        project_version += &format!("m_EditorVersionWithRevision: {target} ({revision})\n");
+    }
+    project
+        .io
+        .write_atomic(
+            "ProjectSettings/ProjectVersion.txt".as_ref(),
+            project_version.as_bytes(),
+        )
+        .await?;
+
+    project.unity_version = target;
+    project.unity_revision = revision.map(ToOwned::to_owned);
+
+    Ok(())
+}
+
+async fn migrate_unity_request<'env>(
+    project: &UnityProject,
+    collection: &'env impl PackageCollection,
+    target: UnityVersion,
+) -> Result<PendingProjectChanges<'env>> {
+    if target < project.unity_version() {
+        return Err(MigrateUnityError::UnityVersionDowngrade);
+    }
+
+    let compatibility = project.unity_compatibility(collection, target);
+
+    let mut packages = vec![];
+    let mut no_compatible = vec![];
+    for package in compatibility.packages() {
+        match package.status() {
+            CompatibilityStatus::Change(changed) => packages.push(*changed),
+            CompatibilityStatus::NoCompatibleVersion => no_compatible.push(package.name().into()),
+            CompatibilityStatus::Compatible | CompatibilityStatus::Unknown => {}
+        }
+    }
+
+    if !no_compatible.is_empty() {
+        return Err(MigrateUnityError::NoCompatibleVersion(no_compatible));
+    }
+
+    let mut request = if packages.is_empty() {
+        pending_project_changes::Builder::new().build_no_resolve()
+    } else {
+        // the changed packages may be older than the locked versions, so the operation is auto-detected
+        project
+            .add_package_request_for_unity(
+                collection,
+                &packages,
+                AddPackageOperation::AutoDetected,
+                false,
+                target,
+            )
+            .await?
+    };
+
+    // legacy XR packages are not available since Unity 2020
+    if project.unity_version().major() == 2019 && target.major() != 2019 {
+        remove_legacy_xr_packages(project, &mut request);
+    }
+
+    Ok(request)
+}
@@ -1,4 +1,6 @@
-use crate::unity_project::{AddPackageErr, AddPackageOperation};
+use crate::unity_project::{
+    AddPackageErr, AddPackageOperation, PendingProjectChanges, pending_project_changes,
+};
 use crate::{PackageCollection, UnityProject, VersionSelector};
 use crate::{PackageInstaller, VRCHAT_RECOMMENDED_2022_UNITY, io};
 use log::warn;
@@ -57,6 +59,17 @@ impl UnityProject {
     ) -> Result {
         migrate_unity_2022(self, collection, installer).await
     }
+
+    /// Collects the package changes of [`migrate_unity_2022`](Self::migrate_unity_2022)
+    /// without changing the project.
+    ///
+    /// The legacy XR packages to be removed from `Packages/manifest.json` are included in the changes.
+    pub async fn migrate_unity_2022_request<'env>(
+        &self,
+        collection: &'env impl PackageCollection,
+    ) -> Result<PendingProjectChanges<'env>> {
+        migrate_unity_2022_request(self, collection).await
+    }
 }
 
 async fn migrate_unity_2022(
@@ -64,6 +77,17 @@ async fn migrate_unity_2022(
     collection: &impl PackageCollection,
     installer: &impl PackageInstaller,
 ) -> Result {
+    // upgrade VRCSDK if installed, and remove legacy XR packages
+    let request = migrate_unity_2022_request(project, collection).await?;
+    project.apply_pending_changes(installer, request).await?;
+
+    Ok(())
+}
+
+async fn migrate_unity_2022_request<'env>(
+    project: &UnityProject,
+    collection: &'env impl PackageCollection,
+) -> Result<PendingProjectChanges<'env>> {
     // See https://misskey.niri.la/notes/9nod7sk4sr for migration process
     if project.unity_version().major() != 2019 {
         return Err(MigrateUnity2022Error::UnityVersionMismatch);
@@ -74,15 +98,6 @@ async fn migrate_unity_2022(
         warn!("It looks migrating projects without vpm VRCSDK. this may not intended");
     }
 
-    // remove legacy XR packages
-    project
-        .upm_manifest
-        .remove_dependency("com.unity.xr.oculus.standalone");
-    project
-        .upm_manifest
-        .remove_dependency("com.unity.xr.openvr.standalone");
-
-    // upgrade VRCSDK if installed
     let mut packages = vec![];
     let migrating_packages = [
         "com.vrchat.base",
@@ -93,7 +108,7 @@ async fn migrate_unity_2022(
     for package in migrating_packages {
         if project.get_locked(package).is_some() {
             let unity_version = Some(VRCHAT_RECOMMENDED_2022_UNITY);
-            let Some(vrcsdk) = collection
+            let Some(vrcsdk) = (project.scoped(collection))
                 .find_package_by_name(package, VersionSelector::latest_for(unity_version, false))
             else {
                 return Err(MigrateUnity2022Error::VpmPackageNotFound(package));
@@ -102,20 +117,22 @@ async fn migrate_unity_2022(
         }
     }
 
-    if !packages.is_empty() {
-        // install packages
-        let request = project
+    let mut request = if packages.is_empty() {
+        pending_project_changes::Builder::new().build_no_resolve()
+    } else {
+        project
             .add_package_request(
                 collection,
                 &packages,
                 AddPackageOperation::InstallToDependencies,
                 false,
             )
-            .await?;
-        project.apply_pending_changes(installer, request).await?;
-    }
+            .await?
+    };
 
-    Ok(())
+    remove_legacy_xr_packages(project, &mut request);
+
+    Ok(request)
 }
 
 // memo /Applications/Unity/Hub/Editor/2022.3.6f1/Unity.app/Contents/MacOS/Unity -quit -batchmode -projectPath .
@@ -134,3 +151,18 @@ fn is_vpm_vrcsdk_installed(project: &UnityProject) -> bool {
     // otherwice warn
     false
 }
+
+/// Adds the legacy XR packages, which are not available since Unity 2020, to be removed from the UPM manifest.
+pub(super) fn remove_legacy_xr_packages(
+    project: &UnityProject,
+    request: &mut PendingProjectChanges,
+) {
+    for name in [
+        "com.unity.xr.oculus.standalone",
+        "com.unity.xr.openvr.standalone",
+    ] {
+        if project.upm_manifest.get_dependency(name).is_some() {
+            request.remove_upm_dependencies.push(name.into());
+        }
+    }
+}
@@ -3,7 +3,7 @@ use log::{debug, info};
 use std::collections::HashSet;
 
 use crate::io::IoTrait;
-use crate::unity_project::{AddPackageErr, AddPackageOperation};
+use crate::unity_project::{AddPackageErr, AddPackageOperation, PendingProjectChanges};
 use crate::{PackageCollection, UnityProject, VersionSelector};
 use crate::{PackageInstaller, ProjectType, io};
 
@@ -66,6 +66,15 @@ impl UnityProject {
     ) -> Result {
         migrate_vpm(self, collection, installer, include_prerelease).await
     }
+
+    /// Collects the package changes of [`migrate_vpm`](Self::migrate_vpm) without changing the project.
+    pub async fn migrate_vpm_request<'env>(
+        &self,
+        collection: &'env impl PackageCollection,
+        include_prerelease: bool,
+    ) -> Result<PendingProjectChanges<'env>> {
+        migrate_vpm_request(self, collection, include_prerelease).await
+    }
 }
 
 async fn migrate_vpm(
@@ -74,6 +83,70 @@ async fn migrate_vpm(
     installer: &impl PackageInstaller,
     include_prerelease: bool,
 ) -> Result {
+    // install packages. this also removes legacy VRCSDK and curated packages
+    let request = migrate_vpm_request(project, collection, include_prerelease).await?;
+
+    project.apply_pending_changes(installer, request).await?;
+
+    // update project settings
+    let project_settings_path = "ProjectSettings/ProjectSettings.asset".as_ref();
+
+    match project.io.open(project_settings_path).await {
+        Ok(mut file) => {
+            let mut buffer = String::new();
+            file.read_to_string(&mut buffer).await?;
+            drop(file);
+
+            fn replace_setting(buffer: &mut String, setting: &str, old: &str, value: &str) -> bool {
+                if let Some(pos) = buffer.find(setting) {
+                    let before_ws = buffer[..pos]
+                        .chars()
+                        .last()
+                        .map(|x| x.is_ascii_whitespace())
+                        .unwrap_or(true);
+                    let after_match = buffer[pos + setting.len()..].starts_with(old);
+                    if before_ws && after_match {
+                        let start = pos + setting.len();
+                        let end = start + old.len();
+                        buffer.replace_range(start..end, value);
+                        return true;
+                    }
+                }
+                false
+            }
+
+            let mut changed = false;
+
+            changed |= replace_setting(
+                &mut buffer,
+                "enableNativePlatformBackendsForNewInputSystem: ",
+                "0",
+                "1",
+            );
+
+            changed |= replace_setting(&mut buffer, "disableOldInputManagerSupport: ", "1", "0");
+
+            if changed {
+                project
+                    .io
+                    .write_sync(project_settings_path, buffer.as_bytes())
+                    .await?;
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            log::error!("ProjectSettings.asset not found");
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(())
+}
+
+async fn migrate_vpm_request<'env>(
+    project: &UnityProject,
+    collection: &'env impl PackageCollection,
+    include_prerelease: bool,
+) -> Result<PendingProjectChanges<'env>> {
     let is_worlds = match project.detect_project_type().await {
         // we only can migrate legacy VRCSDK3 projects
         ProjectType::LegacyWorlds => true,
@@ -94,13 +167,13 @@ async fn migrate_vpm(
     // basic part: install SDK
     if is_worlds {
         adding_packages.push(
-            collection
+            (project.scoped(collection))
                 .find_package_by_name("com.vrchat.worlds", version_selector)
                 .ok_or(MigrateVpmError::VpmPackageNotFound("com.vrchat.worlds"))?,
         );
     } else {
         adding_packages.push(
-            collection
+            (project.scoped(collection))
                 .find_package_by_name("com.vrchat.avatars", version_selector)
                 .ok_or(MigrateVpmError::VpmPackageNotFound("com.vrchat.avatars"))?,
         );
@@ -109,7 +182,7 @@ async fn migrate_vpm(
     // additional part: migrate VRChat-curated packages
     // we find legacy curated package by trying to install it and check if the project has legacy assets
     {
-        let mut curated_packages = collection
+        let mut curated_packages = (project.scoped(collection))
             .get_curated_packages(version_selector)
             .collect::<Vec<_>>();
 
@@ -144,8 +217,6 @@ async fn migrate_vpm(
         adding_packages.extend(curated_packages);
     }
 
-    // install packages. this also removes legacy VRCSDK and curated packages
-
     let request = project
         .add_package_request(
             collection,
@@ -155,58 +226,5 @@ async fn migrate_vpm(
         )
         .await?;
 
-    project.apply_pending_changes(installer, request).await?;
-
-    // update project settings
-    let project_settings_path = "ProjectSettings/ProjectSettings.asset".as_ref();
-
-    match project.io.open(project_settings_path).await {
-        Ok(mut file) => {
-            let mut buffer = String::new();
-            file.read_to_string(&mut buffer).await?;
-            drop(file);
-
-            fn replace_setting(buffer: &mut String, setting: &str, old: &str, value: &str) -> bool {
-                if let Some(pos) = buffer.find(setting) {
-                    let before_ws = buffer[..pos]
-                        .chars()
-                        .last()
-                        .map(|x| x.is_ascii_whitespace())
-                        .unwrap_or(true);
-                    let after_match = buffer[pos + setting.len()..].starts_with(old);
-                    if before_ws && after_match {
-                        let start = pos + setting.len();
-                        let end = start + old.len();
-                        buffer.replace_range(start..end, value);
-                        return true;
-                    }
-                }
-                false
-            }
-
-            let mut changed = false;
-
-            changed |= replace_setting(
-                &mut buffer,
-                "enableNativePlatformBackendsForNewInputSystem: ",
-                "0",
-                "1",
-            );
-
-            changed |= replace_setting(&mut buffer, "disableOldInputManagerSupport: ", "1", "0");
-
-            if changed {
-                project
-                    .io
-                    .write_sync(project_settings_path, buffer.as_bytes())
-                    .await?;
-            }
-        }
-        Err(e) if e.kind() == io::ErrorKind::NotFound => {
-            log::error!("ProjectSettings.asset not found");
-        }
-        Err(e) => return Err(e.into()),
-    }
-
-    Ok(())
+    Ok(request)
 }
@@ -1,7 +1,12 @@
+mod backtracking;
+
 use crate::traits::PackageCollection;
 use crate::unity_project::LockedDependencyInfo;
+use crate::unity_project::pending_project_changes::{ConflictExplanation, Requirement};
+use crate::unity_project::scoped_collection::ScopedCollection;
 use crate::version::{DependencyRange, PrereleaseAcceptance, UnityVersion, Version, VersionRange};
 use crate::{PackageInfo, PackageManifest, VersionSelector};
+use indexmap::IndexMap;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
 
@@ -42,6 +47,8 @@ where
     pub pending_queue: PackageQueue<'env>,
     dependencies: HashMap<&'a str, DependencyInfo<'env, 'a>>,
     unlocked_names: HashSet<&'a str>,
+    // ranges used instead of the ranges required by packages
+    overrides: &'a IndexMap<Box<str>, VersionRange>,
 }
 
 struct Legacy<'env>(&'env [Box<str>]);
@@ -120,13 +127,18 @@ where
     }
 }
 
-impl<'env> ResolutionContext<'env, '_> {
-    fn new(allow_prerelease: bool, packages: Vec<PackageInfo<'env>>) -> Self {
+impl<'env, 'a> ResolutionContext<'env, 'a> {
+    fn new(
+        allow_prerelease: bool,
+        packages: Vec<PackageInfo<'env>>,
+        overrides: &'a IndexMap<Box<str>, VersionRange>,
+    ) -> Self {
         let mut this = Self {
             dependencies: HashMap::new(),
             pending_queue: PackageQueue::new(packages),
             allow_prerelease,
             unlocked_names: HashSet::new(),
+            overrides,
         };
 
         for pkg in &this.pending_queue.pending_queue {
@@ -157,6 +169,11 @@ where
             .insert(name, DependencyInfo::new_dependency(range, allow_pre));
     }
 
+    /// Returns the range required to the dependency by packages, applying the overrides.
+    fn dependency_range(&self, dependency: &str, range: &'a VersionRange) -> &'a VersionRange {
+        self.overrides.get(dependency).unwrap_or(range)
+    }
+
     pub(crate) fn add_unlocked_name(&mut self, name: &'a str) {
         self.unlocked_names.insert(name);
     }
@@ -183,6 +200,7 @@ where
         }
 
         for (dependency, range) in manifest.vpm_dependencies() {
+            let range = self.dependency_range(dependency, range);
             self.dependencies
                 .entry(dependency)
                 .or_default()
@@ -194,7 +212,7 @@ where
     pub(crate) fn add_locked_dependency(
         &mut self,
         locked: LockedDependencyInfo<'a>,
-        env: &'env impl PackageCollection,
+        env: ScopedCollection<'env, '_, impl PackageCollection>,
     ) {
         let info = self.dependencies.entry(locked.name()).or_default();
         info.set_using_info(
@@ -218,6 +236,7 @@ where
         }
 
         for (dependency, range) in locked.dependencies() {
+            let range = self.dependency_range(dependency, range);
             self.dependencies
                 .entry(dependency)
                 .or_default()
@@ -227,6 +246,7 @@ where
     }
 
     pub(crate) fn add_package(&mut self, package: PackageInfo<'env>, force: bool) -> bool {
+        let overrides = self.overrides;
         let entry = self.dependencies.entry(package.name()).or_default();
 
         if entry.is_legacy() {
@@ -258,6 +278,7 @@ where
             self.dependencies.get_mut(*dep).unwrap().remove_range(name);
         }
         for (dependency, range) in vpm_dependencies.iter() {
+            let range = overrides.get(dependency).unwrap_or(range);
             self.dependencies
                 .entry(dependency)
                 .or_default()
@@ -370,6 +391,11 @@ impl<'env> ResolutionContext<'env, '_> {
             }
         }
 
+        let explanations = conflicts
+            .keys()
+            .map(|name| (name.clone(), self.explain_conflict(name)))
+            .collect();
+
         let found_legacy_packages = self
             .dependencies
             .iter()
@@ -387,8 +413,88 @@ impl<'env> ResolutionContext<'env, '_> {
         PackageResolutionResult {
             new_packages,
             conflicts,
+            explanations,
             found_legacy_packages,
+            step_limit_exceeded: false,
+        }
+    }
+
+    /// Builds chains of requirements from the project to the conflicting package
+    fn explain_conflict(&self, name: &str) -> ConflictExplanation {
+        let info = &self.dependencies[name];
+        let allow_prerelease =
+            PrereleaseAcceptance::allow_or_minimum(info.allow_pre || self.allow_prerelease);
+
+        let mut sources = info
+            .requirements
+            .iter()
+            .filter(|&(&source, _)| {
+                (self.dependencies.get(source))
+                    .map(|x| !x.is_legacy())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        sources.sort_by_key(|(source, _)| **source);
+
+        let requirements = sources
+            .into_iter()
+            .map(|(&source, &range)| {
+                let mut chain = self.requirement_chain(source);
+                chain.push(self.requirement(source, name, range));
+                let satisfied = info
+                    .current
+                    .map(|version| range.match_pre(version, allow_prerelease))
+                    .unwrap_or(false);
+                (chain, satisfied)
+            })
+            .collect();
+
+        ConflictExplanation::new(name.into(), info.current.cloned(), requirements)
+    }
+
+    /// Finds the chain of requirements that made the package required
+    fn requirement_chain(&self, package: &str) -> Vec<Requirement> {
+        let mut chain = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current = package;
+
+        while !current.is_empty() && visited.insert(current) {
+            let Some(info) = self.dependencies.get(current) else {
+                break;
+            };
+            // prefer root dependencies to make the chain short
+            let parent = if info.requirements.contains_key("") {
+                Some("")
+            } else {
+                (info.requirements.keys())
+                    .copied()
+                    .filter(|source| !visited.contains(source))
+                    .filter(|source| {
+                        (self.dependencies.get(source))
+                            .map(|x| !x.is_legacy())
+                            .unwrap_or_default()
+                    })
+                    .min()
+            };
+            let Some(parent) = parent else {
+                break;
+            };
+            chain.push(self.requirement(parent, current, info.requirements[parent]));
+            current = parent;
         }
+
+        chain.reverse();
+        chain
+    }
+
+    fn requirement(&self, source: &str, target: &str, range: &VersionRange) -> Requirement {
+        let source = if source.is_empty() {
+            None
+        } else {
+            let version = self.dependencies.get(source).and_then(|x| x.current);
+            version.map(|version| (source.into(), version.clone()))
+        };
+        Requirement::new(source, target.into(), range.clone())
     }
 }
 
@@ -396,8 +502,12 @@ pub struct PackageResolutionResult<'env> {
     pub new_packages: Vec<PackageInfo<'env>>,
     // conflict dependency -> conflicting package[])
     pub conflicts: HashMap<Box<str>, Vec<Box<str>>>,
+    // conflict dependency -> explanation of the conflict
+    pub explanations: HashMap<Box<str>, ConflictExplanation>,
     // list of names of legacy packages we found
     pub found_legacy_packages: Vec<Box<str>>,
+    // true if the backtracking resolution gave up before finding whether the conflicts can be resolved
+    pub step_limit_exceeded: bool,
 }
 
 pub struct MissingDependencies {
@@ -437,13 +547,17 @@ pub(crate) fn collect_adding_packages<'a, 'env>(
     locked_dependencies: impl Iterator<Item = LockedDependencyInfo<'a>>,
     unlocked_packages: impl Iterator<Item = &'a (Box<str>, Option<PackageManifest>)>,
     get_locked: impl Fn(&str) -> Option<LockedDependencyInfo<'a>>,
+    overrides: &'a IndexMap<Box<str>, VersionRange>,
     unity_version: Option<UnityVersion>,
-    env: &'env impl PackageCollection,
+    env: ScopedCollection<'env, '_, impl PackageCollection>,
     packages: Vec<PackageInfo<'env>>,
     allow_prerelease: bool,
     missing_dependencies: &mut MissingDependencies,
 ) -> PackageResolutionResult<'env> {
-    let mut context = ResolutionContext::<'env, '_>::new(allow_prerelease, packages);
+    let locked_dependencies = locked_dependencies.collect::<Vec<_>>();
+    let unlocked_packages = unlocked_packages.collect::<Vec<_>>();
+    let mut context =
+        ResolutionContext::<'env, '_>::new(allow_prerelease, packages.clone(), overrides);
 
     // first, add dependencies
     let root_dependencies = dependencies
@@ -474,12 +588,12 @@ pub(crate) fn collect_adding_packages<'a, 'env>(
     }
 
     // then, add locked dependencies info
-    for locked in locked_dependencies {
+    for locked in locked_dependencies.iter().cloned() {
         context.add_locked_dependency(locked, env);
     }
 
     // add unlocked packages
-    for (unlocked_name, unlocked_manifest) in unlocked_packages {
+    for (unlocked_name, unlocked_manifest) in unlocked_packages.iter().copied() {
         context.add_unlocked_name(unlocked_name.as_ref());
         if let Some(unlocked_package) = unlocked_manifest {
             context.add_unlocked_name(unlocked_package.name());
@@ -495,11 +609,12 @@ pub(crate) fn collect_adding_packages<'a, 'env>(
         if context.add_package(x, force) {
             // add new dependencies
             for (dependency, range) in vpm_dependencies.iter() {
+                let range = context.dependency_range(dependency, range);
                 log::debug!("processing package {name}: dependency {dependency} version {range}");
 
                 if context.should_add_package(dependency, range) {
                     fn get_package<'env>(
-                        env: &'env impl PackageCollection,
+                        env: ScopedCollection<'env, '_, impl PackageCollection>,
                         dependency: &str,
                         unity_version: Option<UnityVersion>,
                         range: &VersionRange,
@@ -513,8 +628,8 @@ pub(crate) fn collect_adding_packages<'a, 'env>(
 
                     struct PackageFinder<'env, 'a, C: PackageCollection> {
                         dependency: &'a str,
-                        env: &'env C,
-                        range: &'env VersionRange,
+                        env: ScopedCollection<'env, 'a, C>,
+                        range: &'a VersionRange,
                     }
 
                     impl<'env, C: PackageCollection> PackageFinder<'env, '_, C> {
@@ -570,5 +685,56 @@ pub(crate) fn collect_adding_packages<'a, 'env>(
         }
     }
 
-    context.build_result()
+    let result = context.build_result();
+
+    if result.conflicts.is_empty() || !missing_dependencies.is_empty() {
+        return result;
+    }
+
+    // The greedy resolution found conflicts, but older versions of packages may resolve them.
+    log::debug!("conflicts found, trying backtracking resolution");
+
+    let problem = backtracking::Problem {
+        root_dependencies: &root_dependencies,
+        locked: &locked_dependencies,
+        unlocked: &unlocked_packages,
+        requested: &packages,
+        overrides,
+        unity_version,
+        allow_prerelease,
+    };
+
+    match backtracking::resolve(&problem, env) {
+        Ok(solution) => PackageResolutionResult {
+            new_packages: solution.new_packages,
+            conflicts: HashMap::new(),
+            explanations: HashMap::new(),
+            found_legacy_packages: solution.found_legacy_packages,
+            step_limit_exceeded: false,
+        },
+        Err(backtracking::Failure::Conflict(explanation)) => {
+            log::debug!("backtracking resolution found no solution, using the greedy result");
+            let mut result = result;
+            // the explanation from the backtracking covers all versions, not only the latest ones
+            if let Some(explanation) = explanation {
+                let conflicts = (result.conflicts)
+                    .entry(explanation.package().into())
+                    .or_default();
+                for (chain, satisfied) in explanation.requirements() {
+                    if let Some((source, _)) = chain.last().and_then(|x| x.source())
+                        && !satisfied
+                        && !conflicts.iter().any(|x| x.as_ref() == source)
+                    {
+                        conflicts.push(source.into());
+                    }
+                }
+                (result.explanations).insert(explanation.package().into(), explanation);
+            }
+            result
+        }
+        Err(backtracking::Failure::StepLimitExceeded) => PackageResolutionResult {
+            step_limit_exceeded: true,
+            ..result
+        },
+    }
 }
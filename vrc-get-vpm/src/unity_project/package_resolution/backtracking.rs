@@ -0,0 +1,545 @@
+//! The backtracking resolver used when the greedy resolution finds conflicts.
+//!
+//! The greedy resolution in the parent module always picks the latest version of each package,
+//! so it may report conflicts even if some older versions can satisfy all requirements.
+//! This module searches for such a set of versions by trying candidates from newer to older.
+//!
+//! The search is conflict-directed: when no version of a package can be used, the resolver
+//! collects the decided packages that caused it, jumps back to the latest of them instead of
+//! the previous decision, and learns the combination of their versions so the same dead end
+//! is not searched again.
+
+use crate::traits::PackageCollection;
+use crate::unity_project::LockedDependencyInfo;
+use crate::unity_project::pending_project_changes::{ConflictExplanation, Requirement};
+use crate::unity_project::scoped_collection::ScopedCollection;
+use crate::version::{PrereleaseAcceptance, UnityVersion, Version, VersionRange};
+use crate::{PackageInfo, PackageManifest, VersionSelector, unity_compatible};
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+
+/// The maximum number of candidates we try before giving up.
+///
+/// Dependency graphs of VPM packages are small, so this is large enough for real projects,
+/// and prevents exponential search for projects that are impossible to resolve.
+const MAX_STEPS: usize = 10000;
+
+pub(super) struct Problem<'env, 'a> {
+    pub root_dependencies: &'a [(&'a str, VersionRange, bool)],
+    pub locked: &'a [LockedDependencyInfo<'a>],
+    pub unlocked: &'a [&'a (Box<str>, Option<PackageManifest>)],
+    pub requested: &'a [PackageInfo<'env>],
+    pub overrides: &'a IndexMap<Box<str>, VersionRange>,
+    pub unity_version: Option<UnityVersion>,
+    pub allow_prerelease: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Candidate<'env, 'a> {
+    version: &'a Version,
+    dependencies: &'a IndexMap<Box<str>, VersionRange>,
+    legacy_packages: &'a [Box<str>],
+    /// `None` if we keep the package currently in the project
+    package: Option<PackageInfo<'env>>,
+}
+
+#[derive(Clone, Default)]
+struct State<'env, 'a> {
+    decided: IndexMap<&'a str, Candidate<'env, 'a>>,
+    legacy: HashSet<&'a str>,
+}
+
+/// The result of successful backtracking resolution.
+pub(super) struct Solution<'env> {
+    pub new_packages: Vec<PackageInfo<'env>>,
+    pub found_legacy_packages: Vec<Box<str>>,
+}
+
+/// The reason the backtracking resolution failed.
+pub(super) enum Failure {
+    /// No combination of versions satisfies all requirements.
+    ///
+    /// The explanation is built for the latest version of the package the search failed at.
+    Conflict(Option<ConflictExplanation>),
+    /// The search was given up after trying [`MAX_STEPS`] candidates.
+    StepLimitExceeded,
+}
+
+/// The dead end found while searching.
+struct DeadEnd<'a> {
+    /// The decided packages caused the dead end. Changing one of them may avoid the dead end.
+    culprits: HashSet<&'a str>,
+    explanation: Option<ConflictExplanation>,
+}
+
+enum SearchError<'a> {
+    DeadEnd(Box<DeadEnd<'a>>),
+    StepLimitExceeded,
+}
+
+/// The combination of versions learned to lead to a dead end.
+struct Nogood<'a> {
+    versions: Vec<(&'a str, &'a Version)>,
+    explanation: Option<ConflictExplanation>,
+}
+
+struct Resolver<'env, 'a, C: PackageCollection> {
+    problem: &'a Problem<'env, 'a>,
+    env: ScopedCollection<'env, 'a, C>,
+    locked: HashMap<&'a str, &'a LockedDependencyInfo<'a>>,
+    unlocked: HashMap<&'a str, &'a PackageManifest>,
+    unlocked_names: HashSet<&'a str>,
+    requested: HashMap<&'a str, PackageInfo<'env>>,
+    /// The packages decided before searching, which cannot be changed
+    fixed: HashSet<&'a str>,
+    candidates: HashMap<&'a str, Vec<Candidate<'env, 'a>>>,
+    nogoods: Vec<Nogood<'a>>,
+    /// The indices of nogoods in `nogoods` for each package in them
+    nogoods_by_package: HashMap<&'a str, Vec<usize>>,
+    steps: usize,
+}
+
+pub(super) fn resolve<'env, 'a>(
+    problem: &'a Problem<'env, 'a>,
+    env: ScopedCollection<'env, 'a, impl PackageCollection>,
+) -> Result<Solution<'env>, Failure>
+where
+    'env: 'a,
+{
+    let mut resolver = Resolver {
+        problem,
+        env,
+        locked: problem.locked.iter().map(|x| (x.name(), x)).collect(),
+        unlocked: HashMap::new(),
+        unlocked_names: HashSet::new(),
+        requested: problem.requested.iter().map(|x| (x.name(), *x)).collect(),
+        fixed: HashSet::new(),
+        candidates: HashMap::new(),
+        nogoods: Vec::new(),
+        nogoods_by_package: HashMap::new(),
+        steps: 0,
+    };
+
+    for (dir_name, manifest) in problem.unlocked {
+        resolver.unlocked_names.insert(dir_name.as_ref());
+        if let Some(manifest) = manifest {
+            resolver.unlocked_names.insert(manifest.name());
+            resolver.unlocked.insert(manifest.name(), manifest);
+        }
+    }
+
+    let mut state = State::default();
+
+    // unlocked packages cannot be changed unless requested
+    for (&name, &manifest) in &resolver.unlocked {
+        if !resolver.requested.contains_key(name) {
+            resolver.fixed.insert(name);
+            state.decided.insert(
+                name,
+                Candidate {
+                    version: manifest.version(),
+                    dependencies: manifest.vpm_dependencies(),
+                    legacy_packages: manifest.legacy_packages(),
+                    package: None,
+                },
+            );
+            state
+                .legacy
+                .extend(manifest.legacy_packages().iter().map(Box::as_ref));
+        }
+    }
+
+    let state = match resolver.search(state) {
+        Ok(state) => state,
+        Err(SearchError::DeadEnd(dead_end)) => {
+            log::debug!(
+                "backtracking: no solution found in {} steps",
+                resolver.steps
+            );
+            return Err(Failure::Conflict(dead_end.explanation));
+        }
+        Err(SearchError::StepLimitExceeded) => return Err(Failure::StepLimitExceeded),
+    };
+
+    log::debug!("backtracking: solution found in {} steps", resolver.steps);
+
+    let new_packages = state
+        .decided
+        .iter()
+        .filter(|(name, _)| !state.legacy.contains(*name))
+        .filter_map(|(_, candidate)| candidate.package)
+        .collect();
+
+    let found_legacy_packages = state.legacy.iter().map(|&x| x.into()).collect();
+
+    Ok(Solution {
+        new_packages,
+        found_legacy_packages,
+    })
+}
+
+impl<'env, 'a, C: PackageCollection> Resolver<'env, 'a, C>
+where
+    'env: 'a,
+{
+    fn search(&mut self, state: State<'env, 'a>) -> Result<State<'env, 'a>, SearchError<'a>> {
+        let Some(name) = self.next_undecided(&state) else {
+            // all packages are decided
+            return Ok(state);
+        };
+
+        log::debug!("backtracking: deciding version of {name}");
+
+        let requirements = self.requirements(&state, name);
+        let acceptance = self.prerelease_acceptance(name);
+        let candidates = self.candidates(name);
+
+        // like the greedy resolution, explain why the latest version cannot be used
+        let latest = candidates.iter().map(|x| x.version).max();
+        let mut explanation = None;
+        let mut culprits = HashSet::new();
+
+        for candidate in candidates {
+            self.steps += 1;
+            if self.steps > MAX_STEPS {
+                log::debug!("backtracking: too many steps, giving up");
+                return Err(SearchError::StepLimitExceeded);
+            }
+
+            let explain = explanation.is_none() && Some(candidate.version) == latest;
+
+            let unsatisfied = (requirements.iter())
+                .filter(|(_, range)| !range.match_pre(candidate.version, acceptance))
+                .map(|&(source, _)| source)
+                .collect::<Vec<_>>();
+            if !unsatisfied.is_empty() {
+                culprits.extend(unsatisfied);
+                if explain {
+                    explanation = Some(self.explain(&state, name, candidate.version));
+                }
+                continue;
+            }
+
+            let mut next = state.clone();
+            next.decided.insert(name, candidate);
+            next.legacy
+                .extend(candidate.legacy_packages.iter().map(Box::as_ref));
+
+            if let Some(dependency) = self.find_inconsistency(&next, name, candidate) {
+                culprits.insert(dependency);
+                if explain {
+                    let version = next.decided[dependency].version;
+                    explanation = Some(self.explain(&next, dependency, version));
+                }
+                continue;
+            }
+
+            if let Some(nogood) = self.find_violated_nogood(&next, name) {
+                let nogood = &self.nogoods[nogood];
+                culprits.extend(nogood.versions.iter().map(|&(package, _)| package));
+                if explain {
+                    explanation = nogood.explanation.clone();
+                }
+                continue;
+            }
+
+            match self.search(next) {
+                Ok(solved) => return Ok(solved),
+                Err(SearchError::DeadEnd(dead_end)) if !dead_end.culprits.contains(name) => {
+                    // other versions of this package cannot avoid the dead end, so jump back further
+                    return Err(SearchError::DeadEnd(dead_end));
+                }
+                Err(SearchError::DeadEnd(dead_end)) => {
+                    culprits.extend(dead_end.culprits);
+                    if explain {
+                        explanation = dead_end.explanation;
+                    }
+                }
+                Err(SearchError::StepLimitExceeded) => {
+                    return Err(SearchError::StepLimitExceeded);
+                }
+            }
+        }
+
+        log::debug!("backtracking: no version of {name} satisfies requirements");
+
+        // the package would not be required if the packages requiring it were changed
+        culprits.extend(requirements.iter().map(|&(source, _)| source));
+        culprits.extend(self.legacy_declarers(&state, name));
+        culprits.retain(|&package| {
+            package != name && state.decided.contains_key(package) && !self.fixed.contains(package)
+        });
+
+        self.learn(&state, &culprits, explanation.clone());
+
+        Err(SearchError::DeadEnd(Box::new(DeadEnd {
+            culprits,
+            explanation,
+        })))
+    }
+
+    /// Returns the package should be decided next
+    fn next_undecided(&self, state: &State<'env, 'a>) -> Option<&'a str> {
+        let requested = self.problem.requested.iter().map(|x| x.name());
+        let root = self.problem.root_dependencies.iter().map(|x| x.0);
+        let locked = self.problem.locked.iter().map(|x| x.name());
+        let dependencies = state
+            .decided
+            .iter()
+            .filter(|(name, _)| !state.legacy.contains(*name))
+            .flat_map(|(_, candidate)| candidate.dependencies.keys())
+            .map(Box::as_ref);
+
+        requested
+            .chain(root)
+            .chain(locked)
+            .chain(dependencies)
+            .filter(|name| !state.decided.contains_key(name))
+            .filter(|name| !state.legacy.contains(name))
+            // unlocked directories without valid package.json cannot be checked
+            .find(|name| self.requested.contains_key(name) || !self.unlocked_names.contains(name))
+    }
+
+    /// Collects version ranges required to the package by the project and decided packages.
+    ///
+    /// The source is empty for the requirements from the project.
+    fn requirements(
+        &self,
+        state: &State<'env, 'a>,
+        name: &str,
+    ) -> Vec<(&'a str, &'a VersionRange)> {
+        let root = self
+            .problem
+            .root_dependencies
+            .iter()
+            .filter(|(dependency, _, _)| *dependency == name)
+            .map(|(_, range, _)| ("", range));
+
+        let overridden = self.problem.overrides.get(name);
+        let dependencies = state
+            .decided
+            .iter()
+            .filter(|(source, _)| !state.legacy.contains(*source))
+            .filter_map(|(&source, candidate)| Some((source, candidate.dependencies.get(name)?)))
+            .map(|(source, range)| (source, overridden.unwrap_or(range)));
+
+        root.chain(dependencies).collect()
+    }
+
+    /// Finds the dependency of newly decided package not satisfied by decided packages
+    fn find_inconsistency(
+        &self,
+        state: &State<'env, 'a>,
+        name: &str,
+        candidate: Candidate<'env, 'a>,
+    ) -> Option<&'a str> {
+        for (dependency, range) in candidate.dependencies {
+            if state.legacy.contains(dependency.as_ref()) {
+                continue;
+            }
+            let range = self.problem.overrides.get(dependency).unwrap_or(range);
+            if let Some((&dependency, decided)) = state.decided.get_key_value(dependency.as_ref())
+                && !range.match_pre(decided.version, self.prerelease_acceptance(dependency))
+            {
+                log::debug!(
+                    "backtracking: {name}@{} requires {dependency}@{range} but {} is decided",
+                    candidate.version,
+                    decided.version
+                );
+                return Some(dependency);
+            }
+        }
+
+        None
+    }
+
+    /// Finds the learned nogood all versions of which are decided
+    fn find_violated_nogood(&self, state: &State<'env, 'a>, name: &str) -> Option<usize> {
+        let nogoods = self.nogoods_by_package.get(name)?;
+        nogoods.iter().copied().find(|&index| {
+            (self.nogoods[index].versions.iter()).all(|&(package, version)| {
+                (state.decided.get(package)).is_some_and(|decided| decided.version == version)
+            })
+        })
+    }
+
+    fn learn(
+        &mut self,
+        state: &State<'env, 'a>,
+        culprits: &HashSet<&'a str>,
+        explanation: Option<ConflictExplanation>,
+    ) {
+        if culprits.is_empty() {
+            // nothing can avoid this dead end so the search ends soon
+            return;
+        }
+
+        let index = self.nogoods.len();
+        let versions = (culprits.iter())
+            .map(|&package| (package, state.decided[package].version))
+            .collect::<Vec<_>>();
+        for &(package, _) in &versions {
+            (self.nogoods_by_package.entry(package).or_default()).push(index);
+        }
+        self.nogoods.push(Nogood {
+            versions,
+            explanation,
+        });
+    }
+
+    /// Lists decided packages which would make the package legacy with other versions
+    fn legacy_declarers(&self, state: &State<'env, 'a>, name: &str) -> Vec<&'a str> {
+        (state.decided.keys().copied())
+            .filter(|package| !self.fixed.contains(package))
+            .filter(|package| {
+                (self.candidates.get(package).into_iter().flatten())
+                    .flat_map(|candidate| candidate.legacy_packages)
+                    .any(|legacy| legacy.as_ref() == name)
+            })
+            .collect()
+    }
+
+    fn prerelease_acceptance(&self, name: &str) -> PrereleaseAcceptance {
+        let allow = self.problem.allow_prerelease
+            || (self.problem.root_dependencies.iter())
+                .any(|(dependency, _, allow_pre)| *dependency == name && *allow_pre)
+            || (self.locked.get(name)).is_some_and(|x| x.version().is_pre())
+            || (self.unlocked.get(name)).is_some_and(|x| x.version().is_pre())
+            || (self.requested.get(name)).is_some_and(|x| x.version().is_pre());
+        PrereleaseAcceptance::allow_or_minimum(allow)
+    }
+
+    /// Explains why the version of the package does not satisfy the requirements
+    fn explain(
+        &self,
+        state: &State<'env, 'a>,
+        name: &str,
+        version: &Version,
+    ) -> ConflictExplanation {
+        let acceptance = self.prerelease_acceptance(name);
+        let mut requirements = self.requirements(state, name);
+        requirements.sort_by_key(|&(source, _)| source);
+
+        let requirements = (requirements.into_iter())
+            .map(|(source, range)| {
+                let mut chain = self.requirement_chain(state, source);
+                chain.push(self.requirement(state, source, name, range));
+                (chain, range.match_pre(version, acceptance))
+            })
+            .collect();
+
+        ConflictExplanation::new(name.into(), Some(version.clone()), requirements)
+    }
+
+    /// Finds the chain of requirements that made the package required
+    fn requirement_chain(&self, state: &State<'env, 'a>, package: &str) -> Vec<Requirement> {
+        let mut chain = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current = package;
+
+        while !current.is_empty() && visited.insert(current) {
+            let requirements = self.requirements(state, current);
+            // prefer root dependencies to make the chain short
+            let parent = (requirements.iter())
+                .filter(|(source, _)| !visited.contains(source))
+                .min_by_key(|&&(source, _)| source);
+            let Some(&(parent, range)) = parent else {
+                break;
+            };
+            chain.push(self.requirement(state, parent, current, range));
+            current = parent;
+        }
+
+        chain.reverse();
+        chain
+    }
+
+    fn requirement(
+        &self,
+        state: &State<'env, 'a>,
+        source: &str,
+        target: &str,
+        range: &VersionRange,
+    ) -> Requirement {
+        let source =
+            (state.decided.get(source)).map(|decided| (source.into(), decided.version.clone()));
+        Requirement::new(source, target.into(), range.clone())
+    }
+
+    /// Lists candidate versions of the package in order of preference
+    fn candidates(&mut self, name: &'a str) -> Vec<Candidate<'env, 'a>> {
+        if let Some(candidates) = self.candidates.get(name) {
+            return candidates.clone();
+        }
+        let candidates = self.list_candidates(name);
+        self.candidates.insert(name, candidates.clone());
+        candidates
+    }
+
+    fn list_candidates(&self, name: &'a str) -> Vec<Candidate<'env, 'a>> {
+        fn from_package<'env: 'a, 'a>(package: PackageInfo<'env>) -> Candidate<'env, 'a> {
+            Candidate {
+                version: package.version(),
+                dependencies: package.vpm_dependencies(),
+                legacy_packages: package.legacy_packages(),
+                package: Some(package),
+            }
+        }
+
+        if let Some(&requested) = self.requested.get(name) {
+            return vec![from_package(requested)];
+        }
+
+        let mut candidates = Vec::new();
+
+        let current = self.locked.get(name).map(|locked| {
+            let legacy_packages = self
+                .env
+                .find_package_by_name(name, VersionSelector::specific_version(locked.version()))
+                .map(|x| x.legacy_packages())
+                .unwrap_or_default();
+            Candidate {
+                version: locked.version(),
+                dependencies: locked.dependencies(),
+                legacy_packages,
+                package: None,
+            }
+        });
+
+        if let Some(current) = current {
+            // keeping the current version is the most preferred
+            candidates.push(current);
+        }
+
+        let mut packages = self
+            .env
+            .find_packages(name)
+            .filter(|x| !x.is_yanked())
+            .filter(|x| Some(x.version()) != current.map(|x| x.version))
+            .collect::<Vec<_>>();
+
+        let unity_compatible = |package: &PackageInfo| {
+            self.problem
+                .unity_version
+                .is_none_or(|unity| unity_compatible(package.package_json(), unity))
+        };
+
+        // prefer unity compatible, stable (unless prerelease is allowed), and newer versions
+        packages.sort_by(|a, b| {
+            (unity_compatible(b).cmp(&unity_compatible(a)))
+                .then_with(|| {
+                    if self.problem.allow_prerelease {
+                        std::cmp::Ordering::Equal
+                    } else {
+                        b.version().is_stable().cmp(&a.version().is_stable())
+                    }
+                })
+                .then_with(|| b.version().cmp(a.version()))
+        });
+        packages.dedup_by(|a, b| a.version() == b.version());
+
+        candidates.extend(packages.into_iter().map(from_package));
+
+        candidates
+    }
+}
@@ -1,7 +1,8 @@
 use crate::io::{DefaultProjectIo, IoTrait};
 use crate::traits::AbortCheck;
 use crate::unity_project::find_legacy_assets::collect_legacy_assets;
-use crate::version::DependencyRange;
+use crate::unity_project::transaction_journal::{TransactionJournal, TransactionPhase};
+use crate::version::{DependencyRange, Version, VersionRange};
 use crate::{PackageInfo, UnityProject, unity_compatible};
 use crate::{PackageInstaller, io};
 use either::Either;
@@ -10,6 +11,7 @@ use futures::prelude::*;
 use log::debug;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::future::ready;
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
@@ -30,6 +32,10 @@ pub struct PendingProjectChanges<'env> {
     pub(crate) remove_legacy_folders: Vec<(Box<Path>, &'env str)>,
 
     pub(crate) conflicts: HashMap<Box<str>, ConflictInfo>,
+
+    pub(crate) overridden_requirements: Vec<OverriddenRequirement>,
+
+    pub(crate) remove_upm_dependencies: Vec<Box<str>>,
 }
 
 #[derive(Debug)]
@@ -100,6 +106,7 @@ pub struct ConflictInfo {
     conflicts_with_unity: bool,
     // The value is the name of directory that is installed as unlocked
     unlocked_names: Vec<Box<str>>,
+    explanation: Option<ConflictExplanation>,
 }
 
 impl ConflictInfo {
@@ -114,6 +121,169 @@ impl ConflictInfo {
     pub fn unlocked_names(&self) -> &[Box<str>] {
         self.unlocked_names.as_slice()
     }
+
+    /// Returns why the package conflicts, if the resolver could explain
+    pub fn explanation(&self) -> Option<&ConflictExplanation> {
+        self.explanation.as_ref()
+    }
+}
+
+/// Explains a conflict with the chains of requirements to the conflicting package.
+///
+/// The [`Display`](fmt::Display) implementation renders this like
+/// "A 2.0.0 requires B >=3 but C 1.4.0 requires B <3".
+#[derive(Debug, Clone)]
+pub struct ConflictExplanation {
+    package: Box<str>,
+    version: Option<Version>,
+    requirements: Vec<(Vec<Requirement>, bool)>,
+}
+
+impl ConflictExplanation {
+    pub(crate) fn new(
+        package: Box<str>,
+        version: Option<Version>,
+        requirements: Vec<(Vec<Requirement>, bool)>,
+    ) -> Self {
+        Self {
+            package,
+            version,
+            requirements,
+        }
+    }
+
+    /// The name of the conflicting package
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// The version of the package to be used, if any
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// The chains of requirements to the package, from the project to the package.
+    ///
+    /// The boolean is `true` if the version to be used satisfies the last requirement in the chain.
+    pub fn requirements(&self) -> impl Iterator<Item = (&[Requirement], bool)> {
+        self.requirements
+            .iter()
+            .map(|(chain, satisfied)| (chain.as_slice(), *satisfied))
+    }
+}
+
+impl fmt::Display for ConflictExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_chains<'a>(
+            f: &mut fmt::Formatter<'_>,
+            chains: impl Iterator<Item = &'a [Requirement]>,
+        ) -> fmt::Result {
+            let mut first = true;
+            for chain in chains {
+                if !first {
+                    f.write_str(" and ")?;
+                }
+                first = false;
+                let mut first_step = true;
+                for requirement in chain {
+                    if !first_step {
+                        f.write_str(", ")?;
+                    }
+                    first_step = false;
+                    write!(f, "{requirement}")?;
+                }
+            }
+            Ok(())
+        }
+
+        let satisfied = (self.requirements().filter(|(_, satisfied)| *satisfied)).map(|x| x.0);
+        let unsatisfied = (self.requirements().filter(|(_, satisfied)| !*satisfied)).map(|x| x.0);
+
+        if self.requirements.iter().any(|(_, satisfied)| *satisfied) {
+            write_chains(f, satisfied)?;
+            f.write_str(" but ")?;
+        }
+        write_chains(f, unsatisfied)
+    }
+}
+
+/// A version range requirement from a package or the project to a package.
+#[derive(Debug, Clone)]
+pub struct Requirement {
+    source: Option<(Box<str>, Version)>,
+    target: Box<str>,
+    range: VersionRange,
+}
+
+impl Requirement {
+    pub(crate) fn new(
+        source: Option<(Box<str>, Version)>,
+        target: Box<str>,
+        range: VersionRange,
+    ) -> Self {
+        Self {
+            source,
+            target,
+            range,
+        }
+    }
+
+    /// The package and version that requires the target. `None` for `dependencies` of the project.
+    pub fn source(&self) -> Option<(&str, &Version)> {
+        self.source
+            .as_ref()
+            .map(|(name, version)| (name.as_ref(), version))
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn range(&self) -> &VersionRange {
+        &self.range
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            Some((name, version)) => write!(f, "{name} {version}")?,
+            None => f.write_str("the project")?,
+        }
+        write!(f, " requires {} {}", self.target, self.range)
+    }
+}
+
+/// A requirement from a package replaced by `overrides` in `vpm-manifest.json`.
+///
+/// The [`Display`](fmt::Display) implementation renders this like
+/// "A 2.0.0 requires B >=3 but overridden with B 2.1.0".
+#[derive(Debug, Clone)]
+pub struct OverriddenRequirement {
+    requirement: Requirement,
+    override_range: VersionRange,
+}
+
+impl OverriddenRequirement {
+    /// The requirement declared in `vpmDependencies` of the package
+    pub fn requirement(&self) -> &Requirement {
+        &self.requirement
+    }
+
+    /// The range used instead of the declared range
+    pub fn override_range(&self) -> &VersionRange {
+        &self.override_range
+    }
+}
+
+impl fmt::Display for OverriddenRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} but overridden with {} {}",
+            self.requirement, self.requirement.target, self.override_range
+        )
+    }
 }
 
 pub(crate) struct Builder<'env> {
@@ -243,6 +413,15 @@ impl<'env> Builder<'env> {
         self
     }
 
+    pub fn conflict_explanation(
+        &mut self,
+        name: Box<str>,
+        explanation: ConflictExplanation,
+    ) -> &mut Self {
+        self.conflicts.entry(name).or_default().explanation = Some(explanation);
+        self
+    }
+
     pub fn conflicts(&mut self, name: Box<str>, conflict: Box<str>) -> &mut Self {
         self.conflicts
             .entry(name)
@@ -342,6 +521,10 @@ impl<'env> Builder<'env> {
 
             remove_legacy_files: vec![],
             remove_legacy_folders: vec![],
+
+            overridden_requirements: vec![],
+
+            remove_upm_dependencies: vec![],
         }
     }
 
@@ -376,6 +559,8 @@ impl<'env> Builder<'env> {
         let legacy_assets =
             collect_legacy_assets(&unity_project.io, &installs, unity_project).await;
 
+        let overridden_requirements = self.collect_overridden_requirements(unity_project);
+
         debug!("Building PendingProjectChanges finished!");
 
         PendingProjectChanges {
@@ -384,7 +569,46 @@ impl<'env> Builder<'env> {
 
             remove_legacy_files: legacy_assets.files,
             remove_legacy_folders: legacy_assets.folders,
+
+            overridden_requirements,
+
+            remove_upm_dependencies: vec![],
+        }
+    }
+
+    /// Collects the requirements of the installing packages replaced by the overrides.
+    fn collect_overridden_requirements(
+        &self,
+        unity_project: &UnityProject,
+    ) -> Vec<OverriddenRequirement> {
+        let mut overridden = Vec::new();
+
+        let packages = (self.package_changes.values())
+            .filter_map(|x| x.as_install())
+            .filter_map(|x| x.package);
+
+        for package in packages {
+            for (dependency, range) in package.vpm_dependencies() {
+                if let Some(override_range) = unity_project.get_override(dependency)
+                    && override_range != range
+                {
+                    overridden.push(OverriddenRequirement {
+                        requirement: Requirement::new(
+                            Some((package.name().into(), package.version().clone())),
+                            dependency.clone(),
+                            range.clone(),
+                        ),
+                        override_range: override_range.clone(),
+                    });
+                }
+            }
         }
+
+        overridden.sort_by(|a, b| {
+            (a.requirement.source.cmp(&b.requirement.source))
+                .then_with(|| a.requirement.target.cmp(&b.requirement.target))
+        });
+        overridden
     }
 
     fn mark_and_sweep_packages(&mut self, unity_project: &UnityProject) {
@@ -527,10 +751,20 @@ impl PendingProjectChanges<'_> {
     pub fn conflicts(&self) -> &HashMap<Box<str>, ConflictInfo> {
         &self.conflicts
     }
+
+    /// The requirements of the installing packages replaced by `overrides` in `vpm-manifest.json`
+    pub fn overridden_requirements(&self) -> &[OverriddenRequirement] {
+        &self.overridden_requirements
+    }
+
+    /// The dependencies to be removed from `Packages/manifest.json`
+    pub fn remove_upm_dependencies(&self) -> &[Box<str>] {
+        &self.remove_upm_dependencies
+    }
 }
 
 static TEMP_DIR: &str = "Temp";
-static PKG_TEMP_DIR: &str = "Temp/vrc-get";
+pub(super) static PKG_TEMP_DIR: &str = "Temp/vrc-get";
 
 impl UnityProject {
     /// Applies the changes specified in `AddPackageRequest` to the project.
@@ -586,10 +820,28 @@ impl UnityProject {
         self.io.create_dir_all(&context.remove_temp_dir).await?;
         self.io.create_dir_all(&context.install_temp_dir).await?;
 
+        // The journal is written before each step to recover the project if the process is killed
+        let mut journal = TransactionJournal::begin(
+            &self.io,
+            &temp_dir_base,
+            installs.iter().map(|x| x.name()),
+            uninstall_packages.iter().copied(),
+            request.remove_legacy_files.iter().map(|(p, _)| p.as_ref()),
+            request
+                .remove_legacy_folders
+                .iter()
+                .map(|(p, _)| p.as_ref()),
+        )
+        .await?;
+
         let mut r: io::Result<()> = async {
             // Firstly, extract packages
             extract_packages(&self.io, env, &context.install_temp_dir, &installs).await?;
 
+            journal
+                .set_phase(&self.io, TransactionPhase::Applying)
+                .await?;
+
             // Then, update packages directory
             context
                 .move_uninstall_packages(&self.io, &uninstall_packages)
@@ -609,13 +861,28 @@ impl UnityProject {
                         );
                     }
 
+                    if let Some(source) = change.package.and_then(|x| x.source_package()) {
+                        self.manifest.set_locked_source(
+                            source.manifest().name(),
+                            source.source(),
+                            source.hash(),
+                        );
+                    }
+
                     if let Some(version) = &change.to_dependencies {
                         self.manifest.add_dependency(name, version.clone());
                     }
                 }
             }
             self.manifest.remove_packages(remove_names.iter().copied());
+            for name in &request.remove_upm_dependencies {
+                self.upm_manifest.remove_dependency(name);
+            }
             self.save().await?;
+
+            journal
+                .set_phase(&self.io, TransactionPhase::Committed)
+                .await?;
             Ok(())
         }
         .await;
@@ -669,8 +936,10 @@ impl UnityProject {
 
                 // If user is familiar with vrc-get internals, user might recover packages
                 // from temp directory so we don't clean temp directory.
+                // The journal is also kept to retry rolling back when the project is loaded next time.
             } else {
                 // cleanup temp directory when rollback successfully finished
+                journal.finish(&self.io).await.ok();
                 cleanup_temp_dir(&self.io).await;
             }
 
@@ -678,9 +947,6 @@ impl UnityProject {
         } else {
             // installation process finished successfully
 
-            // cleanup
-            cleanup_temp_dir(&self.io).await;
-
             // remove legacy assets
             remove_assets(
                 &self.io,
@@ -691,6 +957,10 @@ impl UnityProject {
                     .map(|(p, _)| p.as_ref()),
             )
             .await;
+
+            // cleanup
+            journal.finish(&self.io).await.ok();
+            cleanup_temp_dir(&self.io).await;
         }
 
         async fn cleanup_temp_dir(io: &DefaultProjectIo) {
@@ -830,7 +1100,7 @@ impl<'a> InstallPackageContext<'a> {
     }
 }
 
-async fn remove_assets(
+pub(super) async fn remove_assets(
     io: &DefaultProjectIo,
     legacy_files: impl Iterator<Item = &Path>,
     legacy_folders: impl Iterator<Item = &Path>,
@@ -0,0 +1,295 @@
+//! Collecting the files in the project to back up or copy.
+//!
+//! This is shared with ALCOM so the backups created by vrc-get and ALCOM contain the same files.
+
+use crate::UnityProject;
+use crate::io;
+use crate::io::DefaultProjectIo;
+use futures::future::try_join_all;
+use log::warn;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// The tree of the files and directories in the project.
+///
+/// The relative path of directories ends with `/`, and the relative path of the root is empty.
+#[derive(Debug)]
+pub struct FileSystemTree {
+    relative_path: String,
+    absolute_path: PathBuf,
+    children: Vec<FileSystemTree>,
+}
+
+impl FileSystemTree {
+    fn new_file(relative_path: String, absolute_path: PathBuf) -> Self {
+        assert!(!relative_path.is_empty() && !relative_path.ends_with('/'));
+        Self {
+            relative_path,
+            absolute_path,
+            children: Vec::new(),
+        }
+    }
+
+    fn new_dir(
+        relative_path: String,
+        absolute_path: PathBuf,
+        children: Vec<FileSystemTree>,
+    ) -> Self {
+        assert!(relative_path.is_empty() || relative_path.ends_with('/'));
+        Self {
+            relative_path,
+            absolute_path,
+            children,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.relative_path.is_empty() || self.relative_path.ends_with('/')
+    }
+
+    pub fn relative_path(&self) -> &str {
+        &self.relative_path
+    }
+
+    pub fn absolute_path(&self) -> &Path {
+        &self.absolute_path
+    }
+
+    /// Iterates all files and directories in the tree excluding the root, parents first.
+    pub fn recursive(&self) -> FileSystemTreeRecursive<'_> {
+        FileSystemTreeRecursive {
+            stack: vec![(self, 0)],
+        }
+    }
+
+    /// Iterates the direct children of the directory.
+    pub fn iter(&self) -> FileSystemTreeIter<'_> {
+        FileSystemTreeIter {
+            back: self.children.iter(),
+        }
+    }
+
+    /// Count all files and directories in the tree excluding the root
+    pub fn count_all(&self) -> usize {
+        self.recursive().count()
+    }
+}
+
+pub struct FileSystemTreeRecursive<'a> {
+    stack: Vec<(&'a FileSystemTree, usize)>,
+}
+
+impl<'a> Iterator for FileSystemTreeRecursive<'a> {
+    type Item = &'a FileSystemTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (tree, index) = self.stack.pop()?;
+
+            if index < tree.children.len() {
+                self.stack.push((tree, index + 1));
+                let new_ent = &tree.children[index];
+                self.stack.push((new_ent, 0));
+                return Some(new_ent);
+            }
+        }
+    }
+}
+
+pub struct FileSystemTreeIter<'a> {
+    back: std::slice::Iter<'a, FileSystemTree>,
+}
+
+impl<'a> Iterator for FileSystemTreeIter<'a> {
+    type Item = &'a FileSystemTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.back.next()
+    }
+}
+
+/// Collects the files in the project folder except for `Logs`, `obj`, `Temp`, and `.git` folders.
+///
+/// With `backup`, `Library` folders are not included except for `LastSceneManagerSetup.txt`
+/// to keep the last opened scene.
+/// With `exclude_vpm`, the locked VPM packages are not included since they can be restored by resolving.
+pub async fn collect_notable_project_files_tree(
+    path_buf: PathBuf,
+    exclude_vpm: bool,
+    backup: bool,
+) -> io::Result<FileSystemTree> {
+    let excluded_packages = if exclude_vpm {
+        async fn get_packages(path: &Path) -> Option<Vec<String>> {
+            let unity_project = UnityProject::load(DefaultProjectIo::new(path.into()))
+                .await
+                .ok()?;
+            Some(locked_package_folders(&unity_project))
+        }
+        get_packages(&path_buf).await.unwrap_or_default()
+    } else {
+        vec![]
+    };
+
+    collect_project_files_tree(path_buf, &excluded_packages, backup).await
+}
+
+/// The lowercase folder names of the locked packages to be excluded from the tree.
+pub(crate) fn locked_package_folders(unity_project: &UnityProject) -> Vec<String> {
+    (unity_project.locked_packages())
+        .map(|x| x.name().to_ascii_lowercase())
+        .collect()
+}
+
+/// Collects the files in the project folder except for the package folders in `excluded_packages`.
+pub(crate) async fn collect_project_files_tree(
+    path_buf: PathBuf,
+    excluded_packages: &[String],
+    backup: bool,
+) -> io::Result<FileSystemTree> {
+    // relative path must end with '/' or empty
+    async fn read_dir_to_tree(
+        relative: String,
+        absolute: PathBuf,
+        excluded_packages: &[String],
+        backup: bool,
+        semaphore: Arc<Semaphore>,
+    ) -> io::Result<FileSystemTree> {
+        // we have semaphore to limit simultaneous file access.
+        let semaphore_scope = semaphore.acquire().await.unwrap();
+        let mut read_dir = tokio::fs::read_dir(&absolute).await?;
+
+        // relative, entry, is_dir
+        let mut entries = Vec::new();
+
+        while let Some(entry) = read_dir.next_entry().await? {
+            let Ok(file_name) = entry.file_name().into_string() else {
+                // non-utf8 file name
+                warn!("skipping non-utf8 file name: {}", entry.path().display());
+                continue;
+            };
+            log::trace!("process: {relative}{file_name}");
+
+            let new_relative;
+            let is_dir;
+
+            let file_type = entry.file_type().await?;
+
+            if file_type.is_symlink() {
+                // skip symlink
+                // TODO: further handling
+                warn!("skipping symlink: {}", entry.path().display());
+                continue;
+            }
+
+            if entry.file_type().await?.is_dir() {
+                let lower_name = file_name.to_ascii_lowercase();
+                if relative.is_empty() {
+                    match lower_name.as_str() {
+                        "logs" | "obj" | "temp" => {
+                            continue;
+                        }
+                        _ => {}
+                    }
+                } else if relative.eq_ignore_ascii_case("packages/") {
+                    // the package is excluded
+                    if excluded_packages.contains(&lower_name) {
+                        continue;
+                    }
+                }
+
+                if backup {
+                    // for backups, we exclude the library directory
+                    if starts_with_ascii_ignore(&relative, "library") {
+                        // some people use multiple library folder to speed up switching platforms,
+                        // so we use starts_with way for matching
+
+                        // It's inside a library directory, all directories will be ignored
+
+                        // We won't skip Library directory directly
+                        // since we want to keep LastSceneManagerSetup.txt as a exception
+                        continue;
+                    }
+                }
+
+                if lower_name.as_str() == ".git" {
+                    // any .git folder should be ignored
+                    continue;
+                }
+
+                new_relative = format!("{relative}{file_name}/");
+                is_dir = true;
+            } else {
+                if backup {
+                    // for backups, we exclude the library directory
+                    if starts_with_ascii_ignore(&relative, "library") {
+                        // some people use multiple library folder to speed up switching platforms,
+                        // so we use starts_with way for matching
+
+                        // It's inside a library directory, all files except for few files
+
+                        if file_name.eq_ignore_ascii_case("LastSceneManagerSetup.txt") {
+                            // `LastSceneManagerSetup.txt` will preserve the information which
+                            // scene was opened last time.
+                            //
+                            // Many avatar project users doesn't understand they're editing scene,
+                            // and they don't understand they can create another new scene,
+                            // and can be opened from project window.
+                            // Therefore, some user says that "I restored from backup, but avatars are
+                            // go away from my project" even though they're opening another scene.
+                            // Therefore, we decided to keep this file where possible.
+                        } else {
+                            continue;
+                        }
+                    }
+                }
+                new_relative = format!("{relative}{file_name}");
+                is_dir = false;
+            }
+
+            entries.push((new_relative, entry, is_dir));
+        }
+
+        // release semaphore since directory traversal has finished.
+        drop(semaphore_scope);
+
+        let children = try_join_all(entries.into_iter().map({
+            |(relative, entry, is_dir)| {
+                let semaphore = semaphore.clone();
+                async move {
+                    if is_dir {
+                        read_dir_to_tree(
+                            relative,
+                            entry.path(),
+                            excluded_packages,
+                            backup,
+                            semaphore,
+                        )
+                        .await
+                    } else {
+                        Ok(FileSystemTree::new_file(relative, entry.path()))
+                    }
+                }
+            }
+        }))
+        .await?;
+
+        Ok(FileSystemTree::new_dir(relative, absolute, children))
+    }
+
+    let semaphore = Arc::new(Semaphore::new(100));
+
+    read_dir_to_tree(
+        String::new(),
+        path_buf,
+        excluded_packages,
+        backup,
+        semaphore,
+    )
+    .await
+}
+
+fn starts_with_ascii_ignore(str: &str, pat: &str) -> bool {
+    str.get(..pat.len())
+        .is_some_and(|heading| heading.eq_ignore_ascii_case(pat))
+}
@@ -46,6 +46,7 @@ impl UnityProject {
         env: &'env impl PackageCollection,
         packages: &[&str],
     ) -> Result<PendingProjectChanges<'env>, ReinstalPackagesError> {
+        let env = self.scoped(env);
         let mut changes = pending_project_changes::Builder::new();
         let mut missing_dependencies = MissingDependencies::new();
 
@@ -3,10 +3,11 @@ use std::collections::{HashMap, HashSet};
 use std::fmt;
 
 use crate::unity_project::package_resolution::MissingDependencies;
+use crate::unity_project::scoped_collection::ScopedCollection;
 use crate::unity_project::{
     LockedDependencyInfo, PendingProjectChanges, package_resolution, pending_project_changes,
 };
-use crate::version::{DependencyRange, PrereleaseAcceptance, VersionRange};
+use crate::version::{DependencyRange, PrereleaseAcceptance, Version, VersionRange};
 use crate::{PackageCollection, UnityProject, VersionSelector};
 
 #[derive(Debug)]
@@ -15,6 +16,70 @@ pub enum ResolvePackageErr {
     DependenciesNotFound {
         dependencies: Vec<(Box<str>, VersionRange)>,
     },
+    /// Frozen resolution failed since the `locked` section cannot be used as is.
+    FrozenLockViolation {
+        violations: Vec<FrozenLockViolation>,
+    },
+    /// The resolution gave up since there are too many combinations of versions to try.
+    ResolutionStepLimitExceeded,
+}
+
+/// The reason the `locked` section of `vpm-manifest.json` cannot be used without changes.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FrozenLockViolation {
+    /// The locked version of the package is not found in any repository.
+    LockedNotFound { package: Box<str>, version: Version },
+    /// The locked version of the package does not satisfy the requested range.
+    ///
+    /// `required_by` is `None` if the range is from `dependencies` section.
+    RangeNotSatisfied {
+        package: Box<str>,
+        version: Version,
+        required_by: Option<Box<str>>,
+        range: VersionRange,
+    },
+    /// The package is required but not locked, so it requires re-resolution.
+    ///
+    /// `required_by` is `None` if the range is from `dependencies` section.
+    NotLocked {
+        package: Box<str>,
+        required_by: Option<Box<str>>,
+        range: VersionRange,
+    },
+}
+
+impl fmt::Display for FrozenLockViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn required_by(required_by: &Option<Box<str>>) -> &str {
+            required_by.as_deref().unwrap_or("dependencies")
+        }
+
+        match self {
+            FrozenLockViolation::LockedNotFound { package, version } => {
+                write!(f, "{package}@{version} is not found in any repository")
+            }
+            FrozenLockViolation::RangeNotSatisfied {
+                package,
+                version,
+                required_by: source,
+                range,
+            } => write!(
+                f,
+                "{package}@{version} does not satisfy {range} required by {}",
+                required_by(source)
+            ),
+            FrozenLockViolation::NotLocked {
+                package,
+                required_by: source,
+                range,
+            } => write!(
+                f,
+                "{package}@{range} required by {} is not locked",
+                required_by(source)
+            ),
+        }
+    }
 }
 
 impl fmt::Display for ResolvePackageErr {
@@ -32,6 +97,17 @@ impl fmt::Display for ResolvePackageErr {
                 }
                 Ok(())
             }
+            ResolvePackageErr::FrozenLockViolation { violations } => {
+                write!(f, "The locked packages cannot be used as is:")?;
+                for violation in violations {
+                    write!(f, "\n- {violation}")?;
+                }
+                Ok(())
+            }
+            ResolvePackageErr::ResolutionStepLimitExceeded => write!(
+                f,
+                "Gave up resolving dependencies since there are too many combinations of package versions"
+            ),
         }
     }
 }
@@ -112,6 +188,7 @@ impl UnityProject {
         &self,
         env: &'env impl PackageCollection,
     ) -> Result<PendingProjectChanges<'env>, ResolvePackageErr> {
+        let env = self.scoped(env);
         let mut changes = pending_project_changes::Builder::new();
         let mut missing_dependencies = MissingDependencies::new();
 
@@ -143,9 +220,99 @@ impl UnityProject {
         }
     }
 
-    fn add_just_dependency<'env>(
+    /// Creates the request to install locked packages without changing `locked` section.
+    ///
+    /// Unlike [`resolve_request`](Self::resolve_request), this never re-resolves packages.
+    /// If the locked versions are not found, do not satisfy the ranges requested by
+    /// `dependencies` section or other packages, or some required packages are not locked,
+    /// this returns [`ResolvePackageErr::FrozenLockViolation`].
+    pub async fn resolve_request_frozen<'env>(
         &self,
         env: &'env impl PackageCollection,
+    ) -> Result<PendingProjectChanges<'env>, ResolvePackageErr> {
+        let env = self.scoped(env);
+        let mut changes = pending_project_changes::Builder::new();
+        let mut violations = Vec::new();
+        let mut installed_or_legacy = HashSet::<&str>::new();
+
+        for dep in self.manifest.all_locked() {
+            installed_or_legacy.insert(dep.name());
+            if let Some(pkg) = env
+                .find_package_by_name(dep.name(), VersionSelector::specific_version(dep.version()))
+            {
+                installed_or_legacy.extend(pkg.legacy_packages().iter().map(Box::as_ref));
+                changes.install_already_locked(pkg);
+            } else {
+                violations.push(FrozenLockViolation::LockedNotFound {
+                    package: dep.name().into(),
+                    version: dep.version().clone(),
+                });
+            }
+        }
+
+        // see should_resolve for why we treat unlocked packages as installed
+        for pkg in self
+            .unlocked_packages()
+            .iter()
+            .filter_map(|(_, pkg)| pkg.as_ref())
+        {
+            installed_or_legacy.insert(pkg.name());
+            installed_or_legacy.extend(pkg.legacy_packages().iter().map(Box::as_ref));
+        }
+
+        let mut check = |package: &str, required_by: Option<&str>, range: &VersionRange| {
+            if let Some(locked) = self.manifest.get_locked(package) {
+                if !range.match_pre(locked.version(), PrereleaseAcceptance::Allow) {
+                    violations.push(FrozenLockViolation::RangeNotSatisfied {
+                        package: package.into(),
+                        version: locked.version().clone(),
+                        required_by: required_by.map(Into::into),
+                        range: range.clone(),
+                    });
+                }
+            } else if !installed_or_legacy.contains(package) {
+                violations.push(FrozenLockViolation::NotLocked {
+                    package: package.into(),
+                    required_by: required_by.map(Into::into),
+                    range: range.clone(),
+                });
+            }
+        };
+
+        for (name, range) in self.manifest.dependencies() {
+            check(name, None, &range.as_range());
+        }
+
+        let overrides = self.manifest.overrides();
+
+        for locked in self.manifest.all_locked() {
+            for (dependency, range) in locked.dependencies() {
+                let range = overrides.get(dependency).unwrap_or(range);
+                check(dependency, Some(locked.name()), range);
+            }
+        }
+
+        for pkg in self
+            .unlocked_packages()
+            .iter()
+            .filter_map(|(_, pkg)| pkg.as_ref())
+        {
+            for (dependency, range) in pkg.vpm_dependencies() {
+                let range = overrides.get(dependency).unwrap_or(range);
+                check(dependency, Some(pkg.name()), range);
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(changes.build_resolve(self).await)
+        } else {
+            Err(ResolvePackageErr::FrozenLockViolation { violations })
+        }
+    }
+
+    fn add_just_dependency<'env>(
+        &self,
+        env: ScopedCollection<'env, '_, impl PackageCollection>,
         changes: &mut pending_project_changes::Builder<'env>,
         missing_dependencies: &mut MissingDependencies,
     ) -> Result<(), ResolvePackageErr> {
@@ -181,12 +348,16 @@ impl UnityProject {
             self.manifest.all_locked(),
             self.unlocked_packages.iter(),
             |pkg| self.manifest.get_locked(pkg),
+            self.manifest.overrides(),
             Some(self.unity_version()),
             env,
             to_install,
             allow_prerelease,
             missing_dependencies,
         );
+        if result.step_limit_exceeded {
+            return Err(ResolvePackageErr::ResolutionStepLimitExceeded);
+        }
 
         for x in result.new_packages {
             changes.install_to_locked_replacing(x);
@@ -202,12 +373,16 @@ impl UnityProject {
             changes.conflict_multiple(package, conflicts_with);
         }
 
+        for (package, explanation) in result.explanations {
+            changes.conflict_explanation(package, explanation);
+        }
+
         Ok(())
     }
 
     fn resolve_unlocked<'env>(
         &self,
-        env: &'env impl PackageCollection,
+        env: ScopedCollection<'env, '_, impl PackageCollection>,
         changes: &mut pending_project_changes::Builder<'env>,
         missing_dependencies: &mut MissingDependencies,
     ) -> Result<(), ResolvePackageErr> {
@@ -235,9 +410,10 @@ impl UnityProject {
             .iter()
             .filter_map(|(_, pkg)| pkg.as_ref())
             .flat_map(|pkg| {
-                pkg.vpm_dependencies()
-                    .into_iter()
-                    .map(|(k, v)| (k, v, pkg.version().is_pre()))
+                pkg.vpm_dependencies().into_iter().map(|(k, v)| {
+                    let v = self.manifest.overrides().get(k).unwrap_or(v);
+                    (k, v, pkg.version().is_pre())
+                })
             });
 
         let unlocked_dependencies_versions = dependencies_of_unlocked_packages
@@ -307,12 +483,16 @@ impl UnityProject {
             virtual_locked_dependencies.values().cloned(),
             self.unlocked_packages.iter(),
             |pkg| virtual_locked_dependencies.get(pkg).cloned(),
+            self.manifest.overrides(),
             Some(self.unity_version()),
             env,
             unlocked_dependencies,
             allow_prerelease,
             missing_dependencies,
         );
+        if result.step_limit_exceeded {
+            return Err(ResolvePackageErr::ResolutionStepLimitExceeded);
+        }
 
         for x in result.new_packages {
             changes.install_to_locked_replacing(x);
@@ -322,6 +502,10 @@ impl UnityProject {
             changes.conflict_multiple(package, conflicts_with);
         }
 
+        for (package, explanation) in result.explanations {
+            changes.conflict_explanation(package, explanation);
+        }
+
         Ok(())
     }
 }
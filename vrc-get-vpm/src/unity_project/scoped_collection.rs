@@ -0,0 +1,81 @@
+use crate::{PackageCollection, PackageInfo, ScopedRegistry, UnityProject, VersionSelector};
+
+/// The [`PackageCollection`] with the scoped registries of the project applied.
+///
+/// Remote packages matching the rules of the project are only taken from the repositories of the rules,
+/// in addition to the rules of the collection itself.
+pub(crate) struct ScopedCollection<'env, 'a, C> {
+    env: &'env C,
+    rules: &'a [ScopedRegistry],
+}
+
+impl<C> Clone for ScopedCollection<'_, '_, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for ScopedCollection<'_, '_, C> {}
+
+impl UnityProject {
+    /// Returns the collection with the scoped registries of this project applied.
+    pub(crate) fn scoped<'env, C: PackageCollection>(
+        &self,
+        env: &'env C,
+    ) -> ScopedCollection<'env, '_, C> {
+        ScopedCollection {
+            env,
+            rules: self.scoped_registries(),
+        }
+    }
+
+    /// Finds the latest version of the package matching the selector
+    /// with the scoped registries of this project applied.
+    pub fn find_package_by_name<'env>(
+        &self,
+        env: &'env impl PackageCollection,
+        package: &str,
+        package_selector: VersionSelector,
+    ) -> Option<PackageInfo<'env>> {
+        self.scoped(env)
+            .find_package_by_name(package, package_selector)
+    }
+}
+
+impl<'env, C: PackageCollection> ScopedCollection<'env, '_, C> {
+    fn is_in_scope(&self, package: PackageInfo) -> bool {
+        package.repo().is_none_or(|repo| {
+            ScopedRegistry::allows(self.rules, package.name(), repo.id(), repo.url())
+        })
+    }
+
+    fn has_rules_for(&self, package: &str) -> bool {
+        self.rules.iter().any(|x| x.matches(package))
+    }
+
+    pub fn get_curated_packages(
+        self,
+        version_selector: VersionSelector,
+    ) -> impl Iterator<Item = PackageInfo<'env>> {
+        (self.env.get_curated_packages(version_selector)).filter(move |x| self.is_in_scope(*x))
+    }
+
+    pub fn find_packages(self, package: &str) -> impl Iterator<Item = PackageInfo<'env>> {
+        (self.env.find_packages(package)).filter(move |x| self.is_in_scope(*x))
+    }
+
+    pub fn find_package_by_name(
+        self,
+        package: &str,
+        package_selector: VersionSelector,
+    ) -> Option<PackageInfo<'env>> {
+        if !self.has_rules_for(package) {
+            return self.env.find_package_by_name(package, package_selector);
+        }
+
+        // the latest version in the collection may be out of the scope, so we look for all versions
+        self.find_packages(package)
+            .filter(|x| package_selector.satisfies(x.package_json()))
+            .max_by_key(|x| x.version())
+    }
+}
@@ -0,0 +1,258 @@
+//! The journal of [`UnityProject::apply_pending_changes`](crate::UnityProject::apply_pending_changes)
+//! to recover the project if the process is killed while applying changes.
+//!
+//! The journal is written to `Library/vrc-get` before each step of applying changes,
+//! and removed when the changes are applied or rolled back.
+//! If the journal is found when loading the project and no process is applying changes,
+//! the changes are rolled back if the manifests are not saved yet, or completed otherwise.
+
+use crate::io;
+use crate::io::{DefaultProjectIo, IoTrait};
+use crate::unity_project::pending_project_changes::remove_assets;
+use crate::utils::{read_to_end, try_load_json};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The folder of the journal, the lock, and the manifest backups.
+///
+/// This is not in `Temp` since Unity removes `Temp` on launching and quitting the editor,
+/// which is likely to happen after the process is killed.
+const JOURNAL_DIR: &str = "Library/vrc-get";
+
+/// The path to the journal. Only one change can be applied to a project at once.
+fn journal_path() -> PathBuf {
+    PathBuf::from(format!("{JOURNAL_DIR}/transaction.json"))
+}
+
+fn lock_path() -> PathBuf {
+    PathBuf::from(format!("{JOURNAL_DIR}/transaction.lock"))
+}
+
+fn manifest_backup_dir() -> PathBuf {
+    PathBuf::from(format!("{JOURNAL_DIR}/transaction-manifests"))
+}
+
+/// The manifests saved by applying changes, backed up before changing packages.
+const MANIFEST_FILES: &[&str] = &["Packages/vpm-manifest.json", "Packages/manifest.json"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) enum TransactionPhase {
+    /// Extracting packages to the temp folder. The project is not changed yet.
+    Extracting,
+    /// Moving packages in the `Packages` folder and saving manifests.
+    /// The manifests before changes are backed up in the journal folder.
+    Applying,
+    /// The manifests are saved. Only removing legacy assets and cleanup remain.
+    Committed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct TransactionJournal {
+    temp_dir: PathBuf,
+    phase: TransactionPhase,
+    installs: Vec<Box<str>>,
+    uninstalls: Vec<Box<str>>,
+    remove_legacy_files: Vec<PathBuf>,
+    remove_legacy_folders: Vec<PathBuf>,
+    #[serde(skip)]
+    lock: Option<TransactionLock>,
+}
+
+/// The exclusive lock of the project held by the process applying changes.
+///
+/// This is the file lock of the OS, so the lock is released when the process is killed.
+#[derive(Debug)]
+struct TransactionLock {
+    _file: std::fs::File,
+}
+
+impl TransactionLock {
+    /// returns: `None` if another process holds the lock
+    async fn try_acquire(io: &DefaultProjectIo) -> io::Result<Option<Self>> {
+        io.create_dir_all(JOURNAL_DIR.as_ref()).await?;
+        let file = io.create(&lock_path()).await?.into_inner().into_std().await;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+            Err(std::fs::TryLockError::Error(e)) => Err(e),
+        }
+    }
+}
+
+/// How an interrupted change was recovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Recovered {
+    RolledBack,
+    Completed,
+}
+
+impl TransactionJournal {
+    /// Locks the project and writes the journal of the new change in [`TransactionPhase::Extracting`] phase.
+    ///
+    /// This fails if another process is applying changes, or the journal of an interrupted change
+    /// is left after the project is loaded.
+    pub(super) async fn begin(
+        io: &DefaultProjectIo,
+        temp_dir: &Path,
+        installs: impl IntoIterator<Item = &str>,
+        uninstalls: impl IntoIterator<Item = &str>,
+        remove_legacy_files: impl IntoIterator<Item = &Path>,
+        remove_legacy_folders: impl IntoIterator<Item = &Path>,
+    ) -> io::Result<Self> {
+        let Some(lock) = TransactionLock::try_acquire(io).await? else {
+            return Err(io::Error::new(
+                io::ErrorKind::ResourceBusy,
+                "another process is applying changes to the project",
+            ));
+        };
+        if io.is_file(&journal_path()).await {
+            return Err(io::Error::other(
+                "the project has interrupted changes. please load the project again to recover",
+            ));
+        }
+
+        let journal = Self {
+            temp_dir: temp_dir.to_owned(),
+            phase: TransactionPhase::Extracting,
+            installs: installs.into_iter().map(Into::into).collect(),
+            uninstalls: uninstalls.into_iter().map(Into::into).collect(),
+            remove_legacy_files: remove_legacy_files.into_iter().map(Into::into).collect(),
+            remove_legacy_folders: remove_legacy_folders.into_iter().map(Into::into).collect(),
+            lock: Some(lock),
+        };
+        journal.write(io).await?;
+        Ok(journal)
+    }
+
+    /// Moves to the phase, backing up the manifests before [`TransactionPhase::Applying`].
+    pub(super) async fn set_phase(
+        &mut self,
+        io: &DefaultProjectIo,
+        phase: TransactionPhase,
+    ) -> io::Result<()> {
+        if phase == TransactionPhase::Applying {
+            let backup_dir = manifest_backup_dir();
+            io.create_dir_all(&backup_dir).await?;
+            for manifest in MANIFEST_FILES {
+                let manifest = Path::new(manifest);
+                match io.open(manifest).await {
+                    Ok(file) => {
+                        let content = read_to_end(file).await?;
+                        let backup = backup_dir.join(manifest.file_name().unwrap());
+                        io.write_sync(&backup, &content).await?;
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        self.phase = phase;
+        self.write(io).await
+    }
+
+    /// Removes the journal after the changes are applied or rolled back, and releases the lock.
+    pub(super) async fn finish(self, io: &DefaultProjectIo) -> io::Result<()> {
+        match io.remove_file(&journal_path()).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        io.remove_dir_all(&manifest_backup_dir()).await.ok();
+        Ok(())
+    }
+
+    async fn write(&self, io: &DefaultProjectIo) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        io.write_atomic(&journal_path(), &json).await
+    }
+
+    /// Recovers the project from the interrupted change if the journal exists.
+    ///
+    /// The journal of the change being applied by another process is not recovered.
+    pub(super) async fn recover(io: &DefaultProjectIo) -> io::Result<Option<Recovered>> {
+        // check the journal first not to write to the project when nothing is interrupted
+        if !io.is_file(&journal_path()).await {
+            return Ok(None);
+        }
+        let Some(lock) = TransactionLock::try_acquire(io).await? else {
+            return Ok(None);
+        };
+        // the journal may be removed by the process just finished applying changes
+        let Some(mut journal) = try_load_json::<Self>(io, &journal_path()).await? else {
+            return Ok(None);
+        };
+        journal.lock = Some(lock);
+
+        let recovered = match journal.phase {
+            TransactionPhase::Extracting => Recovered::RolledBack,
+            TransactionPhase::Applying => {
+                journal.roll_back(io).await?;
+                Recovered::RolledBack
+            }
+            TransactionPhase::Committed => {
+                remove_assets(
+                    io,
+                    journal.remove_legacy_files.iter().map(PathBuf::as_path),
+                    journal.remove_legacy_folders.iter().map(PathBuf::as_path),
+                )
+                .await;
+                Recovered::Completed
+            }
+        };
+
+        // we ignore error since some file might be locked by unity.
+        io.remove_dir_all(&journal.temp_dir).await.ok();
+        journal.finish(io).await?;
+
+        Ok(Some(recovered))
+    }
+
+    /// Moves packages back and restores the manifests.
+    ///
+    /// Whether each package is moved is decided by the folders since the process may be killed at any point.
+    async fn roll_back(&self, io: &DefaultProjectIo) -> io::Result<()> {
+        let packages_dir = Path::new("Packages");
+        let install_temp_dir = self.temp_dir.join("install");
+        let remove_temp_dir = self.temp_dir.join("remove");
+
+        // extracted packages are moved out of the install folder only if installed
+        for installed in &self.installs {
+            let package_dir = packages_dir.join(installed.as_ref());
+            let extracted_dir = install_temp_dir.join(installed.as_ref());
+            if !io.is_dir(&extracted_dir).await && io.is_dir(&package_dir).await {
+                io.rename(&package_dir, &extracted_dir).await?;
+            }
+        }
+
+        for removed in &self.uninstalls {
+            let removed_dir = remove_temp_dir.join(removed.as_ref());
+            if io.is_dir(&removed_dir).await {
+                io.rename(&removed_dir, &packages_dir.join(removed.as_ref()))
+                    .await?;
+            }
+        }
+
+        let backup_dir = manifest_backup_dir();
+        for manifest in MANIFEST_FILES {
+            let manifest = Path::new(manifest);
+            let backup = backup_dir.join(manifest.file_name().unwrap());
+            match io.open(&backup).await {
+                Ok(file) => {
+                    let content = read_to_end(file).await?;
+                    io.write_atomic(manifest, &content).await?;
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    // the manifest did not exist before the change
+                    match io.remove_file(manifest).await {
+                        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                        _ => {}
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
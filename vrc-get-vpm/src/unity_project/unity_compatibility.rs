@@ -0,0 +1,232 @@
+use crate::unity_project::package_resolution;
+use crate::unity_project::package_resolution::MissingDependencies;
+use crate::version::{DependencyRange, UnityVersion, Version, VersionRange};
+use crate::{PackageCollection, PackageInfo, UnityProject, VersionSelector, unity_compatible};
+use std::collections::HashMap;
+
+/// The result of [`UnityProject::unity_compatibility`].
+#[derive(Debug)]
+pub struct UnityCompatibility<'env> {
+    unity_version: UnityVersion,
+    packages: Vec<PackageCompatibility<'env>>,
+    new_packages: Vec<PackageInfo<'env>>,
+    conflicts: Vec<(Box<str>, Vec<Box<str>>)>,
+    missing_dependencies: Vec<(Box<str>, VersionRange)>,
+    step_limit_exceeded: bool,
+}
+
+impl<'env> UnityCompatibility<'env> {
+    /// The Unity version checked.
+    pub fn unity_version(&self) -> UnityVersion {
+        self.unity_version
+    }
+
+    /// The compatibility of each locked package.
+    pub fn packages(&self) -> &[PackageCompatibility<'env>] {
+        &self.packages
+    }
+
+    /// The packages not locked now but newly required by the changed packages.
+    pub fn new_packages(&self) -> &[PackageInfo<'env>] {
+        &self.new_packages
+    }
+
+    /// The packages whose version conflicts with the ranges required by the packages.
+    pub fn conflicts(&self) -> &[(Box<str>, Vec<Box<str>>)] {
+        &self.conflicts
+    }
+
+    /// The dependencies not found in the collection.
+    pub fn missing_dependencies(&self) -> &[(Box<str>, VersionRange)] {
+        &self.missing_dependencies
+    }
+
+    /// Returns `true` if the resolution gave up since there are too many combinations of versions to try.
+    ///
+    /// The project is not considered compatible in that case.
+    pub fn step_limit_exceeded(&self) -> bool {
+        self.step_limit_exceeded
+    }
+
+    /// Returns `true` if the project can be used with the Unity version, with or without changing packages.
+    ///
+    /// Packages not found in the collection are assumed to be compatible.
+    pub fn is_compatible(&self) -> bool {
+        !self.step_limit_exceeded
+            && self.conflicts.is_empty()
+            && self.missing_dependencies.is_empty()
+            && !(self.packages.iter())
+                .any(|x| matches!(x.status, CompatibilityStatus::NoCompatibleVersion))
+    }
+
+    /// Returns `true` if any package needs to be changed or installed for the Unity version.
+    pub fn needs_changes(&self) -> bool {
+        !self.new_packages.is_empty()
+            || (self.packages.iter()).any(|x| matches!(x.status, CompatibilityStatus::Change(_)))
+    }
+}
+
+/// The compatibility of one locked package in [`UnityCompatibility`].
+#[derive(Debug)]
+pub struct PackageCompatibility<'env> {
+    name: Box<str>,
+    version: Version,
+    status: CompatibilityStatus<'env>,
+}
+
+impl<'env> PackageCompatibility<'env> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The locked version of the package.
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    pub fn status(&self) -> &CompatibilityStatus<'env> {
+        &self.status
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum CompatibilityStatus<'env> {
+    /// The locked version can be used with the Unity version.
+    Compatible,
+    /// The package needs to be changed to the version for the Unity version.
+    Change(PackageInfo<'env>),
+    /// No version of the package for the Unity version is found.
+    NoCompatibleVersion,
+    /// The locked version is not found in the collection, so the compatibility is unknown.
+    Unknown,
+}
+
+impl UnityProject {
+    /// Checks if the locked packages can be used with the specified Unity version.
+    ///
+    /// The packages not compatible with the Unity version are replaced with the latest version for the Unity version,
+    /// and the dependencies are resolved for the Unity version as installing them.
+    /// This does not change the project, so this can be used before upgrading Unity.
+    pub fn unity_compatibility<'env>(
+        &self,
+        env: &'env impl PackageCollection,
+        unity_version: UnityVersion,
+    ) -> UnityCompatibility<'env> {
+        enum Current<'env> {
+            Compatible,
+            Incompatible(Option<PackageInfo<'env>>),
+            NotFound,
+        }
+
+        let env = self.scoped(env);
+        let current = (self.manifest.all_locked())
+            .map(|locked| {
+                let found = env.find_package_by_name(
+                    locked.name(),
+                    VersionSelector::specific_version(locked.version()),
+                );
+                let current = match found {
+                    None => Current::NotFound,
+                    Some(found) if unity_compatible(found.package_json(), unity_version) => {
+                        Current::Compatible
+                    }
+                    Some(_) => Current::Incompatible(env.find_package_by_name(
+                        locked.name(),
+                        VersionSelector::latest_for(Some(unity_version), locked.version().is_pre()),
+                    )),
+                };
+                (locked, current)
+            })
+            .collect::<Vec<_>>();
+
+        let requested = (current.iter())
+            .filter_map(|(_, current)| match current {
+                Current::Incompatible(replacement) => *replacement,
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut changed = HashMap::new();
+        let mut new_packages = vec![];
+        let mut conflicts = vec![];
+        let mut step_limit_exceeded = false;
+        let mut missing_dependencies = MissingDependencies::new();
+
+        if !requested.is_empty() {
+            // requested versions may be older than the versions in dependencies when downgrading Unity
+            let dependencies = (self.manifest.dependencies())
+                .map(
+                    |(name, range)| match requested.iter().find(|x| x.name() == name) {
+                        Some(package) => {
+                            (name, DependencyRange::version(package.version().clone()))
+                        }
+                        None => (name, range.clone()),
+                    },
+                )
+                .collect::<Vec<_>>();
+
+            let result = package_resolution::collect_adding_packages(
+                dependencies.iter().map(|(name, range)| (*name, range)),
+                self.manifest.all_locked(),
+                self.unlocked_packages.iter(),
+                |pkg| self.manifest.get_locked(pkg),
+                self.manifest.overrides(),
+                Some(unity_version),
+                env,
+                requested,
+                false,
+                &mut missing_dependencies,
+            );
+
+            for package in result.new_packages {
+                if self.manifest.get_locked(package.name()).is_some() {
+                    changed.insert(package.name(), package);
+                } else {
+                    new_packages.push(package);
+                }
+            }
+
+            conflicts.extend(result.conflicts);
+            conflicts.sort_by(|(a, _), (b, _)| a.cmp(b));
+            step_limit_exceeded = result.step_limit_exceeded;
+        }
+
+        let packages = (current.into_iter())
+            .map(|(locked, current)| {
+                let status = match changed.remove(locked.name()) {
+                    Some(package) if package.version() == locked.version() => {
+                        CompatibilityStatus::Compatible
+                    }
+                    // the resolution may use the version not for the Unity version if nothing else is found
+                    Some(package) if !unity_compatible(package.package_json(), unity_version) => {
+                        CompatibilityStatus::NoCompatibleVersion
+                    }
+                    Some(package) => CompatibilityStatus::Change(package),
+                    None => match current {
+                        Current::Compatible => CompatibilityStatus::Compatible,
+                        Current::Incompatible(_) => CompatibilityStatus::NoCompatibleVersion,
+                        Current::NotFound => CompatibilityStatus::Unknown,
+                    },
+                };
+                PackageCompatibility {
+                    name: locked.name().into(),
+                    version: locked.version().clone(),
+                    status,
+                }
+            })
+            .collect();
+
+        let mut missing_dependencies = missing_dependencies.into_vec();
+        missing_dependencies.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        UnityCompatibility {
+            unity_version,
+            packages,
+            new_packages,
+            conflicts,
+            missing_dependencies,
+            step_limit_exceeded,
+        }
+    }
+}
@@ -6,26 +6,116 @@ use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
-use std::fmt::Formatter;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 const MANIFEST_PATH: &str = "Packages/manifest.json";
 
 #[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct Parsed {
     #[serde(default)]
     dependencies: HashMap<Box<str>, UpmDependency>,
+    #[serde(default)]
+    scoped_registries: Vec<UpmScopedRegistry>,
+}
+
+/// The scoped registry in `Packages/manifest.json`.
+///
+/// Unity fetches the packages matching the scopes from the registry instead of the Unity registry.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UpmScopedRegistry {
+    name: Box<str>,
+    url: Box<str>,
+    #[serde(default)]
+    scopes: Vec<Box<str>>,
+}
+
+impl UpmScopedRegistry {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn scopes(&self) -> &[Box<str>] {
+        &self.scopes
+    }
 }
 
+/// The error adding the dependency to `Packages/manifest.json`
+#[non_exhaustive]
 #[derive(Debug)]
-#[allow(dead_code)]
-pub(super) enum UpmDependency {
+pub enum AddUpmDependencyErr {
+    /// `scopedRegistries` or `scopes` of the scoped registry is not an array
+    InvalidScopedRegistries,
+}
+
+impl Display for AddUpmDependencyErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddUpmDependencyErr::InvalidScopedRegistries => {
+                write!(f, "scopedRegistries in manifest.json is not valid")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AddUpmDependencyErr {}
+
+/// Returns `true` if the package is in the scope.
+///
+/// Unity matches scopes by the namespace of the package name.
+pub(super) fn scope_matches(scope: &str, package: &str) -> bool {
+    package
+        .strip_prefix(scope)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Returns `true` if the urls are for the same registry, ignoring the trailing `/`.
+fn is_same_registry(url: &str, other: &str) -> bool {
+    url.trim_end_matches('/') == other.trim_end_matches('/')
+}
+
+/// The dependency in `Packages/manifest.json`
+#[derive(Debug, Clone)]
+pub enum UpmDependency {
     // minimum version name. build meta is not supported by upm
     Version(Version),
     // Other Notation including local file and git url
     OtherNotation(Box<str>),
 }
 
+impl Display for UpmDependency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpmDependency::Version(version) => Display::fmt(version, f),
+            UpmDependency::OtherNotation(notation) => f.write_str(notation),
+        }
+    }
+}
+
+impl UpmDependency {
+    fn parse(notation: &str) -> Self {
+        if let Ok(semver) = Version::from_str(notation) {
+            UpmDependency::Version(semver)
+        } else {
+            UpmDependency::OtherNotation(notation.into())
+        }
+    }
+}
+
+/// Parses the version, or other notation like `file:../package` or git url.
+impl FromStr for UpmDependency {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(s))
+    }
+}
+
 impl<'de> Deserialize<'de> for UpmDependency {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -44,11 +134,7 @@ impl<'de> Deserialize<'de> for UpmDependency {
             where
                 E: Error,
             {
-                if let Ok(semver) = Version::from_str(v) {
-                    Ok(UpmDependency::Version(semver))
-                } else {
-                    Ok(UpmDependency::OtherNotation(v.into()))
-                }
+                Ok(UpmDependency::parse(v))
             }
 
             fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
@@ -112,7 +198,6 @@ impl UpmManifest {
         })
     }
 
-    #[allow(dead_code)]
     pub(super) fn dependencies(&self) -> impl Iterator<Item = (&str, &UpmDependency)> {
         self.controller
             .as_json
@@ -121,35 +206,132 @@ impl UpmManifest {
             .map(|(name, dep)| (name.as_ref(), dep))
     }
 
-    #[allow(dead_code)]
     pub(super) fn get_dependency(&self, package: &str) -> Option<&UpmDependency> {
         self.controller.as_json.dependencies.get(package)
     }
 
-    #[allow(dead_code)]
-    pub(super) fn add_dependency(&mut self, name: &str, version: Version) {
+    pub(super) fn add_dependency(&mut self, name: &str, dependency: UpmDependency) {
         self.controller
             .as_mut()
             .raw
             .get_or_put_mut("dependencies", Map::new)
             .as_object_mut()
             .unwrap()
-            .insert(name.to_string(), Value::String(version.to_string()));
+            .insert(name.to_string(), Value::String(dependency.to_string()));
         self.controller
             .as_mut()
             .as_json
             .dependencies
-            .insert(name.into(), UpmDependency::Version(version));
+            .insert(name.into(), dependency);
     }
 
-    pub(super) fn remove_dependency(&mut self, name: &str) {
+    pub(super) fn remove_dependency(&mut self, name: &str) -> bool {
         self.controller
             .as_mut()
             .raw
             .get_mut("dependencies")
             .and_then(|x| x.as_object_mut())
             .map(|x| x.remove(name));
-        self.controller.as_mut().as_json.dependencies.remove(name);
+        (self.controller.as_mut().as_json.dependencies)
+            .remove(name)
+            .is_some()
+    }
+
+    pub(super) fn scoped_registries(&self) -> &[UpmScopedRegistry] {
+        &self.controller.as_json.scoped_registries
+    }
+
+    /// Adds the scope to the scoped registry with the url, adding the registry if not exists.
+    pub(super) fn add_scope(
+        &mut self,
+        registry_name: &str,
+        url: &str,
+        scope: &str,
+    ) -> Result<(), AddUpmDependencyErr> {
+        let raw = &mut self.controller.as_mut().raw;
+        let registries = raw
+            .get_or_put_mut("scopedRegistries", Vec::<Value>::new)
+            .as_array_mut()
+            .ok_or(AddUpmDependencyErr::InvalidScopedRegistries)?;
+
+        let registry = registries
+            .iter_mut()
+            .filter_map(|x| x.as_object_mut())
+            .find(|x| {
+                (x.get("url").and_then(Value::as_str)).is_some_and(|x| is_same_registry(x, url))
+            });
+
+        match registry {
+            Some(registry) => {
+                let scopes = registry
+                    .get_or_put_mut("scopes", Vec::<Value>::new)
+                    .as_array_mut()
+                    .ok_or(AddUpmDependencyErr::InvalidScopedRegistries)?;
+                if !scopes.iter().any(|x| x.as_str() == Some(scope)) {
+                    scopes.push(Value::String(scope.to_string()));
+                }
+            }
+            None => {
+                let registry = UpmScopedRegistry {
+                    name: registry_name.into(),
+                    url: url.into(),
+                    scopes: vec![scope.into()],
+                };
+                registries.push(serde_json::to_value(registry).unwrap());
+            }
+        }
+
+        self.reload_scoped_registries();
+        Ok(())
+    }
+
+    /// Removes the scope from all scoped registries, and removes the registries without scopes.
+    ///
+    /// The scope is kept if some dependency still matches the scope.
+    pub(super) fn remove_scope(&mut self, scope: &str) {
+        if (self.controller.as_json.dependencies.keys()).any(|x| scope_matches(scope, x)) {
+            return;
+        }
+
+        let raw = &mut self.controller.as_mut().raw;
+        let Some(registries) = raw
+            .get_mut("scopedRegistries")
+            .and_then(Value::as_array_mut)
+        else {
+            return;
+        };
+
+        let mut removed = false;
+        for registry in registries.iter_mut() {
+            if let Some(scopes) = registry.get_mut("scopes").and_then(Value::as_array_mut) {
+                let len = scopes.len();
+                scopes.retain(|x| x.as_str() != Some(scope));
+                removed |= scopes.len() != len;
+            }
+        }
+
+        if !removed {
+            return;
+        }
+
+        registries.retain(|registry| {
+            registry
+                .get("scopes")
+                .and_then(Value::as_array)
+                .is_some_and(|x| !x.is_empty())
+        });
+        if registries.is_empty() {
+            raw.remove("scopedRegistries");
+        }
+
+        self.reload_scoped_registries();
+    }
+
+    fn reload_scoped_registries(&mut self) {
+        let manifest = self.controller.as_mut();
+        manifest.as_json.scoped_registries = (manifest.raw.get("scopedRegistries"))
+            .and_then(|x| Vec::deserialize(x).ok())
+            .unwrap_or_default();
     }
 
     pub(super) async fn save(&mut self, io: &DefaultProjectIo) -> io::Result<()> {
@@ -0,0 +1,204 @@
+use crate::io::{DefaultProjectIo, DirEntry, IoTrait};
+use crate::unity_project::pending_project_changes::PKG_TEMP_DIR;
+use crate::utils::{read_to_end, walk_dir_relative};
+use crate::version::Version;
+use crate::{AbortCheck, PackageCollection, PackageInstaller, UnityProject, VersionSelector, io};
+use futures::prelude::*;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// The result of [`UnityProject::verify_packages`].
+#[derive(Debug)]
+pub struct VerifyPackagesResult {
+    packages: Vec<PackageVerification>,
+    not_found: Vec<(Box<str>, Version)>,
+}
+
+impl VerifyPackagesResult {
+    /// The verified locked packages, including packages without any difference.
+    pub fn packages(&self) -> &[PackageVerification] {
+        &self.packages
+    }
+
+    /// The locked packages not found in the collection, which cannot be verified.
+    pub fn not_found(&self) -> &[(Box<str>, Version)] {
+        &self.not_found
+    }
+
+    /// The names of the packages with any difference from the package contents.
+    pub fn differing_packages(&self) -> impl Iterator<Item = &str> {
+        (self.packages.iter())
+            .filter(|x| !x.is_intact())
+            .map(PackageVerification::name)
+    }
+}
+
+/// The difference of one installed package from the contents of the package.
+///
+/// Paths are relative to the package folder and separated by `/`.
+#[derive(Debug)]
+pub struct PackageVerification {
+    name: Box<str>,
+    version: Version,
+    modified: Vec<Box<str>>,
+    missing: Vec<Box<str>>,
+    extra: Vec<Box<str>>,
+}
+
+impl PackageVerification {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// The files with contents different from the package.
+    pub fn modified(&self) -> &[Box<str>] {
+        &self.modified
+    }
+
+    /// The files in the package but not in the installed folder.
+    pub fn missing(&self) -> &[Box<str>] {
+        &self.missing
+    }
+
+    /// The files in the installed folder but not in the package.
+    pub fn extra(&self) -> &[Box<str>] {
+        &self.extra
+    }
+
+    /// Returns `true` if the installed folder is the same as the package.
+    pub fn is_intact(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+impl UnityProject {
+    /// Compares the folder of each locked package with the contents of the package.
+    ///
+    /// The package is extracted with the installer into a temporary folder in the same way as installing,
+    /// so for remote packages the cached zip file is used, or downloaded if not cached.
+    /// `.meta` files not in the package are not reported as extra files
+    /// since Unity generates them for files without `.meta`.
+    pub async fn verify_packages(
+        &self,
+        env: &impl PackageCollection,
+        installer: &impl PackageInstaller,
+    ) -> io::Result<VerifyPackagesResult> {
+        let temp_dir = PathBuf::from(format!("{PKG_TEMP_DIR}/verify-{}", uuid::Uuid::new_v4()));
+        self.io.create_dir_all(&temp_dir).await?;
+
+        let result = self.verify_packages_in(env, installer, &temp_dir).await;
+
+        self.io.remove_dir_all(&temp_dir).await.ok();
+
+        result
+    }
+
+    async fn verify_packages_in(
+        &self,
+        env: &impl PackageCollection,
+        installer: &impl PackageInstaller,
+        temp_dir: &Path,
+    ) -> io::Result<VerifyPackagesResult> {
+        let abort = AbortCheck::new();
+        let mut packages = Vec::new();
+        let mut not_found = Vec::new();
+
+        let env = self.scoped(env);
+        for locked in self.locked_packages() {
+            let Some(package) = env.find_package_by_name(
+                locked.name(),
+                VersionSelector::specific_version(locked.version()),
+            ) else {
+                not_found.push((locked.name().into(), locked.version().clone()));
+                continue;
+            };
+
+            let expected_dir = temp_dir.join(locked.name());
+            installer
+                .install_package(&self.io, package, &expected_dir, &abort)
+                .await?;
+
+            let installed_dir = Path::new("Packages").join(locked.name());
+            let (modified, missing, extra) =
+                compare_package(&self.io, &expected_dir, &installed_dir).await?;
+            packages.push(PackageVerification {
+                name: locked.name().into(),
+                version: locked.version().clone(),
+                modified,
+                missing,
+                extra,
+            });
+
+            self.io.remove_dir_all(&expected_dir).await.ok();
+        }
+
+        Ok(VerifyPackagesResult {
+            packages,
+            not_found,
+        })
+    }
+}
+
+/// returns: (modified files, missing files, extra files)
+async fn compare_package(
+    io: &DefaultProjectIo,
+    expected_dir: &Path,
+    installed_dir: &Path,
+) -> io::Result<(Vec<Box<str>>, Vec<Box<str>>, Vec<Box<str>>)> {
+    let expected = list_files(io, expected_dir).await;
+    let installed = list_files(io, installed_dir).await;
+
+    let mut modified = Vec::new();
+    let mut missing = Vec::new();
+
+    for path in &expected {
+        if !installed.contains(path) {
+            missing.push(path.as_str().into());
+            continue;
+        }
+        let expected_content = read_to_end(io.open(&expected_dir.join(path)).await?).await?;
+        let installed_content = read_to_end(io.open(&installed_dir.join(path)).await?).await?;
+        if expected_content != installed_content {
+            modified.push(path.as_str().into());
+        }
+    }
+
+    let exists = |asset: &str| {
+        let folder_prefix = format!("{asset}/");
+        (expected.iter().chain(&installed))
+            .any(|path| path == asset || path.starts_with(&folder_prefix))
+    };
+
+    let extra = (installed.difference(&expected))
+        // unity generates .meta files for files and folders without .meta
+        .filter(|path| !path.strip_suffix(".meta").is_some_and(exists))
+        .map(|path| path.as_str().into())
+        .collect();
+
+    Ok((modified, missing, extra))
+}
+
+/// Lists the files in the folder as paths relative to the folder separated by `/`.
+async fn list_files(io: &DefaultProjectIo, dir: &Path) -> BTreeSet<String> {
+    if !io.is_dir(dir).await {
+        return BTreeSet::new();
+    }
+    walk_dir_relative(io, [dir.to_owned()])
+        .filter_map(|(path, entry)| async move {
+            if !entry.file_type().await.ok()?.is_file() {
+                return None;
+            }
+            let relative = path.strip_prefix(dir).ok()?;
+            let components = relative
+                .iter()
+                .map(|x| x.to_string_lossy())
+                .collect::<Vec<_>>();
+            Some(components.join("/"))
+        })
+        .collect()
+        .await
+}
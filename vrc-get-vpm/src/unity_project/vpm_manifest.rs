@@ -1,5 +1,7 @@
+use crate::ScopedRegistry;
 use crate::io;
 use crate::io::DefaultProjectIo;
+use crate::package_source::PackageSource;
 use crate::unity_project::LockedDependencyInfo;
 use crate::utils::{SaveController, load_json_or_default, save_json};
 use crate::version::{DependencyRange, Version, VersionRange};
@@ -15,11 +17,19 @@ struct AsJson {
     dependencies: IndexMap<Box<str>, VpmDependency>,
     #[serde(default)]
     locked: IndexMap<Box<str>, VpmLockedDependency>,
+    // vrc-get specific
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scoped_registries: Vec<ScopedRegistry>,
+    // vrc-get specific
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    overrides: IndexMap<Box<str>, VersionRange>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct VpmDependency {
     pub version: DependencyRange,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PackageSource>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -27,6 +37,11 @@ struct VpmLockedDependency {
     pub version: Version,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dependencies: Option<IndexMap<Box<str>, VersionRange>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PackageSource>,
+    /// The commit hash or the SHA256 of the archive fetched from `source`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<Box<str>>,
 }
 
 #[derive(Debug)]
@@ -57,6 +72,28 @@ impl VpmManifest {
             .map(|x| &x.version)
     }
 
+    /// Returns the dependencies with `source`, and the locked hash if locked with the same source.
+    pub(super) fn sources(&self) -> impl Iterator<Item = (&str, &PackageSource, Option<&str>)> {
+        self.controller
+            .dependencies
+            .iter()
+            .filter_map(|(name, dep)| {
+                let source = dep.source.as_ref()?;
+                let hash = (self.controller.locked.get(name))
+                    .filter(|locked| locked.source.as_ref() == Some(source))
+                    .and_then(|locked| locked.hash.as_deref());
+                Some((name.as_ref(), source, hash))
+            })
+    }
+
+    pub(super) fn scoped_registries(&self) -> &[ScopedRegistry] {
+        &self.controller.scoped_registries
+    }
+
+    pub(super) fn overrides(&self) -> &IndexMap<Box<str>, VersionRange> {
+        &self.controller.overrides
+    }
+
     pub(super) fn all_locked(&self) -> impl Iterator<Item = LockedDependencyInfo<'_>> {
         self.controller.locked.iter().map(|(name, dep)| {
             LockedDependencyInfo::new(name.as_ref(), &dep.version, dep.dependencies.as_ref())
@@ -73,10 +110,20 @@ impl VpmManifest {
     }
 
     pub(super) fn add_dependency(&mut self, name: &str, version: DependencyRange) {
-        self.controller
-            .as_mut()
-            .dependencies
-            .insert(name.into(), VpmDependency { version });
+        // keep the source since the package is still fetched from the source
+        let dependencies = &mut self.controller.as_mut().dependencies;
+        match dependencies.get_mut(name) {
+            Some(dependency) => dependency.version = version,
+            None => {
+                dependencies.insert(
+                    name.into(),
+                    VpmDependency {
+                        version,
+                        source: None,
+                    },
+                );
+            }
+        }
     }
 
     pub(super) fn add_locked(
@@ -90,10 +137,19 @@ impl VpmManifest {
             VpmLockedDependency {
                 version,
                 dependencies: Some(dependencies),
+                source: None,
+                hash: None,
             },
         );
     }
 
+    pub(super) fn set_locked_source(&mut self, name: &str, source: &PackageSource, hash: &str) {
+        if let Some(locked) = self.controller.as_mut().locked.get_mut(name) {
+            locked.source = Some(source.clone());
+            locked.hash = Some(hash.into());
+        }
+    }
+
     pub(crate) fn remove_packages<'a>(&mut self, names: impl Iterator<Item = &'a str>) {
         for name in names {
             self.controller.as_mut().locked.shift_remove(name);
@@ -63,7 +63,7 @@ fn fix_path_separator(p: &str) -> std::borrow::Cow<'_, str> {
     }
 }
 
-fn is_complete_relative(path: &Path) -> bool {
+pub(crate) fn is_complete_relative(path: &Path) -> bool {
     for x in path.components() {
         match x {
             Component::Prefix(_) => return false,
@@ -4,6 +4,7 @@ mod deup_deserializer;
 mod extract_zip;
 mod save_controller;
 mod sha256_async_write;
+mod tar_archive;
 
 use crate::io;
 use crate::io::{DirEntry, IoTrait};
@@ -12,7 +13,7 @@ pub(crate) use copy_recursive::copy_recursive;
 pub(crate) use crlf_json_formatter::to_vec_pretty_os_eol;
 pub(crate) use deup_deserializer::DedupForwarder;
 use either::Either;
-pub(crate) use extract_zip::extract_zip;
+pub(crate) use extract_zip::{extract_zip, is_complete_relative};
 use futures::prelude::*;
 use futures::stream::FuturesUnordered;
 use pin_project_lite::pin_project;
@@ -25,6 +26,7 @@ use std::error::Error;
 use std::path::{Component, Path, PathBuf};
 use std::pin::Pin;
 use std::task::{Context, Poll, ready};
+pub(crate) use tar_archive::TarArchive;
 
 pub(crate) trait PathBufExt {
     fn joined(self, into: impl AsRef<Path>) -> Self;
@@ -0,0 +1,152 @@
+use crate::io;
+use crate::io::IoTrait;
+use crate::utils::is_complete_relative;
+use futures::prelude::*;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tar::{Header, PaxExtensions};
+
+/// The streaming reader of tar archives.
+///
+/// This reads entries one by one without loading the whole archive.
+pub(crate) struct TarArchive<R: ?Sized + AsyncRead + Unpin> {
+    to_skip: u64,
+    reader: R,
+}
+
+pub(crate) struct TarEntry<'a, R: AsyncRead + Unpin> {
+    archive: &'a mut TarArchive<R>,
+    remaining: u64,
+    header: Header,
+}
+
+impl<R: AsyncRead + Unpin> TarArchive<R> {
+    pub fn new(reader: R) -> Self
+    where
+        R: Sized,
+    {
+        Self { reader, to_skip: 0 }
+    }
+
+    pub async fn next_entry(&mut self) -> io::Result<Option<TarEntry<'_, R>>> {
+        const BLOCK_SIZE: u64 = 512;
+        let mut header = Header::new_old();
+        // skip bytes
+        while self.to_skip != 0 {
+            let size = std::cmp::min(self.to_skip, BLOCK_SIZE) as usize;
+            self.reader
+                .read_exact(&mut header.as_mut_bytes()[..size])
+                .await?;
+            self.to_skip -= size as u64;
+        }
+        self.reader.read_exact(header.as_mut_bytes()).await?;
+        if header.as_bytes().iter().all(|&b| b == 0) {
+            // the header is all zeros; trailing header
+            return Ok(None);
+        }
+
+        // Make sure the checksum is ok
+        let sum = (header.as_bytes()[..148].iter())
+            .chain(&header.as_bytes()[156..])
+            .fold(0, |a, b| a + (*b as u32))
+            + 8 * b' ' as u32;
+        let cksum = header.cksum()?;
+        if sum != cksum {
+            return Err(io::Error::other("archive header checksum mismatch"));
+        }
+
+        let size = header.size()?;
+        let to_skip = (size + BLOCK_SIZE - 1) & !(BLOCK_SIZE - 1);
+        self.to_skip = to_skip;
+        Ok(Some(TarEntry {
+            archive: self,
+            remaining: size,
+            header,
+        }))
+    }
+}
+
+impl<R: AsyncRead + Unpin> TarArchive<R> {
+    /// Extracts the archive to the folder.
+    ///
+    /// Only regular files and directories are extracted; links and other special files are ignored.
+    /// Long names in GNU and pax extended headers are supported.
+    pub async fn extract_to(&mut self, io: &impl IoTrait, dest_folder: &Path) -> io::Result<()> {
+        let mut long_name = None::<Vec<u8>>;
+
+        while let Some(mut entry) = self.next_entry().await? {
+            let entry_type = entry.header().entry_type();
+
+            if entry_type.is_gnu_longname() {
+                let mut name = Vec::new();
+                entry.read_to_end(&mut name).await?;
+                if let Some(end) = name.iter().position(|&x| x == 0) {
+                    name.truncate(end);
+                }
+                long_name = Some(name);
+                continue;
+            }
+            if entry_type.is_pax_local_extensions() {
+                let mut records = Vec::new();
+                entry.read_to_end(&mut records).await?;
+                long_name = PaxExtensions::new(&records)
+                    .filter_map(Result::ok)
+                    .find(|x| x.key() == Ok("path"))
+                    .map(|x| x.value_bytes().to_vec());
+                continue;
+            }
+
+            let name = match long_name.take() {
+                Some(name) => name,
+                None => entry.header().path_bytes().into_owned(),
+            };
+            let name = String::from_utf8(name)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid tar: name"))?;
+
+            if !is_complete_relative(name.as_ref()) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("directory traversal detected: {name}"),
+                ));
+            }
+
+            let path = dest_folder.join(&name);
+            if entry_type.is_file() || entry_type.is_contiguous() {
+                if let Some(parent) = path.parent() {
+                    io.create_dir_all(parent).await?;
+                }
+                let mut dest_file = io.create(&path).await?;
+                futures::io::copy(&mut entry, &mut dest_file).await?;
+                dest_file.flush().await?;
+            } else if entry_type.is_dir() {
+                io.create_dir_all(&path).await?;
+            } else {
+                log::debug!("skipping non-regular file in tar: {name}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: AsyncRead + Unpin> TarEntry<'_, R> {
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for TarEntry<'_, R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let buffer_size = std::cmp::min(buf.len() as u64, self.remaining) as usize;
+        let size =
+            ready!(Pin::new(&mut self.archive.reader).poll_read(cx, &mut buf[..buffer_size])?);
+        self.remaining -= size as u64;
+        self.archive.to_skip -= size as u64;
+        Poll::Ready(Ok(size))
+    }
+}
@@ -1175,6 +1175,257 @@ fn conflict_requirements_of_installed_and_installing_related_to_dependencies() {
     })
 }
 
+#[test]
+fn conflict_resolved_with_older_version_of_dependency() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.vrchat.avatars", Version::new(1, 0, 0))
+            .add_locked(
+                "com.vrchat.avatars",
+                Version::new(1, 0, 0),
+                &[("com.vrchat.base", "^1.0.0")],
+            )
+            .add_locked("com.vrchat.base", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.vrchat.avatars", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.vrchat.base", "^1.0.0"),
+            )
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(1, 0, 0),
+            ))
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(2, 0, 0),
+            ))
+            .add(
+                PackageManifest::new("com.anatawa12.helper", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.vrchat.base", "^1.0.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.helper", Version::new(2, 0, 0))
+                    .add_vpm_dependency("com.vrchat.base", "^2.0.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.helper", ">=1.0.0"),
+            )
+            .build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+        let helper_1_0_0 = collection.get_package("com.anatawa12.helper", Version::new(1, 0, 0));
+
+        let resolve = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap();
+
+        // the greedy resolution would use helper 2.0.0 and base 2.0.0, which conflicts with avatars
+        assert_eq!(resolve.package_changes().len(), 2);
+        assert_eq!(resolve.remove_legacy_folders().len(), 0);
+        assert_eq!(resolve.remove_legacy_files().len(), 0);
+        assert_eq!(resolve.conflicts().len(), 0);
+
+        assert_installing_to_both(&resolve, &tool);
+        assert_installing_to_locked_only(&resolve, &helper_1_0_0);
+    })
+}
+
+#[test]
+fn conflict_explanation() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.vrchat.avatars", Version::new(1, 0, 0))
+            .add_locked(
+                "com.vrchat.avatars",
+                Version::new(1, 0, 0),
+                &[("com.vrchat.base", "^1.0.0")],
+            )
+            .add_locked("com.vrchat.base", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.vrchat.avatars", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.vrchat.base", "^1.0.0"),
+            )
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(1, 0, 0),
+            ))
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(2, 0, 0),
+            ))
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.vrchat.base", "^2.0.0"),
+            )
+            .build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+
+        let resolve = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resolve.conflicts().len(), 1);
+
+        let base_conflict = resolve.conflicts().get("com.vrchat.base").unwrap();
+        let explanation = base_conflict.explanation().expect("no explanation");
+        assert_eq!(explanation.package(), "com.vrchat.base");
+        assert_eq!(explanation.version(), Some(&Version::new(2, 0, 0)));
+        assert_eq!(
+            explanation.to_string(),
+            "com.anatawa12.tool 1.0.0 requires com.vrchat.base ^2.0.0 \
+            but the project requires com.vrchat.avatars >=1.0.0, \
+            com.vrchat.avatars 1.0.0 requires com.vrchat.base ^1.0.0"
+        );
+    })
+}
+
+#[test]
+fn conflict_resolved_with_backjumping() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        let mut collection = PackageCollectionBuilder::new()
+            .add(PackageManifest::new(
+                "com.anatawa12.lib",
+                Version::new(2, 0, 0),
+            ))
+            .add(PackageManifest::new(
+                "com.anatawa12.lib",
+                Version::new(1, 0, 0),
+            ))
+            .add(
+                PackageManifest::new("com.anatawa12.helper", Version::new(2, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.lib", "^2.0.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.helper", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.lib", "^1.0.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.checker", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.lib", "^1.0.0"),
+            );
+        let mut tool = PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+            .add_vpm_dependency("com.anatawa12.helper", ">=1.0.0");
+        // the packages decided between helper and checker, which have nothing to do with the conflict
+        for i in 0..6 {
+            let name = format!("com.anatawa12.unrelated{i}");
+            for major in (1..=10).rev() {
+                collection = collection.add(PackageManifest::new(
+                    name.as_str(),
+                    Version::new(major, 0, 0),
+                ));
+            }
+            tool = tool.add_vpm_dependency(name.as_str(), ">=1.0.0");
+        }
+        tool = tool.add_vpm_dependency("com.anatawa12.checker", ">=1.0.0");
+        let collection = collection.add(tool).build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+        let helper_1_0_0 = collection.get_package("com.anatawa12.helper", Version::new(1, 0, 0));
+        let lib_1_0_0 = collection.get_package("com.anatawa12.lib", Version::new(1, 0, 0));
+
+        let resolve = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap();
+
+        // trying every combination of the unrelated packages would exceed the step limit
+        assert_eq!(resolve.package_changes().len(), 10);
+        assert_eq!(resolve.conflicts().len(), 0);
+
+        assert_installing_to_both(&resolve, &tool);
+        assert_installing_to_locked_only(&resolve, &helper_1_0_0);
+        assert_installing_to_locked_only(&resolve, &lib_1_0_0);
+        for i in 0..6 {
+            let name = format!("com.anatawa12.unrelated{i}");
+            let unrelated = collection.get_package(&name, Version::new(10, 0, 0));
+            assert_installing_to_locked_only(&resolve, &unrelated);
+        }
+    })
+}
+
+#[test]
+fn resolution_step_limit_exceeded() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        // the pigeonhole problem: each pigeon package requires one of the hole packages
+        // to be the version for the pigeon, and there are more pigeons than holes.
+        const HOLES: u64 = 10;
+        let mut collection = PackageCollectionBuilder::new();
+        let mut tool = PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0));
+        for pigeon in 1..=HOLES + 1 {
+            let name = format!("com.anatawa12.pigeon{pigeon}");
+            for hole in (1..=HOLES).rev() {
+                collection = collection.add(
+                    PackageManifest::new(name.as_str(), Version::new(hole, 0, 0))
+                        .add_vpm_dependency(
+                            format!("com.anatawa12.hole{hole}"),
+                            &format!("{pigeon}.0.0"),
+                        ),
+                );
+            }
+            tool = tool.add_vpm_dependency(name.as_str(), ">=1.0.0");
+        }
+        for hole in 1..=HOLES {
+            let name = format!("com.anatawa12.hole{hole}");
+            for pigeon in 1..=HOLES + 1 {
+                collection = collection.add(PackageManifest::new(
+                    name.as_str(),
+                    Version::new(pigeon, 0, 0),
+                ));
+            }
+        }
+        let collection = collection.add(tool).build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+
+        let err = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, AddPackageErr::ResolutionStepLimitExceeded),
+            "unexpected error: {err}"
+        );
+    })
+}
+
 // endregion
 
 // region rollback on error
@@ -0,0 +1,85 @@
+use crate::common::*;
+use async_zip::base::read::seek::ZipFileReader;
+use futures::io::Cursor;
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::unity_project::{BackupFormat, ZipCompression};
+use vrc_get_vpm::version::Version;
+
+mod common;
+
+async fn backup_entries(project: &UnityProject, exclude_vpm: bool) -> Vec<String> {
+    let mut zip = Cursor::new(Vec::new());
+    project
+        .create_backup_zip(&mut zip, ZipCompression::Fast, exclude_vpm)
+        .await
+        .unwrap();
+    zip.set_position(0);
+
+    let reader = ZipFileReader::new(zip).await.unwrap();
+    (reader.file().entries().iter())
+        .map(|x| x.filename().as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn backup_excludes_generated_folders() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.vrchat.base", Version::new(1, 0, 0))
+            .add_locked("com.vrchat.base", Version::new(1, 0, 0), &[])
+            .add_file("Assets/Scene.unity", "scene")
+            .add_file("Packages/com.vrchat.base/package.json", "{}")
+            .add_file("Library/LastSceneManagerSetup.txt", "last scene")
+            .add_file("Library/ArtifactDB", "artifact")
+            .add_file("Library/Artifacts/00/hash", "artifact")
+            .add_file("Temp/temp.txt", "temp")
+            .add_file(".git/HEAD", "ref: refs/heads/master")
+            .build()
+            .await
+            .unwrap();
+
+        let entries = backup_entries(&project, false).await;
+        for included in [
+            "Assets/",
+            "Assets/Scene.unity",
+            "Packages/vpm-manifest.json",
+            "Packages/com.vrchat.base/package.json",
+            "Library/",
+            "Library/LastSceneManagerSetup.txt",
+        ] {
+            assert!(entries.iter().any(|x| x == included), "{included} missing");
+        }
+        for excluded in ["Library/ArtifactDB", "Library/Artifacts/", "Temp/", ".git/"] {
+            assert!(
+                !entries.iter().any(|x| x.starts_with(excluded)),
+                "{excluded} included"
+            );
+        }
+
+        let entries = backup_entries(&project, true).await;
+        assert!(entries.iter().any(|x| x == "Packages/vpm-manifest.json"));
+        assert!(
+            !entries
+                .iter()
+                .any(|x| x.starts_with("Packages/com.vrchat.base/"))
+        );
+    })
+}
+
+#[test]
+fn backup_format_from_file_name() {
+    assert_eq!(
+        BackupFormat::from_file_name("project-2026-01-02T03-04-05.zip"),
+        Some(("project-2026-01-02T03-04-05", BackupFormat::Zip))
+    );
+    assert_eq!(
+        BackupFormat::from_file_name("project-2026-01-02T03-04-05-auto.tar.zst"),
+        Some(("project-2026-01-02T03-04-05-auto", BackupFormat::TarZst))
+    );
+    assert_eq!(
+        BackupFormat::from_file_name("project-2026-01-02T03-04-05.snapshot.json"),
+        Some(("project-2026-01-02T03-04-05", BackupFormat::Incremental))
+    );
+    assert_eq!(BackupFormat::from_file_name("project.json"), None);
+    assert_eq!(BackupFormat::from_file_name("projectzip"), None);
+}
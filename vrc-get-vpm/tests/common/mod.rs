@@ -105,6 +105,7 @@ pub fn assert_installing_to_dependencies_only(
 
 pub fn block_on<F: Future>(f: F) -> F::Output {
     tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
         .build()
         .unwrap()
         .block_on(f)
@@ -0,0 +1,120 @@
+use crate::common::*;
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::version::Version;
+
+mod common;
+
+fn paths_to_strings(project: &UnityProject, package: &str) -> Vec<String> {
+    project
+        .dependency_paths(package)
+        .iter()
+        .map(|path| {
+            path.edges()
+                .iter()
+                .map(|edge| {
+                    let dependant = match edge.dependant() {
+                        Some(dependant) => format!("{}@{}", dependant.name(), dependant.version()),
+                        None => "project".to_string(),
+                    };
+                    format!("{dependant} -> {}@{}", edge.dependency(), edge.range())
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect()
+}
+
+#[test]
+fn direct_dependency() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency_range("com.anatawa12.package", "^1.0.0")
+            .add_locked("com.anatawa12.package", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            paths_to_strings(&project, "com.anatawa12.package"),
+            vec!["project -> com.anatawa12.package@^1.0.0"],
+        );
+    })
+}
+
+#[test]
+fn multiple_paths() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency_range("com.anatawa12.package", "^1.0.0")
+            .add_dependency_range("com.anatawa12.tool", "^2.0.0")
+            .add_locked(
+                "com.anatawa12.package",
+                Version::new(1, 0, 0),
+                &[("com.anatawa12.library", "^1.1.0")],
+            )
+            .add_locked(
+                "com.anatawa12.tool",
+                Version::new(2, 0, 0),
+                &[("com.anatawa12.package", "^1.0.0")],
+            )
+            .add_locked("com.anatawa12.library", Version::new(1, 2, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            paths_to_strings(&project, "com.anatawa12.library"),
+            vec![
+                "project -> com.anatawa12.package@^1.0.0, com.anatawa12.package@1.0.0 -> com.anatawa12.library@^1.1.0",
+                "project -> com.anatawa12.tool@^2.0.0, com.anatawa12.tool@2.0.0 -> com.anatawa12.package@^1.0.0, com.anatawa12.package@1.0.0 -> com.anatawa12.library@^1.1.0",
+            ],
+        );
+    })
+}
+
+#[test]
+fn cyclic_dependency() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency_range("com.anatawa12.package", "^1.0.0")
+            .add_locked(
+                "com.anatawa12.package",
+                Version::new(1, 0, 0),
+                &[("com.anatawa12.library", "^1.0.0")],
+            )
+            .add_locked(
+                "com.anatawa12.library",
+                Version::new(1, 0, 0),
+                &[
+                    ("com.anatawa12.package", "^1.0.0"),
+                    ("com.anatawa12.helper", "^1.0.0"),
+                ],
+            )
+            .add_locked("com.anatawa12.helper", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            paths_to_strings(&project, "com.anatawa12.helper"),
+            vec![
+                "project -> com.anatawa12.package@^1.0.0, com.anatawa12.package@1.0.0 -> com.anatawa12.library@^1.0.0, com.anatawa12.library@1.0.0 -> com.anatawa12.helper@^1.0.0",
+            ],
+        );
+    })
+}
+
+#[test]
+fn not_required() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency_range("com.anatawa12.package", "^1.0.0")
+            .add_locked("com.anatawa12.package", Version::new(1, 0, 0), &[])
+            .add_locked("com.anatawa12.library", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        assert!(paths_to_strings(&project, "com.anatawa12.library").is_empty());
+    })
+}
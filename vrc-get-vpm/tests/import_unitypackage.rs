@@ -0,0 +1,395 @@
+use crate::common::*;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use futures::io::Cursor;
+use std::path::Path;
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::unity_project::{
+    ImportConflict, ImportConflictHandling, ImportUnityPackageError, UnityPackageAsset,
+};
+
+mod common;
+
+const FOLDER_GUID: &str = "0123456789abcdef0123456789abcdef";
+const FILE_GUID: &str = "fedcba9876543210fedcba9876543210";
+const OTHER_GUID: &str = "00112233445566778899aabbccddeeff";
+
+fn meta(guid: &str) -> String {
+    format!("fileFormatVersion: 2\nguid: {guid}\n")
+}
+
+/// Creates a unitypackage with the assets of `(guid, pathname, content)`. Folders have no content.
+fn build_unitypackage(assets: &[(&str, &str, Option<&str>)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+
+    let mut append = |path: &str, data: &[u8]| {
+        let mut header = tar::Header::new_ustar();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, data).unwrap();
+    };
+
+    for &(guid, pathname, content) in assets {
+        append(&format!("{guid}/pathname"), pathname.as_bytes());
+        append(&format!("{guid}/asset.meta"), meta(guid).as_bytes());
+        if let Some(content) = content {
+            append(&format!("{guid}/asset"), content.as_bytes());
+        }
+    }
+
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+/// Creates a unitypackage with `Assets/Imported` folder and `Assets/Imported/Asset.txt` file.
+fn unitypackage(content: &str) -> Vec<u8> {
+    build_unitypackage(&[
+        (FOLDER_GUID, "Assets/Imported", None),
+        (FILE_GUID, "Assets/Imported/Asset.txt", Some(content)),
+    ])
+}
+
+async fn import(
+    project: &UnityProject,
+    handling: ImportConflictHandling,
+    dry_run: bool,
+) -> Result<Vec<UnityPackageAsset>, ImportUnityPackageError> {
+    project
+        .import_unitypackage(Cursor::new(unitypackage("imported")), handling, dry_run)
+        .await
+}
+
+fn read(project: &UnityProject, path: &str) -> Option<String> {
+    std::fs::read_to_string(project.project_dir().join(path)).ok()
+}
+
+#[test]
+fn import_to_project() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        let assets = import(&project, ImportConflictHandling::Fail, false)
+            .await
+            .unwrap();
+
+        assert_eq!(assets.len(), 2);
+        assert_eq!(assets[0].path(), "Assets/Imported");
+        assert!(assets[0].is_folder());
+        assert_eq!(assets[1].path(), "Assets/Imported/Asset.txt");
+        assert_eq!(assets[1].guid(), FILE_GUID);
+        assert!(assets.iter().all(|x| x.conflicts().is_empty()));
+
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt").as_deref(),
+            Some("imported")
+        );
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt.meta"),
+            Some(meta(FILE_GUID))
+        );
+        assert_eq!(
+            read(&project, "Assets/Imported.meta"),
+            Some(meta(FOLDER_GUID))
+        );
+    })
+}
+
+#[test]
+fn dry_run_does_not_change_project() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file("Assets/Imported/Asset.txt", "existing")
+            .build()
+            .await
+            .unwrap();
+
+        // conflicts are reported instead of failing with dry run
+        let assets = import(&project, ImportConflictHandling::Fail, true)
+            .await
+            .unwrap();
+
+        assert_eq!(assets[0].conflicts(), []);
+        assert_eq!(assets[1].conflicts(), [ImportConflict::PathExists]);
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt").as_deref(),
+            Some("existing")
+        );
+        assert_eq!(read(&project, "Assets/Imported.meta"), None);
+    })
+}
+
+#[test]
+fn path_conflict() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file("Assets/Imported/Asset.txt", "existing")
+            .build()
+            .await
+            .unwrap();
+
+        let Err(ImportUnityPackageError::Conflicts(conflicts)) =
+            import(&project, ImportConflictHandling::Fail, false).await
+        else {
+            panic!("conflicts expected");
+        };
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path(), "Assets/Imported/Asset.txt");
+        assert_eq!(read(&project, "Assets/Imported.meta"), None);
+
+        import(&project, ImportConflictHandling::Skip, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt").as_deref(),
+            Some("existing")
+        );
+        assert_eq!(read(&project, "Assets/Imported/Asset.txt.meta"), None);
+
+        import(&project, ImportConflictHandling::Overwrite, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt").as_deref(),
+            Some("imported")
+        );
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt.meta"),
+            Some(meta(FILE_GUID))
+        );
+    })
+}
+
+#[test]
+fn guid_conflict() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file("Assets/Moved.txt", "existing")
+            .add_file("Assets/Moved.txt.meta", meta(FILE_GUID))
+            .build()
+            .await
+            .unwrap();
+
+        let assets = import(&project, ImportConflictHandling::Fail, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            assets[1].conflicts(),
+            [ImportConflict::GuidExists(
+                Path::new("Assets/Moved.txt").into()
+            )]
+        );
+
+        // the asset is written to the existing path like Unity
+        import(&project, ImportConflictHandling::Overwrite, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            read(&project, "Assets/Moved.txt").as_deref(),
+            Some("imported")
+        );
+        assert_eq!(read(&project, "Assets/Imported/Asset.txt"), None);
+        assert_eq!(
+            read(&project, "Assets/Imported.meta"),
+            Some(meta(FOLDER_GUID))
+        );
+    })
+}
+
+#[test]
+fn path_and_guid_conflicts() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file("Assets/Imported/Asset.txt", "existing")
+            .add_file("Assets/Imported/Asset.txt.meta", meta(OTHER_GUID))
+            .add_file("Assets/Moved.txt", "moved")
+            .add_file("Assets/Moved.txt.meta", meta(FILE_GUID))
+            .build()
+            .await
+            .unwrap();
+
+        let assets = import(&project, ImportConflictHandling::Fail, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            assets[1].conflicts(),
+            [
+                ImportConflict::PathExists,
+                ImportConflict::GuidMismatch(OTHER_GUID.into()),
+                ImportConflict::GuidExists(Path::new("Assets/Moved.txt").into()),
+            ]
+        );
+        assert!(assets[1].can_overwrite());
+
+        // the asset at the path is kept since the asset is written to the existing path of the GUID
+        import(&project, ImportConflictHandling::Overwrite, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            read(&project, "Assets/Moved.txt").as_deref(),
+            Some("imported")
+        );
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt").as_deref(),
+            Some("existing")
+        );
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt.meta"),
+            Some(meta(OTHER_GUID))
+        );
+    })
+}
+
+#[test]
+fn guid_mismatch_is_not_overwritten() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file("Assets/Imported/Asset.txt", "existing")
+            .add_file("Assets/Imported/Asset.txt.meta", meta(OTHER_GUID))
+            .build()
+            .await
+            .unwrap();
+
+        let Err(ImportUnityPackageError::CannotOverwrite(assets)) =
+            import(&project, ImportConflictHandling::Overwrite, false).await
+        else {
+            panic!("cannot overwrite expected");
+        };
+        assert_eq!(assets.len(), 1);
+        assert_eq!(
+            assets[0].conflicts(),
+            [
+                ImportConflict::PathExists,
+                ImportConflict::GuidMismatch(OTHER_GUID.into()),
+            ]
+        );
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt").as_deref(),
+            Some("existing")
+        );
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt.meta"),
+            Some(meta(OTHER_GUID))
+        );
+        assert_eq!(read(&project, "Assets/Imported.meta"), None);
+    })
+}
+
+#[test]
+fn type_mismatch_is_validated_before_writing() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file("Assets/Imported", "existing")
+            .build()
+            .await
+            .unwrap();
+
+        let Err(ImportUnityPackageError::CannotOverwrite(assets)) =
+            import(&project, ImportConflictHandling::Overwrite, false).await
+        else {
+            panic!("cannot overwrite expected");
+        };
+        assert_eq!(assets.len(), 2);
+        assert_eq!(
+            assets[0].conflicts(),
+            [ImportConflict::PathExists, ImportConflict::TypeMismatch]
+        );
+        // the parent folder of the file asset is a file
+        assert_eq!(assets[1].conflicts(), [ImportConflict::TypeMismatch]);
+
+        assert_eq!(
+            read(&project, "Assets/Imported").as_deref(),
+            Some("existing")
+        );
+        assert_eq!(read(&project, "Assets/Imported.meta"), None);
+    })
+}
+
+#[test]
+fn replace_writes_to_declared_paths() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file("Assets/Imported/Asset.txt", "existing")
+            .add_file("Assets/Imported/Asset.txt.meta", meta(OTHER_GUID))
+            .add_file("Assets/Imported.meta", meta(OTHER_GUID))
+            .add_file("Assets/Moved.txt", "moved")
+            .add_file("Assets/Moved.txt.meta", meta(FILE_GUID))
+            .build()
+            .await
+            .unwrap();
+
+        // the files of the template are written like extracting the unitypackage
+        import(&project, ImportConflictHandling::Replace, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt").as_deref(),
+            Some("imported")
+        );
+        assert_eq!(
+            read(&project, "Assets/Imported/Asset.txt.meta"),
+            Some(meta(FILE_GUID))
+        );
+        assert_eq!(
+            read(&project, "Assets/Imported.meta"),
+            Some(meta(FOLDER_GUID))
+        );
+        assert_eq!(read(&project, "Assets/Moved.txt").as_deref(), Some("moved"));
+    })
+}
+
+#[test]
+fn replace_validates_type_mismatch_before_writing() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file("Assets/Imported", "existing")
+            .build()
+            .await
+            .unwrap();
+
+        let Err(ImportUnityPackageError::CannotOverwrite(assets)) =
+            import(&project, ImportConflictHandling::Replace, false).await
+        else {
+            panic!("cannot overwrite expected");
+        };
+        assert_eq!(assets.len(), 2);
+        assert_eq!(
+            read(&project, "Assets/Imported").as_deref(),
+            Some("existing")
+        );
+        assert_eq!(read(&project, "Assets/Imported.meta"), None);
+    })
+}
+
+#[test]
+fn pathname_is_validated_after_removing_suffix() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        // the suffix after the newline is removed like Unity
+        let package = build_unitypackage(&[(FILE_GUID, "Assets/Asset.txt\n00", Some("imported"))]);
+        project
+            .import_unitypackage(Cursor::new(package), ImportConflictHandling::Fail, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            read(&project, "Assets/Asset.txt").as_deref(),
+            Some("imported")
+        );
+
+        // the traversal is hidden by the suffix
+        for pathname in ["Assets/..\nAsset.txt", "Assets/Folder/\nAsset.txt"] {
+            let package = build_unitypackage(&[(OTHER_GUID, pathname, Some("imported"))]);
+            let Err(ImportUnityPackageError::Io(e)) = project
+                .import_unitypackage(
+                    Cursor::new(package),
+                    ImportConflictHandling::Overwrite,
+                    false,
+                )
+                .await
+            else {
+                panic!("{pathname:?} must be rejected");
+            };
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        }
+    })
+}
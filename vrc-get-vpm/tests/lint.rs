@@ -0,0 +1,211 @@
+use crate::common::*;
+use serde_json::json;
+use vrc_get_vpm::PackageManifest;
+use vrc_get_vpm::lint::{lint_package_manifest, lint_repository};
+use vrc_get_vpm::version::Version;
+
+mod common;
+
+fn known_packages() -> PackageCollection {
+    PackageCollectionBuilder::new()
+        .add(PackageManifest::new(
+            "com.vrchat.base",
+            Version::new(3, 5, 0),
+        ))
+        .build()
+}
+
+fn lint_manifest_strings(json: &serde_json::Value, known: &PackageCollection) -> Vec<String> {
+    lint_package_manifest(json, known)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn lint_repository_strings(json: &serde_json::Value, known: &PackageCollection) -> Vec<String> {
+    let mut issues = lint_repository(json, known)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    issues.sort();
+    issues
+}
+
+#[test]
+fn valid_manifest() {
+    let manifest = json!({
+        "name": "com.anatawa12.package",
+        "version": "1.0.0",
+        "unity": "2022.3",
+        "vpmDependencies": { "com.vrchat.base": "^3.5.0" },
+        "legacyFolders": { "Assets/Package": "0123456789abcdef0123456789ABCDEF" },
+        "legacyFiles": { "Assets/Package.cs": "" },
+    });
+
+    assert!(lint_package_manifest(&manifest, &known_packages()).is_empty());
+}
+
+#[test]
+fn invalid_manifest() {
+    let manifest = json!({
+        "name": "com.anatawa12.package",
+        "version": "1.0.0",
+        "unity": "2022.x",
+        "vpmDependencies": {
+            "com.vrchat.base": "^^3",
+            "com.anatawa12.unknown": "1.0.0",
+        },
+        "legacyFolders": { "Assets/Package": "not-a-guid" },
+    });
+
+    assert_eq!(
+        lint_manifest_strings(&manifest, &known_packages()),
+        vec![
+            "com.anatawa12.package@1.0.0: invalid version range for com.vrchat.base: ^^3",
+            "com.anatawa12.package@1.0.0: dependency com.anatawa12.unknown is not found in any repository",
+            "com.anatawa12.package@1.0.0: invalid GUID for legacy asset Assets/Package: not-a-guid",
+            "com.anatawa12.package@1.0.0: invalid unity version: 2022.x",
+        ],
+    );
+}
+
+#[test]
+fn repository() {
+    let repository = json!({
+        "packages": {
+            "com.anatawa12.a": {
+                "versions": {
+                    "1.0.0": {
+                        "name": "com.anatawa12.a",
+                        "version": "1.0.0",
+                        "url": "https://example.com/a-1.0.0.zip",
+                        "zipSHA256": "0000000000000000000000000000000000000000000000000000000000000000",
+                        "vpmDependencies": { "com.anatawa12.b": "^1.0.0" },
+                    },
+                    "1.1.0": {
+                        "name": "com.anatawa12.wrong",
+                        "version": "1.2.0",
+                        "url": "https://example.com/a-1.1.0.zip",
+                    },
+                }
+            },
+            "com.anatawa12.b": {
+                "versions": {
+                    "1.0.0": {
+                        "name": "com.anatawa12.b",
+                        "version": "1.0.0",
+                        "url": "https://example.com/b-1.0.0.zip",
+                        "zipSHA256": "0000000000000000000000000000000000000000000000000000000000000000",
+                        "vpmDependencies": {
+                            "com.anatawa12.a": "^1.0.0",
+                            "com.vrchat.base": "^3.5.0",
+                        },
+                    },
+                }
+            },
+        }
+    });
+
+    assert_eq!(
+        lint_repository_strings(&repository, &known_packages()),
+        vec![
+            "com.anatawa12.a@1.0.0: circular dependency: com.anatawa12.a -> com.anatawa12.b -> com.anatawa12.a",
+            "com.anatawa12.a@1.1.0: name in the manifest is com.anatawa12.wrong",
+            "com.anatawa12.a@1.1.0: version in the manifest is 1.2.0",
+            "com.anatawa12.a@1.1.0: zipSHA256 is not specified",
+        ],
+    );
+}
+
+#[test]
+fn manifest_circular_dependency_in_known_repository() {
+    let known = PackageCollectionBuilder::new()
+        .add(
+            PackageManifest::new("com.anatawa12.b", Version::new(1, 0, 0))
+                .add_vpm_dependency("com.anatawa12.c", "^1.0.0"),
+        )
+        .add(
+            PackageManifest::new("com.anatawa12.c", Version::new(1, 0, 0))
+                .add_vpm_dependency("com.anatawa12.a", "^1.0.0"),
+        )
+        .build();
+
+    let manifest = json!({
+        "name": "com.anatawa12.a",
+        "version": "1.0.0",
+        "vpmDependencies": { "com.anatawa12.b": "^1.0.0" },
+    });
+    assert_eq!(
+        lint_manifest_strings(&manifest, &known),
+        vec![
+            "com.anatawa12.a@1.0.0: circular dependency: com.anatawa12.a -> com.anatawa12.b -> com.anatawa12.c -> com.anatawa12.a",
+        ],
+    );
+
+    // the version of the package does not match the range in the known repository
+    let manifest = json!({
+        "name": "com.anatawa12.a",
+        "version": "2.0.0",
+        "vpmDependencies": { "com.anatawa12.b": "^1.0.0" },
+    });
+    assert!(lint_manifest_strings(&manifest, &known).is_empty());
+}
+
+#[test]
+fn repository_cycles_checked_per_version() {
+    let versions = |name: &str, dependencies: &[(&str, &str, &str)]| {
+        let versions = (dependencies.iter())
+            .map(|&(version, dependency, range)| {
+                let mut manifest = json!({
+                    "name": name,
+                    "version": version,
+                    "url": format!("https://example.com/{name}-{version}.zip"),
+                    "zipSHA256": "0000000000000000000000000000000000000000000000000000000000000000",
+                });
+                if !dependency.is_empty() {
+                    manifest["vpmDependencies"] = json!({ dependency: range });
+                }
+                (version.to_string(), manifest)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        json!({ "versions": versions })
+    };
+
+    let repository = json!({
+        "packages": {
+            // a@1 -> b@1, b@2 -> a@1: no cycle since a@1 does not accept b@2
+            // a@2 -> b@2 -> a@2 is a cycle
+            "com.anatawa12.a": versions("com.anatawa12.a", &[
+                ("1.0.0", "com.anatawa12.b", "^1.0.0"),
+                ("2.0.0", "com.anatawa12.b", "^2.0.0"),
+            ]),
+            "com.anatawa12.b": versions("com.anatawa12.b", &[
+                ("1.0.0", "", ""),
+                ("2.0.0", "com.anatawa12.a", ">=1.0.0"),
+            ]),
+        }
+    });
+
+    assert_eq!(
+        lint_repository_strings(&repository, &known_packages()),
+        vec![
+            "com.anatawa12.a@2.0.0: circular dependency: com.anatawa12.a -> com.anatawa12.b -> com.anatawa12.a",
+        ],
+    );
+}
+
+#[test]
+fn self_dependency() {
+    let manifest = json!({
+        "name": "com.anatawa12.a",
+        "version": "1.0.0",
+        "vpmDependencies": { "com.anatawa12.a": "^1.0.0" },
+    });
+    assert_eq!(
+        lint_manifest_strings(&manifest, &known_packages()),
+        vec![
+            "com.anatawa12.a@1.0.0: dependency com.anatawa12.a is not found in any repository",
+            "com.anatawa12.a@1.0.0: circular dependency: com.anatawa12.a -> com.anatawa12.a",
+        ],
+    );
+}
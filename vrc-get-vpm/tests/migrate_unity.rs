@@ -0,0 +1,119 @@
+use crate::common::*;
+use serde_json::{Value, json};
+use vrc_get_vpm::PackageManifest;
+use vrc_get_vpm::unity_project::MigrateUnityError;
+use vrc_get_vpm::version::{UnityVersion, Version};
+
+mod common;
+
+const UNITY_2022: UnityVersion = UnityVersion::new_f1(2022, 3, 22);
+
+#[test]
+fn migrate_request_changes_packages_for_target() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.vrchat.worlds", Version::new(3, 4, 2))
+            .add_locked(
+                "com.vrchat.worlds",
+                Version::new(3, 4, 2),
+                &[("com.vrchat.base", "3.4.2")],
+            )
+            .add_locked("com.vrchat.base", Version::new(3, 4, 2), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.vrchat.worlds", Version::new(3, 4, 2))
+                    .add_vpm_dependency("com.vrchat.base", "3.4.2"),
+            )
+            .add(
+                PackageManifest::new("com.vrchat.worlds", Version::new(3, 5, 0))
+                    .add_vpm_dependency("com.vrchat.base", "3.5.0"),
+            )
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(3, 4, 2),
+            ))
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(3, 5, 0),
+            ))
+            .build();
+
+        let result = project
+            .migrate_unity_request(&collection, UNITY_2022)
+            .await
+            .unwrap();
+
+        assert_eq!(result.conflicts().len(), 0);
+        assert_installing_to_locked_only(
+            &result,
+            &collection.get_package("com.vrchat.worlds", Version::new(3, 5, 0)),
+        );
+        assert_installing_to_locked_only(
+            &result,
+            &collection.get_package("com.vrchat.base", Version::new(3, 5, 0)),
+        );
+
+        let result = project
+            .migrate_unity_request(&collection, UnityVersion::new_f1(2019, 4, 30))
+            .await;
+        assert!(matches!(
+            result,
+            Err(MigrateUnityError::UnityVersionDowngrade)
+        ));
+    })
+}
+
+#[test]
+fn migrate_updates_project_version() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_file(
+                "Packages/manifest.json",
+                json!({
+                    "dependencies": {
+                        "com.unity.timeline": "1.2.18",
+                        "com.unity.xr.oculus.standalone": "2.38.4",
+                    },
+                })
+                .to_string(),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new().build();
+        let installer = VirtualInstaller::new();
+
+        project
+            .migrate_unity(&collection, &installer, UNITY_2022, Some("887be4894c44"))
+            .await
+            .unwrap();
+
+        assert_eq!(project.unity_version(), UNITY_2022);
+        assert_eq!(project.unity_revision(), Some("887be4894c44"));
+
+        let project_version = std::fs::read_to_string(
+            project
+                .project_dir()
+                .join("ProjectSettings/ProjectVersion.txt"),
+        )
+        .unwrap();
+        assert_eq!(
+            project_version,
+            "m_EditorVersion: 2022.3.22f1\nm_EditorVersionWithRevision: 2022.3.22f1 (887be4894c44)\n"
+        );
+
+        let manifest: Value = serde_json::from_slice(
+            &std::fs::read(project.project_dir().join("Packages/manifest.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            manifest,
+            json!({"dependencies": {"com.unity.timeline": "1.2.18"}})
+        );
+    })
+}
@@ -0,0 +1,96 @@
+use crate::common::*;
+use serde_json::{Value, json};
+use vrc_get_vpm::unity_project::MigrateUnity2022Error;
+
+mod common;
+
+#[test]
+fn migrate_2022_saves_removing_legacy_xr_without_vrcsdk() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_file(
+                "Packages/manifest.json",
+                json!({
+                    "dependencies": {
+                        "com.unity.timeline": "1.2.18",
+                        "com.unity.xr.openvr.standalone": "1.0.5",
+                    },
+                })
+                .to_string(),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new().build();
+        let installer = VirtualInstaller::new();
+
+        // no packages are changed, but the legacy XR packages are removed from manifest.json
+        project
+            .migrate_unity_2022(&collection, &installer)
+            .await
+            .unwrap();
+
+        let manifest: Value = serde_json::from_slice(
+            &std::fs::read(project.project_dir().join("Packages/manifest.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            manifest,
+            json!({"dependencies": {"com.unity.timeline": "1.2.18"}})
+        );
+    })
+}
+
+#[test]
+fn migrate_2022_request_includes_removing_legacy_xr() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file(
+                "Packages/manifest.json",
+                json!({
+                    "dependencies": {
+                        "com.unity.timeline": "1.2.18",
+                        "com.unity.xr.oculus.standalone": "2.38.4",
+                    },
+                })
+                .to_string(),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new().build();
+
+        let changes = project
+            .migrate_unity_2022_request(&collection)
+            .await
+            .unwrap();
+
+        assert!(changes.package_changes().is_empty());
+        assert_eq!(
+            changes.remove_upm_dependencies(),
+            &["com.unity.xr.oculus.standalone".into()]
+        );
+    })
+}
+
+#[test]
+fn migrate_2022_request_requires_unity_2019() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .with_unity("2022.3.22f1", "887be4894c44")
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new().build();
+
+        let result = project.migrate_unity_2022_request(&collection).await;
+
+        assert!(matches!(
+            result,
+            Err(MigrateUnity2022Error::UnityVersionMismatch)
+        ));
+    })
+}
@@ -0,0 +1,99 @@
+use crate::common::*;
+use serde_json::json;
+use vrc_get_vpm::PackageManifest;
+use vrc_get_vpm::version::{Version, VersionRange};
+
+mod common;
+
+fn collection() -> PackageCollection {
+    PackageCollectionBuilder::new()
+        .add(
+            PackageManifest::new("com.anatawa12.package", Version::new(1, 0, 0))
+                .add_vpm_dependency("com.anatawa12.library", "~1.0.0"),
+        )
+        .add(PackageManifest::new(
+            "com.anatawa12.library",
+            Version::new(1, 0, 0),
+        ))
+        .add(PackageManifest::new(
+            "com.anatawa12.library",
+            Version::new(2, 0, 0),
+        ))
+        .build()
+}
+
+#[test]
+fn override_transitive_requirement() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file(
+                "Packages/vpm-manifest.json",
+                json!({
+                    "dependencies": {"com.anatawa12.package": {"version": "1.0.0"}},
+                    "locked": {},
+                    "overrides": {"com.anatawa12.library": "^2.0.0"},
+                })
+                .to_string(),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let override_range = "^2.0.0".parse::<VersionRange>().unwrap();
+        assert_eq!(
+            project.get_override("com.anatawa12.library"),
+            Some(&override_range)
+        );
+
+        let collection = collection();
+        let result = project.resolve_request(&collection).await.unwrap();
+
+        assert_eq!(result.conflicts().len(), 0);
+        let package = collection.get_package("com.anatawa12.package", Version::new(1, 0, 0));
+        let library = collection.get_package("com.anatawa12.library", Version::new(2, 0, 0));
+        assert_installing_to_both(&result, &package);
+        assert_installing_to_locked_only(&result, &library);
+
+        let overridden = result.overridden_requirements();
+        assert_eq!(overridden.len(), 1);
+        let requirement = overridden[0].requirement();
+        assert_eq!(
+            requirement.source(),
+            Some(("com.anatawa12.package", &Version::new(1, 0, 0)))
+        );
+        assert_eq!(requirement.target(), "com.anatawa12.library");
+        assert_eq!(requirement.range(), &"~1.0.0".parse().unwrap());
+        assert_eq!(overridden[0].override_range(), &override_range);
+    })
+}
+
+#[test]
+fn frozen_resolve_uses_overrides() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file(
+                "Packages/vpm-manifest.json",
+                json!({
+                    "dependencies": {"com.anatawa12.package": {"version": "1.0.0"}},
+                    "locked": {
+                        "com.anatawa12.package": {
+                            "version": "1.0.0",
+                            "dependencies": {"com.anatawa12.library": "~1.0.0"},
+                        },
+                        "com.anatawa12.library": {"version": "2.0.0", "dependencies": {}},
+                    },
+                    "overrides": {"com.anatawa12.library": "^2.0.0"},
+                })
+                .to_string(),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let collection = collection();
+        let result = project.resolve_request_frozen(&collection).await.unwrap();
+
+        assert_eq!(result.conflicts().len(), 0);
+        assert_eq!(result.overridden_requirements().len(), 1);
+    })
+}
@@ -0,0 +1,131 @@
+use crate::common::*;
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+use std::path::Path;
+use vrc_get_vpm::environment::{PackageCollection, PackageInstaller, Settings};
+use vrc_get_vpm::io::DefaultEnvironmentIo;
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::{PackageCollection as _, VersionSelector};
+
+mod common;
+
+fn write_official_repo(root: &Path) {
+    let repos = root.join("Repos");
+    std::fs::create_dir_all(&repos).unwrap();
+    std::fs::write(
+        repos.join("vrc-official.json"),
+        r#"{
+            "repo": {
+                "name": "Official",
+                "id": "com.vrchat.repos.official",
+                "url": "https://packages.vrchat.com/official?download",
+                "packages": {
+                    "com.anatawa12.package": {
+                        "versions": {
+                            "1.0.0": {
+                                "name": "com.anatawa12.package",
+                                "version": "1.0.0",
+                                "url": "https://example.com/1.0.0.zip"
+                            },
+                            "1.1.0": {
+                                "name": "com.anatawa12.package",
+                                "version": "1.1.0",
+                                "url": "https://example.com/1.1.0.zip"
+                            },
+                            "1.2.0": {
+                                "name": "com.anatawa12.package",
+                                "version": "1.2.0",
+                                "url": "https://example.com/1.2.0.zip"
+                            }
+                        }
+                    }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    std::fs::write(repos.join("vrc-curated.json"), r#"{"repo": {}}"#).unwrap();
+}
+
+fn write_cache(root: &Path, name: &str, version: &str, content: &[u8], hash_of: &[u8]) {
+    let folder = root.join("Repos").join(name);
+    std::fs::create_dir_all(&folder).unwrap();
+    let file_name = format!("vrc-get-{name}-{version}.zip");
+    std::fs::write(folder.join(&file_name), content).unwrap();
+    std::fs::write(
+        folder.join(format!("{file_name}.sha256")),
+        format!("{} {file_name}\n", hex::encode(Sha256::digest(hash_of))),
+    )
+    .unwrap();
+}
+
+#[test]
+fn cached_only_collection() {
+    block_on(async {
+        let root = get_temp_path("package_cache");
+        std::fs::remove_dir_all(&root).ok();
+        write_official_repo(&root);
+        // valid cache
+        write_cache(&root, "com.anatawa12.package", "1.0.0", b"zip 1", b"zip 1");
+        // zip file without the sha256 file
+        write_cache(&root, "com.anatawa12.package", "1.1.0", b"zip 2", b"zip 2");
+        std::fs::remove_file(
+            root.join("Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.1.0.zip.sha256"),
+        )
+        .unwrap();
+        // broken cache: the zip is not hashed until it's used
+        write_cache(&root, "com.anatawa12.package", "1.2.0", b"zip 3", b"zip 4");
+
+        let io = DefaultEnvironmentIo::new(root.into_boxed_path());
+        let settings = Settings::load(&io).await.unwrap();
+        let collection = PackageCollection::load_cache(&settings, &io).await.unwrap();
+        let cached = collection.cached_only(&io).await;
+
+        assert_eq!(collection.find_packages("com.anatawa12.package").count(), 3);
+
+        let mut versions = cached
+            .find_packages("com.anatawa12.package")
+            .map(|x| x.version().clone())
+            .collect::<Vec<_>>();
+        versions.sort();
+        assert_eq!(versions, vec![Version::new(1, 0, 0), Version::new(1, 2, 0)]);
+
+        let latest = cached
+            .find_package_by_name(
+                "com.anatawa12.package",
+                VersionSelector::latest_for(None, false),
+            )
+            .unwrap();
+        assert_eq!(latest.version(), &Version::new(1, 2, 0));
+    })
+}
+
+#[test]
+fn broken_cache_is_verified_on_use() {
+    block_on(async {
+        let root = get_temp_path("package_cache_verify");
+        std::fs::remove_dir_all(&root).ok();
+        write_official_repo(&root);
+        write_cache(&root, "com.anatawa12.package", "1.0.0", b"zip 1", b"zip 1");
+        write_cache(&root, "com.anatawa12.package", "1.2.0", b"zip 3", b"zip 4");
+
+        let io = DefaultEnvironmentIo::new(root.into_boxed_path());
+        let settings = Settings::load(&io).await.unwrap();
+        let collection = PackageCollection::load_cache(&settings, &io).await.unwrap();
+        let installer = PackageInstaller::new(&io, None::<&Infallible>);
+        let repository = (collection.get_remote())
+            .find(|x| x.id() == Some("com.vrchat.repos.official"))
+            .unwrap();
+        let get_zip = |version: Version| {
+            let package = (repository.get_versions_of("com.anatawa12.package"))
+                .find(|x| x.version() == &version)
+                .unwrap();
+            installer.get_package_zip(repository, package)
+        };
+
+        let (_, hash) = get_zip(Version::new(1, 0, 0)).await.unwrap();
+        assert_eq!(hash[..], Sha256::digest(b"zip 1")[..]);
+
+        get_zip(Version::new(1, 2, 0)).await.unwrap_err();
+    })
+}
@@ -0,0 +1,291 @@
+use crate::common::*;
+use futures::io::{AsyncRead, Cursor};
+use indexmap::IndexMap;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+use url::Url;
+use vrc_get_vpm::environment::{PackageCollection, PackageInstaller};
+use vrc_get_vpm::io::{DefaultEnvironmentIo, DefaultProjectIo};
+use vrc_get_vpm::package_source::PackageSource;
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::{HttpClient, PackageCollection as _, UnityProject, VersionSelector};
+
+mod common;
+
+#[test]
+fn parse_package_source() {
+    for (notation, git) in [
+        ("git+https://example.com/repo#v1.0.0", true),
+        ("https://example.com/repo.git", true),
+        ("git@example.com:user/repo.git#main", true),
+        (
+            "https://example.com/repo.git?path=/Packages/com.example#0123abcd",
+            true,
+        ),
+        ("https://example.com/package-1.0.0.tgz", false),
+        ("https://example.com/package-1.0.0.zip", false),
+    ] {
+        let source = PackageSource::from_str(notation).unwrap();
+        assert_eq!(source.is_git(), git, "{notation}");
+        assert_eq!(source.to_string(), notation);
+    }
+
+    assert_eq!(
+        PackageSource::from_str("https://example.com/repo.git?path=Packages/com.example/#v1")
+            .unwrap(),
+        PackageSource::from_str("https://example.com/repo.git?path=/Packages/com.example#v1")
+            .unwrap(),
+    );
+
+    assert!(PackageSource::from_str("https://example.com/package").is_err());
+    assert!(PackageSource::from_str("https://example.com/package.zip#v1").is_err());
+    assert!(PackageSource::from_str("file:///package.tgz").is_err());
+
+    // options, local or helper transports, and path traversal are rejected
+    for notation in [
+        "git+--upload-pack=touch /tmp/pwned",
+        "git+https://example.com/repo#--upload-pack=touch",
+        "git+file:///path/to/repo",
+        "git+ext::sh -c touch% /tmp/pwned",
+        "git+/path/to/repo.git",
+        "https://example.com/repo.git?path=/../../..",
+        "https://example.com/repo.git?path=Packages/../../outside",
+    ] {
+        assert!(PackageSource::from_str(notation).is_err(), "{notation}");
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {args:?} failed");
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+#[test]
+fn fetch_and_lock_git_source() {
+    block_on(async {
+        let root = get_temp_path("package_source_git");
+        std::fs::remove_dir_all(&root).ok();
+
+        let repo = root.join("repo");
+        std::fs::create_dir_all(repo.join("Packages/com.anatawa12.package")).unwrap();
+        std::fs::write(
+            repo.join("Packages/com.anatawa12.package/package.json"),
+            r#"{"name": "com.anatawa12.package", "version": "1.0.0"}"#,
+        )
+        .unwrap();
+        git(&repo, &["init", "-q"]);
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "initial"]);
+        git(&repo, &["tag", "v1.0.0"]);
+        let commit = git(&repo, &["rev-parse", "HEAD"]);
+
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+        let project_dir = project.project_dir().to_owned();
+        // file urls are not allowed in sources, so redirect the https url to the local repository.
+        // SAFETY: no other test in this file runs git or reads these variables
+        unsafe {
+            std::env::set_var("GIT_CONFIG_COUNT", "1");
+            std::env::set_var(
+                "GIT_CONFIG_KEY_0",
+                format!("url.file://{}.insteadOf", repo.display()),
+            );
+            std::env::set_var("GIT_CONFIG_VALUE_0", "https://example.com/repo.git");
+        }
+        let source = "https://example.com/repo.git?path=/Packages/com.anatawa12.package#v1.0.0";
+        std::fs::write(
+            project_dir.join("Packages/vpm-manifest.json"),
+            serde_json::json!({
+                "dependencies": {
+                    "com.anatawa12.package": { "version": "1.0.0", "source": source }
+                },
+                "locked": {}
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let mut project = UnityProject::load(DefaultProjectIo::new(project_dir.clone().into()))
+            .await
+            .unwrap();
+
+        let io = DefaultEnvironmentIo::new(root.join("env").into_boxed_path());
+        let http = reqwest::Client::new();
+        let mut collection = PackageCollection::empty();
+        collection
+            .fetch_package_sources(&io, Some(&http), &project)
+            .await
+            .unwrap();
+
+        let package = collection
+            .find_package_by_name(
+                "com.anatawa12.package",
+                VersionSelector::latest_for(None, false),
+            )
+            .unwrap();
+        assert_eq!(package.version(), &Version::new(1, 0, 0));
+        assert_eq!(package.source_package().unwrap().hash(), commit);
+
+        let changes = project.resolve_request(&collection).await.unwrap();
+        let installer = PackageInstaller::new(&io, Some(&http));
+        project
+            .apply_pending_changes(&installer, changes)
+            .await
+            .unwrap();
+
+        assert!(
+            project_dir
+                .join("Packages/com.anatawa12.package/package.json")
+                .exists()
+        );
+
+        let project = UnityProject::load(DefaultProjectIo::new(project_dir.clone().into()))
+            .await
+            .unwrap();
+        let (_, _, locked_hash) = project.package_sources().next().unwrap();
+        assert_eq!(locked_hash, Some(commit.as_str()));
+
+        // the locked commit is used from the cache even if the tag moves
+        git(&repo, &["commit", "-q", "--allow-empty", "-m", "second"]);
+        git(&repo, &["tag", "-f", "v1.0.0"]);
+        let mut collection = PackageCollection::empty();
+        collection
+            .fetch_package_sources(&io, None::<&reqwest::Client>, &project)
+            .await
+            .unwrap();
+        assert_eq!(collection.source_packages()[0].hash(), commit);
+
+        // abbreviated commits cannot be fetched directly, so all branches and tags are fetched
+        let short_commit = git(&repo, &["rev-parse", "--short", &commit]);
+        let source = format!(
+            "https://example.com/repo.git?path=/Packages/com.anatawa12.package#{short_commit}"
+        );
+        std::fs::write(
+            project_dir.join("Packages/vpm-manifest.json"),
+            serde_json::json!({
+                "dependencies": {
+                    "com.anatawa12.package": { "version": "1.0.0", "source": source }
+                },
+                "locked": {}
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let project = UnityProject::load(DefaultProjectIo::new(project_dir.into()))
+            .await
+            .unwrap();
+        let mut collection = PackageCollection::empty();
+        collection
+            .fetch_package_sources(&io, Some(&http), &project)
+            .await
+            .unwrap();
+        assert_eq!(collection.source_packages()[0].hash(), commit);
+    })
+}
+
+/// Serves the same content for any url.
+struct StaticClient(Vec<u8>);
+
+impl HttpClient for StaticClient {
+    async fn get(&self, _: &Url, _: &IndexMap<&str, &str>) -> io::Result<impl AsyncRead + Send> {
+        Ok(Cursor::new(self.0.clone()))
+    }
+
+    async fn get_with_etag(
+        &self,
+        _: &Url,
+        _: &IndexMap<Box<str>, Box<str>>,
+        _: Option<&str>,
+    ) -> io::Result<Option<(impl AsyncRead + Send, Option<Box<str>>)>> {
+        Ok(Some((Cursor::new(self.0.clone()), None)))
+    }
+}
+
+#[test]
+fn fetch_tarball_source() {
+    block_on(async {
+        let root = get_temp_path("package_source_tarball");
+        std::fs::remove_dir_all(&root).ok();
+
+        // npm style tarball with all files in `package` folder
+        let package = root.join("package");
+        std::fs::create_dir_all(package.join("Editor")).unwrap();
+        std::fs::write(
+            package.join("package.json"),
+            r#"{"name": "com.anatawa12.package", "version": "1.2.0"}"#,
+        )
+        .unwrap();
+        std::fs::write(package.join("Editor/Editor.cs"), "class Editor {}").unwrap();
+        let status = Command::new("tar")
+            .arg("-C")
+            .arg(&root)
+            .arg("-czf")
+            .arg(root.join("package.tgz"))
+            .arg("package")
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let tarball = std::fs::read(root.join("package.tgz")).unwrap();
+
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+        let project_dir = project.project_dir().to_owned();
+        let write_manifest = |locked: serde_json::Value| {
+            std::fs::write(
+                project_dir.join("Packages/vpm-manifest.json"),
+                serde_json::json!({
+                    "dependencies": {
+                        "com.anatawa12.package": {
+                            "version": "^1.0.0",
+                            "source": "https://example.com/package-1.2.0.tgz"
+                        }
+                    },
+                    "locked": locked,
+                })
+                .to_string(),
+            )
+            .unwrap();
+        };
+        write_manifest(serde_json::json!({}));
+        let project = UnityProject::load(DefaultProjectIo::new(project_dir.clone().into()))
+            .await
+            .unwrap();
+
+        let io = DefaultEnvironmentIo::new(root.join("env").into_boxed_path());
+        let http = StaticClient(tarball);
+        let mut collection = PackageCollection::empty();
+        collection
+            .fetch_package_sources(&io, Some(&http), &project)
+            .await
+            .unwrap();
+
+        let fetched = &collection.source_packages()[0];
+        assert_eq!(fetched.manifest().version(), &Version::new(1, 2, 0));
+        assert!(fetched.path().join("Editor/Editor.cs").exists());
+
+        // locked with another hash
+        write_manifest(serde_json::json!({
+            "com.anatawa12.package": {
+                "version": "1.2.0",
+                "source": "https://example.com/package-1.2.0.tgz",
+                "hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            }
+        }));
+        let project = UnityProject::load(DefaultProjectIo::new(project_dir.into()))
+            .await
+            .unwrap();
+        let mut collection = PackageCollection::empty();
+        assert!(
+            collection
+                .fetch_package_sources(&io, Some(&http), &project)
+                .await
+                .is_err()
+        );
+    })
+}
@@ -1,4 +1,5 @@
 use crate::common::*;
+use vrc_get_vpm::unity_project::UpmDependency;
 use vrc_get_vpm::version::{ReleaseType, UnityVersion, Version};
 
 mod common;
@@ -59,3 +60,72 @@ fn read_package_json_with_bad_url() {
         assert_eq!(package_json.name(), "com.anatawa12.package");
     })
 }
+
+#[test]
+fn read_dependencies_with_range() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.version", Version::new(1, 2, 0))
+            .add_dependency_range("com.anatawa12.range", ">=1.0.0 <3.0.0")
+            .build()
+            .await
+            .unwrap();
+
+        let dependencies = project
+            .dependencies_with_range()
+            .map(|(name, range)| (name, range.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            dependencies,
+            vec![
+                ("com.anatawa12.version", "1.2.0".to_string()),
+                ("com.anatawa12.range", ">=1.0.0 <3.0.0".to_string()),
+            ]
+        );
+        let (_, version) = (project.dependencies_with_range())
+            .find(|(name, _)| *name == "com.anatawa12.version")
+            .unwrap();
+        assert_eq!(version.as_single_version(), Some(Version::new(1, 2, 0)));
+    })
+}
+
+#[test]
+fn read_upm_dependencies() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_file(
+                "Packages/manifest.json",
+                r#"{
+                    "dependencies": {
+                        "com.unity.timeline": "1.7.6",
+                        "com.anatawa12.embedded": "file:../Embedded",
+                        "com.anatawa12.git": "https://github.com/anatawa12/example.git#v1.0.0"
+                    }
+                }"#,
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let mut dependencies = project.upm_dependencies().collect::<Vec<_>>();
+        dependencies.sort_by_key(|(name, _)| *name);
+
+        assert_eq!(dependencies.len(), 3);
+        assert_eq!(dependencies[0].0, "com.anatawa12.embedded");
+        assert!(
+            matches!(dependencies[0].1, UpmDependency::OtherNotation(notation) if notation.as_ref() == "file:../Embedded")
+        );
+        assert_eq!(dependencies[1].0, "com.anatawa12.git");
+        assert_eq!(
+            dependencies[1].1.to_string(),
+            "https://github.com/anatawa12/example.git#v1.0.0"
+        );
+        assert_eq!(dependencies[2].0, "com.unity.timeline");
+        assert!(
+            matches!(dependencies[2].1, UpmDependency::Version(version) if *version == Version::new(1, 7, 6))
+        );
+        assert!(project.has_upm_package("com.unity.timeline"));
+        assert!(!project.has_upm_package("com.anatawa12.version"));
+    })
+}
@@ -0,0 +1,155 @@
+use crate::common::*;
+use async_zip::base::read::seek::ZipFileReader;
+use futures::io::Cursor;
+use serde_json::json;
+use vrc_get_vpm::io::{DefaultProjectIo, IoTrait};
+use vrc_get_vpm::repository::{PackageZip, RemoteRepository, RepositoryBuilder};
+use vrc_get_vpm::version::Version;
+
+mod common;
+
+fn write_package(root: &std::path::Path, version: &str) {
+    std::fs::remove_dir_all(root).ok();
+    std::fs::create_dir_all(root.join("Editor")).unwrap();
+    std::fs::create_dir_all(root.join("Samples~")).unwrap();
+    std::fs::create_dir_all(root.join(".git")).unwrap();
+    std::fs::write(
+        root.join("package.json"),
+        format!(r#"{{"name": "com.anatawa12.package", "version": "{version}", "displayName": "Package"}}"#),
+    )
+    .unwrap();
+    std::fs::write(root.join("Editor/Editor.cs"), "class Editor {}").unwrap();
+    std::fs::write(root.join("Samples~/Sample.cs"), "class Sample {}").unwrap();
+    std::fs::write(root.join(".git/HEAD"), "ref: refs/heads/master").unwrap();
+}
+
+async fn create_zip(root: &std::path::Path, zip_name: &str) -> PackageZip {
+    let io = DefaultProjectIo::new(root.join("package").into_boxed_path());
+    let zip_io = DefaultProjectIo::new(root.into());
+    let zip_file = zip_io.create(zip_name.as_ref()).await.unwrap();
+    PackageZip::create(&io, zip_file).await.unwrap()
+}
+
+#[test]
+fn create_and_read_zip() {
+    block_on(async {
+        let root = get_temp_path("repository_builder_zip");
+        write_package(&root.join("package"), "1.0.0");
+
+        let created = create_zip(&root, "first.zip").await;
+        let recreated = create_zip(&root, "second.zip").await;
+        assert_eq!(created.sha256(), recreated.sha256());
+
+        let zip_io = DefaultProjectIo::new(root.clone().into_boxed_path());
+        let read = PackageZip::read(zip_io.open("first.zip".as_ref()).await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(read.sha256(), created.sha256());
+        assert_eq!(read.manifest().name(), "com.anatawa12.package");
+        assert_eq!(read.manifest().version(), &Version::new(1, 0, 0));
+        assert_eq!(read.json().get("displayName"), Some(&json!("Package")));
+    })
+}
+
+#[test]
+fn create_zip_excludes_hidden_folders() {
+    block_on(async {
+        let root = get_temp_path("repository_builder_hidden");
+        write_package(&root.join("package"), "1.0.0");
+        create_zip(&root, "package.zip").await;
+
+        let zip = Cursor::new(std::fs::read(root.join("package.zip")).unwrap());
+        let reader = ZipFileReader::new(zip).await.unwrap();
+        let mut entries = (reader.file().entries().iter())
+            .map(|x| x.filename().as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        entries.sort();
+
+        assert_eq!(entries, vec!["Editor/Editor.cs", "package.json"]);
+    })
+}
+
+#[test]
+fn build_and_merge() {
+    block_on(async {
+        let root = get_temp_path("repository_builder_merge");
+        write_package(&root.join("package"), "1.0.0");
+        let old = create_zip(&root, "old.zip").await;
+
+        let mut builder = RepositoryBuilder::new();
+        builder.set_name("Test Repository");
+        builder.set_id("com.anatawa12.repository");
+        builder.add_package(&old, "https://example.com/1.0.0.zip");
+        let repo = builder.build().unwrap();
+
+        write_package(&root.join("package"), "1.1.0");
+        let new = create_zip(&root, "new.zip").await;
+
+        let json = serde_json::to_vec(&repo).unwrap();
+        let repo = serde_json::from_slice::<RemoteRepository>(&json).unwrap();
+        let mut builder = RepositoryBuilder::from_repository(&repo).unwrap();
+        assert!(
+            builder
+                .add_package(&new, "https://example.com/1.1.0.zip")
+                .is_none()
+        );
+        let repo = builder.build().unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&repo).unwrap(),
+            json!({
+                "name": "Test Repository",
+                "id": "com.anatawa12.repository",
+                "packages": {
+                    "com.anatawa12.package": {
+                        "versions": {
+                            "1.0.0": {
+                                "name": "com.anatawa12.package",
+                                "version": "1.0.0",
+                                "displayName": "Package",
+                                "url": "https://example.com/1.0.0.zip",
+                                "zipSHA256": hex::encode(old.sha256()),
+                            },
+                            "1.1.0": {
+                                "name": "com.anatawa12.package",
+                                "version": "1.1.0",
+                                "displayName": "Package",
+                                "url": "https://example.com/1.1.0.zip",
+                                "zipSHA256": hex::encode(new.sha256()),
+                            },
+                        }
+                    }
+                }
+            })
+        );
+        assert_eq!(repo.get_versions_of("com.anatawa12.package").count(), 2);
+    })
+}
+
+#[test]
+fn create_zip_validates_manifest() {
+    block_on(async {
+        let root = get_temp_path("repository_builder_validate");
+        let package = root.join("package");
+        write_package(&package, "1.0.0");
+
+        std::fs::write(
+            package.join("package.json"),
+            r#"{"name": "com.anatawa12.package", "version": "1.0.0", "vrc-get": {"yanked": "broken", "aliases": ["Package"]}}"#,
+        )
+        .unwrap();
+        let created = create_zip(&root, "valid.zip").await;
+        assert!(created.manifest().is_yanked());
+        assert_eq!(created.manifest().aliases(), &["Package".into()]);
+
+        std::fs::write(
+            package.join("package.json"),
+            r#"{"name": "com.anatawa12.package", "version": "1.0.0", "vrc-get": {"yanked": 1}}"#,
+        )
+        .unwrap();
+        let io = DefaultProjectIo::new(package.into_boxed_path());
+        let zip_io = DefaultProjectIo::new(root.into_boxed_path());
+        let zip_file = zip_io.create("invalid.zip".as_ref()).await.unwrap();
+        assert!(PackageZip::create(&io, zip_file).await.is_err());
+    })
+}
@@ -0,0 +1,163 @@
+use crate::common::*;
+use futures::io::{AsyncRead, Cursor};
+use indexmap::IndexMap;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use std::collections::HashMap;
+use std::io;
+use std::str::FromStr;
+use url::Url;
+use vrc_get_vpm::HttpClient;
+use vrc_get_vpm::environment::{AddRepositoryErr, Settings, add_remote_repo};
+use vrc_get_vpm::io::DefaultEnvironmentIo;
+use vrc_get_vpm::repository::{RepositoryPublicKey, SignatureError, signature_url};
+
+mod common;
+
+/// The http client serving the files in the map
+struct FileServer(HashMap<String, Vec<u8>>);
+
+impl FileServer {
+    fn find(&self, url: &Url) -> io::Result<impl AsyncRead> {
+        (self.0.get(url.as_str()).cloned())
+            .map(Cursor::new)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, url.as_str()))
+    }
+}
+
+impl HttpClient for FileServer {
+    async fn get(&self, url: &Url, _: &IndexMap<&str, &str>) -> io::Result<impl AsyncRead> {
+        self.find(url)
+    }
+
+    async fn get_with_etag(
+        &self,
+        url: &Url,
+        _: &IndexMap<Box<str>, Box<str>>,
+        _: Option<&str>,
+    ) -> io::Result<Option<(impl AsyncRead, Option<Box<str>>)>> {
+        Ok(Some((self.find(url)?, None)))
+    }
+}
+
+fn key_pair() -> Ed25519KeyPair {
+    Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap()
+}
+
+fn encode(bytes: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+#[test]
+fn verify_signature() {
+    let key_pair = key_pair();
+    let public_key =
+        RepositoryPublicKey::from_str(&encode(key_pair.public_key().as_ref())).unwrap();
+    assert_eq!(
+        public_key.to_string(),
+        encode(key_pair.public_key().as_ref())
+    );
+
+    let json = br#"{"name":"Test Repository","packages":{}}"#;
+    let signature = encode(key_pair.sign(json).as_ref());
+
+    public_key
+        .verify(json, format!("{signature}\n").as_bytes())
+        .unwrap();
+    assert!(matches!(
+        public_key.verify(
+            br#"{"name":"Evil Repository","packages":{}}"#,
+            signature.as_bytes()
+        ),
+        Err(SignatureError::Mismatch)
+    ));
+    assert!(matches!(
+        public_key.verify(json, b"not a signature"),
+        Err(SignatureError::InvalidSignature)
+    ));
+}
+
+#[test]
+fn invalid_public_key() {
+    assert!(matches!(
+        RepositoryPublicKey::from_str("not a key"),
+        Err(SignatureError::InvalidPublicKey)
+    ));
+    assert!(matches!(
+        RepositoryPublicKey::from_str(&encode(&[0; 16])),
+        Err(SignatureError::InvalidPublicKey)
+    ));
+}
+
+#[test]
+fn signature_url_appends_sig() {
+    let url = Url::parse("https://example.com/vpm/index.json?download").unwrap();
+    assert_eq!(
+        signature_url(&url).as_str(),
+        "https://example.com/vpm/index.json.sig?download"
+    );
+}
+
+#[test]
+fn add_repository_with_public_key() {
+    block_on(async {
+        let key_pair = key_pair();
+        let public_key = encode(key_pair.public_key().as_ref());
+        let other_key = encode(
+            Ed25519KeyPair::from_seed_unchecked(&[8; 32])
+                .unwrap()
+                .public_key()
+                .as_ref(),
+        );
+
+        let url = Url::parse("https://example.com/vpm/index.json").unwrap();
+        let json = br#"{"name":"Test Repository","id":"com.anatawa12.repository","packages":{}}"#;
+        let http = FileServer(HashMap::from([
+            (url.to_string(), json.to_vec()),
+            (
+                signature_url(&url).to_string(),
+                encode(key_pair.sign(json).as_ref()).into_bytes(),
+            ),
+        ]));
+
+        let root = get_temp_path("repository_signature_add");
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::create_dir_all(&root).unwrap();
+        let io = DefaultEnvironmentIo::new(root.clone().into_boxed_path());
+        let mut settings = Settings::load(&io).await.unwrap();
+
+        let result = add_remote_repo(
+            &mut settings,
+            url.clone(),
+            None,
+            IndexMap::new(),
+            Some(&other_key),
+            &io,
+            &http,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(AddRepositoryErr::Signature(SignatureError::Mismatch))
+        ));
+        assert!(settings.get_user_repos().is_empty());
+        assert!(!root.join("Repos").exists());
+
+        add_remote_repo(
+            &mut settings,
+            url.clone(),
+            None,
+            IndexMap::new(),
+            Some(&public_key),
+            &io,
+            &http,
+        )
+        .await
+        .unwrap();
+        let [repo] = settings.get_user_repos() else {
+            panic!("repository is not added");
+        };
+        assert_eq!(repo.public_key(), Some(public_key.as_str()));
+        assert_eq!(std::fs::read_dir(root.join("Repos")).unwrap().count(), 1);
+    })
+}
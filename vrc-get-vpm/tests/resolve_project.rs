@@ -1,5 +1,6 @@
 use crate::common::*;
 use vrc_get_vpm::PackageManifest;
+use vrc_get_vpm::unity_project::{FrozenLockViolation, ResolvePackageErr};
 use vrc_get_vpm::version::Version;
 
 mod common;
@@ -342,3 +343,167 @@ fn dependencies_of_unlocked_package_require_newer_version_than_locked_version()
         assert_installing_to_locked_only(&result, &library2);
     })
 }
+
+#[test]
+fn frozen_resolve_fully_locked() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency_range("com.anatawa12.package", "^1.0.0")
+            .add_locked(
+                "com.anatawa12.package",
+                Version::new(1, 0, 0),
+                &[("com.anatawa12.library", "^1.0.0")],
+            )
+            .add_locked("com.anatawa12.library", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        // newer versions must not be used in frozen mode
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.anatawa12.package", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.library", "^1.0.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.package", Version::new(1, 1, 0))
+                    .add_vpm_dependency("com.anatawa12.library", "^1.0.0"),
+            )
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(1, 0, 0),
+            ))
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(1, 1, 0),
+            ))
+            .build();
+
+        let result = project.resolve_request_frozen(&collection).await.unwrap();
+
+        assert_eq!(result.package_changes().len(), 2);
+        assert_eq!(result.remove_legacy_folders().len(), 0);
+        assert_eq!(result.remove_legacy_files().len(), 0);
+        assert_eq!(result.conflicts().len(), 0);
+
+        let package = collection.get_package("com.anatawa12.package", Version::new(1, 0, 0));
+        let library = collection.get_package("com.anatawa12.library", Version::new(1, 0, 0));
+        assert_install_only(&result, &package);
+        assert_install_only(&result, &library);
+    })
+}
+
+#[test]
+fn frozen_resolve_locked_not_found() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.package", Version::new(1, 0, 0))
+            .add_locked("com.anatawa12.package", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(PackageManifest::new(
+                "com.anatawa12.package",
+                Version::new(1, 1, 0),
+            ))
+            .build();
+
+        let err = project
+            .resolve_request_frozen(&collection)
+            .await
+            .unwrap_err();
+
+        let ResolvePackageErr::FrozenLockViolation { violations } = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(
+            &violations[0],
+            FrozenLockViolation::LockedNotFound { package, version }
+                if package.as_ref() == "com.anatawa12.package" && *version == Version::new(1, 0, 0)
+        ));
+    })
+}
+
+#[test]
+fn frozen_resolve_range_not_satisfied() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.package", Version::new(1, 0, 0))
+            .add_locked(
+                "com.anatawa12.package",
+                Version::new(1, 0, 0),
+                &[("com.anatawa12.library", "^1.1.0")],
+            )
+            .add_locked("com.anatawa12.library", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.anatawa12.package", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.library", "^1.1.0"),
+            )
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(1, 0, 0),
+            ))
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(1, 1, 0),
+            ))
+            .build();
+
+        let err = project
+            .resolve_request_frozen(&collection)
+            .await
+            .unwrap_err();
+
+        let ResolvePackageErr::FrozenLockViolation { violations } = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(
+            &violations[0],
+            FrozenLockViolation::RangeNotSatisfied { package, required_by: Some(required_by), .. }
+                if package.as_ref() == "com.anatawa12.library"
+                    && required_by.as_ref() == "com.anatawa12.package"
+        ));
+    })
+}
+
+#[test]
+fn frozen_resolve_not_locked_dependency() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency_range("com.anatawa12.package", "^1.0.0")
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(PackageManifest::new(
+                "com.anatawa12.package",
+                Version::new(1, 0, 0),
+            ))
+            .build();
+
+        let err = project
+            .resolve_request_frozen(&collection)
+            .await
+            .unwrap_err();
+
+        let ResolvePackageErr::FrozenLockViolation { violations } = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(
+            &violations[0],
+            FrozenLockViolation::NotLocked { package, required_by: None, .. }
+                if package.as_ref() == "com.anatawa12.package"
+        ));
+    })
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use crate::common::*;
+use serde_json::json;
+use std::path::Path;
+use vrc_get_vpm::environment::{PackageCollection, Settings};
+use vrc_get_vpm::io::DefaultEnvironmentIo;
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::{PackageCollection as _, ScopedRegistry, VersionSelector};
+
+#[test]
+fn scope_matching() {
+    let rule = ScopedRegistry::new(
+        "com.ourstudio.vpm".into(),
+        vec!["com.ourstudio.*".into(), "com.vrchat.avatars".into()],
+    );
+
+    assert!(rule.matches("com.ourstudio.tools"));
+    assert!(rule.matches("com.vrchat.avatars"));
+    assert!(!rule.matches("com.vrchat.avatars.extra"));
+    assert!(!rule.matches("com.other.tools"));
+
+    let url = "https://vpm.ourstudio.com/index.json".parse().unwrap();
+    assert!(rule.is_repository(Some("com.ourstudio.vpm"), None));
+    assert!(!rule.is_repository(Some("com.squatter.vpm"), Some(&url)));
+
+    let rule = ScopedRegistry::new(url.as_str().into(), vec!["com.ourstudio.*".into()]);
+    assert!(rule.is_repository(None, Some(&url)));
+}
+
+fn write_repository(path: &Path, id: &str, version: &str) {
+    let repo = json!({
+        "repo": {
+            "name": id,
+            "id": id,
+            "url": format!("https://{id}/index.json"),
+            "packages": {
+                "com.ourstudio.tools": {
+                    "versions": {
+                        version: {
+                            "name": "com.ourstudio.tools",
+                            "version": version,
+                            "url": format!("https://{id}/tools-{version}.zip"),
+                        }
+                    }
+                }
+            }
+        }
+    });
+    std::fs::write(path, serde_json::to_vec(&repo).unwrap()).unwrap();
+}
+
+/// Creates the environment with two repositories providing `com.ourstudio.tools`.
+fn create_environment(name: &str) -> DefaultEnvironmentIo {
+    let root = get_temp_path(name);
+    let repos = root.join("Repos");
+    std::fs::create_dir_all(&repos).unwrap();
+    write_repository(&repos.join("ourstudio.json"), "vpm.ourstudio.com", "1.0.0");
+    write_repository(&repos.join("squatter.json"), "vpm.squatter.com", "2.0.0");
+
+    let settings = json!({
+        "userRepos": [
+            {
+                "localPath": repos.join("ourstudio.json"),
+                "url": "https://vpm.ourstudio.com/index.json",
+                "id": "vpm.ourstudio.com",
+            },
+            {
+                "localPath": repos.join("squatter.json"),
+                "url": "https://vpm.squatter.com/index.json",
+                "id": "vpm.squatter.com",
+            },
+        ],
+    });
+    std::fs::write(
+        root.join("settings.json"),
+        serde_json::to_vec(&settings).unwrap(),
+    )
+    .unwrap();
+
+    DefaultEnvironmentIo::new(root.into_boxed_path())
+}
+
+#[test]
+fn scoped_registries_restrict_repositories() {
+    block_on(async {
+        let io = create_environment("scoped_registries");
+        let settings = Settings::load(&io).await.unwrap();
+        let mut collection = PackageCollection::load_cache(&settings, &io).await.unwrap();
+
+        // without rules, the newest version from any repository wins
+        let found = collection
+            .find_package_by_name(
+                "com.ourstudio.tools",
+                VersionSelector::latest_for(None, true),
+            )
+            .unwrap();
+        assert_eq!(found.version(), &Version::new(2, 0, 0));
+
+        collection.add_scoped_registries(&[ScopedRegistry::new(
+            "vpm.ourstudio.com".into(),
+            vec!["com.ourstudio.*".into()],
+        )]);
+
+        let found = collection
+            .find_package_by_name(
+                "com.ourstudio.tools",
+                VersionSelector::latest_for(None, true),
+            )
+            .unwrap();
+        assert_eq!(found.version(), &Version::new(1, 0, 0));
+        assert_eq!(found.repo().unwrap().id(), Some("vpm.ourstudio.com"));
+
+        let versions = collection
+            .find_packages("com.ourstudio.tools")
+            .map(|x| x.version().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec![Version::new(1, 0, 0)]);
+        assert_eq!(collection.get_all_packages().count(), 1);
+
+        let out_of_scope = collection
+            .find_packages_out_of_scope("com.ourstudio.tools")
+            .collect::<Vec<_>>();
+        assert_eq!(out_of_scope.len(), 1);
+        assert_eq!(
+            out_of_scope[0].repo().unwrap().id(),
+            Some("vpm.squatter.com")
+        );
+    })
+}
+
+#[test]
+fn project_scoped_registries_are_applied() {
+    block_on(async {
+        let io = create_environment("project_scoped_registries");
+        let settings = Settings::load(&io).await.unwrap();
+        let collection = PackageCollection::load_cache(&settings, &io).await.unwrap();
+
+        let project = VirtualProjectBuilder::new()
+            .add_file(
+                "Packages/vpm-manifest.json",
+                json!({
+                    "dependencies": {
+                        "com.ourstudio.tools": { "version": ">=1.0.0" },
+                    },
+                    "locked": {},
+                    "scopedRegistries": [
+                        {
+                            "repository": "vpm.ourstudio.com",
+                            "scopes": ["com.ourstudio.*"],
+                        },
+                    ],
+                })
+                .to_string(),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        // the rules of the project are applied without adding them to the collection
+        let found = project
+            .find_package_by_name(
+                &collection,
+                "com.ourstudio.tools",
+                VersionSelector::latest_for(None, true),
+            )
+            .unwrap();
+        assert_eq!(found.version(), &Version::new(1, 0, 0));
+
+        let result = project.resolve_request(&collection).await.unwrap();
+        let installing = result.package_changes()["com.ourstudio.tools"]
+            .as_install()
+            .and_then(|x| x.install_package())
+            .unwrap();
+        assert_eq!(installing.version(), &Version::new(1, 0, 0));
+        assert_eq!(installing.repo().unwrap().id(), Some("vpm.ourstudio.com"));
+    })
+}
@@ -0,0 +1,161 @@
+use crate::common::*;
+use serde_json::json;
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::io::DefaultProjectIo;
+use vrc_get_vpm::version::Version;
+
+mod common;
+
+const TEMP_DIR: &str = "Temp/vrc-get/00000000-0000-0000-0000-000000000000";
+
+fn package_json(version: &str) -> String {
+    json!({"name": "com.anatawa12.package", "version": version}).to_string()
+}
+
+fn locked_manifest(version: &str) -> String {
+    json!({
+        "dependencies": {"com.anatawa12.package": {"version": version}},
+        "locked": {"com.anatawa12.package": {"version": version, "dependencies": {}}},
+    })
+    .to_string()
+}
+
+#[test]
+fn roll_back_interrupted_changes() {
+    block_on(async {
+        // killed after installing 2.0.0 and saving vpm-manifest.json
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.package", Version::new(2, 0, 0))
+            .add_locked("com.anatawa12.package", Version::new(2, 0, 0), &[])
+            .add_package_json("com.anatawa12.package", package_json("2.0.0"))
+            .add_file(
+                format!("{TEMP_DIR}/remove/com.anatawa12.package/package.json"),
+                package_json("1.0.0"),
+            )
+            .add_dir(format!("{TEMP_DIR}/install"))
+            .add_file(
+                "Library/vrc-get/transaction-manifests/vpm-manifest.json",
+                locked_manifest("1.0.0"),
+            )
+            .add_file(
+                "Library/vrc-get/transaction.json",
+                json!({
+                    "tempDir": TEMP_DIR,
+                    "phase": "applying",
+                    "installs": ["com.anatawa12.package"],
+                    "uninstalls": ["com.anatawa12.package"],
+                    "removeLegacyFiles": ["Assets/Legacy.cs"],
+                    "removeLegacyFolders": [],
+                })
+                .to_string(),
+            )
+            .add_file("Assets/Legacy.cs", "class Legacy {}")
+            .build()
+            .await
+            .unwrap();
+
+        let locked = project.get_locked("com.anatawa12.package").unwrap();
+        assert_eq!(locked.version(), &Version::new(1, 0, 0));
+
+        let installed = project.get_installed_package("com.anatawa12.package");
+        assert_eq!(installed.unwrap().version(), &Version::new(1, 0, 0));
+
+        let dir = project.project_dir();
+        assert!(!dir.join("Library/vrc-get/transaction.json").exists());
+        assert!(!dir.join(TEMP_DIR).exists());
+        assert!(!dir.join("Library/vrc-get/transaction-manifests").exists());
+        // legacy assets are removed only after the changes are committed
+        assert!(dir.join("Assets/Legacy.cs").exists());
+    })
+}
+
+#[test]
+fn complete_committed_changes() {
+    block_on(async {
+        // killed after saving manifests but before removing legacy assets
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.package", Version::new(2, 0, 0))
+            .add_locked("com.anatawa12.package", Version::new(2, 0, 0), &[])
+            .add_package_json("com.anatawa12.package", package_json("2.0.0"))
+            .add_file(
+                format!("{TEMP_DIR}/remove/com.anatawa12.package/package.json"),
+                package_json("1.0.0"),
+            )
+            .add_file(
+                "Library/vrc-get/transaction.json",
+                json!({
+                    "tempDir": TEMP_DIR,
+                    "phase": "committed",
+                    "installs": ["com.anatawa12.package"],
+                    "uninstalls": ["com.anatawa12.package"],
+                    "removeLegacyFiles": ["Assets/Legacy.cs"],
+                    "removeLegacyFolders": [],
+                })
+                .to_string(),
+            )
+            .add_file("Assets/Legacy.cs", "class Legacy {}")
+            .add_file("Assets/Legacy.cs.meta", "guid: 0")
+            .build()
+            .await
+            .unwrap();
+
+        let locked = project.get_locked("com.anatawa12.package").unwrap();
+        assert_eq!(locked.version(), &Version::new(2, 0, 0));
+
+        let dir = project.project_dir();
+        assert!(!dir.join("Library/vrc-get/transaction.json").exists());
+        assert!(!dir.join(TEMP_DIR).exists());
+        assert!(!dir.join("Assets/Legacy.cs").exists());
+        assert!(!dir.join("Assets/Legacy.cs.meta").exists());
+    })
+}
+
+#[test]
+fn keep_changes_being_applied() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.package", Version::new(1, 0, 0))
+            .add_locked("com.anatawa12.package", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.package", package_json("1.0.0"))
+            .build()
+            .await
+            .unwrap();
+        let dir = project.project_dir().to_owned();
+        // loading the project does not write anything without interrupted changes
+        assert!(!dir.join("Library/vrc-get").exists());
+
+        // another process is installing 2.0.0
+        let journal = dir.join("Library/vrc-get/transaction.json");
+        std::fs::create_dir_all(dir.join(TEMP_DIR).join("install")).unwrap();
+        std::fs::create_dir_all(dir.join("Library/vrc-get")).unwrap();
+        std::fs::write(
+            &journal,
+            json!({
+                "tempDir": TEMP_DIR,
+                "phase": "applying",
+                "installs": ["com.anatawa12.package"],
+                "uninstalls": ["com.anatawa12.package"],
+                "removeLegacyFiles": [],
+                "removeLegacyFolders": [],
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let lock = std::fs::File::create(dir.join("Library/vrc-get/transaction.lock")).unwrap();
+        lock.try_lock().unwrap();
+
+        UnityProject::load(DefaultProjectIo::new(dir.clone().into()))
+            .await
+            .unwrap();
+        assert!(journal.exists());
+        assert!(dir.join(TEMP_DIR).exists());
+
+        // the process is killed
+        drop(lock);
+        UnityProject::load(DefaultProjectIo::new(dir.clone().into()))
+            .await
+            .unwrap();
+        assert!(!journal.exists());
+        assert!(!dir.join(TEMP_DIR).exists());
+    })
+}
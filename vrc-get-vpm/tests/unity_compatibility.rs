@@ -0,0 +1,111 @@
+use crate::common::*;
+use vrc_get_vpm::PackageManifest;
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::unity_project::{CompatibilityStatus, UnityCompatibility};
+use vrc_get_vpm::version::{UnityVersion, Version};
+
+mod common;
+
+const UNITY_2019: UnityVersion = UnityVersion::new_f1(2019, 4, 31);
+const UNITY_2022: UnityVersion = UnityVersion::new_f1(2022, 3, 22);
+
+async fn vrcsdk_project() -> UnityProject {
+    VirtualProjectBuilder::new()
+        .add_dependency("com.vrchat.worlds", Version::new(3, 4, 2))
+        .add_locked(
+            "com.vrchat.worlds",
+            Version::new(3, 4, 2),
+            &[("com.vrchat.base", "3.4.2")],
+        )
+        .add_locked("com.vrchat.base", Version::new(3, 4, 2), &[])
+        .add_locked(
+            "com.anatawa12.not-in-repository",
+            Version::new(1, 0, 0),
+            &[],
+        )
+        .build()
+        .await
+        .unwrap()
+}
+
+fn status<'a>(result: &'a UnityCompatibility, name: &str) -> &'a CompatibilityStatus<'a> {
+    (result.packages().iter())
+        .find(|x| x.name() == name)
+        .unwrap()
+        .status()
+}
+
+#[test]
+fn vrcsdk_for_2019_is_changed_for_2022() {
+    block_on(async {
+        let project = vrcsdk_project().await;
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.vrchat.worlds", Version::new(3, 4, 2))
+                    .add_vpm_dependency("com.vrchat.base", "3.4.2"),
+            )
+            .add(
+                PackageManifest::new("com.vrchat.worlds", Version::new(3, 5, 0))
+                    .add_vpm_dependency("com.vrchat.base", "3.5.0"),
+            )
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(3, 4, 2),
+            ))
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(3, 5, 0),
+            ))
+            .build();
+
+        let result = project.unity_compatibility(&collection, UNITY_2019);
+        assert!(result.is_compatible());
+        assert!(!result.needs_changes());
+
+        let result = project.unity_compatibility(&collection, UNITY_2022);
+        assert!(result.is_compatible());
+        assert!(result.needs_changes());
+        assert!(result.conflicts().is_empty());
+        assert!(result.new_packages().is_empty());
+        for name in ["com.vrchat.worlds", "com.vrchat.base"] {
+            assert!(matches!(
+                status(&result, name),
+                CompatibilityStatus::Change(package) if package.version() == &Version::new(3, 5, 0)
+            ));
+        }
+        assert!(matches!(
+            status(&result, "com.anatawa12.not-in-repository"),
+            CompatibilityStatus::Unknown
+        ));
+    })
+}
+
+#[test]
+fn no_compatible_version() {
+    block_on(async {
+        let project = vrcsdk_project().await;
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.vrchat.worlds", Version::new(3, 4, 2))
+                    .add_vpm_dependency("com.vrchat.base", "3.4.2"),
+            )
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(3, 4, 2),
+            ))
+            .build();
+
+        let result = project.unity_compatibility(&collection, UNITY_2022);
+        assert!(!result.is_compatible());
+        assert!(matches!(
+            status(&result, "com.vrchat.worlds"),
+            CompatibilityStatus::NoCompatibleVersion
+        ));
+        assert!(matches!(
+            status(&result, "com.vrchat.base"),
+            CompatibilityStatus::NoCompatibleVersion
+        ));
+    })
+}
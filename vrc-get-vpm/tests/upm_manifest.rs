@@ -0,0 +1,160 @@
+use crate::common::*;
+use serde_json::{Value, json};
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::unity_project::UpmDependency;
+use vrc_get_vpm::version::Version;
+
+mod common;
+
+const REGISTRY: &str = "https://package.openupm.com";
+
+fn read_manifest(project: &UnityProject) -> Value {
+    let path = project.project_dir().join("Packages/manifest.json");
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn add_and_remove_dependencies() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_file(
+                "Packages/manifest.json",
+                json!({
+                    "dependencies": {"com.unity.timeline": "1.7.6"},
+                    "testables": ["com.unity.timeline"],
+                })
+                .to_string(),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        project
+            .add_upm_dependency(
+                "com.example.registry",
+                "1.0.0".parse().unwrap(),
+                Some(("OpenUPM", REGISTRY)),
+            )
+            .unwrap();
+        project
+            .add_upm_dependency(
+                "com.example.other",
+                "2.0.0".parse().unwrap(),
+                Some(("OpenUPM", REGISTRY)),
+            )
+            .unwrap();
+        project
+            .add_upm_dependency(
+                "com.example.git",
+                "https://github.com/example/package.git#v1.0.0"
+                    .parse()
+                    .unwrap(),
+                None,
+            )
+            .unwrap();
+        project.save().await.unwrap();
+
+        assert_eq!(
+            read_manifest(&project),
+            json!({
+                "dependencies": {
+                    "com.unity.timeline": "1.7.6",
+                    "com.example.registry": "1.0.0",
+                    "com.example.other": "2.0.0",
+                    "com.example.git": "https://github.com/example/package.git#v1.0.0",
+                },
+                "testables": ["com.unity.timeline"],
+                "scopedRegistries": [{
+                    "name": "OpenUPM",
+                    "url": REGISTRY,
+                    "scopes": ["com.example.registry", "com.example.other"],
+                }],
+            })
+        );
+        assert!(matches!(
+            project.get_upm_dependency("com.example.registry"),
+            Some(UpmDependency::Version(version)) if version == &Version::new(1, 0, 0)
+        ));
+        assert_eq!(project.upm_scoped_registries()[0].scopes().len(), 2);
+
+        assert!(project.remove_upm_dependency("com.example.registry"));
+        assert!(project.remove_upm_dependency("com.example.other"));
+        assert!(!project.remove_upm_dependency("com.example.not-found"));
+        project.save().await.unwrap();
+
+        assert_eq!(
+            read_manifest(&project),
+            json!({
+                "dependencies": {
+                    "com.unity.timeline": "1.7.6",
+                    "com.example.git": "https://github.com/example/package.git#v1.0.0",
+                },
+                "testables": ["com.unity.timeline"],
+            })
+        );
+        assert!(project.upm_scoped_registries().is_empty());
+    })
+}
+
+#[test]
+fn scoped_registries_are_shared() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_file(
+                "Packages/manifest.json",
+                json!({
+                    "dependencies": {"com.example.tools.extra": "1.0.0"},
+                    "scopedRegistries": [{
+                        "name": "OpenUPM",
+                        "url": REGISTRY,
+                        "scopes": ["com.example.tools"],
+                    }],
+                })
+                .to_string(),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        // the trailing slash is ignored to find the registry
+        project
+            .add_upm_dependency(
+                "com.example.tools",
+                "1.0.0".parse().unwrap(),
+                Some(("OpenUPM", &format!("{REGISTRY}/"))),
+            )
+            .unwrap();
+        assert_eq!(project.upm_scoped_registries().len(), 1);
+
+        // the scope is still used by com.example.tools.extra
+        assert!(project.remove_upm_dependency("com.example.tools"));
+        project.save().await.unwrap();
+
+        assert_eq!(
+            read_manifest(&project),
+            json!({
+                "dependencies": {"com.example.tools.extra": "1.0.0"},
+                "scopedRegistries": [{
+                    "name": "OpenUPM",
+                    "url": REGISTRY,
+                    "scopes": ["com.example.tools"],
+                }],
+            })
+        );
+    })
+}
+
+#[test]
+fn vpm_package_is_detected() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.example.package", Version::new(1, 0, 0))
+            .add_locked("com.example.package", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        assert!(project.is_vpm_package("com.example.package"));
+        assert!(!project.is_vpm_package("com.example.other"));
+    })
+}
@@ -0,0 +1,137 @@
+use crate::common::*;
+use std::io;
+use std::path::Path;
+use vrc_get_vpm::io::{DefaultProjectIo, IoTrait};
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::{AbortCheck, PackageInfo, PackageInstaller, PackageManifest};
+
+mod common;
+
+const PACKAGE_JSON: &str = r#"{"name":"com.anatawa12.package","version":"1.0.0"}"#;
+
+/// Installs the package with fixed contents.
+struct FixedInstaller;
+
+impl PackageInstaller for FixedInstaller {
+    async fn install_package(
+        &self,
+        io: &DefaultProjectIo,
+        _: PackageInfo<'_>,
+        dest_dir: &Path,
+        _: &AbortCheck,
+    ) -> io::Result<()> {
+        io.create_dir_all(&dest_dir.join("Runtime")).await?;
+        io.write(&dest_dir.join("package.json"), PACKAGE_JSON.as_bytes())
+            .await?;
+        io.write(&dest_dir.join("Runtime/Script.cs"), b"class Script {}")
+            .await?;
+        io.write(&dest_dir.join("Runtime/Other.cs"), b"class Other {}")
+            .await?;
+        Ok(())
+    }
+}
+
+fn collection() -> PackageCollection {
+    PackageCollectionBuilder::new()
+        .add(PackageManifest::new(
+            "com.anatawa12.package",
+            Version::new(1, 0, 0),
+        ))
+        .build()
+}
+
+#[test]
+fn intact_package() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.package", Version::new(1, 0, 0))
+            .add_locked("com.anatawa12.package", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.package", PACKAGE_JSON)
+            .add_file(
+                "Packages/com.anatawa12.package/Runtime/Script.cs",
+                "class Script {}",
+            )
+            .add_file(
+                "Packages/com.anatawa12.package/Runtime/Other.cs",
+                "class Other {}",
+            )
+            // generated by unity
+            .add_file(
+                "Packages/com.anatawa12.package/Runtime/Other.cs.meta",
+                "guid: 0",
+            )
+            .add_file("Packages/com.anatawa12.package/Runtime.meta", "guid: 1")
+            .build()
+            .await
+            .unwrap();
+
+        let result = project
+            .verify_packages(&collection(), &FixedInstaller)
+            .await
+            .unwrap();
+
+        assert_eq!(result.packages().len(), 1);
+        assert!(result.packages()[0].is_intact());
+        assert_eq!(result.differing_packages().count(), 0);
+        assert!(result.not_found().is_empty());
+    })
+}
+
+#[test]
+fn modified_package() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.package", Version::new(1, 0, 0))
+            .add_locked("com.anatawa12.package", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.package", PACKAGE_JSON)
+            .add_file(
+                "Packages/com.anatawa12.package/Runtime/Script.cs",
+                "class Script { int edited; }",
+            )
+            .add_file(
+                "Packages/com.anatawa12.package/Runtime/Extra.cs",
+                "class Extra {}",
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let result = project
+            .verify_packages(&collection(), &FixedInstaller)
+            .await
+            .unwrap();
+
+        let package = &result.packages()[0];
+        assert_eq!(package.name(), "com.anatawa12.package");
+        assert_eq!(package.modified(), &["Runtime/Script.cs".into()]);
+        assert_eq!(package.missing(), &["Runtime/Other.cs".into()]);
+        assert_eq!(package.extra(), &["Runtime/Extra.cs".into()]);
+        assert_eq!(
+            result.differing_packages().collect::<Vec<_>>(),
+            vec!["com.anatawa12.package"]
+        );
+    })
+}
+
+#[test]
+fn package_not_found() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.package", Version::new(2, 0, 0))
+            .add_locked("com.anatawa12.package", Version::new(2, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let result = project
+            .verify_packages(&collection(), &FixedInstaller)
+            .await
+            .unwrap();
+
+        assert!(result.packages().is_empty());
+        assert_eq!(
+            result.not_found(),
+            &[("com.anatawa12.package".into(), Version::new(2, 0, 0))]
+        );
+    })
+}
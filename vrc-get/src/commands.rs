@@ -4,6 +4,8 @@ use itertools::Itertools;
 
 use futures::future::join_all;
 use log::warn;
+use output::{ErrorDetails, OutputArgs, StructuredError};
+use plan::PlanArgs;
 use reqwest::Url;
 use reqwest::header::{HeaderName, HeaderValue, InvalidHeaderName, InvalidHeaderValue};
 use serde::Serialize;
@@ -23,10 +25,10 @@ use vrc_get_vpm::environment::{
 };
 use vrc_get_vpm::io::{DefaultEnvironmentIo, DefaultProjectIo, IoTrait};
 use vrc_get_vpm::repositories_file::RepositoriesFile;
-use vrc_get_vpm::repository::RemoteRepository;
+use vrc_get_vpm::repository::{RemoteRepository, RepositoryPublicKey};
 use vrc_get_vpm::unity_project::pending_project_changes::{PackageChange, RemoveReason};
 use vrc_get_vpm::unity_project::{AddPackageOperation, PendingProjectChanges};
-use vrc_get_vpm::version::Version;
+use vrc_get_vpm::version::{UnityVersion, Version, VersionRange};
 use vrc_get_vpm::{
     PackageCollection as _, PackageInfo, PackageManifest, UnityProject, UserRepoSetting,
     VersionSelector,
@@ -69,15 +71,20 @@ macro_rules! multi_command {
 // small wrapper utilities
 
 macro_rules! exit_with {
-    ($($tt:tt)*) => {{
-        eprintln!($($tt)*);
-        ::std::process::exit(1)
-    }};
+    ($($tt:tt)*) => {
+        $crate::commands::output::exit_with_error(
+            None,
+            &format!($($tt)*),
+            $crate::commands::output::ErrorDetails::Other,
+        )
+    };
 }
 
 #[derive(Args, Default)]
 struct EnvArgs {
     /// do not connect to remote servers, use local caches only. implicitly --no-update
+    ///
+    /// When installing packages, only the packages in the package cache will be used.
     #[arg(long)]
     offline: bool,
     /// do not update local repository cache.
@@ -106,6 +113,72 @@ async fn load_collection(
     collection
 }
 
+/// Loads the collection to resolve packages to be installed.
+///
+/// Packages from the sources in vpm-manifest.json are fetched and added to the collection.
+/// With --offline, the collection is restricted to the packages in the package cache,
+/// and the full collection is returned as well to report the packages need downloading.
+async fn load_collection_for_install(
+    io: &DefaultEnvironmentIo,
+    http: Option<&reqwest::Client>,
+    env_args: &EnvArgs,
+    unity: &UnityProject,
+) -> (PackageCollection, Option<PackageCollection>) {
+    let mut collection = load_collection(io, http, env_args.no_update).await;
+    collection
+        .fetch_package_sources(io, http, unity)
+        .await
+        .exit_context("fetching package sources");
+    if env_args.offline {
+        let cached = collection.cached_only(io).await;
+        (cached, Some(collection))
+    } else {
+        (collection, None)
+    }
+}
+
+/// Collects the changes to the project with the collection, or exits with the error.
+///
+/// With --offline, if collecting changes failed, this also prints the packages that need downloading.
+async fn collect_changes<'env, E: StructuredError>(
+    collection: &'env PackageCollection,
+    full: Option<&'env PackageCollection>,
+    context: &str,
+    request: impl AsyncFn(&'env PackageCollection) -> Result<PendingProjectChanges<'env>, E>,
+) -> PendingProjectChanges<'env> {
+    match request(collection).await {
+        Ok(changes) => changes,
+        Err(e) => {
+            if let Some(full) = full
+                && let Ok(changes) = request(full).await
+            {
+                print_need_download(&changes, collection);
+            }
+            output::exit_with_error(Some(context), &e.to_string(), e.details())
+        }
+    }
+}
+
+/// Prints the packages in the changes that are not in the cached collection.
+fn print_need_download(changes: &PendingProjectChanges, cached: &PackageCollection) {
+    let need_download = (changes.package_changes().values())
+        .filter_map(|x| x.as_install())
+        .filter_map(|x| x.install_package())
+        .filter(|x| {
+            cached
+                .find_package_by_name(x.name(), VersionSelector::specific_version(x.version()))
+                .is_none()
+        })
+        .collect::<Vec<_>>();
+
+    if !need_download.is_empty() {
+        eprintln!("The following packages are not in the package cache and need to be downloaded:");
+        for package in need_download {
+            eprintln!("- {} version {}", package.name(), package.version());
+        }
+    }
+}
+
 async fn load_unity(path: Option<Box<Path>>) -> UnityProject {
     let io = match path {
         None => {
@@ -154,16 +227,37 @@ async fn update_project_last_modified(_: &DefaultEnvironmentIo, _: &Path) {}
 
 fn get_package<'env>(
     env: &'env PackageCollection,
+    full: Option<&PackageCollection>,
     name: &str,
     selector: VersionSelector,
 ) -> PackageInfo<'env> {
-    env.find_package_by_name(name, selector)
-        .unwrap_or_else(|| exit_with!("no matching package not found"))
+    env.find_package_by_name(name, selector).unwrap_or_else(|| {
+        if let Some(package) = full.and_then(|full| full.find_package_by_name(name, selector)) {
+            exit_with!(
+                "{name} version {} is not in the package cache and needs to be downloaded",
+                package.version()
+            )
+        }
+        exit_with!("no matching package not found")
+    })
+}
+
+fn parse_unity_version(version: &str) -> Result<UnityVersion, String> {
+    UnityVersion::parse(version).ok_or_else(|| format!("invalid unity version: {version}"))
 }
 
+/// Asks the user to confirm.
+///
+/// With `--output json`, this exits with an error instead since stdout is used for json.
+/// Commands should skip this with `--yes` in that case.
 fn confirm_prompt(msg: &str) -> bool {
     use std::io;
     use std::io::Write;
+
+    if output::is_json() {
+        exit_with!("confirmation is required. use --yes to skip confirm")
+    }
+
     fn _impl(msg: &str) -> io::Result<bool> {
         let mut stdout = io::stdout();
         let stdin = io::stdin();
@@ -189,11 +283,16 @@ fn confirm_prompt(msg: &str) -> bool {
     _impl(msg).unwrap_or(false)
 }
 
-fn print_prompt_install(changes: &PendingProjectChanges) {
+fn print_prompt_install(unity: &UnityProject, changes: &PendingProjectChanges) {
     if changes.package_changes().is_empty() {
         exit_with!("nothing to do")
     }
 
+    if output::is_json() {
+        plan::print_json(unity, changes);
+        return;
+    }
+
     let mut newly_installed = Vec::new();
     let mut adding_to_dependencies = Vec::new();
     let mut removed = Vec::new();
@@ -270,10 +369,21 @@ fn print_prompt_install(changes: &PendingProjectChanges) {
                 for conflict in conflicts.conflicting_packages() {
                     println!("- {conflict}");
                 }
+                if let Some(explanation) = conflicts.explanation() {
+                    println!("because {explanation}");
+                }
             }
         }
     }
 
+    // process overridden requirements
+    if !changes.overridden_requirements().is_empty() {
+        println!("**Those requirements are overridden by overrides in vpm-manifest.json**");
+        for overridden in changes.overridden_requirements() {
+            println!("- {overridden}");
+        }
+    }
+
     // process unity conflicts
     {
         let mut unity_conflicts = (changes.conflicts().iter())
@@ -310,7 +420,9 @@ fn print_prompt_install(changes: &PendingProjectChanges) {
 
 fn prompt_install(yes: bool) {
     if yes {
-        println!("--yes is set. skipping confirm");
+        if !output::is_json() {
+            println!("--yes is set. skipping confirm");
+        }
     } else if !confirm_prompt("Do you want to apply those changes?") {
         exit(1);
     }
@@ -357,6 +469,11 @@ trait ResultExt<T, E>: Sized {
     fn exit_context(self, context: &str) -> T
     where
         E: Display;
+
+    /// Same as [`ResultExt::exit_context`] but the error is printed with the details with `--output json`.
+    fn exit_structured(self, context: &str) -> T
+    where
+        E: StructuredError;
 }
 
 impl<T, E> ResultExt<T, E> for Result<T, E> {
@@ -366,19 +483,58 @@ impl<T, E> ResultExt<T, E> for Result<T, E> {
     {
         match self {
             Ok(value) => value,
-            Err(err) => exit_with!("error {context}: {err}"),
+            Err(err) => {
+                output::exit_with_error(Some(context), &err.to_string(), ErrorDetails::Other)
+            }
+        }
+    }
+
+    fn exit_structured(self, context: &str) -> T
+    where
+        E: StructuredError,
+    {
+        match self {
+            Ok(value) => value,
+            Err(err) => output::exit_with_error(Some(context), &err.to_string(), err.details()),
         }
     }
 }
 
+mod backup;
+mod import;
 mod info;
+mod lint;
 mod migrate;
+mod output;
+mod pack;
+mod plan;
+mod repo_build;
+mod serve;
+mod tree;
+mod unity_compat;
+mod upm;
 #[cfg(feature = "experimental-vcc")]
 mod vcc;
+mod verify;
 
 /// Open Source command line interface of VRChat Package Manager.
 #[derive(Parser)]
 #[command(author, version, about)]
+pub struct Cli {
+    #[command(flatten)]
+    output_args: OutputArgs,
+    #[command(subcommand)]
+    command: Command,
+}
+
+impl Cli {
+    pub async fn run(self) {
+        self.output_args.init();
+        self.command.run().await
+    }
+}
+
+#[derive(Subcommand)]
 pub enum Command {
     #[command(alias = "i")]
     Install(Install),
@@ -386,21 +542,31 @@ pub enum Command {
     #[command(alias = "rm")]
     Remove(Remove),
     Reinstall(Reinstall),
+    Verify(verify::Verify),
     Update(Update),
     Outdated(Outdated),
+    Why(Why),
+    Tree(tree::Tree),
     Upgrade(Upgrade),
     Downgrade(Downgrade),
     Search(Search),
+    Import(import::Import),
     #[command(subcommand)]
     Repo(Repo),
     #[command(subcommand)]
     UserPackage(UserPackage),
     #[command(subcommand)]
+    Upm(upm::Upm),
+    #[command(subcommand)]
     Info(info::Info),
     #[command(subcommand)]
     Migrate(migrate::Migrate),
+    UnityCompat(unity_compat::UnityCompat),
     #[command(subcommand)]
     Cache(Cache),
+    Serve(serve::Serve),
+    Pack(pack::Pack),
+    Lint(lint::Lint),
     #[cfg(feature = "experimental-vcc")]
     #[command(subcommand)]
     Vcc(vcc::Vcc),
@@ -416,16 +582,25 @@ multi_command!(Command is
     Resolve,
     Remove,
     Reinstall,
+    Verify,
     Update,
     Outdated,
+    Why,
+    Tree,
     Upgrade,
     Downgrade,
     Search,
+    Import,
     Repo,
     UserPackage,
+    Upm,
     Info,
     Migrate,
+    UnityCompat,
     Cache,
+    Serve,
+    Pack,
+    Lint,
     Vcc,
     Completion,
 );
@@ -458,6 +633,8 @@ pub struct Install {
     project: Option<Box<Path>>,
     #[command(flatten)]
     env_args: EnvArgs,
+    #[command(flatten)]
+    plan_args: PlanArgs,
 
     /// skip confirm
     #[arg(short, long)]
@@ -471,6 +648,8 @@ impl Install {
             return Resolve {
                 project: self.project,
                 env_args: self.env_args,
+                plan_args: self.plan_args,
+                frozen: false,
             }
             .run()
             .await;
@@ -478,9 +657,10 @@ impl Install {
 
         let client = crate::create_client(self.env_args.offline);
         let io = DefaultEnvironmentIo::new_default();
-        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
-        let installer = PackageInstaller::new(&io, client.as_ref());
         let mut unity = load_unity(self.project).await;
+        let (collection, full) =
+            load_collection_for_install(&io, client.as_ref(), &self.env_args, &unity).await;
+        let installer = PackageInstaller::new(&io, client.as_ref());
 
         let version_selector = match self.version {
             None => VersionSelector::latest_for(Some(unity.unity_version()), self.prerelease),
@@ -510,25 +690,43 @@ impl Install {
             }
             packages.into_iter().unique_by(|x| x.name()).collect()
         } else {
-            vec![get_package(&collection, &name, version_selector)]
+            vec![get_package(
+                &collection,
+                full.as_ref(),
+                &name,
+                version_selector,
+            )]
         };
 
-        let changes = unity
-            .add_package_request(
-                &collection,
-                &packages,
-                AddPackageOperation::InstallToDependencies,
-                self.prerelease,
-            )
-            .await
-            .exit_context("collecting packages to be installed");
+        let changes = collect_changes(
+            &collection,
+            full.as_ref(),
+            "collecting packages to be installed",
+            async |env| {
+                unity
+                    .add_package_request(
+                        env,
+                        &packages,
+                        AddPackageOperation::InstallToDependencies,
+                        self.prerelease,
+                    )
+                    .await
+            },
+        )
+        .await;
+
+        if self.plan_args.dry_run(&unity, &changes) {
+            return;
+        }
 
-        print_prompt_install(&changes);
+        print_prompt_install(&unity, &changes);
 
         if require_prompt_for_install(&changes, name.as_str(), None) {
             prompt_install(self.yes);
         }
 
+        self.plan_args.backup(&io, &unity).await;
+
         unity
             .apply_pending_changes(&installer, changes)
             .await
@@ -550,23 +748,47 @@ pub struct Resolve {
     project: Option<Box<Path>>,
     #[command(flatten)]
     env_args: EnvArgs,
+    #[command(flatten)]
+    plan_args: PlanArgs,
+
+    /// Do not change locked packages. Fails if locked packages cannot be installed as is.
+    ///
+    /// This is useful for CI to make sure the project is restored with exactly the locked versions.
+    #[arg(long)]
+    frozen: bool,
 }
 
 impl Resolve {
     pub async fn run(self) {
         let client = crate::create_client(self.env_args.offline);
         let io = DefaultEnvironmentIo::new_default();
-        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
         let mut unity = load_unity(self.project).await;
+        let (collection, full) =
+            load_collection_for_install(&io, client.as_ref(), &self.env_args, &unity).await;
 
         let installer = PackageInstaller::new(&io, client.as_ref());
 
-        let changes = unity
-            .resolve_request(&collection)
-            .await
-            .exit_context("collecting packages to be installed");
+        let changes = collect_changes(
+            &collection,
+            full.as_ref(),
+            "collecting packages to be installed",
+            async |env| {
+                if self.frozen {
+                    unity.resolve_request_frozen(env).await
+                } else {
+                    unity.resolve_request(env).await
+                }
+            },
+        )
+        .await;
 
-        print_prompt_install(&changes);
+        if self.plan_args.dry_run(&unity, &changes) {
+            return;
+        }
+
+        print_prompt_install(&unity, &changes);
+
+        self.plan_args.backup(&io, &unity).await;
 
         unity
             .apply_pending_changes(&installer, changes)
@@ -588,6 +810,8 @@ pub struct Remove {
     project: Option<Box<Path>>,
     #[command(flatten)]
     env_args: EnvArgs,
+    #[command(flatten)]
+    plan_args: PlanArgs,
 
     /// skip confirm
     #[arg(short, long)]
@@ -602,10 +826,14 @@ impl Remove {
         let changes = unity
             .remove_request(&self.names.iter().map(String::as_ref).collect::<Vec<_>>())
             .await
-            .exit_context("collecting packages to be removed");
+            .exit_structured("collecting packages to be removed");
         let installer = PackageInstaller::new(&io, None::<&reqwest::Client>);
 
-        print_prompt_install(&changes);
+        if self.plan_args.dry_run(&unity, &changes) {
+            return;
+        }
+
+        print_prompt_install(&unity, &changes);
 
         let confirm =
             changes.package_changes().len() >= self.names.len() || !changes.conflicts().is_empty();
@@ -614,6 +842,8 @@ impl Remove {
             prompt_install(self.yes);
         }
 
+        self.plan_args.backup(&io, &unity).await;
+
         unity
             .apply_pending_changes(&installer, changes)
             .await
@@ -636,6 +866,8 @@ pub struct Reinstall {
     project: Option<Box<Path>>,
     #[command(flatten)]
     env_args: EnvArgs,
+    #[command(flatten)]
+    plan_args: PlanArgs,
 
     /// skip confirm
     #[arg(short, long)]
@@ -646,19 +878,26 @@ impl Reinstall {
     pub async fn run(self) {
         let io = DefaultEnvironmentIo::new_default();
         let client = crate::create_client(self.env_args.offline);
-        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
-        let installer = PackageInstaller::new(&io, client.as_ref());
-
         let mut unity = load_unity(self.project).await;
+        let mut collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
+        collection
+            .fetch_package_sources(&io, client.as_ref(), &unity)
+            .await
+            .exit_context("fetching package sources");
+        let installer = PackageInstaller::new(&io, client.as_ref());
 
         let names = self.names.iter().map(String::as_ref).collect::<Vec<_>>();
 
         let changes = unity
             .reinstall_request(&collection, &names)
             .await
-            .exit_context("collecting packages to be removed");
+            .exit_structured("collecting packages to be removed");
+
+        if self.plan_args.dry_run(&unity, &changes) {
+            return;
+        }
 
-        print_prompt_install(&changes);
+        print_prompt_install(&unity, &changes);
 
         let confirm =
             changes.package_changes().len() >= self.names.len() || !changes.conflicts().is_empty();
@@ -667,6 +906,8 @@ impl Reinstall {
             prompt_install(self.yes);
         }
 
+        self.plan_args.backup(&io, &unity).await;
+
         unity
             .apply_pending_changes(&installer, changes)
             .await
@@ -720,7 +961,7 @@ impl Outdated {
         let selector = VersionSelector::latest_for(Some(unity.unity_version()), self.prerelease);
 
         for locked in unity.locked_packages() {
-            match collection.find_package_by_name(locked.name(), selector) {
+            match unity.find_package_by_name(&collection, locked.name(), selector) {
                 None => log::error!("latest version for package {} not found.", locked.name()),
                 // if found version is newer: add to outdated
                 Some(pkg) if locked.version() < pkg.version() => {
@@ -740,7 +981,7 @@ impl Outdated {
             }
         }
 
-        match self.json_format.map(|x| x.get()).unwrap_or(0) {
+        match output::json_format(self.json_format, 1) {
             0 => {
                 for (name, (found, installed)) in &outdated_packages {
                     println!("{name}: installed: {installed}, found: {}", found.version());
@@ -768,6 +1009,94 @@ impl Outdated {
     }
 }
 
+/// Show why the package is in the project
+///
+/// Prints every dependency path from the dependencies of the project to the package.
+/// With --json-format, this will emit machine-readable information with json
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Why {
+    /// Name of Package
+    #[arg()]
+    name: String,
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+}
+
+impl Why {
+    pub async fn run(self) {
+        let unity = load_unity(self.project).await;
+
+        let paths = unity.dependency_paths(&self.name);
+        let locked = unity.get_locked(&self.name).map(|x| x.version());
+
+        match output::json_format(self.json_format, 1) {
+            0 => {
+                match locked {
+                    Some(version) => println!("{} version {version}", self.name),
+                    None => println!("{} (not locked)", self.name),
+                }
+
+                if paths.is_empty() {
+                    println!("not required by dependencies of the project");
+                }
+
+                for path in &paths {
+                    let edges = path
+                        .edges()
+                        .iter()
+                        .map(|edge| format!("{} {}", edge.dependency(), edge.range()))
+                        .join(" -> ");
+                    println!("project -> {edges}");
+                }
+            }
+            1 => {
+                #[derive(Serialize)]
+                struct WhyInfo<'a> {
+                    package: &'a str,
+                    locked: Option<&'a Version>,
+                    paths: Vec<Vec<EdgeInfo<'a>>>,
+                }
+
+                #[derive(Serialize)]
+                struct EdgeInfo<'a> {
+                    /// null for the project
+                    dependant: Option<&'a str>,
+                    dependant_version: Option<&'a Version>,
+                    dependency: &'a str,
+                    range: &'a VersionRange,
+                }
+
+                let info = WhyInfo {
+                    package: &self.name,
+                    locked,
+                    paths: paths
+                        .iter()
+                        .map(|path| {
+                            path.edges()
+                                .iter()
+                                .map(|edge| EdgeInfo {
+                                    dependant: edge.dependant().map(|x| x.name()),
+                                    dependant_version: edge.dependant().map(|x| x.version()),
+                                    dependency: edge.dependency(),
+                                    range: edge.range(),
+                                })
+                                .collect()
+                        })
+                        .collect(),
+                };
+                println!("{}", serde_json::to_string(&info).unwrap());
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+    }
+}
+
 /// Upgrade specified package or all packages to latest or specified version.
 ///
 /// With install command, you'll add to dependencies. With upgrade command,
@@ -790,6 +1119,8 @@ pub struct Upgrade {
     project: Option<Box<Path>>,
     #[command(flatten)]
     env_args: EnvArgs,
+    #[command(flatten)]
+    plan_args: PlanArgs,
 
     /// skip confirm
     #[arg(short, long)]
@@ -800,16 +1131,17 @@ impl Upgrade {
     pub async fn run(self) {
         let io = DefaultEnvironmentIo::new_default();
         let client = crate::create_client(self.env_args.offline);
-        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
-        let installer = PackageInstaller::new(&io, client.as_ref());
         let mut unity = load_unity(self.project).await;
+        let (collection, full) =
+            load_collection_for_install(&io, client.as_ref(), &self.env_args, &unity).await;
+        let installer = PackageInstaller::new(&io, client.as_ref());
 
         let updates = if let Some(name) = &self.name {
             let version_selector = match self.version {
                 None => VersionSelector::latest_for(Some(unity.unity_version()), self.prerelease),
                 Some(ref version) => VersionSelector::specific_version(version),
             };
-            let package = get_package(&collection, name, version_selector);
+            let package = get_package(&collection, full.as_ref(), name, version_selector);
 
             vec![package]
         } else {
@@ -818,21 +1150,34 @@ impl Upgrade {
 
             unity
                 .locked_packages()
-                .map(|locked| get_package(&collection, locked.name(), version_selector))
+                .map(|locked| {
+                    get_package(&collection, full.as_ref(), locked.name(), version_selector)
+                })
                 .collect()
         };
 
-        let changes = unity
-            .add_package_request(
-                &collection,
-                &updates,
-                AddPackageOperation::UpgradeLocked,
-                self.prerelease,
-            )
-            .await
-            .exit_context("collecting packages to be upgraded");
+        let changes = collect_changes(
+            &collection,
+            full.as_ref(),
+            "collecting packages to be upgraded",
+            async |env| {
+                unity
+                    .add_package_request(
+                        env,
+                        &updates,
+                        AddPackageOperation::UpgradeLocked,
+                        self.prerelease,
+                    )
+                    .await
+            },
+        )
+        .await;
+
+        if self.plan_args.dry_run(&unity, &changes) {
+            return;
+        }
 
-        print_prompt_install(&changes);
+        print_prompt_install(&unity, &changes);
 
         let require_prompt = if let Some(name) = &self.name {
             require_prompt_for_install(&changes, name.as_str(), None)
@@ -850,13 +1195,17 @@ impl Upgrade {
             .map(|x| (x.name().to_owned(), x.version().clone()))
             .collect::<Vec<_>>();
 
+        self.plan_args.backup(&io, &unity).await;
+
         unity
             .apply_pending_changes(&installer, changes)
             .await
             .exit_context("upgrading packages");
 
-        for (name, version) in updates {
-            println!("upgraded {name} to {version}");
+        if !output::is_json() {
+            for (name, version) in updates {
+                println!("upgraded {name} to {version}");
+            }
         }
 
         update_project_last_modified(&io, unity.project_dir()).await;
@@ -885,6 +1234,8 @@ pub struct Downgrade {
     project: Option<Box<Path>>,
     #[command(flatten)]
     env_args: EnvArgs,
+    #[command(flatten)]
+    plan_args: PlanArgs,
 
     /// skip confirm
     #[arg(short, long)]
@@ -895,27 +1246,40 @@ impl Downgrade {
     pub async fn run(self) {
         let client = crate::create_client(self.env_args.offline);
         let io = DefaultEnvironmentIo::new_default();
-        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
-        let installer = PackageInstaller::new(&io, client.as_ref());
         let mut unity = load_unity(self.project).await;
+        let (collection, full) =
+            load_collection_for_install(&io, client.as_ref(), &self.env_args, &unity).await;
+        let installer = PackageInstaller::new(&io, client.as_ref());
 
         let updates = [get_package(
             &collection,
+            full.as_ref(),
             &self.name,
             VersionSelector::specific_version(&self.version),
         )];
 
-        let changes = unity
-            .add_package_request(
-                &collection,
-                &updates,
-                AddPackageOperation::Downgrade,
-                self.prerelease,
-            )
-            .await
-            .exit_context("collecting packages to be upgraded");
+        let changes = collect_changes(
+            &collection,
+            full.as_ref(),
+            "collecting packages to be upgraded",
+            async |env| {
+                unity
+                    .add_package_request(
+                        env,
+                        &updates,
+                        AddPackageOperation::Downgrade,
+                        self.prerelease,
+                    )
+                    .await
+            },
+        )
+        .await;
+
+        if self.plan_args.dry_run(&unity, &changes) {
+            return;
+        }
 
-        print_prompt_install(&changes);
+        print_prompt_install(&unity, &changes);
 
         if require_prompt_for_install(&changes, self.name.as_str(), None) {
             prompt_install(self.yes)
@@ -927,13 +1291,17 @@ impl Downgrade {
             .map(|x| (x.name().to_owned(), x.version().clone()))
             .collect::<Vec<_>>();
 
+        self.plan_args.backup(&io, &unity).await;
+
         unity
             .apply_pending_changes(&installer, changes)
             .await
             .exit_context("upgrading packages");
 
-        for (name, version) in downgrades {
-            println!("downgraded {name} to {version}");
+        if !output::is_json() {
+            for (name, version) in downgrades {
+                println!("downgraded {name} to {version}");
+            }
         }
 
         update_project_last_modified(&io, unity.project_dir()).await;
@@ -950,6 +1318,10 @@ pub struct Search {
     #[arg(required = true, name = "QUERY")]
     queries: Vec<String>,
 
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+
     #[command(flatten)]
     env_args: EnvArgs,
 }
@@ -985,6 +1357,31 @@ impl Search {
                     .all(|query| search_targets.iter().any(|x| x.contains(query)))
             });
 
+        match output::json_format(self.json_format, 1) {
+            0 => {}
+            1 => {
+                #[derive(Serialize)]
+                struct SearchResult<'a> {
+                    name: &'a str,
+                    display_name: Option<&'a str>,
+                    version: &'a Version,
+                    description: Option<&'a str>,
+                }
+                let results = found_packages
+                    .iter()
+                    .map(|x| SearchResult {
+                        name: x.name(),
+                        display_name: x.package_json().display_name(),
+                        version: x.version(),
+                        description: x.package_json().description(),
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&results).unwrap());
+                return;
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+
         if found_packages.is_empty() {
             println!("No matching package found!")
         } else {
@@ -1015,9 +1412,11 @@ pub enum Repo {
     Packages(RepoPackages),
     Import(RepoImport),
     Export(RepoExport),
+    Build(repo_build::RepoBuild),
+    PinKey(RepoPinKey),
 }
 
-multi_command!(Repo is List, Add, Remove, Cleanup, Packages, Import, Export);
+multi_command!(Repo is List, Add, Remove, Cleanup, Packages, Import, Export, Build, PinKey);
 
 /// List all repositories
 #[derive(Parser)]
@@ -1025,6 +1424,10 @@ multi_command!(Repo is List, Add, Remove, Cleanup, Packages, Import, Export);
 pub struct RepoList {
     #[command(flatten)]
     env_args: EnvArgs,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
 }
 
 impl RepoList {
@@ -1032,6 +1435,30 @@ impl RepoList {
         let io = DefaultEnvironmentIo::new_default();
         let settings = Settings::load(&io).await.exit_context("loading settings");
 
+        match output::json_format(self.json_format, 1) {
+            0 => {}
+            1 => {
+                #[derive(Serialize)]
+                struct RepoInfo<'a> {
+                    id: Option<&'a str>,
+                    name: Option<&'a str>,
+                    url: Option<&'a str>,
+                    local_path: &'a Path,
+                }
+                let repos = (settings.get_user_repos().iter())
+                    .map(|repo| RepoInfo {
+                        id: repo.id(),
+                        name: repo.name(),
+                        url: repo.url().map(Url::as_str),
+                        local_path: repo.local_path(),
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&repos).unwrap());
+                return;
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+
         for repo in settings.get_user_repos() {
             println!(
                 "{}: {} (from {})",
@@ -1060,6 +1487,10 @@ pub struct RepoAdd {
     #[arg(short='H', long, value_parser = HeaderPair::from_str)]
     header: Vec<HeaderPair>,
 
+    /// Base64 ed25519 public key to verify the signature of the repository with
+    #[arg(long, value_parser = RepositoryPublicKey::from_str)]
+    public_key: Option<RepositoryPublicKey>,
+
     #[command(flatten)]
     env_args: EnvArgs,
 }
@@ -1128,15 +1559,19 @@ impl RepoAdd {
             }
             add_remote_repo(
                 &mut settings,
-                url,
+                url.clone(),
                 self.name.as_deref(),
                 headers,
+                self.public_key.map(|x| x.to_string()).as_deref(),
                 &io,
                 &http.unwrap_or_else(|| exit_with!("offline mode")),
             )
             .await
-            .exit_context("adding repository")
+            .exit_context("adding repository");
         } else {
+            if self.public_key.is_some() {
+                exit_with!("public key can only be pinned for remote repositories");
+            }
             let normalized = absolute_path(&self.path_or_url);
             if !normalized.exists() {
                 exit_with!("path not found: {}", normalized.display());
@@ -1242,7 +1677,61 @@ impl RepoRemove {
         )
         .await;
 
-        println!("removed {} repositories with {}", removed.len(), searcher);
+        if !output::is_json() {
+            println!("removed {} repositories with {}", removed.len(), searcher);
+        }
+
+        settings.save(&io).await.exit_context("saving settings");
+    }
+}
+
+/// Pin the public key to verify the signature of the repository with
+///
+/// The repository json must be signed with the key, and the signature must be served at
+/// the url of the repository with `.sig` appended.
+/// Packages from the repository cannot be installed unless the signature is verified.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct RepoPinKey {
+    /// id, url, name, or path of repository
+    #[arg()]
+    finder: String,
+
+    /// Base64 ed25519 public key. Unpins the key if not specified
+    #[arg(value_parser = RepositoryPublicKey::from_str)]
+    public_key: Option<RepositoryPublicKey>,
+
+    #[clap(flatten)]
+    searcher: RepoSearcherArgs,
+
+    #[command(flatten)]
+    env_args: EnvArgs,
+}
+
+impl RepoPinKey {
+    pub async fn run(self) {
+        let io = DefaultEnvironmentIo::new_default();
+        let mut settings = Settings::load(&io).await.exit_context("loading settings");
+
+        let finder = OsStr::new(self.finder.as_str());
+        let searcher = self.searcher.as_searcher();
+        let public_key = self.public_key.as_ref().map(ToString::to_string);
+
+        let updated = settings.set_repo_public_key(
+            |x| x.url().is_some() && searcher.get(x) == Some(finder),
+            public_key.as_deref(),
+        );
+
+        if updated == 0 {
+            exit_with!("no remote repositories with {} {}", searcher, self.finder);
+        }
+
+        if !output::is_json() {
+            match public_key {
+                Some(_) => println!("pinned public key for {updated} repositories"),
+                None => println!("unpinned public key for {updated} repositories"),
+            }
+        }
 
         settings.save(&io).await.exit_context("saving settings");
     }
@@ -1277,10 +1766,19 @@ pub struct RepoPackages {
 
     #[command(flatten)]
     env_args: EnvArgs,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
 }
 
 impl RepoPackages {
     pub async fn run(self) {
+        let format_version = output::json_format(self.json_format, 1);
+        if format_version > 1 {
+            exit_with!("unsupported json version: {format_version}");
+        }
+
         fn print_repo(packages: &RemoteRepository) {
             for versions in packages.get_packages() {
                 if let Some(pkg) =
@@ -1308,6 +1806,42 @@ impl RepoPackages {
             }
         }
 
+        #[derive(Serialize)]
+        struct PackageInfo<'a> {
+            name: &'a str,
+            display_name: Option<&'a str>,
+            description: Option<&'a str>,
+            versions: Vec<VersionInfo<'a>>,
+        }
+
+        #[derive(Serialize)]
+        struct VersionInfo<'a> {
+            version: &'a Version,
+            url: Option<&'a str>,
+            is_yanked: bool,
+        }
+
+        fn json_packages(packages: &RemoteRepository) -> impl Iterator<Item = PackageInfo<'_>> {
+            packages.get_packages().filter_map(|versions| {
+                let pkg =
+                    versions.get_latest_may_yanked(VersionSelector::latest_for(None, true))?;
+                let mut versions = versions.all_versions().collect::<Vec<_>>();
+                versions.sort_by_key(|pkg| pkg.version());
+                Some(PackageInfo {
+                    name: pkg.name(),
+                    display_name: pkg.display_name(),
+                    description: pkg.description(),
+                    versions: (versions.into_iter())
+                        .map(|pkg| VersionInfo {
+                            version: pkg.version(),
+                            url: pkg.url().map(Url::as_str),
+                            is_yanked: pkg.is_yanked(),
+                        })
+                        .collect(),
+                })
+            })
+        }
+
         if let Ok(url) = Url::parse(&self.name_or_url) {
             if self.env_args.offline {
                 exit_with!("remote repository specified but offline mode.");
@@ -1317,24 +1851,35 @@ impl RepoPackages {
                 .await
                 .exit_context("downloading repository");
 
-            print_repo(&repo);
+            if format_version == 1 {
+                let packages = json_packages(&repo).collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&packages).unwrap());
+            } else {
+                print_repo(&repo);
+            }
         } else {
             let client = crate::create_client(self.env_args.offline);
             let io = DefaultEnvironmentIo::new_default();
             let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
 
             let some_name = Some(self.name_or_url.as_str());
-            let mut found = false;
+            let repos = (collection.get_remote())
+                .filter(|repo| repo.name() == some_name || repo.id() == some_name)
+                .collect::<Vec<_>>();
 
-            for repo in collection.get_remote() {
-                if repo.name() == some_name || repo.id() == some_name {
-                    print_repo(repo.repo());
-                    found = true;
-                }
+            if repos.is_empty() {
+                exit_with!("no repository named {} found!", self.name_or_url);
             }
 
-            if !found {
-                exit_with!("no repository named {} found!", self.name_or_url);
+            if format_version == 1 {
+                let packages = (repos.iter())
+                    .flat_map(|repo| json_packages(repo.repo()))
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&packages).unwrap());
+            } else {
+                for repo in repos {
+                    print_repo(repo.repo());
+                }
             }
         }
     }
@@ -1365,23 +1910,32 @@ impl RepoImport {
 
         let result = RepositoriesFile::parse(&repositories_file);
 
-        println!("You're importing the following repositories:");
-        for repository in result.parsed().repositories() {
-            if repository.headers().is_empty() {
-                println!("- {}", repository.url());
-            } else {
-                println!("- {} (with headers)", repository.url());
+        if output::is_json() {
+            if !self.yes {
+                // we cannot prompt since stdout is used for json
+                exit_with!(
+                    "confirmation is required to import repositories. use --yes to skip confirm"
+                )
+            }
+        } else {
+            println!("You're importing the following repositories:");
+            for repository in result.parsed().repositories() {
+                if repository.headers().is_empty() {
+                    println!("- {}", repository.url());
+                } else {
+                    println!("- {} (with headers)", repository.url());
+                }
+            }
+            println!("The following lines are invalid and will be ignored:");
+            for line in result.unparseable_lines() {
+                println!("- {line}");
             }
-        }
-        println!("The following lines are invalid and will be ignored:");
-        for line in result.unparseable_lines() {
-            println!("- {line}");
-        }
 
-        if self.yes {
-            println!("--yes is set. skipping confirm");
-        } else if !confirm_prompt("Do you want to install those repositories?") {
-            exit(1);
+            if self.yes {
+                println!("--yes is set. skipping confirm");
+            } else if !confirm_prompt("Do you want to install those repositories?") {
+                exit(1);
+            }
         }
 
         for repository in result.parsed().repositories() {
@@ -1390,6 +1944,7 @@ impl RepoImport {
                 repository.url().clone(),
                 None,
                 repository.headers().clone(),
+                None,
                 &io,
                 http.as_ref().unwrap_or_else(|| exit_with!("offline mode")),
             )
@@ -1427,7 +1982,20 @@ impl RepoExport {
     pub async fn run(self) {
         let io = DefaultEnvironmentIo::new_default();
         let settings = Settings::load(&io).await.exit_context("loading settings");
-        print!("{}", settings.export_repositories());
+        let exported = settings.export_repositories();
+        if output::is_json() {
+            #[derive(Serialize)]
+            struct ExportInfo<'a> {
+                /// The contents of the repository list file
+                repositories_file: &'a str,
+            }
+            let info = ExportInfo {
+                repositories_file: &exported,
+            };
+            println!("{}", serde_json::to_string(&info).unwrap());
+        } else {
+            print!("{exported}");
+        }
     }
 }
 
@@ -1445,7 +2013,11 @@ multi_command!(UserPackage is List, Add, Remove);
 /// List all user packages
 #[derive(Parser)]
 #[command(author, version)]
-pub struct UserPackageList {}
+pub struct UserPackageList {
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+}
 
 impl UserPackageList {
     pub async fn run(self) {
@@ -1453,6 +2025,30 @@ impl UserPackageList {
         let settings = Settings::load(&io).await.exit_context("loading settings");
         let packages = UserPackageCollection::load(&settings, &io).await;
 
+        match output::json_format(self.json_format, 1) {
+            0 => {}
+            1 => {
+                #[derive(Serialize)]
+                struct UserPackageInfo<'a> {
+                    name: &'a str,
+                    display_name: Option<&'a str>,
+                    version: &'a Version,
+                    path: &'a Path,
+                }
+                let packages = (packages.packages())
+                    .map(|(path, package)| UserPackageInfo {
+                        name: package.name(),
+                        display_name: package.display_name(),
+                        version: package.version(),
+                        path,
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&packages).unwrap());
+                return;
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+
         for (path, package) in packages.packages() {
             println!(
                 "{}: {} version {} at {}",
@@ -1564,12 +2160,7 @@ impl Completion {
             }
         }
 
-        clap_complete::generate(
-            shell,
-            &mut Command::command(),
-            bin_name,
-            &mut std::io::stdout(),
-        );
+        clap_complete::generate(shell, &mut Cli::command(), bin_name, &mut std::io::stdout());
     }
 }
 
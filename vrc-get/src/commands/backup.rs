@@ -0,0 +1,185 @@
+use crate::commands::ResultExt;
+use chrono::NaiveDateTime;
+use futures::TryStreamExt;
+use log::{info, warn};
+use serde::Deserialize;
+use std::path::Path;
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::environment::Settings;
+use vrc_get_vpm::io::{DefaultEnvironmentIo, DirEntry, IoTrait};
+use vrc_get_vpm::unity_project::{
+    AUTOMATIC_BACKUP_SUFFIX, BACKUP_TIMESTAMP_FORMAT, BackupFormat, ZipCompression,
+    collect_notable_project_files_tree, write_backup_tar_zst,
+};
+
+/// The backup settings of ALCOM in `vrc-get/gui-config.json`.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct BackupConfig {
+    #[serde(default)]
+    backup_format: String,
+    #[serde(default)]
+    exclude_vpm_packages_from_backup: bool,
+}
+
+impl BackupConfig {
+    async fn load(io: &DefaultEnvironmentIo) -> Self {
+        let path = io.resolve("vrc-get/gui-config.json".as_ref());
+        let json = match tokio::fs::read(&path).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!(
+                    "error reading {}, using default backup format: {e}",
+                    path.display()
+                );
+                return Self::default();
+            }
+        };
+        serde_json::from_slice(&json).unwrap_or_else(|e| {
+            warn!(
+                "error parsing {}, using default backup format: {e}",
+                path.display()
+            );
+            Self::default()
+        })
+    }
+}
+
+/// Creates a backup of the project in the backup folder of the settings.
+///
+/// The backup is named `{project_name}-{timestamp}-auto` with the extension of the format,
+/// like the automatic backups of ALCOM.
+/// The backup format and whether to exclude VPM packages are read from the settings of ALCOM.
+/// If `keep` is specified, older automatic backups of the project beyond the count are removed.
+pub async fn backup_project(io: &DefaultEnvironmentIo, unity: &UnityProject, keep: Option<usize>) {
+    let settings = Settings::load(io).await.exit_context("loading settings");
+    let Some(backup_dir) = settings.project_backup_path() else {
+        exit_with!(
+            "backup folder is not configured. please set projectBackupPath in settings.json or use ALCOM to configure"
+        )
+    };
+    let backup_dir = Path::new(backup_dir);
+    let config = BackupConfig::load(io).await;
+
+    let project_name = (unity.project_dir().file_name())
+        .and_then(|x| x.to_str())
+        .unwrap_or_else(|| exit_with!("project folder name is not valid utf8"));
+
+    io.create_dir_all(backup_dir)
+        .await
+        .exit_context("creating backup folder");
+
+    let timestamp = chrono::Local::now().format(BACKUP_TIMESTAMP_FORMAT);
+    let backup_name = format!("{project_name}-{timestamp}{AUTOMATIC_BACKUP_SUFFIX}");
+    let exclude_vpm = config.exclude_vpm_packages_from_backup;
+
+    let compression = match config.backup_format.to_ascii_lowercase().as_str() {
+        "" | "default" | "zip-fast" => Some(ZipCompression::Fast),
+        "zip-store" => Some(ZipCompression::Stored),
+        "zip-best" => Some(ZipCompression::Best),
+        "tar-zst" => None,
+        "incremental" => {
+            warn!("incremental backups are only supported by ALCOM, creating zip backup");
+            Some(ZipCompression::Fast)
+        }
+        unknown => {
+            warn!("unknown backup format: {unknown}, creating zip backup");
+            Some(ZipCompression::Fast)
+        }
+    };
+
+    let format = match compression {
+        Some(_) => BackupFormat::Zip,
+        None => BackupFormat::TarZst,
+    };
+    let backup_path = backup_dir.join(format!("{backup_name}.{}", format.extension()));
+
+    info!("Creating backup at {}", backup_path.display());
+
+    let file = io
+        .create_new(&backup_path)
+        .await
+        .exit_context("creating backup file");
+    let result = match compression {
+        Some(compression) => {
+            unity
+                .create_backup_zip(file, compression, exclude_vpm)
+                .await
+        }
+        None => {
+            let file = file.into_inner().into_std().await;
+            create_backup_tar_zst(unity.project_dir(), file, exclude_vpm).await
+        }
+    };
+    if let Err(e) = result {
+        io.remove_file(&backup_path).await.ok();
+        exit_with!("error creating backup: {e}");
+    }
+
+    info!("Backup created at {}", backup_path.display());
+
+    if let Some(keep) = keep {
+        remove_old_backups(io, backup_dir, project_name, keep)
+            .await
+            .exit_context("removing old backups");
+    }
+}
+
+/// Creates the tar archive compressed with zstd like the tar.zst backups of ALCOM.
+async fn create_backup_tar_zst(
+    project_dir: &Path,
+    file: std::fs::File,
+    exclude_vpm: bool,
+) -> std::io::Result<()> {
+    let tree =
+        collect_notable_project_files_tree(project_dir.to_path_buf(), exclude_vpm, true).await?;
+
+    tokio::task::spawn_blocking(move || {
+        let file = write_backup_tar_zst(&tree, file, |_, _| Ok(()))?;
+        file.sync_all()
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// Removes the automatic backups of the project except for the newest `keep` backups.
+///
+/// Incremental backups of ALCOM are not removed since they share the chunk store.
+async fn remove_old_backups(
+    io: &DefaultEnvironmentIo,
+    backup_dir: &Path,
+    project_name: &str,
+    keep: usize,
+) -> std::io::Result<()> {
+    let mut backups = vec![];
+    let mut read_dir = io.read_dir(backup_dir).await?;
+    while let Some(entry) = read_dir.try_next().await? {
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        let timestamp = BackupFormat::from_file_name(file_name)
+            .filter(|(_, format)| *format != BackupFormat::Incremental)
+            .and_then(|(name, _)| name.strip_suffix(AUTOMATIC_BACKUP_SUFFIX))
+            .and_then(|x| x.strip_prefix(project_name))
+            .and_then(|x| x.strip_prefix('-'));
+        // backups of other projects with the same prefix fail to parse here
+        if let Some(timestamp) = timestamp
+            && let Ok(created_at) =
+                NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT)
+        {
+            backups.push((created_at, backup_dir.join(file_name)));
+        }
+    }
+
+    backups.sort_by_key(|b| std::cmp::Reverse(b.0));
+    for (_, path) in backups.into_iter().skip(keep) {
+        info!("Removing old backup {}", path.display());
+        if let Err(e) = io.remove_file(&path).await {
+            warn!("error removing old backup {}: {e}", path.display());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use crate::commands::output::{StructuredError, asset_info};
+use crate::commands::{absolute_path, load_unity, output, update_project_last_modified};
+use clap::Parser;
+use futures::io::BufReader;
+use itertools::Itertools;
+use serde::Serialize;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use vrc_get_vpm::io::{DefaultEnvironmentIo, IoTrait};
+use vrc_get_vpm::unity_project::{
+    ImportConflict, ImportConflictHandling, ImportUnityPackageError, UnityPackageAsset,
+};
+
+/// Import a unitypackage to the unity project
+///
+/// By default, nothing is imported if any asset conflicts with the project,
+/// that is, a file exists at the path of the asset or an asset with the same GUID exists at another path.
+/// The package is validated before writing anything, so the project is not changed on conflicts.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Import {
+    /// Path to the unitypackage file
+    #[arg()]
+    file: PathBuf,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+
+    /// Print the assets to be imported and the conflicts without importing
+    #[arg(long)]
+    dry_run: bool,
+    /// Import the assets except for the conflicting assets
+    #[arg(long, conflicts_with = "overwrite")]
+    skip_existing: bool,
+    /// Overwrite the conflicting assets. Assets with existing GUIDs are written to the existing paths.
+    /// Nothing is imported if an existing asset has another GUID or a file and a folder would be replaced
+    #[arg(long)]
+    overwrite: bool,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+}
+
+impl Import {
+    pub async fn run(self) {
+        let json_format = output::json_format(self.json_format, 1);
+        if json_format > 1 {
+            exit_with!("unsupported json version: {json_format}");
+        }
+
+        let io = DefaultEnvironmentIo::new_default();
+        let unity = load_unity(self.project).await;
+
+        let conflict_handling = if self.skip_existing {
+            ImportConflictHandling::Skip
+        } else if self.overwrite {
+            ImportConflictHandling::Overwrite
+        } else {
+            ImportConflictHandling::Fail
+        };
+
+        let file = io
+            .open(&absolute_path(&self.file))
+            .await
+            .unwrap_or_else(|e| exit_with!("error opening {}: {e}", self.file.display()));
+
+        let assets = match unity
+            .import_unitypackage(BufReader::new(file), conflict_handling, self.dry_run)
+            .await
+        {
+            Ok(assets) => assets,
+            Err(e) => {
+                let message = match &e {
+                    ImportUnityPackageError::Conflicts(conflicts) => Some((
+                        conflicts,
+                        "assets conflict with the project. use --skip-existing or --overwrite to import anyway",
+                    )),
+                    ImportUnityPackageError::CannotOverwrite(conflicts) => Some((
+                        conflicts,
+                        "assets cannot be overwritten. use --skip-existing to import the others",
+                    )),
+                    ImportUnityPackageError::Io(_) | _ => None,
+                };
+                if let Some((conflicts, message)) = message {
+                    if json_format == 0 {
+                        for asset in conflicts {
+                            print_asset(asset);
+                        }
+                    }
+                    output::exit_with_error(
+                        None,
+                        &format!("{} {message}", conflicts.len()),
+                        e.details(),
+                    );
+                }
+                output::exit_with_error(
+                    Some(&format!("importing {}", self.file.display())),
+                    &e.to_string(),
+                    e.details(),
+                )
+            }
+        };
+
+        let imported = if self.dry_run {
+            0
+        } else if conflict_handling == ImportConflictHandling::Skip {
+            assets.iter().filter(|x| x.conflicts().is_empty()).count()
+        } else {
+            assets.len()
+        };
+
+        if json_format != 0 {
+            #[derive(Serialize)]
+            struct ImportInfo<'a> {
+                /// The assets in the unitypackage with the conflicts with the project
+                assets: Vec<output::AssetInfo<'a>>,
+                /// The number of imported assets, which is 0 with --dry-run
+                imported: usize,
+            }
+            let info = ImportInfo {
+                assets: assets.iter().map(asset_info).collect(),
+                imported,
+            };
+            println!("{}", serde_json::to_string(&info).unwrap());
+        } else if self.dry_run {
+            for asset in &assets {
+                print_asset(asset);
+            }
+        } else {
+            println!("imported {imported} assets from {}", self.file.display());
+        }
+
+        if self.dry_run {
+            return;
+        }
+
+        update_project_last_modified(&io, unity.project_dir()).await;
+    }
+}
+
+fn print_asset(asset: &UnityPackageAsset) {
+    let path = asset.path();
+    let folder = if asset.is_folder() { "/" } else { "" };
+    if asset.conflicts().is_empty() {
+        println!("{path}{folder}");
+        return;
+    }
+    let conflicts = (asset.conflicts().iter())
+        .map(|conflict| match conflict {
+            ImportConflict::PathExists => "exists".to_owned(),
+            ImportConflict::GuidMismatch(guid) => format!("existing guid is {guid}"),
+            ImportConflict::GuidExists(existing) => {
+                format!("guid exists at {}", existing.to_string_lossy())
+            }
+            ImportConflict::TypeMismatch => "file and folder mismatch".to_owned(),
+        })
+        .join(", ");
+    println!("{path}{folder} ({conflicts})");
+}
@@ -1,6 +1,7 @@
-use super::{UnityProject, load_collection};
+use super::{UnityProject, load_collection, output};
 use crate::commands::load_unity;
 use clap::{Parser, Subcommand};
+use indexmap::IndexMap;
 use itertools::Itertools;
 use serde::Serialize;
 use std::collections::HashSet;
@@ -40,7 +41,7 @@ impl Project {
     pub async fn run(self) {
         let unity = load_unity(self.project).await;
 
-        match self.json_format.map(|x| x.get()).unwrap_or_default() {
+        match output::json_format(self.json_format, 1) {
             0 => {
                 Self::human_readable(&unity).await;
             }
@@ -87,6 +88,15 @@ impl Project {
                 );
             }
         }
+
+        let mut overrides = unity.overrides().peekable();
+        if overrides.peek().is_some() {
+            eprintln!();
+            eprintln!("Overrides:");
+            for (package, range) in overrides {
+                eprintln!("{package} version {range}");
+            }
+        }
     }
 
     pub async fn version1(unity: &UnityProject) {
@@ -94,6 +104,9 @@ impl Project {
         struct Project<'a> {
             unity_version: Option<UnityVersion>,
             packages: &'a [PackageInfo<'a>],
+            // vrc-get specific
+            #[serde(skip_serializing_if = "IndexMap::is_empty")]
+            overrides: IndexMap<&'a str, &'a VersionRange>,
         }
 
         #[derive(Serialize)]
@@ -155,6 +168,7 @@ impl Project {
         let project = Project {
             unity_version: Some(unity.unity_version()),
             packages: packages.as_slice(),
+            overrides: unity.overrides().collect(),
         };
 
         println!("{}", serde_json::to_string(&project).unwrap());
@@ -167,6 +181,9 @@ impl Project {
 pub struct Package {
     #[arg()]
     package: String,
+    /// Path to project dir to apply the scoped registries of the project.
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
     #[command(flatten)]
     env_args: super::EnvArgs,
 
@@ -179,9 +196,13 @@ impl Package {
     pub async fn run(self) {
         let client = crate::create_client(self.env_args.offline);
         let io = DefaultEnvironmentIo::new_default();
-        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
+        let mut collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
+        if let Some(project) = self.project {
+            let unity = load_unity(Some(project)).await;
+            collection.add_scoped_registries(unity.scoped_registries());
+        }
 
-        let format_version = match self.json_format.map(|x| x.get()).unwrap_or_default() {
+        let format_version = match output::json_format(self.json_format, 1) {
             0 => {
                 eprintln!("warning: no --json-format is specified! using lastest version 1");
                 1
@@ -201,9 +222,38 @@ impl Package {
             })
             .collect();
 
+        let out_of_scope: Vec<_> = collection
+            .find_packages_out_of_scope(&self.package)
+            .map(|x| OutOfScopeVersionInfo {
+                version: x.version(),
+                repository: x
+                    .repo()
+                    .and_then(|repo| repo.id().or(repo.url().map(|x| x.as_str()))),
+            })
+            .collect();
+
+        if !out_of_scope.is_empty() {
+            let allowed = collection
+                .scoped_registries_for(&self.package)
+                .map(|x| x.repository())
+                .join(", ");
+            for version in &out_of_scope {
+                eprintln!(
+                    "warning: {}@{} from {} is ignored since {} may only come from {allowed}",
+                    self.package,
+                    version.version,
+                    version.repository.unwrap_or("unknown repository"),
+                    self.package,
+                );
+            }
+        }
+
         #[derive(Serialize)]
         struct PackageInfo<'a> {
             versions: &'a [PackageVersionInfo<'a>],
+            // vrc-get specific
+            #[serde(skip_serializing_if = "<[_]>::is_empty")]
+            out_of_scope: &'a [OutOfScopeVersionInfo<'a>],
         }
 
         #[derive(Serialize)]
@@ -212,8 +262,16 @@ impl Package {
             is_yanked: bool,
         }
 
+        /// The version ignored since the repository is not allowed by the scoped registries.
+        #[derive(Serialize)]
+        struct OutOfScopeVersionInfo<'a> {
+            version: &'a Version,
+            repository: Option<&'a str>,
+        }
+
         let package_info = PackageInfo {
             versions: versions.as_slice(),
+            out_of_scope: out_of_scope.as_slice(),
         };
 
         println!("{}", serde_json::to_string(&package_info).unwrap());
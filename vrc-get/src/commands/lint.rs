@@ -0,0 +1,164 @@
+use crate::commands::{EnvArgs, load_collection, output};
+use clap::Parser;
+use serde::Serialize;
+use serde_json::Value;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use tokio::fs;
+use vrc_get_vpm::io::DefaultEnvironmentIo;
+use vrc_get_vpm::lint::{LintIssue, LintIssueKind, lint_package_manifest, lint_repository};
+
+/// Validate a package.json or a repository json
+///
+/// Problems that make the package ignored or fail to resolve are reported,
+/// including dependencies not found in the repositories added to this PC.
+/// Exits with non-zero status if any problem is found.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Lint {
+    /// Path to the package folder, package.json, or repository json. Defaults to the current directory
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    #[command(flatten)]
+    env_args: EnvArgs,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+}
+
+impl Lint {
+    pub async fn run(self) {
+        let json_format = output::json_format(self.json_format, 1);
+        if json_format > 1 {
+            exit_with!("unsupported json version: {json_format}");
+        }
+
+        let path = if self.path.is_dir() {
+            self.path.join("package.json")
+        } else {
+            self.path
+        };
+
+        let json = fs::read(&path)
+            .await
+            .unwrap_or_else(|e| exit_with!("error reading {}: {e}", path.display()));
+        let json = json.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&json);
+        let json = serde_json::from_slice::<Value>(json)
+            .unwrap_or_else(|e| exit_with!("error parsing {}: {e}", path.display()));
+
+        let client = crate::create_client(self.env_args.offline);
+        let io = DefaultEnvironmentIo::new_default();
+        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
+
+        let issues = if json.get("packages").is_some_and(Value::is_object) {
+            lint_repository(&json, &collection)
+        } else {
+            lint_package_manifest(&json, &collection)
+        };
+
+        if json_format != 0 {
+            print_json(&path, &issues);
+        } else if issues.is_empty() {
+            println!("no problems found in {}", path.display());
+        } else {
+            for issue in &issues {
+                println!("{issue}");
+            }
+        }
+
+        if !issues.is_empty() {
+            eprintln!("{} problems found in {}", issues.len(), path.display());
+            exit(1);
+        }
+    }
+}
+
+fn print_json(path: &Path, issues: &[LintIssue]) {
+    #[derive(Serialize)]
+    struct LintInfo<'a> {
+        path: &'a Path,
+        issues: Vec<IssueInfo<'a>>,
+    }
+
+    #[derive(Serialize)]
+    struct IssueInfo<'a> {
+        package: &'a str,
+        version: Option<&'a str>,
+        message: String,
+        #[serde(flatten)]
+        kind: IssueKind<'a>,
+    }
+
+    /// The kind of the issue with the details, identified by `kind`.
+    #[derive(Serialize)]
+    #[serde(tag = "kind", rename_all = "snake_case")]
+    enum IssueKind<'a> {
+        InvalidManifest {
+            error: &'a str,
+        },
+        InvalidVersionRange {
+            dependency: &'a str,
+            range: &'a str,
+        },
+        UnknownDependency {
+            dependency: &'a str,
+        },
+        CircularDependency {
+            cycle: &'a [Box<str>],
+        },
+        InvalidLegacyGuid {
+            path: &'a str,
+            guid: &'a str,
+        },
+        InvalidUnityVersion {
+            unity: &'a str,
+        },
+        MissingZipSha256,
+        MismatchedName {
+            name: &'a str,
+        },
+        MismatchedVersion {
+            version: &'a str,
+        },
+        /// The issue without structured information
+        Other,
+    }
+
+    let issues = (issues.iter())
+        .map(|issue| IssueInfo {
+            package: issue.package(),
+            version: issue.version(),
+            message: issue.kind().to_string(),
+            kind: match issue.kind() {
+                LintIssueKind::InvalidManifest { error } => IssueKind::InvalidManifest { error },
+                LintIssueKind::InvalidVersionRange { dependency, range } => {
+                    IssueKind::InvalidVersionRange { dependency, range }
+                }
+                LintIssueKind::UnknownDependency { dependency } => {
+                    IssueKind::UnknownDependency { dependency }
+                }
+                LintIssueKind::CircularDependency { cycle } => {
+                    IssueKind::CircularDependency { cycle }
+                }
+                LintIssueKind::InvalidLegacyGuid { path, guid } => {
+                    IssueKind::InvalidLegacyGuid { path, guid }
+                }
+                LintIssueKind::InvalidUnityVersion { unity } => {
+                    IssueKind::InvalidUnityVersion { unity }
+                }
+                LintIssueKind::MissingZipSha256 => IssueKind::MissingZipSha256,
+                LintIssueKind::MismatchedName { name } => IssueKind::MismatchedName { name },
+                LintIssueKind::MismatchedVersion { version } => {
+                    IssueKind::MismatchedVersion { version }
+                }
+                _ => IssueKind::Other,
+            },
+        })
+        .collect();
+
+    let info = LintInfo { path, issues };
+    println!("{}", serde_json::to_string(&info).unwrap());
+}
@@ -1,5 +1,7 @@
+use crate::commands::plan::PlanArgs;
 use crate::commands::{
-    EnvArgs, ResultExt, confirm_prompt, load_collection, load_unity, update_project_last_modified,
+    EnvArgs, ResultExt, confirm_prompt, load_collection, load_unity, output, parse_unity_version,
+    print_prompt_install, update_project_last_modified,
 };
 use clap::{Parser, Subcommand};
 use log::info;
@@ -8,16 +10,137 @@ use std::process::exit;
 use tokio::process::Command;
 use vrc_get_vpm::environment::PackageInstaller;
 use vrc_get_vpm::io::DefaultEnvironmentIo;
+use vrc_get_vpm::version::UnityVersion;
 
 /// Migrate Unity Project
 #[derive(Subcommand)]
 #[command(author, version)]
 pub enum Migrate {
+    Unity(Unity),
     Unity2022(Unity2022),
     Vpm(Vpm),
 }
 
-multi_command!(Migrate is Unity2022, Vpm);
+multi_command!(Migrate is Unity, Unity2022, Vpm);
+
+async fn launch_unity_batch_mode(unity: &Path, project_dir: &Path) {
+    let status = Command::new(unity)
+        .args([
+            "-quit".as_ref(),
+            "-batchmode".as_ref(),
+            "-projectPath".as_ref(),
+            project_dir.as_os_str(),
+        ])
+        .status()
+        .await
+        .exit_context("launching unity to finalize migration");
+
+    if !status.success() {
+        exit_with!("Unity exited with status {}", status);
+    }
+}
+
+fn confirm_migration(message: &str, yes: bool) {
+    let warning = format!(
+        "{message}\n\
+        It's hard to undo this command.\n\
+        You MUST create backup of your project before running this command."
+    );
+    // stdout is used for json, so the warning is printed to stderr
+    if output::is_json() {
+        eprintln!("{warning}");
+    } else {
+        println!("{warning}");
+    }
+    if yes {
+        if !output::is_json() {
+            println!("--yes is set. skipping confirm");
+        }
+    } else if !confirm_prompt("Do you want to continue?") {
+        exit(1);
+    }
+}
+
+/// Migrate your project to another Unity version
+///
+/// The packages not compatible with the Unity version are upgraded to the versions for the Unity version,
+/// and ProjectVersion.txt is updated.
+/// Use `vrc-get unity-compat` to check the packages to be changed before migration.
+#[derive(Parser)]
+pub struct Unity {
+    /// The Unity version to migrate to
+    #[arg(value_parser = parse_unity_version)]
+    version: UnityVersion,
+    /// The revision of the Unity version, like `887be4894c44` for 2022.3.22f1
+    #[arg(long)]
+    revision: Option<String>,
+    /// Path to unity executable of the version. if specified, unity is launched in batch mode to reimport the project
+    #[arg(long)]
+    unity: Option<PathBuf>,
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+    #[command(flatten)]
+    env_args: EnvArgs,
+    #[command(flatten)]
+    plan_args: PlanArgs,
+    /// skip confirm
+    #[arg(short, long)]
+    yes: bool,
+}
+
+impl Unity {
+    pub async fn run(self) {
+        let mut project = load_unity(self.project).await;
+
+        let client = crate::create_client(self.env_args.offline);
+        let io = DefaultEnvironmentIo::new_default();
+        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
+        let installer = PackageInstaller::new(&io, client.as_ref());
+
+        let changes = project
+            .migrate_unity_request(&collection, self.version)
+            .await
+            .exit_context("collecting packages to be migrated");
+        if self.plan_args.dry_run_migrate_unity(
+            &project,
+            &changes,
+            self.version,
+            self.revision.as_deref(),
+        ) {
+            return;
+        }
+        if !changes.package_changes().is_empty() {
+            print_prompt_install(&project, &changes);
+        }
+
+        confirm_migration(
+            &format!(
+                "You're migrating your project from Unity {} to Unity {} in-place.",
+                project.unity_version(),
+                self.version
+            ),
+            self.yes,
+        );
+
+        self.plan_args.backup(&io, &project).await;
+
+        project
+            .migrate_unity_with_changes(&installer, changes, self.version, self.revision.as_deref())
+            .await
+            .exit_context("migrating unity project");
+
+        info!("Updating manifest file finished successfully.");
+
+        if let Some(unity) = &self.unity {
+            info!("Launching Unity to reimport the project...");
+            launch_unity_batch_mode(unity, project.project_dir()).await;
+            info!("Unity exited successfully. Migration finished.");
+        }
+
+        update_project_last_modified(&io, project.project_dir()).await;
+    }
+}
 
 /// Migrate your project to Unity 2022
 #[derive(Parser)]
@@ -35,17 +158,15 @@ pub struct Unity2022 {
     unity: Option<PathBuf>,
     #[command(flatten)]
     env_args: EnvArgs,
+    #[command(flatten)]
+    plan_args: PlanArgs,
+    /// skip confirm
+    #[arg(short, long)]
+    yes: bool,
 }
 
 impl Unity2022 {
     pub async fn run(self) {
-        println!("You're migrating your project to Unity 2022 in-place.");
-        println!("It's hard to undo this command.");
-        println!("You MUST create backup of your project before running this command.");
-        if !confirm_prompt("Do you want to continue?") {
-            exit(1);
-        }
-
         let mut project = load_unity(self.project).await;
 
         let client = crate::create_client(self.env_args.offline);
@@ -53,6 +174,23 @@ impl Unity2022 {
         let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
         let installer = PackageInstaller::new(&io, client.as_ref());
 
+        // the changes are collected again in migration, so collect here only for printing
+        if self.plan_args.is_dry_run() {
+            let changes = project
+                .migrate_unity_2022_request(&collection)
+                .await
+                .exit_context("collecting packages to be migrated");
+            self.plan_args.dry_run(&project, &changes);
+            return;
+        }
+
+        confirm_migration(
+            "You're migrating your project to Unity 2022 in-place.",
+            self.yes,
+        );
+
+        self.plan_args.backup(&io, &project).await;
+
         #[cfg(feature = "experimental-vcc")]
         let connection = vrc_get_vpm::environment::VccDatabaseConnection::connect(&io)
             .await
@@ -95,20 +233,7 @@ impl Unity2022 {
             }
         };
 
-        let status = Command::new(&unity)
-            .args([
-                "-quit".as_ref(),
-                "-batchmode".as_ref(),
-                "-projectPath".as_ref(),
-                project.project_dir().as_os_str(),
-            ])
-            .status()
-            .await
-            .exit_context("launching unity to finalize migration");
-
-        if !status.success() {
-            exit_with!("Unity exited with status {}", status);
-        }
+        launch_unity_batch_mode(&unity, project.project_dir()).await;
 
         info!("Unity exited successfully. Migration finished.");
 
@@ -124,17 +249,15 @@ pub struct Vpm {
     project: Option<Box<Path>>,
     #[command(flatten)]
     env_args: EnvArgs,
+    #[command(flatten)]
+    plan_args: PlanArgs,
+    /// skip confirm
+    #[arg(short, long)]
+    yes: bool,
 }
 
 impl Vpm {
     pub async fn run(self) {
-        println!("You're migrating your project to vpm in-place.");
-        println!("It's hard to undo this command.");
-        println!("You MUST create backup of your project before running this command.");
-        if !confirm_prompt("Do you want to continue?") {
-            exit(1);
-        }
-
         let mut project = load_unity(self.project).await;
 
         let client = crate::create_client(self.env_args.offline);
@@ -142,6 +265,20 @@ impl Vpm {
         let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
         let installer = PackageInstaller::new(&io, client.as_ref());
 
+        // the changes are collected again in migration, so collect here only for printing
+        if self.plan_args.is_dry_run() {
+            let changes = project
+                .migrate_vpm_request(&collection, false)
+                .await
+                .exit_context("collecting packages to be migrated");
+            self.plan_args.dry_run(&project, &changes);
+            return;
+        }
+
+        confirm_migration("You're migrating your project to vpm in-place.", self.yes);
+
+        self.plan_args.backup(&io, &project).await;
+
         project
             .migrate_vpm(&collection, &installer, false)
             .await
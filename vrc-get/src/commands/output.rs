@@ -0,0 +1,332 @@
+//! The global output format of the commands.
+//!
+//! With `--output json`, commands print json in the latest version of their format
+//! instead of human-readable text, and errors are printed as json objects to stdout.
+
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::OnceLock;
+use vrc_get_vpm::unity_project::{
+    AddPackageErr, FrozenLockViolation, ImportConflict, ImportUnityPackageError,
+    ReinstalPackagesError, RemovePackageErr, ResolvePackageErr, UnityPackageAsset,
+};
+use vrc_get_vpm::version::{Version, VersionRange};
+
+/// The version of the json format of errors.
+const ERROR_FORMAT_VERSION: u32 = 1;
+
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+#[derive(Default, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// Machine-readable json in the latest version of the format of each command
+    Json,
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => f.write_str("text"),
+            OutputFormat::Json => f.write_str("json"),
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct OutputArgs {
+    /// The output format of the command.
+    ///
+    /// With json, each command prints json in the latest version of its format.
+    /// Use --json-format of the command to pin the version.
+    #[arg(long, global = true, default_value_t)]
+    output: OutputFormat,
+}
+
+impl OutputArgs {
+    pub fn init(&self) {
+        OUTPUT_FORMAT.set(self.output).ok();
+    }
+}
+
+/// Returns `true` if `--output json` is specified.
+pub fn is_json() -> bool {
+    OUTPUT_FORMAT.get().copied().unwrap_or_default() == OutputFormat::Json
+}
+
+/// Returns the version of the json format to print, or `0` for human-readable text.
+///
+/// The version specified with `--json-format` is preferred, and `latest` is used with `--output json`.
+pub fn json_format(json_format: Option<NonZeroU32>, latest: u32) -> u32 {
+    match json_format {
+        Some(version) => version.get(),
+        None if is_json() => latest,
+        None => 0,
+    }
+}
+
+/// Prints the error and exits with non-zero status.
+///
+/// `context` is what the command was doing, and `details` is the structured information of the error.
+pub fn exit_with_error(context: Option<&str>, message: &str, details: ErrorDetails) -> ! {
+    if is_json() {
+        #[derive(Serialize)]
+        struct ErrorOutput<'a> {
+            format_version: u32,
+            error: ErrorObject<'a>,
+        }
+
+        #[derive(Serialize)]
+        struct ErrorObject<'a> {
+            context: Option<&'a str>,
+            message: &'a str,
+            #[serde(flatten)]
+            details: ErrorDetails<'a>,
+        }
+
+        let output = ErrorOutput {
+            format_version: ERROR_FORMAT_VERSION,
+            error: ErrorObject {
+                context,
+                message,
+                details,
+            },
+        };
+        println!("{}", serde_json::to_string(&output).unwrap());
+    } else {
+        match context {
+            Some(context) => eprintln!("error {context}: {message}"),
+            None => eprintln!("{message}"),
+        }
+    }
+    std::process::exit(1)
+}
+
+/// The structured information of the error, identified by `kind`.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ErrorDetails<'a> {
+    /// The error without structured information
+    Other,
+    DependenciesNotFound {
+        dependencies: Vec<DependencyInfo<'a>>,
+    },
+    UpgradingNonLockedPackage {
+        package: &'a str,
+    },
+    DowngradingNonLockedPackage {
+        package: &'a str,
+    },
+    UpgradingWithDowngrade {
+        package: &'a str,
+    },
+    FrozenLockViolation {
+        violations: Vec<ViolationInfo<'a>>,
+    },
+    ResolutionStepLimitExceeded,
+    NotInstalled {
+        packages: Vec<&'a str>,
+    },
+    ConflictsWith {
+        packages: &'a [Box<str>],
+    },
+    ImportConflicts {
+        assets: Vec<AssetInfo<'a>>,
+    },
+    Io,
+}
+
+#[derive(Serialize)]
+pub struct DependencyInfo<'a> {
+    package: &'a str,
+    range: &'a VersionRange,
+}
+
+/// `required_by` is `None` if the range is from `dependencies` section.
+#[derive(Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ViolationInfo<'a> {
+    LockedNotFound {
+        package: &'a str,
+        version: &'a Version,
+    },
+    RangeNotSatisfied {
+        package: &'a str,
+        version: &'a Version,
+        required_by: Option<&'a str>,
+        range: &'a VersionRange,
+    },
+    NotLocked {
+        package: &'a str,
+        required_by: Option<&'a str>,
+        range: &'a VersionRange,
+    },
+    Other,
+}
+
+/// The asset in a unitypackage with the conflicts with the project.
+#[derive(Serialize)]
+pub struct AssetInfo<'a> {
+    path: &'a str,
+    guid: &'a str,
+    is_folder: bool,
+    conflicts: Vec<ConflictInfo<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConflictInfo<'a> {
+    PathExists,
+    GuidMismatch { guid: &'a str },
+    GuidExists { path: &'a Path },
+    TypeMismatch,
+}
+
+pub fn asset_info(asset: &UnityPackageAsset) -> AssetInfo<'_> {
+    AssetInfo {
+        path: asset.path(),
+        guid: asset.guid(),
+        is_folder: asset.is_folder(),
+        conflicts: (asset.conflicts().iter())
+            .map(|conflict| match conflict {
+                ImportConflict::PathExists => ConflictInfo::PathExists,
+                ImportConflict::GuidMismatch(guid) => ConflictInfo::GuidMismatch { guid },
+                ImportConflict::GuidExists(path) => ConflictInfo::GuidExists { path },
+                ImportConflict::TypeMismatch => ConflictInfo::TypeMismatch,
+            })
+            .collect(),
+    }
+}
+
+/// The error with structured information for `--output json`.
+pub trait StructuredError: Display {
+    fn details(&self) -> ErrorDetails<'_>;
+}
+
+fn dependencies_not_found(dependencies: &[(Box<str>, VersionRange)]) -> ErrorDetails<'_> {
+    ErrorDetails::DependenciesNotFound {
+        dependencies: dependencies
+            .iter()
+            .map(|(package, range)| DependencyInfo { package, range })
+            .collect(),
+    }
+}
+
+impl StructuredError for AddPackageErr {
+    fn details(&self) -> ErrorDetails<'_> {
+        match self {
+            AddPackageErr::DependenciesNotFound { dependencies } => {
+                dependencies_not_found(dependencies)
+            }
+            AddPackageErr::UpgradingNonLockedPackage { package_name } => {
+                ErrorDetails::UpgradingNonLockedPackage {
+                    package: package_name,
+                }
+            }
+            AddPackageErr::DowngradingNonLockedPackage { package_name } => {
+                ErrorDetails::DowngradingNonLockedPackage {
+                    package: package_name,
+                }
+            }
+            AddPackageErr::UpgradingWithDowngrade { package_name } => {
+                ErrorDetails::UpgradingWithDowngrade {
+                    package: package_name,
+                }
+            }
+            AddPackageErr::ResolutionStepLimitExceeded => ErrorDetails::ResolutionStepLimitExceeded,
+            _ => ErrorDetails::Other,
+        }
+    }
+}
+
+impl StructuredError for ResolvePackageErr {
+    fn details(&self) -> ErrorDetails<'_> {
+        match self {
+            ResolvePackageErr::DependenciesNotFound { dependencies } => {
+                dependencies_not_found(dependencies)
+            }
+            ResolvePackageErr::FrozenLockViolation { violations } => {
+                ErrorDetails::FrozenLockViolation {
+                    violations: violations.iter().map(violation_info).collect(),
+                }
+            }
+            ResolvePackageErr::ResolutionStepLimitExceeded => {
+                ErrorDetails::ResolutionStepLimitExceeded
+            }
+            _ => ErrorDetails::Other,
+        }
+    }
+}
+
+fn violation_info(violation: &FrozenLockViolation) -> ViolationInfo<'_> {
+    match violation {
+        FrozenLockViolation::LockedNotFound { package, version } => {
+            ViolationInfo::LockedNotFound { package, version }
+        }
+        FrozenLockViolation::RangeNotSatisfied {
+            package,
+            version,
+            required_by,
+            range,
+        } => ViolationInfo::RangeNotSatisfied {
+            package,
+            version,
+            required_by: required_by.as_deref(),
+            range,
+        },
+        FrozenLockViolation::NotLocked {
+            package,
+            required_by,
+            range,
+        } => ViolationInfo::NotLocked {
+            package,
+            required_by: required_by.as_deref(),
+            range,
+        },
+        _ => ViolationInfo::Other,
+    }
+}
+
+impl StructuredError for RemovePackageErr {
+    fn details(&self) -> ErrorDetails<'_> {
+        match self {
+            RemovePackageErr::Io(_) => ErrorDetails::Io,
+            RemovePackageErr::NotInstalled(packages) => ErrorDetails::NotInstalled {
+                packages: packages.iter().map(Box::as_ref).collect(),
+            },
+            RemovePackageErr::ConflictsWith(packages) => ErrorDetails::ConflictsWith { packages },
+        }
+    }
+}
+
+impl StructuredError for ReinstalPackagesError {
+    fn details(&self) -> ErrorDetails<'_> {
+        match self {
+            ReinstalPackagesError::NotInstalled { package_name } => ErrorDetails::NotInstalled {
+                packages: vec![package_name],
+            },
+            ReinstalPackagesError::DependenciesNotFound { dependencies } => {
+                dependencies_not_found(dependencies)
+            }
+            _ => ErrorDetails::Other,
+        }
+    }
+}
+
+impl StructuredError for ImportUnityPackageError {
+    fn details(&self) -> ErrorDetails<'_> {
+        match self {
+            ImportUnityPackageError::Conflicts(assets)
+            | ImportUnityPackageError::CannotOverwrite(assets) => ErrorDetails::ImportConflicts {
+                assets: assets.iter().map(asset_info).collect(),
+            },
+            ImportUnityPackageError::Io(_) => ErrorDetails::Io,
+            _ => ErrorDetails::Other,
+        }
+    }
+}
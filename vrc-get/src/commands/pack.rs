@@ -0,0 +1,87 @@
+use crate::commands::output;
+use crate::commands::repo_build::pack_folder;
+use clap::Parser;
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+/// Pack a package folder into a zip file for release
+///
+/// package.json is validated in the same way as when installing the package.
+/// `.git` folders and folders whose names end with `~` are not included.
+/// The zip file is reproducible; packing the same folder always produces the same zip file.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Pack {
+    /// Path to the package folder with package.json
+    folder: PathBuf,
+
+    /// The path to write the zip file. Defaults to `<name>-<version>.zip` in the current directory
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// The url the zip file will be published at. Used for the printed manifest entry
+    #[arg(long)]
+    url: Option<Url>,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+}
+
+impl Pack {
+    pub async fn run(self) {
+        let (zip_dir, file_name) = match &self.output {
+            Some(output) => {
+                let Some(file_name) = output.file_name() else {
+                    exit_with!("invalid output path: {}", output.display());
+                };
+                let zip_dir = output.parent().unwrap_or(Path::new(""));
+                (zip_dir, Some(file_name.to_string_lossy().into_owned()))
+            }
+            None => (Path::new(""), None),
+        };
+
+        let (package, file_name) = pack_folder(&self.folder, zip_dir, file_name).await;
+
+        let mut entry = package.json().clone();
+        if let Some(url) = &self.url {
+            entry.insert("url".to_owned(), Value::String(url.to_string()));
+        }
+        entry.insert(
+            "zipSHA256".to_owned(),
+            Value::String(hex::encode(package.sha256())),
+        );
+
+        match output::json_format(self.json_format, 1) {
+            0 => {}
+            1 => {
+                #[derive(Serialize)]
+                struct PackInfo<'a> {
+                    path: &'a Path,
+                    sha256: String,
+                    manifest_entry: &'a Map<String, Value>,
+                }
+                let info = PackInfo {
+                    path: &zip_dir.join(&file_name),
+                    sha256: hex::encode(package.sha256()),
+                    manifest_entry: &entry,
+                };
+                println!("{}", serde_json::to_string(&info).unwrap());
+                return;
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+
+        println!("packed to {}", zip_dir.join(&file_name).display());
+        println!("sha256: {}", hex::encode(package.sha256()));
+        println!("manifest entry:");
+        println!(
+            "{}: {}",
+            Value::String(package.manifest().version().to_string()),
+            serde_json::to_string_pretty(&entry).unwrap()
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use clap::Args;
+use serde::Serialize;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::io::DefaultEnvironmentIo;
+use vrc_get_vpm::unity_project::PendingProjectChanges;
+use vrc_get_vpm::unity_project::pending_project_changes::{PackageChange, RemoveReason};
+use vrc_get_vpm::version::{UnityVersion, Version, VersionRange};
+
+/// The options to show the changes without applying them, or to back up the project before applying them.
+#[derive(Args)]
+pub struct PlanArgs {
+    /// Print the changes to the project without applying them
+    #[arg(long)]
+    dry_run: bool,
+    /// Print the changes as json. Requires --dry-run
+    ///
+    /// This is implied by --output json.
+    #[arg(long, requires = "dry_run")]
+    json: bool,
+    /// Create a backup of the project in the backup folder of the settings before applying the changes
+    #[arg(long, conflicts_with = "dry_run")]
+    backup: bool,
+    /// Keep only the specified number of the backups created with --backup for the project
+    #[arg(long, requires = "backup")]
+    backup_keep: Option<NonZeroUsize>,
+}
+
+/// Prints the changes as json to be applied with `--output json`.
+pub fn print_json(unity: &UnityProject, changes: &PendingProjectChanges) {
+    println!("{}", to_json(unity, changes));
+}
+
+/// Returns the changes as json to be embedded in the json output of the command.
+pub fn to_json(unity: &UnityProject, changes: &PendingProjectChanges) -> serde_json::Value {
+    serde_json::to_value(Plan::new(unity, changes)).unwrap()
+}
+
+impl PlanArgs {
+    /// Returns whether --dry-run is specified, to collect the changes only for printing.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Prints the plan if --dry-run is specified.
+    ///
+    /// returns: `true` if the changes must not be applied
+    pub fn dry_run(&self, unity: &UnityProject, changes: &PendingProjectChanges) -> bool {
+        self.dry_run_plan(Plan::new(unity, changes))
+    }
+
+    /// Prints the plan with the change to ProjectSettings/ProjectVersion.txt if --dry-run is specified.
+    ///
+    /// returns: `true` if the changes must not be applied
+    pub fn dry_run_migrate_unity(
+        &self,
+        unity: &UnityProject,
+        changes: &PendingProjectChanges,
+        target: UnityVersion,
+        revision: Option<&str>,
+    ) -> bool {
+        let mut plan = Plan::new(unity, changes);
+        plan.project_version = Some(PlannedProjectVersion {
+            from: unity.unity_version(),
+            to: target,
+            revision,
+        });
+        self.dry_run_plan(plan)
+    }
+
+    fn dry_run_plan(&self, plan: Plan) -> bool {
+        if !self.dry_run {
+            return false;
+        }
+
+        if self.json || super::output::is_json() {
+            println!("{}", serde_json::to_value(plan).unwrap());
+        } else {
+            plan.print();
+        }
+
+        true
+    }
+
+    /// Creates the backup of the project if --backup is specified.
+    ///
+    /// This should be called after the prompts and just before applying the changes.
+    pub async fn backup(&self, io: &DefaultEnvironmentIo, unity: &UnityProject) {
+        if self.backup {
+            let keep = self.backup_keep.map(NonZeroUsize::get);
+            super::backup::backup_project(io, unity, keep).await;
+        }
+    }
+}
+
+/// The complete changes to the project.
+#[derive(Serialize)]
+struct Plan<'a> {
+    install: Vec<PlannedInstall<'a>>,
+    remove: Vec<PlannedRemove<'a>>,
+    remove_legacy_files: Vec<&'a Path>,
+    remove_legacy_folders: Vec<&'a Path>,
+    conflicts: Vec<PlannedConflict<'a>>,
+    overridden_requirements: Vec<PlannedOverride<'a>>,
+    /// The dependencies to be removed from Packages/manifest.json
+    remove_upm_dependencies: Vec<&'a str>,
+    /// The Unity version to be written to ProjectSettings/ProjectVersion.txt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project_version: Option<PlannedProjectVersion<'a>>,
+}
+
+#[derive(Serialize)]
+struct PlannedProjectVersion<'a> {
+    from: UnityVersion,
+    to: UnityVersion,
+    revision: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct PlannedInstall<'a> {
+    name: &'a str,
+    /// The locked version before the change, if any
+    from: Option<&'a Version>,
+    /// The version to be installed, or `None` if only the dependency is added
+    to: Option<&'a Version>,
+    is_yanked: bool,
+    /// The version range added to the dependencies
+    to_dependencies: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PlannedRemove<'a> {
+    name: &'a str,
+    version: Option<&'a Version>,
+    reason: &'static str,
+}
+
+#[derive(Serialize)]
+struct PlannedConflict<'a> {
+    package: &'a str,
+    conflicting_packages: &'a [Box<str>],
+    conflicts_with_unity: bool,
+    /// The directories in Packages to be removed since they conflict with the package
+    unlocked_directories: &'a [Box<str>],
+    explanation: Option<String>,
+}
+
+/// The requirement replaced by overrides in vpm-manifest.json
+#[derive(Serialize)]
+struct PlannedOverride<'a> {
+    package: Option<&'a str>,
+    version: Option<&'a Version>,
+    dependency: &'a str,
+    range: &'a VersionRange,
+    override_range: &'a VersionRange,
+}
+
+fn reason_name(reason: RemoveReason) -> &'static str {
+    match reason {
+        RemoveReason::Requested => "requested",
+        RemoveReason::Legacy => "legacy",
+        RemoveReason::Unused => "unused",
+    }
+}
+
+impl<'a> Plan<'a> {
+    fn new(unity: &'a UnityProject, changes: &'a PendingProjectChanges) -> Self {
+        let locked_version = |name: &str| unity.get_locked(name).map(|x| x.version());
+
+        let mut install = Vec::new();
+        let mut remove = Vec::new();
+
+        for (name, change) in changes.package_changes() {
+            match change {
+                PackageChange::Install(change) => {
+                    let package = change.install_package();
+                    install.push(PlannedInstall {
+                        name,
+                        from: locked_version(name),
+                        to: package.map(|x| x.version()),
+                        is_yanked: package.is_some_and(|x| x.is_yanked()),
+                        to_dependencies: change.to_dependencies().map(|x| x.to_string()),
+                    });
+                }
+                PackageChange::Remove(change) => {
+                    remove.push(PlannedRemove {
+                        name,
+                        version: locked_version(name),
+                        reason: reason_name(change.reason()),
+                    });
+                }
+            }
+        }
+
+        install.sort_by_key(|x| x.name);
+        remove.sort_by_key(|x| x.name);
+
+        let mut conflicts = (changes.conflicts().iter())
+            .map(|(package, conflict)| PlannedConflict {
+                package,
+                conflicting_packages: conflict.conflicting_packages(),
+                conflicts_with_unity: conflict.conflicts_with_unity(),
+                unlocked_directories: conflict.unlocked_names(),
+                explanation: conflict.explanation().map(|x| x.to_string()),
+            })
+            .collect::<Vec<_>>();
+        conflicts.sort_by_key(|x| x.package);
+
+        Self {
+            install,
+            remove,
+            remove_legacy_files: (changes.remove_legacy_files().iter())
+                .map(|(path, _)| path.as_ref())
+                .collect(),
+            remove_legacy_folders: (changes.remove_legacy_folders().iter())
+                .map(|(path, _)| path.as_ref())
+                .collect(),
+            conflicts,
+            overridden_requirements: (changes.overridden_requirements().iter())
+                .map(|overridden| {
+                    let requirement = overridden.requirement();
+                    PlannedOverride {
+                        package: requirement.source().map(|x| x.0),
+                        version: requirement.source().map(|x| x.1),
+                        dependency: requirement.target(),
+                        range: requirement.range(),
+                        override_range: overridden.override_range(),
+                    }
+                })
+                .collect(),
+            remove_upm_dependencies: (changes.remove_upm_dependencies().iter())
+                .map(Box::as_ref)
+                .collect(),
+            project_version: None,
+        }
+    }
+
+    fn print(&self) {
+        if self.install.is_empty()
+            && self.remove.is_empty()
+            && self.remove_upm_dependencies.is_empty()
+            && self.project_version.is_none()
+        {
+            println!("nothing to do");
+            return;
+        }
+
+        if !self.install.is_empty() {
+            println!("Packages to be installed:");
+            for x in &self.install {
+                let mut line = format!("- {}", x.name);
+                match (x.from, x.to) {
+                    (Some(from), Some(to)) => line += &format!(" {from} -> {to}"),
+                    (None, Some(to)) => line += &format!(" {to}"),
+                    (_, None) => {}
+                }
+                if x.is_yanked {
+                    line += " (yanked)";
+                }
+                if let Some(range) = &x.to_dependencies {
+                    line += &format!(" (adding {range} to dependencies)");
+                }
+                println!("{line}");
+            }
+        }
+
+        if !self.remove.is_empty() {
+            println!("Packages to be removed:");
+            for x in &self.remove {
+                match x.version {
+                    Some(version) => println!("- {} {version} ({})", x.name, x.reason),
+                    None => println!("- {} ({})", x.name, x.reason),
+                }
+            }
+        }
+
+        if let Some(version) = &self.project_version {
+            let mut line = format!("Unity version: {} -> {}", version.from, version.to);
+            if let Some(revision) = version.revision {
+                line += &format!(" ({revision})");
+            }
+            println!("{line}");
+        }
+
+        if !self.remove_upm_dependencies.is_empty() {
+            println!("Packages to be removed from Packages/manifest.json:");
+            for name in &self.remove_upm_dependencies {
+                println!("- {name}");
+            }
+        }
+
+        if !self.remove_legacy_files.is_empty() || !self.remove_legacy_folders.is_empty() {
+            println!("Legacy assets to be removed:");
+            for path in self
+                .remove_legacy_folders
+                .iter()
+                .chain(&self.remove_legacy_files)
+            {
+                println!("- {}", path.display());
+            }
+        }
+
+        if !self.conflicts.is_empty() {
+            println!("Conflicts:");
+            for x in &self.conflicts {
+                println!("- {}", x.package);
+                for conflict in x.conflicting_packages {
+                    println!("  conflicts with {conflict}");
+                }
+                if x.conflicts_with_unity {
+                    println!("  incompatible with the unity version");
+                }
+                for directory in x.unlocked_directories {
+                    println!("  Packages/{directory} will be removed");
+                }
+                if let Some(explanation) = &x.explanation {
+                    println!("  because {explanation}");
+                }
+            }
+        }
+
+        if !self.overridden_requirements.is_empty() {
+            println!("Overridden requirements:");
+            for x in &self.overridden_requirements {
+                if let (Some(package), Some(version)) = (x.package, x.version) {
+                    print!("- {package} {version} requires ");
+                } else {
+                    print!("- ");
+                }
+                println!(
+                    "{} {} (overridden with {})",
+                    x.dependency, x.range, x.override_range
+                );
+            }
+        }
+    }
+}
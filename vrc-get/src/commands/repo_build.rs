@@ -0,0 +1,252 @@
+use crate::commands::{ResultExt, output};
+use clap::Parser;
+use log::{info, warn};
+use reqwest::Url;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use vrc_get_vpm::io::{DefaultProjectIo, IoTrait};
+use vrc_get_vpm::repository::{PackageZip, RemoteRepository, RepositoryBuilder};
+
+/// Build a repository json from package zips or package folders
+///
+/// Each zip file and each folder with package.json in the source directory is added to the repository.
+/// Package folders are zipped into `--zip-dir` before being added.
+/// The output is sorted by package name and version so the same packages always produce the same json.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct RepoBuild {
+    /// The directory containing package zips or package folders
+    source: PathBuf,
+
+    /// The url of package zips.
+    /// `{name}`, `{version}` and `{file}` are replaced with the package name, the version, and the file name of the zip
+    #[arg(long)]
+    url_template: String,
+
+    /// The path to write the repository json
+    #[arg(short, long, default_value = "index.json")]
+    output: PathBuf,
+
+    /// The existing repository json to merge with. Versions not in the source directory are kept
+    #[arg(long)]
+    merge: Option<PathBuf>,
+
+    /// The directory to write zips of package folders. Defaults to the directory of the output
+    #[arg(long)]
+    zip_dir: Option<PathBuf>,
+
+    /// The name of the repository
+    #[arg(long)]
+    name: Option<String>,
+
+    /// The id of the repository
+    #[arg(long)]
+    id: Option<String>,
+
+    /// The url the repository json will be published at
+    #[arg(long)]
+    url: Option<String>,
+
+    /// The author of the repository
+    #[arg(long)]
+    author: Option<String>,
+}
+
+impl RepoBuild {
+    pub async fn run(self) {
+        let mut builder = match &self.merge {
+            Some(merge) => {
+                let json = fs::read(merge)
+                    .await
+                    .exit_context("reading repository to merge");
+                let repo = serde_json::from_slice::<RemoteRepository>(&json)
+                    .exit_context("parsing repository to merge");
+                RepositoryBuilder::from_repository(&repo).exit_context("loading repository")
+            }
+            None => RepositoryBuilder::new(),
+        };
+
+        if let Some(name) = &self.name {
+            builder.set_name(name);
+        }
+        if let Some(id) = &self.id {
+            builder.set_id(id);
+        }
+        if let Some(url) = &self.url {
+            builder.set_url(url);
+        }
+        if let Some(author) = &self.author {
+            builder.set_author(author);
+        }
+
+        let zip_dir = match &self.zip_dir {
+            Some(zip_dir) => zip_dir.clone(),
+            None => (self.output.parent()).unwrap_or(Path::new("")).to_owned(),
+        };
+
+        let (zips, folders) = self.scan_source().await;
+
+        let mut packages = Vec::new();
+        let mut created = HashSet::new();
+
+        for folder in folders {
+            let (package, file_name) = pack_folder(&folder, &zip_dir, None).await;
+            info!("packed {} to {file_name}", folder.display());
+
+            created.insert(
+                fs::canonicalize(zip_dir.join(&file_name))
+                    .await
+                    .exit_context("resolving zip path"),
+            );
+            packages.push((package, file_name));
+        }
+
+        let source_io = DefaultProjectIo::new(self.source.clone().into_boxed_path());
+        for zip in zips {
+            let file_name = zip.file_name().unwrap().to_string_lossy().into_owned();
+            if let Ok(path) = fs::canonicalize(&zip).await
+                && created.contains(&path)
+            {
+                // the zip created from the package folder in this build
+                continue;
+            }
+            let file = source_io
+                .open(file_name.as_ref())
+                .await
+                .unwrap_or_else(|e| exit_with!("error opening {}: {e}", zip.display()));
+            let package = PackageZip::read(file)
+                .await
+                .unwrap_or_else(|e| exit_with!("error reading {}: {e}", zip.display()));
+            packages.push((package, file_name));
+        }
+
+        let mut added = HashSet::new();
+        for (package, file_name) in &packages {
+            let manifest = package.manifest();
+            if !added.insert((manifest.name(), manifest.version())) {
+                exit_with!(
+                    "{} version {} is found more than once in the source directory",
+                    manifest.name(),
+                    manifest.version()
+                );
+            }
+
+            let url = self
+                .url_template
+                .replace("{name}", manifest.name())
+                .replace("{version}", &manifest.version().to_string())
+                .replace("{file}", file_name);
+            if let Err(e) = Url::parse(&url) {
+                exit_with!("invalid url {url} for {}: {e}", manifest.name());
+            }
+
+            if builder.add_package(package, &url).is_some() {
+                warn!(
+                    "{} version {} in the merged repository is replaced",
+                    manifest.name(),
+                    manifest.version()
+                );
+            }
+        }
+
+        let repo = builder.build().exit_context("building repository");
+        let mut json = serde_json::to_string_pretty(&repo).unwrap();
+        json.push('\n');
+        fs::write(&self.output, json)
+            .await
+            .exit_context("writing repository json");
+
+        if !output::is_json() {
+            println!(
+                "added {} packages to {}",
+                packages.len(),
+                self.output.display()
+            );
+        }
+    }
+
+    /// Returns the zip files and the package folders in the source directory, sorted by path
+    async fn scan_source(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let mut zips = Vec::new();
+        let mut folders = Vec::new();
+
+        let mut read_dir = fs::read_dir(&self.source)
+            .await
+            .exit_context("reading source directory");
+        while let Some(entry) = read_dir
+            .next_entry()
+            .await
+            .exit_context("reading source directory")
+        {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let file_type = entry
+                .file_type()
+                .await
+                .exit_context("reading source directory");
+            if file_type.is_dir() {
+                if fs::try_exists(path.join("package.json"))
+                    .await
+                    .unwrap_or(false)
+                {
+                    folders.push(path);
+                }
+            } else if path
+                .extension()
+                .is_some_and(|x| x.eq_ignore_ascii_case("zip"))
+            {
+                zips.push(path);
+            }
+        }
+
+        zips.sort();
+        folders.sort();
+
+        (zips, folders)
+    }
+}
+
+/// Packs the package folder into `zip_dir`.
+///
+/// The name of the zip file defaults to `<name>-<version>.zip`.
+/// Returns the package and the name of the zip file.
+pub(super) async fn pack_folder(
+    folder: &Path,
+    zip_dir: &Path,
+    file_name: Option<String>,
+) -> (PackageZip, String) {
+    let zip_dir = if zip_dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        zip_dir
+    };
+    fs::create_dir_all(zip_dir)
+        .await
+        .exit_context("creating zip directory");
+
+    let zip_io = DefaultProjectIo::new(zip_dir.into());
+    let temp_name = Path::new(".vrc-get-pack.zip.tmp");
+    let zip_file = zip_io.create(temp_name).await.exit_context("creating zip");
+
+    let package = match PackageZip::create(&DefaultProjectIo::new(folder.into()), zip_file).await {
+        Ok(package) => package,
+        Err(e) => {
+            zip_io.remove_file(temp_name).await.ok();
+            exit_with!("error packing {}: {e}", folder.display());
+        }
+    };
+
+    let file_name = file_name.unwrap_or_else(|| {
+        let manifest = package.manifest();
+        format!("{}-{}.zip", manifest.name(), manifest.version())
+    });
+    zip_io
+        .rename(temp_name, file_name.as_ref())
+        .await
+        .exit_context("renaming zip");
+
+    (package, file_name)
+}
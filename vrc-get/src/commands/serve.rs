@@ -0,0 +1,430 @@
+use crate::commands::{EnvArgs, ResultExt, load_collection};
+use clap::Parser;
+use futures::prelude::*;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Empty, Full, StreamBody};
+use hyper::body::{Bytes, Frame, Incoming};
+use hyper::header::{
+    ALLOW, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_TYPE, ETAG, HeaderValue, IF_NONE_MATCH,
+};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use log::{error, info, warn};
+use reqwest::{Client, Url};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use vrc_get_vpm::environment::{PackageCollection, PackageInstaller};
+use vrc_get_vpm::io::{DefaultEnvironmentIo, TokioFile};
+use vrc_get_vpm::version::Version;
+
+/// Serves the cached repositories and packages as a VPM repository over HTTP.
+///
+/// All packages in the repositories added to this PC are served as one repository at `/index.json`,
+/// and the url of each package is rewritten to the zip file served by this server.
+/// Zip files are served from the package cache, and downloaded into the cache if not cached yet.
+///
+/// The repositories are loaded once at startup. Restart the server to reflect updates of repositories.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Serve {
+    /// The address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: SocketAddr,
+
+    /// The base url other machines use to access this server.
+    /// Defaults to `http://<bind address>/`, and required if the bind address is unspecified like `0.0.0.0`
+    #[arg(long)]
+    base_url: Option<Url>,
+
+    #[command(flatten)]
+    env_args: EnvArgs,
+}
+
+const INDEX_PATH: &str = "/index.json";
+const PACKAGES_PATH: &str = "/packages/";
+
+impl Serve {
+    pub async fn run(self) {
+        let client = crate::create_client(self.env_args.offline);
+        let io = DefaultEnvironmentIo::new_default();
+        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
+
+        let base_url = match self.base_url {
+            Some(mut base_url) => {
+                if !base_url.path().ends_with('/') {
+                    base_url.set_path(&format!("{}/", base_url.path()));
+                }
+                base_url
+            }
+            None if self.bind.ip().is_unspecified() => exit_with!(
+                "--base-url is required to listen on {}, since other machines cannot access the packages with that address",
+                self.bind
+            ),
+            None => Url::parse(&format!("http://{}/", self.bind)).exit_context("creating base url"),
+        };
+
+        let server = Arc::new(Server::new(collection, io, client, &base_url));
+
+        let listener = TcpListener::bind(self.bind)
+            .await
+            .exit_context("binding address");
+
+        info!(
+            "serving {} packages from {} repositories",
+            server.packages.len(),
+            server.collection.get_remote().count()
+        );
+        info!("add {base_url}index.json as the repository on other machines");
+
+        loop {
+            let (stream, remote) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!("error accepting connection: {e}");
+                    continue;
+                }
+            };
+
+            let server = server.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |request| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle(request).await) }
+                });
+
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    warn!("error serving connection from {remote}: {e}");
+                }
+            });
+        }
+    }
+}
+
+type Body = BoxBody<Bytes, std::io::Error>;
+type PackageKey = (Box<str>, Version);
+
+struct Server {
+    collection: PackageCollection,
+    io: DefaultEnvironmentIo,
+    client: Option<Client>,
+    index: Bytes,
+    index_etag: String,
+    /// (name, version) to the index of the repository in the collection
+    packages: HashMap<PackageKey, usize>,
+    /// the locks to prevent downloading the same package concurrently
+    download_locks: Mutex<HashMap<PackageKey, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl Server {
+    fn new(
+        collection: PackageCollection,
+        io: DefaultEnvironmentIo,
+        client: Option<Client>,
+        base_url: &Url,
+    ) -> Self {
+        let mut packages = HashMap::new();
+        let mut json_packages = Map::<String, Value>::new();
+
+        for (repo_index, repo) in collection.get_remote().enumerate() {
+            let Ok(Value::Object(mut json)) = serde_json::to_value(repo.repo()) else {
+                continue;
+            };
+            let Some(Value::Object(repo_packages)) = json.remove("packages") else {
+                continue;
+            };
+
+            for (name, package) in repo_packages {
+                let Value::Object(mut package) = package else {
+                    continue;
+                };
+                let Some(Value::Object(versions)) = package.remove("versions") else {
+                    continue;
+                };
+
+                let json_versions = json_packages
+                    .entry(name.clone())
+                    .or_insert_with(|| {
+                        Value::Object(Map::from_iter([(
+                            "versions".to_owned(),
+                            Value::Object(Map::new()),
+                        )]))
+                    })
+                    .get_mut("versions")
+                    .and_then(Value::as_object_mut)
+                    .unwrap();
+
+                for (version_str, manifest) in versions {
+                    let Value::Object(mut manifest) = manifest else {
+                        continue;
+                    };
+                    let Ok(version) = Version::from_str(&version_str) else {
+                        continue;
+                    };
+                    if repo.repo().get_package_version(&name, &version).is_none() {
+                        // the manifest is broken
+                        continue;
+                    }
+                    let key = (Box::<str>::from(name.as_str()), version);
+                    if packages.contains_key(&key) {
+                        // the package from former repository is used
+                        continue;
+                    }
+
+                    let mut url = base_url.clone();
+                    url.path_segments_mut().unwrap().pop_if_empty().extend([
+                        "packages",
+                        &name,
+                        &format!("{version_str}.zip"),
+                    ]);
+                    manifest.insert("url".to_owned(), Value::String(url.to_string()));
+                    // the headers are used by this server to download the package
+                    manifest.remove("headers");
+
+                    json_versions.insert(version_str, Value::Object(manifest));
+                    packages.insert(key, repo_index);
+                }
+            }
+        }
+
+        let mut index_url = base_url.clone();
+        index_url.set_path(&format!("{}index.json", base_url.path()));
+
+        let index = serde_json::json!({
+            "name": "vrc-get mirror",
+            "id": format!("vrc-get-mirror.{}", base_url.host_str().unwrap_or("localhost")),
+            "url": index_url.as_str(),
+            "author": "vrc-get",
+            "packages": json_packages,
+        });
+        let index = Bytes::from(serde_json::to_vec(&index).unwrap());
+        let index_etag = format!("\"{}\"", hex::encode(Sha256::digest(&index)));
+
+        Self {
+            collection,
+            io,
+            client,
+            index,
+            index_etag,
+            packages,
+            download_locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn handle(&self, request: Request<Incoming>) -> Response<Body> {
+        let head = match *request.method() {
+            Method::GET => false,
+            Method::HEAD => true,
+            _ => {
+                let mut response = status_response(StatusCode::METHOD_NOT_ALLOWED);
+                (response.headers_mut()).insert(ALLOW, HeaderValue::from_static("GET, HEAD"));
+                return response;
+            }
+        };
+
+        let path = request.uri().path();
+        let if_none_match = request.headers().get(IF_NONE_MATCH);
+
+        let response = if path == INDEX_PATH {
+            self.serve_index(if_none_match, head)
+        } else if let Some(package_path) = path.strip_prefix(PACKAGES_PATH) {
+            self.serve_package(package_path, if_none_match, head).await
+        } else {
+            status_response(StatusCode::NOT_FOUND)
+        };
+
+        info!("{} {path} {}", request.method(), response.status());
+
+        response
+    }
+
+    fn serve_index(&self, if_none_match: Option<&HeaderValue>, head: bool) -> Response<Body> {
+        if etag_matches(if_none_match, &self.index_etag) {
+            return not_modified(&self.index_etag);
+        }
+
+        let body = if head {
+            empty_body()
+        } else {
+            Full::new(self.index.clone())
+                .map_err(|never| match never {})
+                .boxed()
+        };
+
+        Response::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_LENGTH, self.index.len())
+            .header(ETAG, &self.index_etag)
+            .header(CACHE_CONTROL, "no-cache")
+            .body(body)
+            .unwrap()
+    }
+
+    async fn serve_package(
+        &self,
+        package_path: &str,
+        if_none_match: Option<&HeaderValue>,
+        head: bool,
+    ) -> Response<Body> {
+        // packages/<name>/<version>.zip
+        let Some((name, version)) = package_path
+            .strip_suffix(".zip")
+            .and_then(|x| x.split_once('/'))
+        else {
+            return status_response(StatusCode::NOT_FOUND);
+        };
+        let (Ok(name), Ok(version)) = (
+            percent_decode(name),
+            percent_decode(version).and_then(|x| Version::from_str(&x).map_err(|_| ())),
+        ) else {
+            return status_response(StatusCode::NOT_FOUND);
+        };
+
+        let key = (Box::<str>::from(name.as_str()), version);
+        let Some(&repo_index) = self.packages.get(&key) else {
+            return status_response(StatusCode::NOT_FOUND);
+        };
+        let repo = self.collection.get_remote().nth(repo_index).unwrap();
+        let manifest = repo.repo().get_package_version(&key.0, &key.1).unwrap();
+
+        let lock = self
+            .download_locks
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let guard = lock.lock().await;
+
+        let installer = PackageInstaller::new(&self.io, self.client.as_ref());
+        let result = installer.get_package_zip(repo, manifest).await;
+
+        drop(guard);
+        {
+            // remove the lock unless other requests are waiting for it
+            let mut locks = self.download_locks.lock().unwrap();
+            if Arc::strong_count(&lock) == 2 {
+                locks.remove(&key);
+            }
+        }
+
+        let (file, hash) = match result {
+            Ok(file) => file,
+            Err(e) => {
+                error!("error getting {}@{}: {e}", key.0, key.1);
+                return status_response(StatusCode::BAD_GATEWAY);
+            }
+        };
+
+        let etag = format!("\"{}\"", hex::encode(hash));
+        if etag_matches(if_none_match, &etag) {
+            return not_modified(&etag);
+        }
+
+        let length = match file.get_ref().metadata().await {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                error!("error reading {}@{}: {e}", key.0, key.1);
+                return status_response(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        let body = if head { empty_body() } else { file_body(file) };
+
+        Response::builder()
+            .header(CONTENT_TYPE, "application/zip")
+            .header(CONTENT_LENGTH, length)
+            .header(ETAG, etag)
+            .body(body)
+            .unwrap()
+    }
+}
+
+fn etag_matches(if_none_match: Option<&HeaderValue>, etag: &str) -> bool {
+    let Some(if_none_match) = if_none_match.and_then(|x| x.to_str().ok()) else {
+        return false;
+    };
+
+    if_none_match.split(',').map(str::trim).any(|tag| {
+        // weak comparison is used for If-None-Match
+        tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag
+    })
+}
+
+fn percent_decode(segment: &str) -> Result<String, ()> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut iter = segment.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next().ok_or(())?, iter.next().ok_or(())?];
+            bytes.push(<[u8; 1] as hex::FromHex>::from_hex(hex).map_err(|_| ())?[0]);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| ())
+}
+
+fn empty_body() -> Body {
+    Empty::new().map_err(|never| match never {}).boxed()
+}
+
+fn file_body(file: TokioFile) -> Body {
+    let stream = stream::try_unfold(file, |mut file| async move {
+        let mut buffer = vec![0; 64 * 1024];
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.truncate(read);
+        Ok(Some((Frame::data(Bytes::from(buffer)), file)))
+    });
+
+    BodyExt::boxed(StreamBody::new(stream))
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let body = Full::new(Bytes::from(status.to_string()))
+        .map_err(|never| match never {})
+        .boxed();
+    Response::builder().status(status).body(body).unwrap()
+}
+
+fn not_modified(etag: &str) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .header(ETAG, etag)
+        .body(empty_body())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_segment() {
+        assert_eq!(
+            percent_decode("com.anatawa12.package"),
+            Ok("com.anatawa12.package".into())
+        );
+        assert_eq!(percent_decode("1.0.0%2Bbuild"), Ok("1.0.0+build".into()));
+        assert_eq!(percent_decode("%e3%81%82"), Ok("\u{3042}".into()));
+        // truncated escape
+        assert_eq!(percent_decode("1.0.0%2"), Err(()));
+        // not hex
+        assert_eq!(percent_decode("%zz"), Err(()));
+        // not utf8
+        assert_eq!(percent_decode("%ff"), Err(()));
+    }
+}
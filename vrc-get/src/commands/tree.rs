@@ -0,0 +1,409 @@
+use super::{load_unity, output};
+use clap::{Parser, ValueEnum};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter, Write};
+use std::path::Path;
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::version::{PrereleaseAcceptance, Version};
+
+/// Show dependency tree of installed packages in the project
+///
+/// Packages not locked in vpm-manifest.json, legacy packages replaced by other packages,
+/// and dependencies with unsatisfied version ranges are marked in the output.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Tree {
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+
+    /// The output format.
+    #[arg(long, default_value_t)]
+    format: TreeFormat,
+}
+
+#[derive(Default, Copy, Clone, Eq, PartialEq, ValueEnum)]
+enum TreeFormat {
+    /// Human-readable tree
+    #[default]
+    Text,
+    /// Graphviz DOT graph
+    Dot,
+    /// Machine-readable json
+    Json,
+}
+
+impl Display for TreeFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeFormat::Text => f.write_str("text"),
+            TreeFormat::Dot => f.write_str("dot"),
+            TreeFormat::Json => f.write_str("json"),
+        }
+    }
+}
+
+impl Tree {
+    pub async fn run(self) {
+        let unity = load_unity(self.project).await;
+        let graph = Graph::new(&unity);
+
+        let format = if self.format == TreeFormat::Text && output::is_json() {
+            TreeFormat::Json
+        } else {
+            self.format
+        };
+
+        match format {
+            TreeFormat::Text => print!("{}", graph.to_text()),
+            TreeFormat::Dot => print!("{}", graph.to_dot()),
+            TreeFormat::Json => println!("{}", serde_json::to_string(&graph.to_json()).unwrap()),
+        }
+    }
+}
+
+struct Graph<'a> {
+    dependencies: Vec<Edge<'a>>,
+    packages: IndexMap<&'a str, Node<'a>>,
+    upm_dependencies: Vec<(&'a str, String)>,
+}
+
+struct Node<'a> {
+    version: &'a Version,
+    unlocked: bool,
+    dependencies: Vec<Edge<'a>>,
+}
+
+struct Edge<'a> {
+    name: &'a str,
+    range: String,
+    status: EdgeStatus<'a>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum EdgeStatus<'a> {
+    Satisfied,
+    Unsatisfied,
+    Legacy { replaced_by: &'a str },
+    Missing,
+}
+
+impl<'a> Graph<'a> {
+    fn new(unity: &'a UnityProject) -> Self {
+        let mut packages = IndexMap::new();
+
+        for info in unity.all_packages() {
+            let unlocked = !unity.is_locked(info.name());
+            packages.entry(info.name()).or_insert((info, unlocked));
+        }
+
+        let legacy_packages = unity
+            .all_installed_packages()
+            .flat_map(|pkg| {
+                (pkg.legacy_packages().iter()).map(move |legacy| (legacy.as_ref(), pkg.name()))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let status = |name: &str, matches: &dyn Fn(&Version) -> bool| {
+            if let Some(&replaced_by) = legacy_packages.get(name) {
+                EdgeStatus::Legacy { replaced_by }
+            } else if let Some((info, _)) = packages.get(name) {
+                if matches(info.version()) {
+                    EdgeStatus::Satisfied
+                } else {
+                    EdgeStatus::Unsatisfied
+                }
+            } else {
+                EdgeStatus::Missing
+            }
+        };
+
+        let dependencies = unity
+            .dependencies_with_range()
+            .map(|(name, range)| Edge {
+                name,
+                range: range.to_string(),
+                status: status(name, &|version| range.matches(version)),
+            })
+            .collect();
+
+        let nodes = packages
+            .iter()
+            .map(|(&name, (info, unlocked))| {
+                let dependencies = info
+                    .dependencies()
+                    .iter()
+                    .map(|(dependency, range)| Edge {
+                        name: dependency,
+                        range: range.to_string(),
+                        status: status(dependency, &|version| {
+                            range.match_pre(version, PrereleaseAcceptance::Allow)
+                        }),
+                    })
+                    .collect();
+                let node = Node {
+                    version: info.version(),
+                    unlocked: *unlocked,
+                    dependencies,
+                };
+                (name, node)
+            })
+            .collect();
+
+        let mut upm_dependencies = unity
+            .upm_dependencies()
+            .map(|(name, dependency)| (name, dependency.to_string()))
+            .collect::<Vec<_>>();
+        upm_dependencies.sort_by_key(|(name, _)| *name);
+
+        Self {
+            dependencies,
+            packages: nodes,
+            upm_dependencies,
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        let mut printed = HashSet::new();
+
+        out.push_str("project\n");
+        self.write_children(&mut out, &self.dependencies, "", &mut printed);
+
+        // packages not reachable from the dependencies of the project
+        let rest = (self.packages.keys())
+            .filter(|name| !printed.contains(*name))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for name in rest {
+            if printed.contains(name) {
+                continue;
+            }
+            let node = &self.packages[name];
+            printed.insert(name);
+            writeln!(out, "{name} {}{}", node.version, node_markers(node)).unwrap();
+            self.write_children(&mut out, &node.dependencies, "", &mut printed);
+        }
+
+        if !self.upm_dependencies.is_empty() {
+            out.push('\n');
+            out.push_str("UPM packages\n");
+            for (index, (name, dependency)) in self.upm_dependencies.iter().enumerate() {
+                let branch = if index + 1 == self.upm_dependencies.len() {
+                    "└── "
+                } else {
+                    "├── "
+                };
+                writeln!(out, "{branch}{name} {dependency}").unwrap();
+            }
+        }
+
+        out
+    }
+
+    fn write_children(
+        &self,
+        out: &mut String,
+        edges: &[Edge<'a>],
+        prefix: &str,
+        printed: &mut HashSet<&'a str>,
+    ) {
+        for (index, edge) in edges.iter().enumerate() {
+            let last = index + 1 == edges.len();
+            let (branch, child_prefix) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+
+            write!(out, "{prefix}{branch}{} ({})", edge.name, edge.range).unwrap();
+
+            match edge.status {
+                EdgeStatus::Legacy { replaced_by } => {
+                    writeln!(out, " [legacy, replaced by {replaced_by}]").unwrap();
+                    continue;
+                }
+                EdgeStatus::Missing => {
+                    writeln!(out, " [missing]").unwrap();
+                    continue;
+                }
+                EdgeStatus::Satisfied | EdgeStatus::Unsatisfied => {}
+            }
+
+            let node = &self.packages[edge.name];
+            write!(out, " {}{}", node.version, node_markers(node)).unwrap();
+            if let EdgeStatus::Unsatisfied = edge.status {
+                out.push_str(" [unsatisfied]");
+            }
+
+            if !printed.insert(edge.name) {
+                // already printed; avoid printing the same subtree and cyclic dependencies
+                if !node.dependencies.is_empty() {
+                    out.push_str(" (*)");
+                }
+                out.push('\n');
+                continue;
+            }
+            out.push('\n');
+
+            let prefix = format!("{prefix}{child_prefix}");
+            self.write_children(out, &node.dependencies, &prefix, printed);
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("digraph dependencies {\n");
+        out.push_str("    \"project\" [shape=box];\n");
+
+        for (name, node) in &self.packages {
+            let mut label = format!(
+                "{}\\n{}",
+                dot_escape(name),
+                dot_escape(&node.version.to_string())
+            );
+            let mut attrs = String::new();
+            if node.unlocked {
+                label.push_str("\\n(unlocked)");
+                attrs.push_str(", style=dashed");
+            }
+            writeln!(
+                out,
+                "    \"{}\" [label=\"{}\"{attrs}];",
+                dot_escape(name),
+                label
+            )
+            .unwrap();
+        }
+
+        let mut missing = HashSet::new();
+        let mut write_edges = |out: &mut String, from: &str, edges: &[Edge]| {
+            for edge in edges {
+                let attrs = match edge.status {
+                    EdgeStatus::Satisfied => String::new(),
+                    EdgeStatus::Unsatisfied => ", color=red, fontcolor=red".to_string(),
+                    EdgeStatus::Legacy { replaced_by } => {
+                        format!(
+                            ", style=dotted, xlabel=\"replaced by {}\"",
+                            dot_escape(replaced_by)
+                        )
+                    }
+                    EdgeStatus::Missing => {
+                        if missing.insert(edge.name.to_string()) {
+                            writeln!(
+                                out,
+                                "    \"{}\" [color=red, fontcolor=red];",
+                                dot_escape(edge.name)
+                            )
+                            .unwrap();
+                        }
+                        ", color=red, fontcolor=red".to_string()
+                    }
+                };
+                writeln!(
+                    out,
+                    "    \"{}\" -> \"{}\" [label=\"{}\"{attrs}];",
+                    dot_escape(from),
+                    dot_escape(edge.name),
+                    dot_escape(&edge.range)
+                )
+                .unwrap();
+            }
+        };
+
+        write_edges(&mut out, "project", &self.dependencies);
+        for (name, node) in &self.packages {
+            write_edges(&mut out, name, &node.dependencies);
+        }
+
+        if !self.upm_dependencies.is_empty() {
+            out.push_str("    subgraph cluster_upm {\n");
+            out.push_str("        label=\"UPM packages\";\n");
+            for (name, dependency) in &self.upm_dependencies {
+                writeln!(
+                    out,
+                    "        \"upm:{}\" [label=\"{}\\n{}\", shape=box];",
+                    dot_escape(name),
+                    dot_escape(name),
+                    dot_escape(dependency)
+                )
+                .unwrap();
+            }
+            out.push_str("    }\n");
+        }
+
+        out.push_str("}\n");
+
+        out
+    }
+
+    fn to_json(&self) -> impl Serialize + '_ {
+        #[derive(Serialize)]
+        struct TreeInfo<'a> {
+            dependencies: Vec<EdgeInfo<'a>>,
+            packages: Vec<PackageInfo<'a>>,
+            upm_dependencies: Vec<UpmInfo<'a>>,
+        }
+
+        #[derive(Serialize)]
+        struct PackageInfo<'a> {
+            name: &'a str,
+            version: &'a Version,
+            unlocked: bool,
+            dependencies: Vec<EdgeInfo<'a>>,
+        }
+
+        #[derive(Serialize)]
+        struct EdgeInfo<'a> {
+            name: &'a str,
+            range: &'a str,
+            #[serde(flatten)]
+            status: EdgeStatus<'a>,
+        }
+
+        #[derive(Serialize)]
+        struct UpmInfo<'a> {
+            name: &'a str,
+            version: &'a str,
+        }
+
+        fn edges<'a>(edges: &'a [Edge<'a>]) -> Vec<EdgeInfo<'a>> {
+            edges
+                .iter()
+                .map(|edge| EdgeInfo {
+                    name: edge.name,
+                    range: &edge.range,
+                    status: edge.status,
+                })
+                .collect()
+        }
+
+        TreeInfo {
+            dependencies: edges(&self.dependencies),
+            packages: (self.packages.iter())
+                .map(|(name, node)| PackageInfo {
+                    name,
+                    version: node.version,
+                    unlocked: node.unlocked,
+                    dependencies: edges(&node.dependencies),
+                })
+                .collect(),
+            upm_dependencies: (self.upm_dependencies.iter())
+                .map(|(name, version)| UpmInfo { name, version })
+                .collect(),
+        }
+    }
+}
+
+fn node_markers(node: &Node) -> &'static str {
+    if node.unlocked { " [unlocked]" } else { "" }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
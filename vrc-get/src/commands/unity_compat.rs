@@ -0,0 +1,227 @@
+use super::{EnvArgs, load_collection, load_unity, output, parse_unity_version};
+use clap::Parser;
+use serde::Serialize;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::process::exit;
+use vrc_get_vpm::io::DefaultEnvironmentIo;
+use vrc_get_vpm::unity_project::{CompatibilityStatus, UnityCompatibility};
+use vrc_get_vpm::version::{UnityVersion, Version, VersionRange};
+
+/// Check which Unity versions the packages of the project can be used with
+///
+/// For each Unity version, the packages not compatible with the version are replaced with
+/// the latest version for the Unity version, and the packages need to be changed or
+/// without compatible version are reported.
+/// Exits with non-zero status if the project cannot be used with any of the Unity versions.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct UnityCompat {
+    /// The Unity versions to check. by default the Unity installations registered in VCC will be used
+    #[arg(value_parser = parse_unity_version)]
+    unity_versions: Vec<UnityVersion>,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+    #[command(flatten)]
+    env_args: EnvArgs,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+}
+
+impl UnityCompat {
+    pub async fn run(self) {
+        let unity = load_unity(self.project).await;
+
+        let client = crate::create_client(self.env_args.offline);
+        let io = DefaultEnvironmentIo::new_default();
+        let collection = load_collection(&io, client.as_ref(), self.env_args.no_update).await;
+
+        let mut unity_versions = self.unity_versions;
+        if unity_versions.is_empty() {
+            unity_versions = installed_unity_versions(&io).await;
+        }
+        unity_versions.sort();
+        unity_versions.dedup();
+
+        let results = (unity_versions.iter())
+            .map(|&version| unity.unity_compatibility(&collection, version))
+            .collect::<Vec<_>>();
+
+        match output::json_format(self.json_format, 1) {
+            0 => {
+                for result in &results {
+                    print_human_readable(result);
+                }
+            }
+            1 => print_json(&results),
+            v => exit_with!("unsupported json version: {v}"),
+        }
+
+        if !results.iter().all(UnityCompatibility::is_compatible) {
+            exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "experimental-vcc")]
+async fn installed_unity_versions(io: &DefaultEnvironmentIo) -> Vec<UnityVersion> {
+    use super::ResultExt;
+    let connection = vrc_get_vpm::environment::VccDatabaseConnection::connect(io)
+        .await
+        .exit_context("connecting to database");
+    let versions = (connection.get_unity_installations().iter())
+        .filter_map(|x| x.version())
+        .collect::<Vec<_>>();
+    if versions.is_empty() {
+        exit_with!(
+            "no unity installations found. please load from unity hub with `vrc-get vcc unity update` or specify unity versions."
+        );
+    }
+    versions
+}
+
+#[cfg(not(feature = "experimental-vcc"))]
+async fn installed_unity_versions(_: &DefaultEnvironmentIo) -> Vec<UnityVersion> {
+    exit_with!("please specify unity versions to check");
+}
+
+fn print_human_readable(result: &UnityCompatibility) {
+    let summary = if !result.is_compatible() {
+        "not compatible"
+    } else if result.needs_changes() {
+        "compatible with changes"
+    } else {
+        "compatible"
+    };
+    println!("Unity {}: {summary}", result.unity_version());
+
+    for package in result.packages() {
+        let (name, version) = (package.name(), package.version());
+        match package.status() {
+            CompatibilityStatus::Compatible => {}
+            CompatibilityStatus::Change(changed) => {
+                println!("  {name}: {version} -> {}", changed.version())
+            }
+            CompatibilityStatus::NoCompatibleVersion => {
+                println!("  {name}: {version} has no compatible version")
+            }
+            CompatibilityStatus::Unknown => {
+                println!("  {name}: {version} is not found so compatibility is unknown")
+            }
+            _ => {}
+        }
+    }
+    for package in result.new_packages() {
+        println!("  {}: {} (new)", package.name(), package.version());
+    }
+    for (package, conflicts_with) in result.conflicts() {
+        println!("  {package}: conflicts with {}", conflicts_with.join(", "));
+    }
+    for (package, range) in result.missing_dependencies() {
+        println!("  {package}@{range}: not found");
+    }
+    if result.step_limit_exceeded() {
+        println!("  gave up resolving dependencies: too many combinations of package versions");
+    }
+}
+
+fn print_json(results: &[UnityCompatibility]) {
+    #[derive(Serialize)]
+    struct CompatibilityInfo<'a> {
+        unity_version: UnityVersion,
+        compatible: bool,
+        needs_changes: bool,
+        packages: Vec<PackageInfo<'a>>,
+        new_packages: Vec<NewPackageInfo<'a>>,
+        conflicts: Vec<ConflictInfo<'a>>,
+        missing_dependencies: Vec<MissingInfo<'a>>,
+        /// true if the resolution gave up since there are too many combinations of versions
+        step_limit_exceeded: bool,
+    }
+
+    #[derive(Serialize)]
+    struct PackageInfo<'a> {
+        name: &'a str,
+        version: &'a Version,
+        /// one of compatible, change, no_compatible_version, or unknown
+        status: &'static str,
+        /// the version to change to if status is change
+        new_version: Option<&'a Version>,
+    }
+
+    #[derive(Serialize)]
+    struct NewPackageInfo<'a> {
+        name: &'a str,
+        version: &'a Version,
+    }
+
+    #[derive(Serialize)]
+    struct ConflictInfo<'a> {
+        package: &'a str,
+        conflicts_with: &'a [Box<str>],
+    }
+
+    #[derive(Serialize)]
+    struct MissingInfo<'a> {
+        package: &'a str,
+        range: &'a VersionRange,
+    }
+
+    #[derive(Serialize)]
+    struct UnityCompatInfo<'a> {
+        unity_versions: Vec<CompatibilityInfo<'a>>,
+    }
+
+    let info = UnityCompatInfo {
+        unity_versions: (results.iter())
+            .map(|result| CompatibilityInfo {
+                unity_version: result.unity_version(),
+                compatible: result.is_compatible(),
+                needs_changes: result.needs_changes(),
+                packages: (result.packages().iter())
+                    .map(|package| {
+                        let (status, new_version) = match package.status() {
+                            CompatibilityStatus::Compatible => ("compatible", None),
+                            CompatibilityStatus::Change(changed) => {
+                                ("change", Some(changed.version()))
+                            }
+                            CompatibilityStatus::NoCompatibleVersion => {
+                                ("no_compatible_version", None)
+                            }
+                            CompatibilityStatus::Unknown => ("unknown", None),
+                            _ => ("unknown", None),
+                        };
+                        PackageInfo {
+                            name: package.name(),
+                            version: package.version(),
+                            status,
+                            new_version,
+                        }
+                    })
+                    .collect(),
+                new_packages: (result.new_packages().iter())
+                    .map(|package| NewPackageInfo {
+                        name: package.name(),
+                        version: package.version(),
+                    })
+                    .collect(),
+                conflicts: (result.conflicts().iter())
+                    .map(|(package, conflicts_with)| ConflictInfo {
+                        package,
+                        conflicts_with,
+                    })
+                    .collect(),
+                missing_dependencies: (result.missing_dependencies().iter())
+                    .map(|(package, range)| MissingInfo { package, range })
+                    .collect(),
+                step_limit_exceeded: result.step_limit_exceeded(),
+            })
+            .collect(),
+    };
+
+    println!("{}", serde_json::to_string(&info).unwrap());
+}
@@ -0,0 +1,209 @@
+use super::{ResultExt, load_unity, output};
+use clap::{Parser, Subcommand};
+use log::warn;
+use reqwest::Url;
+use serde::Serialize;
+use std::num::NonZeroU32;
+use std::path::Path;
+use vrc_get_vpm::UnityProject;
+use vrc_get_vpm::unity_project::{UpmDependency, UpmScopedRegistry};
+
+/// Commands around Unity Package Manager dependencies in Packages/manifest.json
+#[derive(Subcommand)]
+#[command(author, version)]
+pub enum Upm {
+    List(UpmList),
+    Add(UpmAdd),
+    Remove(UpmRemove),
+}
+
+multi_command!(Upm is List, Add, Remove);
+
+/// Returns the scoped registry for the package.
+fn find_registry<'a>(unity: &'a UnityProject, name: &str) -> Option<&'a UpmScopedRegistry> {
+    // scopes are matched by the namespace of the package name
+    unity.upm_scoped_registries().iter().find(|registry| {
+        (registry.scopes().iter()).any(|scope| {
+            name.strip_prefix(scope.as_ref())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    })
+}
+
+fn warn_vpm_package(unity: &UnityProject, name: &str) {
+    if unity.is_vpm_package(name) {
+        warn!(
+            "{name} is also managed by vpm in this project. \
+            Unity uses the package in Packages/{name} and the dependency in manifest.json is ignored."
+        );
+    }
+}
+
+/// List dependencies and scoped registries in Packages/manifest.json
+#[derive(Parser)]
+#[command(author, version)]
+pub struct UpmList {
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+}
+
+impl UpmList {
+    pub async fn run(self) {
+        let unity = load_unity(self.project).await;
+
+        let mut dependencies = unity.upm_dependencies().collect::<Vec<_>>();
+        dependencies.sort_by_key(|(name, _)| *name);
+
+        match output::json_format(self.json_format, 1) {
+            0 => {}
+            1 => {
+                #[derive(Serialize)]
+                struct UpmInfo<'a> {
+                    dependencies: Vec<DependencyInfo<'a>>,
+                    scoped_registries: &'a [UpmScopedRegistry],
+                }
+
+                #[derive(Serialize)]
+                struct DependencyInfo<'a> {
+                    name: &'a str,
+                    /// The version, git url, or local path
+                    dependency: String,
+                    /// The name of the scoped registry
+                    registry: Option<&'a str>,
+                    is_vpm_package: bool,
+                }
+
+                let info = UpmInfo {
+                    dependencies: (dependencies.iter())
+                        .map(|(name, dependency)| DependencyInfo {
+                            name,
+                            dependency: dependency.to_string(),
+                            registry: find_registry(&unity, name).map(|x| x.name()),
+                            is_vpm_package: unity.is_vpm_package(name),
+                        })
+                        .collect(),
+                    scoped_registries: unity.upm_scoped_registries(),
+                };
+                println!("{}", serde_json::to_string(&info).unwrap());
+                return;
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+
+        for (name, dependency) in &dependencies {
+            let mut line = format!("{name}: {dependency}");
+            if let Some(registry) = find_registry(&unity, name) {
+                line += &format!(" (from {})", registry.name());
+            }
+            if unity.is_vpm_package(name) {
+                line += " (also managed by vpm)";
+            }
+            println!("{line}");
+        }
+
+        if !unity.upm_scoped_registries().is_empty() {
+            println!();
+            println!("Scoped Registries:");
+            for registry in unity.upm_scoped_registries() {
+                println!("{} ({})", registry.name(), registry.url());
+                for scope in registry.scopes() {
+                    println!("- {scope}");
+                }
+            }
+        }
+    }
+}
+
+/// Add a dependency to Packages/manifest.json
+///
+/// The dependency can be a version in the registry, a git url, or a local path starting with `file:`.
+/// If the dependency exists, it will be replaced.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct UpmAdd {
+    /// Name of the package
+    #[arg()]
+    name: String,
+    /// Version, git url, or `file:` path of the package
+    #[arg()]
+    dependency: UpmDependency,
+
+    /// Url of the scoped registry to get the package from
+    ///
+    /// The package is added to the scopes of the registry, and the registry is added if not exists.
+    #[arg(long)]
+    registry: Option<Url>,
+    /// Name of the scoped registry added with --registry. by default the host of the url will be used
+    #[arg(long, requires = "registry")]
+    registry_name: Option<String>,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+}
+
+impl UpmAdd {
+    pub async fn run(self) {
+        let mut unity = load_unity(self.project).await;
+
+        let registry_name = (self.registry_name.as_deref())
+            .or(self.registry.as_ref().and_then(Url::host_str))
+            .unwrap_or("registry")
+            .to_owned();
+        let registry_url = self
+            .registry
+            .as_ref()
+            .map(|x| x.as_str().trim_end_matches('/'));
+
+        unity
+            .add_upm_dependency(
+                &self.name,
+                self.dependency,
+                registry_url.map(|url| (registry_name.as_str(), url)),
+            )
+            .exit_context("adding the dependency");
+
+        warn_vpm_package(&unity, &self.name);
+
+        unity.save().await.exit_context("saving manifest file");
+    }
+}
+
+/// Remove dependencies from Packages/manifest.json
+///
+/// The scopes for the packages are removed from the scoped registries,
+/// and the scoped registries without scopes are removed as well.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct UpmRemove {
+    /// Name of the packages to remove
+    #[arg(required = true)]
+    names: Vec<String>,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+}
+
+impl UpmRemove {
+    pub async fn run(self) {
+        let mut unity = load_unity(self.project).await;
+
+        for name in &self.names {
+            if !unity.has_upm_package(name) {
+                exit_with!("{name} is not in Packages/manifest.json");
+            }
+        }
+
+        for name in &self.names {
+            unity.remove_upm_dependency(name);
+        }
+
+        unity.save().await.exit_context("saving manifest file");
+    }
+}
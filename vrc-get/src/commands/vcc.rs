@@ -1,11 +1,14 @@
-use crate::commands::{ResultExt, absolute_path};
+use crate::commands::{ResultExt, absolute_path, output};
 use clap::{Parser, Subcommand, ValueEnum};
 use log::warn;
+use serde::Serialize;
 use std::cmp::Reverse;
 use std::fmt::{Display, Formatter};
+use std::num::NonZeroU32;
 use std::path::Path;
 use vrc_get_vpm::environment::{Settings, VccDatabaseConnection, find_unity_hub};
 use vrc_get_vpm::io::{DefaultEnvironmentIo, DefaultProjectIo};
+use vrc_get_vpm::version::UnityVersion;
 use vrc_get_vpm::{UnityProject, unity_hub};
 
 /// Experimental VCC commands
@@ -57,6 +60,10 @@ async fn migrate_sanitize_projects(
 pub struct ProjectList {
     #[command(flatten)]
     env_args: super::EnvArgs,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
 }
 
 impl ProjectList {
@@ -84,6 +91,34 @@ impl ProjectList {
 
         projects.sort_by_key(|x| Reverse(x.last_modified()));
 
+        match output::json_format(self.json_format, 1) {
+            0 => {}
+            1 => {
+                #[derive(Serialize)]
+                struct ProjectInfo<'a> {
+                    name: &'a str,
+                    path: &'a str,
+                    unity_version: Option<UnityVersion>,
+                    target: String,
+                    is_favorite: bool,
+                }
+                let projects = (projects.iter())
+                    .filter_map(|project| {
+                        Some(ProjectInfo {
+                            name: project.name()?,
+                            path: project.path()?,
+                            unity_version: project.unity_version(),
+                            target: project.project_type().to_string(),
+                            is_favorite: project.favorite(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&projects).unwrap());
+                return;
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+
         for project in projects.iter() {
             let Some(path) = project.path() else { continue };
             let Some(name) = project.name() else { continue };
@@ -160,7 +195,7 @@ impl ProjectRemove {
             .find_project(self.path.as_ref())
             .exit_context("getting projects")
         else {
-            return println!("No project found at {}", self.path);
+            return eprintln!("No project found at {}", self.path);
         };
 
         migrate_sanitize_projects(&mut connection, &io, &settings).await;
@@ -193,6 +228,10 @@ multi_command!(Unity is List, Add, Remove, Update);
 pub struct UnityList {
     #[command(flatten)]
     env_args: super::EnvArgs,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
 }
 
 impl UnityList {
@@ -206,6 +245,29 @@ impl UnityList {
 
         unity_installations.sort_by_key(|x| Reverse(x.version()));
 
+        match output::json_format(self.json_format, 1) {
+            0 => {}
+            1 => {
+                #[derive(Serialize)]
+                struct UnityInfo<'a> {
+                    /// null if the version is unknown
+                    version: Option<UnityVersion>,
+                    path: &'a str,
+                }
+                let installations = (unity_installations.iter())
+                    .filter_map(|unity| {
+                        Some(UnityInfo {
+                            version: unity.version(),
+                            path: unity.path()?,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&installations).unwrap());
+                return;
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+
         for unity in unity_installations.iter() {
             if let Some(path) = unity.path() {
                 if let Some(unity_version) = unity.version() {
@@ -244,7 +306,9 @@ impl UnityAdd {
 
         connection.save(&io).await.exit_context("saving database");
 
-        println!("Added version {} at {}", unity_version, self.path);
+        if !output::is_json() {
+            println!("Added version {} at {}", unity_version, self.path);
+        }
     }
 }
 
@@ -0,0 +1,176 @@
+use super::{
+    EnvArgs, ResultExt, load_collection_for_install, load_unity, output, plan,
+    print_prompt_install, prompt_install, update_project_last_modified,
+};
+use clap::Parser;
+use serde::Serialize;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::process::exit;
+use vrc_get_vpm::environment::PackageInstaller;
+use vrc_get_vpm::io::DefaultEnvironmentIo;
+use vrc_get_vpm::unity_project::VerifyPackagesResult;
+use vrc_get_vpm::version::Version;
+
+/// Check installed packages are not modified
+///
+/// Each locked package folder is compared with the contents of the package,
+/// and modified, missing, and extra files are reported.
+/// Exits with non-zero status if any package differs, unless --repair is specified.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Verify {
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+    #[command(flatten)]
+    env_args: EnvArgs,
+
+    /// Reinstall the packages with differences
+    #[arg(long)]
+    repair: bool,
+
+    /// skip confirm
+    #[arg(short, long)]
+    yes: bool,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+}
+
+impl Verify {
+    pub async fn run(self) {
+        let json_format = output::json_format(self.json_format, 1);
+        if json_format > 1 {
+            exit_with!("unsupported json version: {json_format}");
+        }
+
+        let io = DefaultEnvironmentIo::new_default();
+        let client = crate::create_client(self.env_args.offline);
+        let mut unity = load_unity(self.project).await;
+        let (collection, _) =
+            load_collection_for_install(&io, client.as_ref(), &self.env_args, &unity).await;
+        let installer = PackageInstaller::new(&io, client.as_ref());
+
+        let result = unity
+            .verify_packages(&collection, &installer)
+            .await
+            .exit_context("verifying packages");
+
+        if json_format == 0 {
+            print_result(&result);
+        }
+
+        let differing = result.differing_packages().collect::<Vec<_>>();
+        if differing.is_empty() || !self.repair {
+            if json_format != 0 {
+                print_json(&result, None);
+            } else if differing.is_empty() {
+                println!("all {} packages are intact", result.packages().len());
+            }
+
+            if !differing.is_empty() {
+                eprintln!(
+                    "{} packages differ from the package contents. use --repair to reinstall them",
+                    differing.len()
+                );
+                exit(1);
+            }
+            return;
+        }
+
+        let changes = unity
+            .reinstall_request(&collection, &differing)
+            .await
+            .exit_structured("collecting packages to be reinstalled");
+
+        // the plan is printed with the result after reinstalling with json
+        let repair = if json_format != 0 {
+            Some(plan::to_json(&unity, &changes))
+        } else {
+            print_prompt_install(&unity, &changes);
+            None
+        };
+        prompt_install(self.yes);
+
+        unity
+            .apply_pending_changes(&installer, changes)
+            .await
+            .exit_context("reinstalling packages");
+
+        if let Some(repair) = repair {
+            print_json(&result, Some(repair));
+        }
+
+        update_project_last_modified(&io, unity.project_dir()).await;
+    }
+}
+
+fn print_result(result: &VerifyPackagesResult) {
+    for (name, version) in result.not_found() {
+        eprintln!("warning: {name}@{version} is not found so cannot be verified");
+    }
+
+    for package in result.packages() {
+        if package.is_intact() {
+            continue;
+        }
+        println!("{}@{}:", package.name(), package.version());
+        for path in package.modified() {
+            println!("  modified: {path}");
+        }
+        for path in package.missing() {
+            println!("  missing:  {path}");
+        }
+        for path in package.extra() {
+            println!("  extra:    {path}");
+        }
+    }
+}
+
+fn print_json(result: &VerifyPackagesResult, repair: Option<serde_json::Value>) {
+    #[derive(Serialize)]
+    struct VerifyInfo<'a> {
+        packages: Vec<PackageInfo<'a>>,
+        /// The locked packages not found in the repositories, which cannot be verified
+        not_found: Vec<NotFoundInfo<'a>>,
+        /// The changes applied with --repair
+        #[serde(skip_serializing_if = "Option::is_none")]
+        repair: Option<serde_json::Value>,
+    }
+
+    #[derive(Serialize)]
+    struct PackageInfo<'a> {
+        name: &'a str,
+        version: &'a Version,
+        intact: bool,
+        modified: &'a [Box<str>],
+        missing: &'a [Box<str>],
+        extra: &'a [Box<str>],
+    }
+
+    #[derive(Serialize)]
+    struct NotFoundInfo<'a> {
+        name: &'a str,
+        version: &'a Version,
+    }
+
+    let info = VerifyInfo {
+        packages: (result.packages().iter())
+            .map(|package| PackageInfo {
+                name: package.name(),
+                version: package.version(),
+                intact: package.is_intact(),
+                modified: package.modified(),
+                missing: package.missing(),
+                extra: package.extra(),
+            })
+            .collect(),
+        not_found: (result.not_found().iter())
+            .map(|(name, version)| NotFoundInfo { name, version })
+            .collect(),
+        repair,
+    };
+    println!("{}", serde_json::to_string(&info).unwrap());
+}
@@ -10,7 +10,7 @@ mod commands;
 #[tokio::main]
 async fn main() {
     init_log();
-    commands::Command::parse().run().await;
+    commands::Cli::parse().run().await;
 }
 
 fn init_log() {
@@ -0,0 +1,231 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+const ZIP: &[u8] = b"the zip file of the package";
+
+/// Creates the environment with the official repository and the cached zip of its package.
+fn create_environment(name: &str) -> PathBuf {
+    let data_home = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("serve")
+        .join(name);
+    match fs::remove_dir_all(&data_home) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        result => result.unwrap(),
+    }
+
+    let repos = data_home.join("VRChatCreatorCompanion/Repos");
+    fs::create_dir_all(&repos).unwrap();
+    fs::write(
+        repos.join("vrc-official.json"),
+        r#"{
+            "repo": {
+                "name": "Official",
+                "id": "com.vrchat.repos.official",
+                "url": "https://packages.vrchat.com/official?download",
+                "packages": {
+                    "com.anatawa12.package": {
+                        "versions": {
+                            "1.0.0": {
+                                "name": "com.anatawa12.package",
+                                "version": "1.0.0",
+                                "url": "https://example.com/1.0.0.zip",
+                                "headers": { "Authorization": "Bearer secret" }
+                            }
+                        }
+                    }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    fs::write(repos.join("vrc-curated.json"), r#"{"repo": {}}"#).unwrap();
+
+    let cache = repos.join("com.anatawa12.package");
+    fs::create_dir_all(&cache).unwrap();
+    let file_name = "vrc-get-com.anatawa12.package-1.0.0.zip";
+    fs::write(cache.join(file_name), ZIP).unwrap();
+    fs::write(
+        cache.join(format!("{file_name}.sha256")),
+        format!("{} {file_name}\n", hex::encode(Sha256::digest(ZIP))),
+    )
+    .unwrap();
+
+    data_home
+}
+
+fn serve_command(data_home: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_vrc-get"));
+    command
+        .env("XDG_DATA_HOME", data_home)
+        .args(["serve", "--offline"]);
+    command
+}
+
+struct Server {
+    child: Child,
+    port: u16,
+}
+
+impl Server {
+    fn start(data_home: &Path) -> Self {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let child = serve_command(data_home)
+            .args(["--bind", &format!("127.0.0.1:{port}")])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let server = Self { child, port };
+
+        for _ in 0..100 {
+            if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                return server;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        panic!("the server did not start");
+    }
+
+    fn get(&self, path: &str, if_none_match: Option<&str>) -> Response {
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        let mut request =
+            format!("GET {path} HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n");
+        if let Some(etag) = if_none_match {
+            request.push_str(&format!("If-None-Match: {etag}\r\n"));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).unwrap();
+
+        let mut response = vec![];
+        stream.read_to_end(&mut response).unwrap();
+        Response::parse(&response)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn parse(response: &[u8]) -> Self {
+        let header_end = response
+            .windows(4)
+            .position(|x| x == b"\r\n\r\n")
+            .expect("no end of headers");
+        let head = std::str::from_utf8(&response[..header_end]).unwrap();
+        let mut lines = head.split("\r\n");
+        let status = lines.next().unwrap().split(' ').nth(1).unwrap();
+        let headers = lines
+            .map(|line| {
+                let (name, value) = line.split_once(':').unwrap();
+                (name.to_ascii_lowercase(), value.trim().to_owned())
+            })
+            .collect();
+
+        Self {
+            status: status.parse().unwrap(),
+            headers,
+            body: response[header_end + 4..].to_vec(),
+        }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        (self.headers.iter())
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[test]
+fn index_rewrites_package_urls() {
+    let server = Server::start(&create_environment("index"));
+
+    let response = server.get("/index.json", None);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("content-type"), Some("application/json"));
+
+    let index: Value = serde_json::from_slice(&response.body).unwrap();
+    let base_url = format!("http://127.0.0.1:{}/", server.port);
+    assert_eq!(index["url"], format!("{base_url}index.json"));
+
+    let package = &index["packages"]["com.anatawa12.package"]["versions"]["1.0.0"];
+    assert_eq!(
+        package["url"],
+        format!("{base_url}packages/com.anatawa12.package/1.0.0.zip")
+    );
+    // the headers to download from the original repository must not be exposed
+    assert_eq!(package.get("headers"), None);
+}
+
+#[test]
+fn index_not_modified_with_etag() {
+    let server = Server::start(&create_environment("index_etag"));
+
+    let response = server.get("/index.json", None);
+    let etag = response.header("etag").expect("no etag").to_owned();
+
+    let response = server.get("/index.json", Some(&etag));
+    assert_eq!(response.status, 304);
+    assert_eq!(response.header("etag"), Some(etag.as_str()));
+    assert!(response.body.is_empty());
+
+    let response = server.get("/index.json", Some("\"other\""));
+    assert_eq!(response.status, 200);
+}
+
+#[test]
+fn serves_cached_zip() {
+    let server = Server::start(&create_environment("zip"));
+
+    let response = server.get("/packages/com.anatawa12.package/1.0.0.zip", None);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("content-type"), Some("application/zip"));
+    assert_eq!(response.body, ZIP);
+
+    let etag = format!("\"{}\"", hex::encode(Sha256::digest(ZIP)));
+    assert_eq!(response.header("etag"), Some(etag.as_str()));
+
+    let response = server.get("/packages/com.anatawa12.package/1.0.0.zip", Some(&etag));
+    assert_eq!(response.status, 304);
+    assert!(response.body.is_empty());
+
+    // percent-encoded path
+    let response = server.get("/packages/com%2Eanatawa12%2Epackage/1.0.0.zip", None);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, ZIP);
+
+    let response = server.get("/packages/com.anatawa12.package/2.0.0.zip", None);
+    assert_eq!(response.status, 404);
+}
+
+#[test]
+fn wildcard_bind_requires_base_url() {
+    let data_home = create_environment("wildcard");
+
+    let output = serve_command(&data_home)
+        .args(["--bind", "0.0.0.0:0"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--base-url"));
+}
@@ -0,0 +1,210 @@
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Creates the project with the locked, unlocked, legacy, and UPM packages.
+fn create_project(name: &str) -> PathBuf {
+    let project = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("tree")
+        .join(name);
+    match fs::remove_dir_all(&project) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        result => result.unwrap(),
+    }
+
+    let files = [
+        (
+            "ProjectSettings/ProjectVersion.txt",
+            "m_EditorVersion: 2022.3.22f1\nm_EditorVersionWithRevision: 2022.3.22f1 (887be4894c44)\n",
+        ),
+        (
+            "Packages/vpm-manifest.json",
+            r#"{
+                "dependencies": {
+                    "com.anatawa12.tool": { "version": "1.0.0" }
+                },
+                "locked": {
+                    "com.anatawa12.tool": {
+                        "version": "1.0.0",
+                        "dependencies": {
+                            "com.anatawa12.lib": "^1.0.0",
+                            "com.anatawa12.old-lib": "^1.0.0",
+                            "com.anatawa12.missing": ">=1.0.0"
+                        }
+                    },
+                    "com.anatawa12.lib": {
+                        "version": "2.0.0",
+                        "dependencies": {
+                            "com.anatawa12.new-lib": "^1.0.0"
+                        }
+                    },
+                    "com.anatawa12.new-lib": {
+                        "version": "1.0.0"
+                    }
+                }
+            }"#,
+        ),
+        (
+            "Packages/com.anatawa12.tool/package.json",
+            r#"{ "name": "com.anatawa12.tool", "version": "1.0.0" }"#,
+        ),
+        (
+            "Packages/com.anatawa12.lib/package.json",
+            r#"{ "name": "com.anatawa12.lib", "version": "2.0.0" }"#,
+        ),
+        (
+            "Packages/com.anatawa12.new-lib/package.json",
+            r#"{
+                "name": "com.anatawa12.new-lib",
+                "version": "1.0.0",
+                "legacyPackages": ["com.anatawa12.old-lib"]
+            }"#,
+        ),
+        (
+            "Packages/com.anatawa12.local/package.json",
+            r#"{
+                "name": "com.anatawa12.local",
+                "version": "0.1.0",
+                "vpmDependencies": { "com.anatawa12.lib": ">=2.0.0" }
+            }"#,
+        ),
+        (
+            "Packages/manifest.json",
+            r#"{
+                "dependencies": {
+                    "com.unity.timeline": "1.7.6"
+                }
+            }"#,
+        ),
+    ];
+
+    for (path, contents) in files {
+        let path = project.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    project
+}
+
+fn tree(project: &Path, format: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_vrc-get"))
+        .args(["tree", "--format", format, "--project"])
+        .arg(project)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "vrc-get tree failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn text_output() {
+    let project = create_project("text");
+
+    assert_eq!(
+        tree(&project, "text"),
+        "\
+project
+└── com.anatawa12.tool (1.0.0) 1.0.0
+    ├── com.anatawa12.lib (^1.0.0) 2.0.0 [unsatisfied]
+    │   └── com.anatawa12.new-lib (^1.0.0) 1.0.0
+    ├── com.anatawa12.old-lib (^1.0.0) [legacy, replaced by com.anatawa12.new-lib]
+    └── com.anatawa12.missing (>=1.0.0) [missing]
+com.anatawa12.local 0.1.0 [unlocked]
+└── com.anatawa12.lib (>=2.0.0) 2.0.0 (*)
+
+UPM packages
+└── com.unity.timeline 1.7.6
+"
+    );
+}
+
+#[test]
+fn dot_output() {
+    let project = create_project("dot");
+
+    assert_eq!(
+        tree(&project, "dot"),
+        r#"digraph dependencies {
+    "project" [shape=box];
+    "com.anatawa12.tool" [label="com.anatawa12.tool\n1.0.0"];
+    "com.anatawa12.lib" [label="com.anatawa12.lib\n2.0.0"];
+    "com.anatawa12.new-lib" [label="com.anatawa12.new-lib\n1.0.0"];
+    "com.anatawa12.local" [label="com.anatawa12.local\n0.1.0\n(unlocked)", style=dashed];
+    "project" -> "com.anatawa12.tool" [label="1.0.0"];
+    "com.anatawa12.tool" -> "com.anatawa12.lib" [label="^1.0.0", color=red, fontcolor=red];
+    "com.anatawa12.tool" -> "com.anatawa12.old-lib" [label="^1.0.0", style=dotted, xlabel="replaced by com.anatawa12.new-lib"];
+    "com.anatawa12.missing" [color=red, fontcolor=red];
+    "com.anatawa12.tool" -> "com.anatawa12.missing" [label=">=1.0.0", color=red, fontcolor=red];
+    "com.anatawa12.lib" -> "com.anatawa12.new-lib" [label="^1.0.0"];
+    "com.anatawa12.local" -> "com.anatawa12.lib" [label=">=2.0.0"];
+    subgraph cluster_upm {
+        label="UPM packages";
+        "upm:com.unity.timeline" [label="com.unity.timeline\n1.7.6", shape=box];
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn json_output() {
+    let project = create_project("json");
+
+    let output: serde_json::Value = serde_json::from_str(&tree(&project, "json")).unwrap();
+    assert_eq!(
+        output,
+        json!({
+            "dependencies": [
+                { "name": "com.anatawa12.tool", "range": "1.0.0", "status": "satisfied" },
+            ],
+            "packages": [
+                {
+                    "name": "com.anatawa12.tool",
+                    "version": "1.0.0",
+                    "unlocked": false,
+                    "dependencies": [
+                        { "name": "com.anatawa12.lib", "range": "^1.0.0", "status": "unsatisfied" },
+                        {
+                            "name": "com.anatawa12.old-lib",
+                            "range": "^1.0.0",
+                            "status": "legacy",
+                            "replaced_by": "com.anatawa12.new-lib",
+                        },
+                        { "name": "com.anatawa12.missing", "range": ">=1.0.0", "status": "missing" },
+                    ],
+                },
+                {
+                    "name": "com.anatawa12.lib",
+                    "version": "2.0.0",
+                    "unlocked": false,
+                    "dependencies": [
+                        { "name": "com.anatawa12.new-lib", "range": "^1.0.0", "status": "satisfied" },
+                    ],
+                },
+                {
+                    "name": "com.anatawa12.new-lib",
+                    "version": "1.0.0",
+                    "unlocked": false,
+                    "dependencies": [],
+                },
+                {
+                    "name": "com.anatawa12.local",
+                    "version": "0.1.0",
+                    "unlocked": true,
+                    "dependencies": [
+                        { "name": "com.anatawa12.lib", "range": ">=2.0.0", "status": "satisfied" },
+                    ],
+                },
+            ],
+            "upm_dependencies": [
+                { "name": "com.unity.timeline", "version": "1.7.6" },
+            ],
+        })
+    );
+}